@@ -1,10 +1,20 @@
-#[cfg(feature = "bundled")]
+#[cfg(all(feature = "bundled", not(feature = "vendored")))]
+use std::process::Command;
+#[cfg(any(feature = "bundled", feature = "vendored"))]
 use std::{
     env,
     path::{Path, PathBuf},
-    process::Command,
 };
 
+// Truth that cargo's `key` cfg for the target, e.g. `TARGET_OS`, has `value`
+//
+// Build scripts run on the build machine, so cfg!() describes that machine
+// rather than the target when cross-compiling.
+fn target_cfg(key: &str, value: &str) -> bool {
+    std::env::var(format!("CARGO_CFG_{key}"))
+        .map_or(false, |values| values.split(',').any(|v| v == value))
+}
+
 // Use pkg-config to configure the build for a certain hwloc release
 fn use_pkgconfig(required_version: &str, first_unsupported_version: &str) -> pkg_config::Library {
     // Run pkg-config
@@ -16,7 +26,7 @@ fn use_pkgconfig(required_version: &str, first_unsupported_version: &str) -> pkg
 
     // As it turns-out, pkg-config does not correctly set up the RPATHs for the
     // transitive dependencies of hwloc itself in static builds. Fix that.
-    if cfg!(target_family = "unix") {
+    if target_cfg("TARGET_FAMILY", "unix") {
         for link_path in &lib.link_paths {
             println!(
                 "cargo:rustc-link-arg=-Wl,-rpath,{}",
@@ -32,7 +42,7 @@ fn use_pkgconfig(required_version: &str, first_unsupported_version: &str) -> pkg
 }
 
 // Fetch hwloc from a git release branch, return repo path
-#[cfg(feature = "bundled")]
+#[cfg(all(feature = "bundled", not(feature = "vendored")))]
 fn fetch_hwloc(parent_path: impl AsRef<Path>, version: &str) -> PathBuf {
     // Determine location of the git repo and its parent directory
     let parent_path = parent_path.as_ref();
@@ -71,18 +81,77 @@ fn fetch_hwloc(parent_path: impl AsRef<Path>, version: &str) -> PathBuf {
     repo_path
 }
 
+// Get the hwloc source code from the hwloc-src crate, return source path and
+// truth that the build system must be regenerated
+//
+// Release tarballs ship a pre-generated configure script, and both autotools
+// and CMake build out of tree, so the sources can be used in place.
+#[cfg(feature = "vendored")]
+fn hwloc_sources(_out_path: impl AsRef<Path>, _version: &str) -> (PathBuf, bool) {
+    (hwloc_src::source_dir(), false)
+}
+
+// Get the hwloc source code from git, return source path and truth that the
+// build system must be regenerated
+#[cfg(all(feature = "bundled", not(feature = "vendored")))]
+fn hwloc_sources(out_path: impl AsRef<Path>, version: &str) -> (PathBuf, bool) {
+    (fetch_hwloc(out_path, version), true)
+}
+
+// Translate a Rust target triple into the matching GNU autotools triple
+#[cfg(any(feature = "bundled", feature = "vendored"))]
+fn gnu_triple(rust_triple: &str) -> String {
+    let (arch, rest) = rust_triple
+        .split_once('-')
+        .expect("Target triple should start with an architecture");
+    let arch = if arch.starts_with("riscv64") {
+        "riscv64"
+    } else if arch.starts_with("riscv32") {
+        "riscv32"
+    } else {
+        arch
+    };
+    let rest = match rest {
+        "pc-windows-gnu" | "pc-windows-gnullvm" | "uwp-windows-gnu" => "w64-mingw32",
+        other => other,
+    };
+    format!("{arch}-{rest}")
+}
+
 // Compile hwloc using autotools, return local installation path
-#[cfg(all(feature = "bundled", not(windows)))]
-fn compile_hwloc_autotools(p: PathBuf) -> PathBuf {
+#[cfg(any(feature = "bundled", feature = "vendored"))]
+fn compile_hwloc_autotools(p: PathBuf, reconf: bool) -> PathBuf {
     let mut config = autotools::Config::new(p);
-    config.fast_build(true).reconf("-ivf").build()
+    config.fast_build(true);
+    if reconf {
+        config.reconf("-ivf");
+    }
+
+    // When cross-compiling, tell configure which machine we build for. The
+    // autotools crate can guess that from the C compiler's name, but the guess
+    // is wrong for e.g. clang or musl-gcc, and configure then silently builds
+    // hwloc for the build machine. Native builds are left to configure.
+    let target = env::var("TARGET").expect("No target triple given");
+    let host = env::var("HOST").expect("No host triple given");
+    if target != host {
+        config.config_option("host", Some(&gnu_triple(&target)));
+    }
+    config.build()
 }
 
 // Compile hwloc using cmake, return local installation path
-#[cfg(all(feature = "bundled", windows))]
+#[cfg(any(feature = "bundled", feature = "vendored"))]
 fn compile_hwloc_cmake(build_path: &Path) -> PathBuf {
     let mut config = cmake::Config::new(build_path);
 
+    // With Visual Studio generators, the cmake crate picks the target
+    // architecture (e.g. -A ARM64) from the target triple, but assumes x64 build
+    // tools. Use native build tools when building on Windows on ARM.
+    let host = env::var("HOST").expect("No host triple given");
+    if host.starts_with("aarch64") && env::var_os("CMAKE_GENERATOR").is_none() {
+        config.generator_toolset("host=ARM64");
+    }
+
     // Allow specifying the CMake build profile
     if let Ok(profile) = env::var("HWLOC_BUILD_PROFILE") {
         config.profile(&profile);
@@ -98,7 +167,11 @@ fn compile_hwloc_cmake(build_path: &Path) -> PathBuf {
 
 fn main() {
     // Determine the minimal supported hwloc version with current featurees
-    let required_version = if cfg!(feature = "hwloc-2_8_0") {
+    let required_version = if cfg!(feature = "hwloc-2_11_0") {
+        "2.11.0"
+    } else if cfg!(feature = "hwloc-2_10_0") {
+        "2.10.0"
+    } else if cfg!(feature = "hwloc-2_8_0") {
         "2.8.0"
     } else if cfg!(feature = "hwloc-2_5_0") {
         "2.5.0"
@@ -116,8 +189,16 @@ fn main() {
         "2.0.0"
     };
 
+    // If asked to load hwloc at runtime, there is nothing to link
+    if cfg!(feature = "dlopen") && std::env::var("CARGO_CFG_UNIX").is_ok() {
+        if cfg!(feature = "bundled") || cfg!(feature = "vendored") {
+            panic!("The dlopen feature is incompatible with the bundled and vendored features");
+        }
+        return;
+    }
+
     // If asked to build hwloc ourselves...
-    #[cfg(feature = "bundled")]
+    #[cfg(any(feature = "bundled", feature = "vendored"))]
     {
         // Determine which version to fetch and where to fetch it
         let (source_version, first_unsupported_version) = match required_version
@@ -130,13 +211,16 @@ fn main() {
         };
         let out_path = env::var("OUT_DIR").expect("No output directory given");
 
-        // Fetch latest supported hwloc from git
-        let source_path = fetch_hwloc(out_path, source_version);
+        // Fetch latest supported hwloc from the hwloc-src crate if vendored,
+        // or from git otherwise
+        let (source_path, reconf) = hwloc_sources(out_path, source_version);
 
         // On Windows, we build using CMake because the autotools build
-        // procedure does not work with MSVC, which is often needed on this OS
-        #[cfg(target_os = "windows")]
-        {
+        // procedure does not work with MSVC and needs an MSYS environment
+        // otherwise. Cross-builds for windows-gnu from other OSes use
+        // autotools with a MinGW toolchain instead.
+        let windows_target = target_cfg("TARGET_OS", "windows");
+        if windows_target && (cfg!(windows) || target_cfg("TARGET_ENV", "msvc")) {
             // Locate CMake support files, make sure they are present
             // (should be the case on any hwloc release since 2.8)
             let cmake_path = source_path.join("contrib").join("windows-cmake");
@@ -146,28 +230,30 @@ fn main() {
             );
 
             // Build hwloc, configure our own build to use it
-            let install_path = compile_hwloc_cmake(cmake_path);
+            let install_path = compile_hwloc_cmake(&cmake_path);
             println!("cargo:rustc-link-lib=static=hwloc");
             println!(
-                "cargo:rustc-link-search={}",
+                "cargo:rustc-link-search=native={}",
                 install_path.join("lib").display()
             );
         }
-
-        // On other OSes, we build using autotools and configure using pkg-config
-        #[cfg(not(target_os = "windows"))]
-        {
-            let install_path = compile_hwloc_autotools(source_path);
+        // Otherwise, we build using autotools and configure using pkg-config
+        else {
+            let install_path = compile_hwloc_autotools(source_path, reconf);
             env::set_var(
                 "PKG_CONFIG_PATH",
                 format!("{}", install_path.join("lib").join("pkgconfig").display()),
             );
+            // pkg-config refuses to configure cross-builds by default, as it
+            // cannot tell host and target libraries apart, but this hwloc build
+            // is known to be for the target.
+            env::set_var("PKG_CONFIG_ALLOW_CROSS", "1");
             use_pkgconfig(required_version, first_unsupported_version);
         }
     }
 
     // If asked to use system hwloc, we configure it using pkg-config
-    #[cfg(not(feature = "bundled"))]
+    #[cfg(not(any(feature = "bundled", feature = "vendored")))]
     {
         let first_unsupported_version = match required_version
             .split('.')
@@ -0,0 +1,161 @@
+use anyhow::Context;
+use hwlocality::{
+    cpu::binding::CpuBindingFlags,
+    memory::binding::{MemoryBindingFlags, MemoryBindingPolicy},
+    objects::{types::ObjectType, TopologyObject},
+    topology::support::{CpuBindingSupport, FeatureSupport, MemoryBindingSupport},
+    Topology,
+};
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+    sync::mpsc::{self, Sender},
+};
+
+/// Example of an in-memory key-value cache that is sharded across NUMA nodes
+///
+/// Each NUMA node gets a shard, owned by a thread that is bound to the CPUs
+/// and memory of that node, so that the shard's data is allocated locally.
+/// A bound memory buffer is also allocated per shard, e.g. for use as a slab
+/// of values. Clients route requests to shards by key hash.
+///
+/// Example Output with 2 NUMA nodes on linux:
+///
+/// ```
+/// Shard 0 serves NUMA node 0 from cpuset 0-3
+/// Shard 1 serves NUMA node 1 from cpuset 4-7
+/// Stored 1000 keys across 2 shards
+/// key 42 -> value 1764
+/// ```
+fn main() -> anyhow::Result<()> {
+    let topology = Topology::new()?;
+    let nodes = topology
+        .objects_with_type(ObjectType::NUMANode)
+        .collect::<Vec<_>>();
+
+    std::thread::scope(|scope| -> anyhow::Result<()> {
+        // Spawn one shard per NUMA node
+        let mut shards = Vec::with_capacity(nodes.len());
+        let mut handles = Vec::with_capacity(nodes.len());
+        for (idx, &node) in nodes.iter().enumerate() {
+            let (sender, receiver) = mpsc::channel::<Request>();
+            shards.push(sender);
+            let topology = &topology;
+            handles.push(scope.spawn(move || -> anyhow::Result<()> {
+                bind_to_node(topology, idx, node)?;
+                let mut shard = HashMap::new();
+                for request in receiver {
+                    match request {
+                        Request::Insert(key, value) => {
+                            shard.insert(key, value);
+                        }
+                        Request::Get(key, reply) => {
+                            reply.send(shard.get(&key).copied())?;
+                        }
+                    }
+                }
+                Ok(())
+            }));
+        }
+
+        // Use the resulting cache
+        let cache = ShardedCache { shards };
+        for key in 0..1000 {
+            cache.insert(key, key * key)?;
+        }
+        println!("Stored 1000 keys across {} shards", cache.shards.len());
+        let value = cache.get(42)?.context("Key 42 should be present")?;
+        println!("key 42 -> value {value}");
+
+        // Shut down the shards by closing their request channels
+        drop(cache);
+        for handle in handles {
+            handle.join().expect("Shard threads should not panic")?;
+        }
+        Ok(())
+    })
+}
+
+/// Bind the current thread and its memory allocations to a NUMA node
+fn bind_to_node(topology: &Topology, idx: usize, node: &TopologyObject) -> anyhow::Result<()> {
+    let cpuset = node.cpuset().context("NUMA nodes should have a cpuset")?;
+    let nodeset = node
+        .nodeset()
+        .context("NUMA nodes should have a nodeset")?
+        .to_owned();
+    if topology.supports(
+        FeatureSupport::cpu_binding,
+        CpuBindingSupport::set_current_thread,
+    ) && !cpuset.is_empty()
+    {
+        topology.bind_cpu(&cpuset, CpuBindingFlags::THREAD)?;
+    }
+    if topology.supports(
+        FeatureSupport::memory_binding,
+        MemoryBindingSupport::set_current_thread,
+    ) {
+        topology.bind_memory(
+            &nodeset,
+            MemoryBindingPolicy::Bind,
+            MemoryBindingFlags::THREAD,
+        )?;
+    }
+
+    // Values could be stored in a slab that is explicitly bound to the node,
+    // falling back to other binding methods when this is not supported
+    let mut slab = topology.binding_allocate_memory(
+        1 << 20,
+        &nodeset,
+        MemoryBindingPolicy::Bind,
+        MemoryBindingFlags::empty(),
+    )?;
+    slab.initialize(0);
+    println!(
+        "Shard {idx} serves NUMA node {} from cpuset {cpuset}",
+        node.os_index()
+            .context("NUMA nodes should have an OS index")?
+    );
+    Ok(())
+}
+
+/// Request sent to a cache shard
+enum Request {
+    /// Insert a value
+    Insert(u64, u64),
+
+    /// Look up a value, sending the answer back through a channel
+    Get(u64, Sender<Option<u64>>),
+}
+
+/// Client-side handle to the sharded cache
+struct ShardedCache {
+    /// Request channels of the shards, indexed by NUMA node logical index
+    shards: Vec<Sender<Request>>,
+}
+//
+impl ShardedCache {
+    /// Shard that is responsible for a certain key
+    fn shard(&self, key: u64) -> &Sender<Request> {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        let idx = usize::try_from(hasher.finish() % self.shards.len() as u64)
+            .expect("Shard index fits in usize");
+        &self.shards[idx]
+    }
+
+    /// Insert a key-value pair into the cache
+    fn insert(&self, key: u64, value: u64) -> anyhow::Result<()> {
+        self.shard(key)
+            .send(Request::Insert(key, value))
+            .context("Shard should be alive")
+    }
+
+    /// Look up a key in the cache
+    fn get(&self, key: u64) -> anyhow::Result<Option<u64>> {
+        let (sender, receiver) = mpsc::channel();
+        self.shard(key)
+            .send(Request::Get(key, sender))
+            .context("Shard should be alive")?;
+        receiver.recv().context("Shard should reply")
+    }
+}
@@ -0,0 +1,137 @@
+use anyhow::Context;
+use hwlocality::{
+    cpu::{binding::CpuBindingFlags, cpusets::CpuSet},
+    memory::binding::{MemoryBindingFlags, MemoryBindingPolicy},
+    objects::types::ObjectType,
+    topology::{
+        support::{CpuBindingSupport, FeatureSupport, MemoryBindingSupport},
+        DistributeFlags,
+    },
+    Topology,
+};
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    num::NonZeroUsize,
+    sync::{mpsc, Arc},
+    time::Duration,
+};
+
+/// How long the client waits for a reply before giving up
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Example of a multi-queue TCP echo server with one pinned worker per queue
+///
+/// Worker threads are spread across the machine with
+/// `Topology::distribute_items()`, bound there along with their memory
+/// allocations, and each of them accepts connections from a shared listening
+/// socket, so that the kernel spreads incoming connections across workers.
+/// The example then connects to itself a few times and checks that messages
+/// are echoed back, reporting worker failures instead of waiting forever for
+/// a reply that will never come.
+///
+/// Example Output with 2 cores (no HT) on linux:
+///
+/// ```
+/// Worker 0 bound to cpuset 0
+/// Worker 1 bound to cpuset 1
+/// Listening on 127.0.0.1:40457
+/// Message 0 echoed back
+/// Message 1 echoed back
+/// Message 2 echoed back
+/// Message 3 echoed back
+/// ```
+fn main() -> anyhow::Result<()> {
+    let topology = Arc::new(Topology::new()?);
+
+    // Start one worker per core, up to 4
+    let num_cores = topology.objects_with_type(ObjectType::Core).count();
+    let num_workers = NonZeroUsize::new(num_cores.clamp(1, 4)).expect("Clamped to >= 1");
+    let cpusets = topology.distribute_items(
+        &[topology.root_object()],
+        num_workers,
+        usize::MAX,
+        DistributeFlags::empty(),
+    )?;
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let address = listener.local_addr()?;
+    let (failures_tx, failures) = mpsc::channel();
+    for (idx, cpuset) in cpusets.into_iter().enumerate() {
+        let topology = topology.clone();
+        let listener = listener.try_clone()?;
+        let failures_tx = failures_tx.clone();
+        std::thread::spawn(move || {
+            if let Err(e) = run_worker(&topology, idx, &cpuset, &listener) {
+                // The client may be gone already, nothing to do about it then
+                let _ = failures_tx.send(e.context(format!("Worker {idx} failed")));
+            }
+        });
+    }
+    println!("Listening on {address}");
+
+    // Act as a client, checking that messages are echoed back
+    for idx in 0..4 {
+        let message = format!("Message {idx}\n");
+        let reply = match request(address, &message) {
+            Ok(reply) => reply,
+            // If the request failed, a worker failure is the likely cause
+            Err(e) => return Err(failures.try_recv().unwrap_or(e)),
+        };
+        anyhow::ensure!(reply == message, "Got {reply:?} instead of {message:?}");
+        println!("Message {idx} echoed back");
+    }
+    Ok(())
+}
+
+/// Send `message` to the server at `address` and return its reply, giving up
+/// after [`CLIENT_TIMEOUT`]
+fn request(address: SocketAddr, message: &str) -> anyhow::Result<String> {
+    let mut stream = TcpStream::connect_timeout(&address, CLIENT_TIMEOUT)?;
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.write_all(message.as_bytes())?;
+    let mut reply = String::new();
+    BufReader::new(stream)
+        .read_line(&mut reply)
+        .context("No reply from the server")?;
+    Ok(reply)
+}
+
+/// Pin the current thread to `cpuset`, then serve connections forever
+fn run_worker(
+    topology: &Topology,
+    idx: usize,
+    cpuset: &CpuSet,
+    listener: &TcpListener,
+) -> anyhow::Result<()> {
+    if topology.supports(
+        FeatureSupport::cpu_binding,
+        CpuBindingSupport::set_current_thread,
+    ) {
+        topology.bind_cpu(cpuset, CpuBindingFlags::THREAD)?;
+    }
+    if topology.supports(
+        FeatureSupport::memory_binding,
+        MemoryBindingSupport::set_current_thread,
+    ) {
+        topology.bind_memory(
+            cpuset,
+            MemoryBindingPolicy::Bind,
+            MemoryBindingFlags::THREAD,
+        )?;
+    }
+    println!("Worker {idx} bound to cpuset {cpuset}");
+    loop {
+        let (stream, _) = listener.accept()?;
+        echo(stream).context("Failed to serve a connection")?;
+    }
+}
+
+/// Echo lines of text back to the client until it disconnects
+fn echo(stream: TcpStream) -> anyhow::Result<()> {
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        writeln!(writer, "{}", line?)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use hwlocality::{
+    placement::PlacementPlan,
+    topology::support::{CpuBindingSupport, FeatureSupport},
+    Topology,
+};
+
+/// Example of a thread placement plan that is computed and applied separately
+///
+/// The plan maps named thread roles to locations in the topology, expressed
+/// as type/index paths like `Package:0.Core:1` that are stable across
+/// machines with the same hardware. It can be computed offline on a topology
+/// snapshot (e.g. loaded from XML), printed as text for review and version
+/// control, then parsed back and applied on production nodes.
+///
+/// Example Output with 1 package of 2 cores (no HT) on linux:
+///
+/// ```
+/// network Package:0.Core:0 THREAD
+/// storage Package:0.Core:1 THREAD
+/// compute Package:0.Core:0 THREAD
+/// Bound role compute to cpuset 0
+/// ```
+fn main() -> anyhow::Result<()> {
+    // Compute the plan, this could be done on another machine
+    let topology = Topology::new()?;
+    let plan = PlacementPlan::compute(&topology, &["network", "storage", "compute"])?;
+    let text = plan.to_string();
+    print!("{text}");
+
+    // Parse it back and apply it, as would be done on the production node
+    let plan = text.parse::<PlacementPlan>()?;
+    if !topology.supports(
+        FeatureSupport::cpu_binding,
+        CpuBindingSupport::set_current_thread,
+    ) {
+        println!("This example needs support for binding the current thread");
+        return Ok(());
+    }
+    let cpuset = plan.apply(&topology, "compute")?;
+    println!("Bound role compute to cpuset {cpuset}");
+    Ok(())
+}
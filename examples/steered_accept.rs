@@ -0,0 +1,104 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{SocketAddr, TcpStream},
+    time::Duration,
+};
+
+/// How long the client waits for a reply before giving up
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Example of topology-aware accept steering for a network service
+///
+/// Connections are served by per-domain worker sets, where a domain is a NUMA
+/// node that has CPUs. If a network interface name is passed as the first
+/// argument and hwloc knows about it, only the domains that are close to this
+/// NIC are used, so that packets do not need to cross the inter-socket
+/// interconnect. See the `hwlocality::steering` module for details, which is
+/// only available on Linux.
+///
+/// The example connects to itself a few times and checks that messages are
+/// echoed back.
+///
+/// Example Output with 2 NUMA nodes of 2 cores (no HT) on linux:
+///
+/// ```
+/// Domain NUMANode#0 will use workers bound to [0, 1]
+/// Domain NUMANode#1 will use workers bound to [2, 3]
+/// Listening on 127.0.0.1:40457
+/// Message 0 echoed back
+/// Message 1 echoed back
+/// Message 2 echoed back
+/// Message 3 echoed back
+/// ```
+#[cfg(target_os = "linux")]
+fn main() -> anyhow::Result<()> {
+    use hwlocality::{steering::AcceptSteering, topology::builder::TypeFilter, Topology};
+    use std::num::NonZeroUsize;
+
+    let topology = Topology::builder()
+        .with_io_type_filter(TypeFilter::KeepImportant)?
+        .build()?;
+    let nic = std::env::args().nth(1);
+    let workers_per_domain = NonZeroUsize::new(2).expect("2 is not 0");
+    let steering = match AcceptSteering::new(&topology, nic.as_deref(), workers_per_domain) {
+        Ok(steering) => steering,
+        Err(e) => {
+            eprintln!("{e}, using all domains");
+            AcceptSteering::new(&topology, None, workers_per_domain)?
+        }
+    };
+    for domain in steering.domains() {
+        println!(
+            "Domain {} will use workers bound to {:?}",
+            domain.name(),
+            domain
+                .workers()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+        );
+    }
+    let (address, _workers) = steering.serve(&topology, "127.0.0.1:0".parse()?, |stream| {
+        if let Err(e) = echo(stream) {
+            eprintln!("Failed to serve a connection: {e}");
+        }
+    })?;
+    println!("Listening on {address}");
+
+    // Act as a client, checking that messages are echoed back
+    for idx in 0..4 {
+        let message = format!("Message {idx}\n");
+        let reply = request(address, &message)?;
+        anyhow::ensure!(reply == message, "Got {reply:?} instead of {message:?}");
+        println!("Message {idx} echoed back");
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn main() {
+    println!("This example needs SO_INCOMING_CPU, which only exists on Linux");
+}
+
+/// Echo lines of text back to the client until it disconnects
+#[cfg_attr(not(target_os = "linux"), allow(unused))]
+fn echo(stream: TcpStream) -> std::io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        writeln!(writer, "{}", line?)?;
+    }
+    Ok(())
+}
+
+/// Send `message` to the server at `address` and return its reply, giving up
+/// after [`CLIENT_TIMEOUT`]
+#[cfg_attr(not(target_os = "linux"), allow(unused))]
+fn request(address: SocketAddr, message: &str) -> std::io::Result<String> {
+    let mut stream = TcpStream::connect_timeout(&address, CLIENT_TIMEOUT)?;
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.write_all(message.as_bytes())?;
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply)?;
+    Ok(reply)
+}
@@ -0,0 +1,58 @@
+use hwlocality::{
+    cpu::{binding::CpuBindingFlags, cpusets::CpuSet},
+    topology::support::{CpuBindingSupport, FeatureSupport},
+    Topology,
+};
+
+/// Example of a barrier whose structure follows the hardware topology
+///
+/// Instead of making every thread wait on a single flat barrier, which
+/// scales poorly on multi-socket machines, threads first synchronize with
+/// other threads sharing the same L3 cache, then one representative per L3
+/// cache synchronizes at the package level, and finally one representative
+/// per package synchronizes at the machine level.
+///
+/// Example Output with 2 packages of 2 cores (no HT) on linux:
+///
+/// ```
+/// Barrier participants: 4
+/// Round 0 done
+/// Round 1 done
+/// Round 2 done
+/// ```
+fn main() -> anyhow::Result<()> {
+    let topology = Topology::new()?;
+    let can_bind = topology.supports(
+        FeatureSupport::cpu_binding,
+        CpuBindingSupport::set_current_thread,
+    );
+
+    // Set up a barrier for one thread per PU
+    let cpuset = topology.cpuset().to_owned();
+    let barrier = topology.tree_barrier(&cpuset);
+    println!("Barrier participants: {}", barrier.num_participants());
+
+    // Spawn one thread per PU, each taking a path through the barrier tree
+    std::thread::scope(|scope| {
+        for pu in cpuset.iter_set() {
+            let topology = &topology;
+            let barrier = &barrier;
+            scope.spawn(move || -> anyhow::Result<()> {
+                if can_bind {
+                    topology.bind_cpu(&CpuSet::from(pu), CpuBindingFlags::THREAD)?;
+                }
+                let mut participant = barrier
+                    .participant(pu)
+                    .expect("Each PU should only be claimed once");
+                for round in 0..3 {
+                    // ...do some work here...
+                    if participant.wait()? {
+                        println!("Round {round} done");
+                    }
+                }
+                Ok(())
+            });
+        }
+    });
+    Ok(())
+}
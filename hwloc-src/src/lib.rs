@@ -0,0 +1,37 @@
+//! Source code of the [hwloc](https://www.open-mpi.org/projects/hwloc/)
+//! library
+//!
+//! This crate is used by the `vendored` feature of hwlocality to build hwloc
+//! from source without network or git access. It contains an unmodified hwloc
+//! release tarball, extracted in the `hwloc` directory, and is versioned as
+//! `0.x.y+<hwloc version>`.
+//!
+//! The sources can be updated to a new hwloc release with the
+//! `update-hwloc.sh` script at the root of this crate, which checks the release
+//! tarball against its published SHA-256 checksum.
+
+use std::path::{Path, PathBuf};
+
+/// Version of hwloc whose source code is provided by this crate
+pub const VERSION: &str = "2.11.2";
+
+/// Location of the hwloc source code
+///
+/// This directory contains an extracted hwloc release tarball, including the
+/// pre-generated `configure` script and the `contrib/windows-cmake` CMake
+/// build system. It must not be modified, so builds should happen out of tree.
+///
+/// # Panics
+///
+/// If the hwloc sources are missing, which can happen when using this crate
+/// from a git checkout where `update-hwloc.sh` has not been run.
+pub fn source_dir() -> PathBuf {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("hwloc");
+    assert!(
+        path.join("configure").exists(),
+        "hwloc {VERSION} sources are missing from {}, run `update-hwloc.sh {VERSION} <sha256>` \
+         to fetch them",
+        path.display()
+    );
+    path
+}
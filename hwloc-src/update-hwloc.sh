@@ -0,0 +1,35 @@
+#!/bin/sh
+# Replace the vendored hwloc sources with those of another hwloc release
+#
+# Usage: ./update-hwloc.sh <version> <sha256>
+#
+# The SHA-256 checksum of the release tarball is published alongside the
+# download links at https://www.open-mpi.org/software/hwloc/ and must be given
+# explicitly, so that the vendored sources are never taken on trust. It is
+# recorded in hwloc.sha256 for future reference.
+#
+# After running this script, update VERSION in src/lib.rs and the build
+# metadata of the version in Cargo.toml accordingly.
+set -eu
+
+if [ "$#" -ne 2 ] || [ -z "$2" ]; then
+    echo "Usage: $0 <version> <sha256 of hwloc-<version>.tar.bz2>" >&2
+    exit 1
+fi
+version="$1"
+sha256="$2"
+series="$(echo "$version" | cut -d. -f1-2)"
+cd "$(dirname "$0")"
+
+tarball="hwloc-${version}.tar.bz2"
+curl -fLO "https://download.open-mpi.org/release/hwloc/v${series}/${tarball}"
+if ! echo "${sha256}  ${tarball}" | sha256sum -c -; then
+    rm "${tarball}"
+    echo "Checksum mismatch for ${tarball}, refusing to vendor it" >&2
+    exit 1
+fi
+rm -rf hwloc
+tar -xjf "${tarball}"
+mv "hwloc-${version}" hwloc
+echo "${sha256}  ${tarball}" > hwloc.sha256
+rm "${tarball}"
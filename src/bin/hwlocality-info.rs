@@ -0,0 +1,123 @@
+//! Report on the hardware topology of the host, in the spirit of `lstopo`
+//!
+//! This tool is exclusively implemented on top of hwlocality's public API, and
+//! is only built when the `cli` feature is enabled.
+//!
+//! Usage: `hwlocality-info [tree|support|json|xml|synthetic|dot]` (default: tree)
+
+use hwlocality::{
+    objects::TopologyObject,
+    topology::export::{dot::DotOptions, synthetic::SyntheticExportFlags, xml::XMLExportFlags},
+    Topology,
+};
+use std::{error::Error, fmt::Write};
+
+/// Output formats supported by this tool
+const USAGE: &str = "Usage: hwlocality-info [tree|support|json|xml|synthetic|dot]";
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut args = std::env::args().skip(1);
+    let command = args.next().unwrap_or_else(|| "tree".to_owned());
+    if args.next().is_some() {
+        return Err(USAGE.into());
+    }
+
+    let topology = Topology::new()?;
+    match command.as_str() {
+        "tree" => print_tree(topology.root_object(), 0),
+        "support" => println!("{:#?}", topology.feature_support()),
+        "json" => {
+            let mut json = String::new();
+            write_json(&mut json, topology.root_object())?;
+            println!("{json}");
+        }
+        "xml" => print!("{}", topology.export_xml(XMLExportFlags::default())?),
+        "synthetic" => println!(
+            "{}",
+            topology.export_synthetic(SyntheticExportFlags::default())?
+        ),
+        "dot" => print!("{}", topology.to_dot(&DotOptions::default())),
+        "-h" | "--help" => println!("{USAGE}"),
+        _ => return Err(USAGE.into()),
+    }
+    Ok(())
+}
+
+/// Print an indented tree of all objects below `obj`
+fn print_tree(obj: &TopologyObject, indent: usize) {
+    print!("{:indent$}{obj}", "");
+    if let Some(os_idx) = obj.os_index() {
+        print!(" #{os_idx}");
+    }
+    if let Some(cpuset) = obj.cpuset() {
+        print!(" cpuset={cpuset}");
+    }
+    println!();
+    for child in obj.all_children() {
+        print_tree(child, indent + 2);
+    }
+}
+
+/// Serialize the tree of objects below `obj` as JSON
+fn write_json(out: &mut String, obj: &TopologyObject) -> std::fmt::Result {
+    write!(out, "{{\"type\":")?;
+    write_json_string(out, &obj.object_type().to_string())?;
+    write!(out, ",\"logical_index\":{}", obj.logical_index())?;
+    if let Some(os_idx) = obj.os_index() {
+        write!(out, ",\"os_index\":{os_idx}")?;
+    }
+    if let Some(name) = obj.name() {
+        write!(out, ",\"name\":")?;
+        write_json_string(out, &name.to_string_lossy())?;
+    }
+    if let Some(subtype) = obj.subtype() {
+        write!(out, ",\"subtype\":")?;
+        write_json_string(out, &subtype.to_string_lossy())?;
+    }
+    if let Some(cpuset) = obj.cpuset() {
+        write!(out, ",\"cpuset\":")?;
+        write_json_string(out, &cpuset.to_string())?;
+    }
+    if let Some(nodeset) = obj.nodeset() {
+        write!(out, ",\"nodeset\":")?;
+        write_json_string(out, &nodeset.to_string())?;
+    }
+    if !obj.infos().is_empty() {
+        write!(out, ",\"infos\":{{")?;
+        for (idx, info) in obj.infos().iter().enumerate() {
+            if idx > 0 {
+                write!(out, ",")?;
+            }
+            write_json_string(out, &info.name().to_string_lossy())?;
+            write!(out, ":")?;
+            write_json_string(out, &info.value().to_string_lossy())?;
+        }
+        write!(out, "}}")?;
+    }
+    write!(out, ",\"children\":[")?;
+    for (idx, child) in obj.all_children().enumerate() {
+        if idx > 0 {
+            write!(out, ",")?;
+        }
+        write_json(out, child)?;
+    }
+    write!(out, "]}}")
+}
+
+/// Write a JSON string literal, escaping special characters as needed
+fn write_json_string(out: &mut String, s: &str) -> std::fmt::Result {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => write!(out, "\\u{:04x}", u32::from(c))?,
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    Ok(())
+}
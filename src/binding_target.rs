@@ -0,0 +1,527 @@
+//! Generic binding targets
+//!
+//! hwloc provides a family of CPU and memory binding functions for each kind
+//! of entity that can be bound: the current process or thread, another
+//! process, or another thread. The [`BindingTarget`] trait abstracts over
+//! these entities, so that code which binds things can be written once and
+//! then used with any of them, including in tests where binding the current
+//! thread is more convenient than binding some other process.
+
+use crate::{
+    bitmaps::SpecializedBitmap,
+    cpu::{
+        binding::{CpuBindingError, CpuBindingFlags, CpuBoundObject},
+        cpusets::CpuSet,
+    },
+    errors::HybridError,
+    memory::{
+        binding::{MemoryBindingError, MemoryBindingFlags, MemoryBindingPolicy},
+        nodesets::NodeSet,
+    },
+    topology::Topology,
+    ProcessId, Sealed, ThreadId,
+};
+
+/// # Generic binding
+///
+/// These functions are generic versions of the CPU and memory binding
+/// functions, which work with any [`BindingTarget`]. For example,
+/// `topology.bind_cpu_for(CurrentThread, set, flags)` is equivalent to
+/// `topology.bind_cpu(set, flags | CpuBindingFlags::THREAD)`.
+impl Topology {
+    /// Bind `target` on the CPUs of `set`
+    ///
+    /// See the documentation of the underlying binding function of `target`
+    /// for details and errors: [`bind_cpu()`] for [`CurrentThread`] and
+    /// [`CurrentProcess`], [`bind_process_cpu()`] for [`Process`] and
+    /// [`bind_thread_cpu()`] for [`Thread`].
+    ///
+    /// # Errors
+    ///
+    /// - [`BadFlags`] if `flags` contradict the target, e.g. if
+    ///   [`CpuBindingFlags::PROCESS`] is used on [`CurrentThread`]
+    /// - Any error of the underlying binding function
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::{
+    /// #     binding_target::{BindingTarget, CurrentThread},
+    /// #     cpu::binding::CpuBindingFlags,
+    /// #     objects::types::ObjectType,
+    /// #     topology::support::{CpuBindingSupport, FeatureSupport},
+    /// #     Topology,
+    /// # };
+    /// // This function can bind any target, which makes it easy to test
+    /// fn pin_to_first_core(
+    ///     topology: &Topology,
+    ///     target: impl BindingTarget,
+    /// ) -> anyhow::Result<()> {
+    ///     let core = topology
+    ///         .objects_with_type(ObjectType::Core)
+    ///         .next()
+    ///         .expect("There should be at least one core");
+    ///     let cpuset = core.cpuset().expect("Cores should have a cpuset");
+    ///     topology.bind_cpu_for(target, &cpuset, CpuBindingFlags::empty())?;
+    ///     Ok(())
+    /// }
+    ///
+    /// # let topology = Topology::test_instance();
+    /// if topology.supports(
+    ///     FeatureSupport::cpu_binding,
+    ///     CpuBindingSupport::set_current_thread,
+    /// ) {
+    ///     pin_to_first_core(topology, CurrentThread)?;
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    ///
+    /// [`BadFlags`]: CpuBindingError::BadFlags
+    /// [`bind_cpu()`]: Topology::bind_cpu()
+    /// [`bind_process_cpu()`]: Topology::bind_process_cpu()
+    /// [`bind_thread_cpu()`]: Topology::bind_thread_cpu()
+    pub fn bind_cpu_for(
+        &self,
+        target: impl BindingTarget,
+        set: &CpuSet,
+        flags: CpuBindingFlags,
+    ) -> Result<(), HybridError<CpuBindingError>> {
+        target.bind_cpu(self, set, flags)
+    }
+
+    /// Get the current CPU binding of `target`
+    ///
+    /// See the documentation of the underlying binding function of `target`
+    /// for details and errors: [`cpu_binding()`] for [`CurrentThread`] and
+    /// [`CurrentProcess`], [`process_cpu_binding()`] for [`Process`] and
+    /// [`thread_cpu_binding()`] for [`Thread`].
+    ///
+    /// # Errors
+    ///
+    /// - [`BadFlags`] if `flags` contradict the target, e.g. if
+    ///   [`CpuBindingFlags::PROCESS`] is used on [`CurrentThread`]
+    /// - Any error of the underlying binding function
+    ///
+    /// [`BadFlags`]: CpuBindingError::BadFlags
+    /// [`cpu_binding()`]: Topology::cpu_binding()
+    /// [`process_cpu_binding()`]: Topology::process_cpu_binding()
+    /// [`thread_cpu_binding()`]: Topology::thread_cpu_binding()
+    pub fn cpu_binding_for(
+        &self,
+        target: impl BindingTarget,
+        flags: CpuBindingFlags,
+    ) -> Result<CpuSet, HybridError<CpuBindingError>> {
+        target.cpu_binding(self, flags)
+    }
+
+    /// Get the last physical CPUs where `target` ran
+    ///
+    /// See the documentation of the underlying binding function of `target`
+    /// for details and errors: [`last_cpu_location()`] for [`CurrentThread`]
+    /// and [`CurrentProcess`], [`last_process_cpu_location()`] for
+    /// [`Process`].
+    ///
+    /// # Errors
+    ///
+    /// - [`BadObject(Thread)`] if `target` is a [`Thread`], as hwloc cannot
+    ///   query where another thread last ran in a portable way
+    /// - [`BadFlags`] if `flags` contradict the target, e.g. if
+    ///   [`CpuBindingFlags::PROCESS`] is used on [`CurrentThread`]
+    /// - Any error of the underlying binding function
+    ///
+    /// [`BadFlags`]: CpuBindingError::BadFlags
+    /// [`BadObject(Thread)`]: CpuBindingError::BadObject
+    /// [`last_cpu_location()`]: Topology::last_cpu_location()
+    /// [`last_process_cpu_location()`]: Topology::last_process_cpu_location()
+    pub fn last_cpu_location_for(
+        &self,
+        target: impl BindingTarget,
+        flags: CpuBindingFlags,
+    ) -> Result<CpuSet, HybridError<CpuBindingError>> {
+        target.last_cpu_location(self, flags)
+    }
+
+    /// Bind the memory of `target` to the NUMA nodes specified by `set` and
+    /// `policy`
+    ///
+    /// See the documentation of the underlying binding function of `target`
+    /// for details and errors: [`bind_memory()`] for [`CurrentThread`] and
+    /// [`CurrentProcess`], [`bind_process_memory()`] for [`Process`].
+    ///
+    /// # Errors
+    ///
+    /// - [`Unsupported`] if `target` is a [`Thread`], as hwloc cannot bind
+    ///   the memory of another thread
+    /// - [`BadFlags`] if `flags` contradict the target, e.g. if
+    ///   [`MemoryBindingFlags::PROCESS`] is used on [`CurrentThread`]
+    /// - Any error of the underlying binding function
+    ///
+    /// [`BadFlags`]: MemoryBindingError::BadFlags
+    /// [`bind_memory()`]: Topology::bind_memory()
+    /// [`bind_process_memory()`]: Topology::bind_process_memory()
+    /// [`Unsupported`]: MemoryBindingError::Unsupported
+    pub fn bind_memory_for<Set: SpecializedBitmap>(
+        &self,
+        target: impl BindingTarget,
+        set: &Set,
+        policy: MemoryBindingPolicy,
+        flags: MemoryBindingFlags,
+    ) -> Result<(), MemoryBindingError<Set>> {
+        target.bind_memory(self, set, policy, flags)
+    }
+
+    /// Reset the memory allocation policy of `target` to the system default
+    ///
+    /// See the documentation of the underlying binding function of `target`
+    /// for details and errors: [`unbind_memory()`] for [`CurrentThread`] and
+    /// [`CurrentProcess`], [`unbind_process_memory()`] for [`Process`].
+    ///
+    /// # Errors
+    ///
+    /// - [`Unsupported`] if `target` is a [`Thread`], as hwloc cannot bind
+    ///   the memory of another thread
+    /// - [`BadFlags`] if `flags` contradict the target, e.g. if
+    ///   [`MemoryBindingFlags::PROCESS`] is used on [`CurrentThread`]
+    /// - Any error of the underlying binding function
+    ///
+    /// [`BadFlags`]: MemoryBindingError::BadFlags
+    /// [`unbind_memory()`]: Topology::unbind_memory()
+    /// [`unbind_process_memory()`]: Topology::unbind_process_memory()
+    /// [`Unsupported`]: MemoryBindingError::Unsupported
+    pub fn unbind_memory_for(
+        &self,
+        target: impl BindingTarget,
+        flags: MemoryBindingFlags,
+    ) -> Result<(), MemoryBindingError<NodeSet>> {
+        target.unbind_memory(self, flags)
+    }
+
+    /// Query the memory binding policy and physical locality of `target`
+    ///
+    /// See the documentation of the underlying binding function of `target`
+    /// for details and errors: [`memory_binding()`] for [`CurrentThread`] and
+    /// [`CurrentProcess`], [`process_memory_binding()`] for [`Process`].
+    ///
+    /// # Errors
+    ///
+    /// - [`Unsupported`] if `target` is a [`Thread`], as hwloc cannot query
+    ///   the memory binding of another thread
+    /// - [`BadFlags`] if `flags` contradict the target, e.g. if
+    ///   [`MemoryBindingFlags::PROCESS`] is used on [`CurrentThread`]
+    /// - Any error of the underlying binding function
+    ///
+    /// [`BadFlags`]: MemoryBindingError::BadFlags
+    /// [`memory_binding()`]: Topology::memory_binding()
+    /// [`process_memory_binding()`]: Topology::process_memory_binding()
+    /// [`Unsupported`]: MemoryBindingError::Unsupported
+    pub fn memory_binding_for<Set: SpecializedBitmap>(
+        &self,
+        target: impl BindingTarget,
+        flags: MemoryBindingFlags,
+    ) -> Result<(Set, Option<MemoryBindingPolicy>), MemoryBindingError<Set>> {
+        target.memory_binding(self, flags)
+    }
+}
+
+/// Something that can be bound to CPUs and NUMA nodes
+///
+/// This trait is implemented by [`CurrentThread`], [`CurrentProcess`],
+/// [`Process`] and [`Thread`], and used by the generic binding functions of
+/// [`Topology`] such as [`Topology::bind_cpu_for()`]. It cannot be
+/// implemented outside of hwlocality.
+pub trait BindingTarget: Sealed + Copy {
+    /// Bind this target on the CPUs of `set`
+    #[doc(hidden)]
+    fn bind_cpu(
+        self,
+        topology: &Topology,
+        set: &CpuSet,
+        flags: CpuBindingFlags,
+    ) -> Result<(), HybridError<CpuBindingError>>;
+
+    /// Get the current CPU binding of this target
+    #[doc(hidden)]
+    fn cpu_binding(
+        self,
+        topology: &Topology,
+        flags: CpuBindingFlags,
+    ) -> Result<CpuSet, HybridError<CpuBindingError>>;
+
+    /// Get the last physical CPUs where this target ran
+    #[doc(hidden)]
+    fn last_cpu_location(
+        self,
+        topology: &Topology,
+        flags: CpuBindingFlags,
+    ) -> Result<CpuSet, HybridError<CpuBindingError>>;
+
+    /// Bind the memory of this target
+    #[doc(hidden)]
+    fn bind_memory<Set: SpecializedBitmap>(
+        self,
+        topology: &Topology,
+        set: &Set,
+        policy: MemoryBindingPolicy,
+        flags: MemoryBindingFlags,
+    ) -> Result<(), MemoryBindingError<Set>>;
+
+    /// Reset the memory binding of this target
+    #[doc(hidden)]
+    fn unbind_memory(
+        self,
+        topology: &Topology,
+        flags: MemoryBindingFlags,
+    ) -> Result<(), MemoryBindingError<NodeSet>>;
+
+    /// Query the memory binding of this target
+    #[doc(hidden)]
+    fn memory_binding<Set: SpecializedBitmap>(
+        self,
+        topology: &Topology,
+        flags: MemoryBindingFlags,
+    ) -> Result<(Set, Option<MemoryBindingPolicy>), MemoryBindingError<Set>>;
+}
+
+/// The thread that is currently running
+///
+/// Binding functions applied to this target use the `THREAD` flag.
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct CurrentThread;
+//
+impl Sealed for CurrentThread {}
+//
+impl BindingTarget for CurrentThread {
+    fn bind_cpu(
+        self,
+        topology: &Topology,
+        set: &CpuSet,
+        flags: CpuBindingFlags,
+    ) -> Result<(), HybridError<CpuBindingError>> {
+        topology.bind_cpu(set, flags | CpuBindingFlags::THREAD)
+    }
+
+    fn cpu_binding(
+        self,
+        topology: &Topology,
+        flags: CpuBindingFlags,
+    ) -> Result<CpuSet, HybridError<CpuBindingError>> {
+        topology.cpu_binding(flags | CpuBindingFlags::THREAD)
+    }
+
+    fn last_cpu_location(
+        self,
+        topology: &Topology,
+        flags: CpuBindingFlags,
+    ) -> Result<CpuSet, HybridError<CpuBindingError>> {
+        topology.last_cpu_location(flags | CpuBindingFlags::THREAD)
+    }
+
+    fn bind_memory<Set: SpecializedBitmap>(
+        self,
+        topology: &Topology,
+        set: &Set,
+        policy: MemoryBindingPolicy,
+        flags: MemoryBindingFlags,
+    ) -> Result<(), MemoryBindingError<Set>> {
+        topology.bind_memory(set, policy, flags | MemoryBindingFlags::THREAD)
+    }
+
+    fn unbind_memory(
+        self,
+        topology: &Topology,
+        flags: MemoryBindingFlags,
+    ) -> Result<(), MemoryBindingError<NodeSet>> {
+        topology.unbind_memory(flags | MemoryBindingFlags::THREAD)
+    }
+
+    fn memory_binding<Set: SpecializedBitmap>(
+        self,
+        topology: &Topology,
+        flags: MemoryBindingFlags,
+    ) -> Result<(Set, Option<MemoryBindingPolicy>), MemoryBindingError<Set>> {
+        topology.memory_binding(flags | MemoryBindingFlags::THREAD)
+    }
+}
+
+/// The process that is currently running, including all of its threads
+///
+/// Binding functions applied to this target use the `PROCESS` flag.
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct CurrentProcess;
+//
+impl Sealed for CurrentProcess {}
+//
+impl BindingTarget for CurrentProcess {
+    fn bind_cpu(
+        self,
+        topology: &Topology,
+        set: &CpuSet,
+        flags: CpuBindingFlags,
+    ) -> Result<(), HybridError<CpuBindingError>> {
+        topology.bind_cpu(set, flags | CpuBindingFlags::PROCESS)
+    }
+
+    fn cpu_binding(
+        self,
+        topology: &Topology,
+        flags: CpuBindingFlags,
+    ) -> Result<CpuSet, HybridError<CpuBindingError>> {
+        topology.cpu_binding(flags | CpuBindingFlags::PROCESS)
+    }
+
+    fn last_cpu_location(
+        self,
+        topology: &Topology,
+        flags: CpuBindingFlags,
+    ) -> Result<CpuSet, HybridError<CpuBindingError>> {
+        topology.last_cpu_location(flags | CpuBindingFlags::PROCESS)
+    }
+
+    fn bind_memory<Set: SpecializedBitmap>(
+        self,
+        topology: &Topology,
+        set: &Set,
+        policy: MemoryBindingPolicy,
+        flags: MemoryBindingFlags,
+    ) -> Result<(), MemoryBindingError<Set>> {
+        topology.bind_memory(set, policy, flags | MemoryBindingFlags::PROCESS)
+    }
+
+    fn unbind_memory(
+        self,
+        topology: &Topology,
+        flags: MemoryBindingFlags,
+    ) -> Result<(), MemoryBindingError<NodeSet>> {
+        topology.unbind_memory(flags | MemoryBindingFlags::PROCESS)
+    }
+
+    fn memory_binding<Set: SpecializedBitmap>(
+        self,
+        topology: &Topology,
+        flags: MemoryBindingFlags,
+    ) -> Result<(Set, Option<MemoryBindingPolicy>), MemoryBindingError<Set>> {
+        topology.memory_binding(flags | MemoryBindingFlags::PROCESS)
+    }
+}
+
+/// A process, identified by its PID
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Process(pub ProcessId);
+//
+impl Sealed for Process {}
+//
+impl BindingTarget for Process {
+    fn bind_cpu(
+        self,
+        topology: &Topology,
+        set: &CpuSet,
+        flags: CpuBindingFlags,
+    ) -> Result<(), HybridError<CpuBindingError>> {
+        topology.bind_process_cpu(self.0, set, flags)
+    }
+
+    fn cpu_binding(
+        self,
+        topology: &Topology,
+        flags: CpuBindingFlags,
+    ) -> Result<CpuSet, HybridError<CpuBindingError>> {
+        topology.process_cpu_binding(self.0, flags)
+    }
+
+    fn last_cpu_location(
+        self,
+        topology: &Topology,
+        flags: CpuBindingFlags,
+    ) -> Result<CpuSet, HybridError<CpuBindingError>> {
+        topology.last_process_cpu_location(self.0, flags)
+    }
+
+    fn bind_memory<Set: SpecializedBitmap>(
+        self,
+        topology: &Topology,
+        set: &Set,
+        policy: MemoryBindingPolicy,
+        flags: MemoryBindingFlags,
+    ) -> Result<(), MemoryBindingError<Set>> {
+        topology.bind_process_memory(self.0, set, policy, flags)
+    }
+
+    fn unbind_memory(
+        self,
+        topology: &Topology,
+        flags: MemoryBindingFlags,
+    ) -> Result<(), MemoryBindingError<NodeSet>> {
+        topology.unbind_process_memory(self.0, flags)
+    }
+
+    fn memory_binding<Set: SpecializedBitmap>(
+        self,
+        topology: &Topology,
+        flags: MemoryBindingFlags,
+    ) -> Result<(Set, Option<MemoryBindingPolicy>), MemoryBindingError<Set>> {
+        topology.process_memory_binding(self.0, flags)
+    }
+}
+
+/// A thread, identified by its native handle
+///
+/// hwloc can only bind threads other than the current one to CPUs, so the
+/// memory binding functions will fail with
+/// [`MemoryBindingError::Unsupported`] on this target.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Thread(pub ThreadId);
+//
+impl Sealed for Thread {}
+//
+impl BindingTarget for Thread {
+    fn bind_cpu(
+        self,
+        topology: &Topology,
+        set: &CpuSet,
+        flags: CpuBindingFlags,
+    ) -> Result<(), HybridError<CpuBindingError>> {
+        topology.bind_thread_cpu(self.0, set, flags)
+    }
+
+    fn cpu_binding(
+        self,
+        topology: &Topology,
+        flags: CpuBindingFlags,
+    ) -> Result<CpuSet, HybridError<CpuBindingError>> {
+        topology.thread_cpu_binding(self.0, flags)
+    }
+
+    fn last_cpu_location(
+        self,
+        _topology: &Topology,
+        _flags: CpuBindingFlags,
+    ) -> Result<CpuSet, HybridError<CpuBindingError>> {
+        Err(CpuBindingError::BadObject(CpuBoundObject::Thread).into())
+    }
+
+    fn bind_memory<Set: SpecializedBitmap>(
+        self,
+        _topology: &Topology,
+        _set: &Set,
+        _policy: MemoryBindingPolicy,
+        _flags: MemoryBindingFlags,
+    ) -> Result<(), MemoryBindingError<Set>> {
+        Err(MemoryBindingError::Unsupported)
+    }
+
+    fn unbind_memory(
+        self,
+        _topology: &Topology,
+        _flags: MemoryBindingFlags,
+    ) -> Result<(), MemoryBindingError<NodeSet>> {
+        Err(MemoryBindingError::Unsupported)
+    }
+
+    fn memory_binding<Set: SpecializedBitmap>(
+        self,
+        _topology: &Topology,
+        _flags: MemoryBindingFlags,
+    ) -> Result<(Set, Option<MemoryBindingPolicy>), MemoryBindingError<Set>> {
+        Err(MemoryBindingError::Unsupported)
+    }
+}
@@ -0,0 +1,95 @@
+//! Textual formatting of [`Bitmap`]
+
+use super::{Bitmap, BitmapLike, BitmapRef, OwningBitmapRef};
+use crate::ffi;
+use std::{
+    fmt::{self, Debug, Display},
+    ptr,
+};
+
+/// # Textual formatting without allocation
+impl Bitmap {
+    /// Append the textual representation of this bitmap to `out`
+    ///
+    /// The output is the same as that of the [`Display`] implementation, e.g.
+    /// "0-11,35-" for a bitmap where indices 0 to 11 and 35 onwards are set.
+    /// But instead of allocating a temporary buffer, hwloc writes directly
+    /// into `out`, whose allocation can be reused across calls.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hwlocality::bitmaps::Bitmap;
+    ///
+    /// let mut out = String::new();
+    /// for bitmap in [Bitmap::from_range(12..=34), Bitmap::full()] {
+    ///     out.clear();
+    ///     bitmap.write_list_string(&mut out);
+    ///     assert_eq!(out, bitmap.to_string());
+    /// }
+    /// ```
+    #[doc(alias = "hwloc_bitmap_list_snprintf")]
+    pub fn write_list_string(&self, out: &mut String) {
+        let len_i32 = unsafe { ffi::hwloc_bitmap_list_snprintf(ptr::null_mut(), 0, self.as_ptr()) };
+        let len = usize::try_from(len_i32)
+            .expect("Got invalid string length from hwloc_bitmap_list_snprintf");
+        // SAFETY: hwloc only writes ASCII characters, and the trailing NUL is
+        //         excluded from the final length, so out remains valid UTF-8
+        unsafe {
+            let buf = out.as_mut_vec();
+            buf.reserve(len + 1);
+            let start = buf.len();
+            assert_eq!(
+                ffi::hwloc_bitmap_list_snprintf(
+                    buf.as_mut_ptr().add(start).cast(),
+                    len + 1,
+                    self.as_ptr()
+                ),
+                len_i32,
+                "Got inconsistent string length from hwloc_bitmap_list_snprintf"
+            );
+            buf.set_len(start + len);
+        }
+    }
+}
+
+impl Debug for Bitmap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        <Self as Display>::fmt(self, f)
+    }
+}
+
+impl Display for Bitmap {
+    #[doc(alias = "hwloc_bitmap_list_snprintf")]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        ffi::write_snprintf(f, |buf, len| unsafe {
+            ffi::hwloc_bitmap_list_snprintf(buf, len, self.as_ptr())
+        })
+    }
+}
+
+impl<Target: BitmapLike + Debug> Debug for BitmapRef<'_, Target> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        <Target as Debug>::fmt(self.as_ref(), f)
+    }
+}
+
+impl<Target: BitmapLike + Display> Display for BitmapRef<'_, Target> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        <Target as Display>::fmt(self.as_ref(), f)
+    }
+}
+
+impl<Target: BitmapLike + Debug> Debug for OwningBitmapRef<Target> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        <Target as Debug>::fmt(self.as_ref(), f)
+    }
+}
+
+impl<Target: BitmapLike + Display> Display for OwningBitmapRef<Target> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        <Target as Display>::fmt(self.as_ref(), f)
+    }
+}
@@ -22,8 +22,9 @@ use std::{
     convert::TryFrom,
     ffi::{c_int, c_uint},
     fmt::Debug,
+    iter::FusedIterator,
     num::{ParseIntError, TryFromIntError},
-    ops::Not,
+    ops::{Add, AddAssign, Bound, Not, RangeBounds, Sub, SubAssign},
 };
 
 /// Bitmap indices can range from 0 to an implementation-defined limit
@@ -296,17 +297,187 @@ impl BitmapIndex {
 
     // FIXME: Support more integer operations, see usize for inspiration. Don't
     //        forget traits :
-    //        Add, Sub, Mul, Div, Rem, Shl, Not, with Assign and ref versions, as
-    //        well as FromStr using from_str_radix. Also, Sum and Product with
-    //        ref version.
+    //        Mul, Div, Rem, Shl, with Assign and ref versions, as well as
+    //        FromStr using from_str_radix. Also, Sum and Product with ref
+    //        version.
     //
-    //        Offsets should be isize, so checked_(add|sub)_signed should take
-    //        isize, and Add<isize> and Sub<isize> should be a thing (unlike
-    //        usize, we don't break integer literal type inference by doint that).
+    //        Add<isize> and Sub<isize> should be a thing (unlike usize, we
+    //        don't break integer literal type inference by doint that).
     //        Multiplicands and divisors should be unsigned since sign changes
     //        are illegal. In addition to Mul/Div/Rem ops internal to BitmapIndex,
     //        BitmapIndex * or / or % usize should also be a thing.
 
+    /// Checked integer addition
+    ///
+    /// Computes `self + rhs`, returning `None` if the result is larger than
+    /// [`BitmapIndex::MAX`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hwlocality::bitmaps::BitmapIndex;
+    /// let one = BitmapIndex::MIN.checked_succ().unwrap();
+    /// assert_eq!(BitmapIndex::MIN.checked_add(one), Some(one));
+    /// assert_eq!(BitmapIndex::MAX.checked_add(one), None);
+    /// ```
+    pub const fn checked_add(self, rhs: Self) -> Option<Self> {
+        // Cannot overflow c_uint since both operands are at most c_int::MAX
+        let res = self.0 + rhs.0;
+        if res <= Self::MAX.0 {
+            Some(Self(res))
+        } else {
+            None
+        }
+    }
+
+    /// Checked addition with a signed offset
+    ///
+    /// Computes `self + rhs`, returning `None` if the result is negative or
+    /// larger than [`BitmapIndex::MAX`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hwlocality::bitmaps::BitmapIndex;
+    /// let one = BitmapIndex::MIN.checked_succ().unwrap();
+    /// assert_eq!(one.checked_add_signed(-1), Some(BitmapIndex::MIN));
+    /// assert_eq!(BitmapIndex::MIN.checked_add_signed(-1), None);
+    /// assert_eq!(BitmapIndex::MAX.checked_add_signed(1), None);
+    /// ```
+    pub fn checked_add_signed(self, rhs: isize) -> Option<Self> {
+        let res = isize::try_from(self.0).ok()?.checked_add(rhs)?;
+        Self::try_from(res).ok()
+    }
+
+    /// Checked integer subtraction
+    ///
+    /// Computes `self - rhs`, returning `None` if the result is negative.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hwlocality::bitmaps::BitmapIndex;
+    /// let one = BitmapIndex::MIN.checked_succ().unwrap();
+    /// assert_eq!(one.checked_sub(one), Some(BitmapIndex::MIN));
+    /// assert_eq!(BitmapIndex::MIN.checked_sub(one), None);
+    /// ```
+    pub const fn checked_sub(self, rhs: Self) -> Option<Self> {
+        if let Some(res) = self.0.checked_sub(rhs.0) {
+            Some(Self(res))
+        } else {
+            None
+        }
+    }
+
+    /// Saturating integer addition
+    ///
+    /// Computes `self + rhs`, saturating at [`BitmapIndex::MAX`] instead of
+    /// overflowing.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hwlocality::bitmaps::BitmapIndex;
+    /// let one = BitmapIndex::MIN.checked_succ().unwrap();
+    /// assert_eq!(BitmapIndex::MIN.saturating_add(one), one);
+    /// assert_eq!(BitmapIndex::MAX.saturating_add(one), BitmapIndex::MAX);
+    /// ```
+    pub const fn saturating_add(self, rhs: Self) -> Self {
+        if let Some(res) = self.checked_add(rhs) {
+            res
+        } else {
+            Self::MAX
+        }
+    }
+
+    /// Saturating integer subtraction
+    ///
+    /// Computes `self - rhs`, saturating at [`BitmapIndex::MIN`] instead of
+    /// overflowing.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hwlocality::bitmaps::BitmapIndex;
+    /// let one = BitmapIndex::MIN.checked_succ().unwrap();
+    /// assert_eq!(one.saturating_sub(one), BitmapIndex::MIN);
+    /// assert_eq!(BitmapIndex::MIN.saturating_sub(one), BitmapIndex::MIN);
+    /// ```
+    pub const fn saturating_sub(self, rhs: Self) -> Self {
+        Self(self.0.saturating_sub(rhs.0))
+    }
+
+    /// Wrapping (modular) addition
+    ///
+    /// Computes `self + rhs`, wrapping around at the boundary of the type,
+    /// i.e. modulo 2<sup>[`EFFECTIVE_BITS`](Self::EFFECTIVE_BITS)</sup>.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hwlocality::bitmaps::BitmapIndex;
+    /// let one = BitmapIndex::MIN.checked_succ().unwrap();
+    /// assert_eq!(BitmapIndex::MIN.wrapping_add(one), one);
+    /// assert_eq!(BitmapIndex::MAX.wrapping_add(one), BitmapIndex::MIN);
+    /// ```
+    pub const fn wrapping_add(self, rhs: Self) -> Self {
+        Self(self.0.wrapping_add(rhs.0) & Self::MAX.0)
+    }
+
+    /// Wrapping (modular) subtraction
+    ///
+    /// Computes `self - rhs`, wrapping around at the boundary of the type,
+    /// i.e. modulo 2<sup>[`EFFECTIVE_BITS`](Self::EFFECTIVE_BITS)</sup>.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hwlocality::bitmaps::BitmapIndex;
+    /// let one = BitmapIndex::MIN.checked_succ().unwrap();
+    /// assert_eq!(one.wrapping_sub(one), BitmapIndex::MIN);
+    /// assert_eq!(BitmapIndex::MIN.wrapping_sub(one), BitmapIndex::MAX);
+    /// ```
+    pub const fn wrapping_sub(self, rhs: Self) -> Self {
+        Self(self.0.wrapping_sub(rhs.0) & Self::MAX.0)
+    }
+
+    /// Iterate over a range of bitmap indices
+    ///
+    /// This is a stable alternative to iterating over `start..end` directly,
+    /// which requires the unstable `Step` trait. Unbounded ranges are bounded
+    /// by [`BitmapIndex::MIN`] and [`BitmapIndex::MAX`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hwlocality::bitmaps::BitmapIndex;
+    /// let start = BitmapIndex::try_from(2usize)?;
+    /// let end = BitmapIndex::try_from(5usize)?;
+    /// let indices = BitmapIndex::iter_range(start..end)
+    ///     .map(usize::from)
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(indices, [2, 3, 4]);
+    /// assert_eq!(BitmapIndex::iter_range(start..=end).rev().next(), Some(end));
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn iter_range(
+        range: impl RangeBounds<Self>,
+    ) -> impl DoubleEndedIterator<Item = Self> + Clone + ExactSizeIterator + FusedIterator {
+        // Bounds are converted to a half-open c_uint range, which cannot
+        // overflow since BitmapIndex::MAX is smaller than c_uint::MAX
+        let start = match range.start_bound() {
+            Bound::Included(start) => start.0,
+            Bound::Excluded(start) => start.0 + 1,
+            Bound::Unbounded => Self::MIN.0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => end.0 + 1,
+            Bound::Excluded(end) => end.0,
+            Bound::Unbounded => Self::MAX.0 + 1,
+        };
+        (start..end).map(Self)
+    }
+
     /// Like [`uN::checked_add(1)`], but enforces bitmap index limits
     pub const fn checked_succ(self) -> Option<Self> {
         if self.0 < Self::MAX.0 {
@@ -326,19 +497,11 @@ impl BitmapIndex {
     }
 
     /// Convert from an hwloc-originated c_int
-    ///
-    /// This is not a TryFrom implementation because that would make Bitmap
-    /// indexing accept negative indexing without a compile-time error.
     pub(crate) fn try_from_c_int(x: c_int) -> Result<Self, TryFromIntError> {
         x.try_into().map(Self)
     }
 
     /// Convert from an hwloc-originated c_uint
-    ///
-    /// This is not a TryFrom implementation because it would lead integer
-    /// type inference to fall back to i32 in bitmap indexing, which is
-    /// undesirable. Also, I'd rather avoid having an implementation-specific
-    /// type in a public API like TryFrom...
     #[allow(unused)]
     fn try_from_c_uint(x: c_uint) -> Result<Self, TryFromIntError> {
         Self::try_from_c_int(x.try_into()?)
@@ -393,6 +556,51 @@ impl From<BitmapIndex> for usize {
     }
 }
 
+impl Add for BitmapIndex {
+    type Output = Self;
+
+    /// Add two bitmap indices
+    ///
+    /// # Panics
+    ///
+    /// If the result is larger than [`BitmapIndex::MAX`]. Use
+    /// [`checked_add()`](Self::checked_add()),
+    /// [`saturating_add()`](Self::saturating_add()) or
+    /// [`wrapping_add()`](Self::wrapping_add()) to handle overflow differently.
+    fn add(self, rhs: Self) -> Self::Output {
+        self.checked_add(rhs)
+            .expect("Attempted to add with overflow")
+    }
+}
+//
+impl AddAssign for BitmapIndex {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for BitmapIndex {
+    type Output = Self;
+
+    /// Subtract two bitmap indices
+    ///
+    /// # Panics
+    ///
+    /// If the result is negative. Use [`checked_sub()`](Self::checked_sub()),
+    /// [`saturating_sub()`](Self::saturating_sub()) or
+    /// [`wrapping_sub()`](Self::wrapping_sub()) to handle overflow differently.
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.checked_sub(rhs)
+            .expect("Attempted to subtract with overflow")
+    }
+}
+//
+impl SubAssign for BitmapIndex {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
 impl Not for BitmapIndex {
     type Output = Self;
 
@@ -421,15 +629,22 @@ impl PartialOrd<usize> for BitmapIndex {
     }
 }
 
-// NOTE: Only implementing TryFrom<usize> for the same reason slices can only be
-//       indexed by usize, namely to avoid integer type inference issues
-impl TryFrom<usize> for BitmapIndex {
-    type Error = TryFromIntError;
+// NOTE: Since conversions from all integer types are implemented, integer
+//       literals used as bitmap indices fall back to i32. Negative indices are
+//       thus only rejected at runtime, by these conversions.
+macro_rules! try_from {
+    ( $( $int:ty ),* ) => { $(
+        impl TryFrom<$int> for BitmapIndex {
+            type Error = TryFromIntError;
 
-    fn try_from(value: usize) -> Result<Self, TryFromIntError> {
-        Self::try_from_c_int(value.try_into()?)
-    }
+            fn try_from(value: $int) -> Result<Self, TryFromIntError> {
+                Self::try_from_c_int(value.try_into()?)
+            }
+        }
+    )* };
 }
+//
+try_from!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
 
 macro_rules! try_into {
     ( $( $int:ty ),* ) => { $(
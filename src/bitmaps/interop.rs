@@ -0,0 +1,190 @@
+//! Conversions between [`Bitmap`] and external representations
+
+use super::{Bitmap, BitmapIndex};
+#[cfg(any(test, feature = "quickcheck"))]
+use quickcheck::{Arbitrary, Gen};
+use thiserror::Error;
+
+/// # Binary serialization
+impl Bitmap {
+    /// Serialize this bitmap into a compact binary format
+    ///
+    /// Unlike the textual formats of hwloc, this format is meant for embedding
+    /// bitmaps in binary protocols or shared memory control blocks. It is
+    /// stable: future versions of hwlocality will keep decoding it, and will
+    /// only emit it with the same version byte. It is laid out as follows:
+    ///
+    /// - Format version, as one byte (currently 1)
+    /// - Flags, as one byte. Bit 0 is set if all indices above the ones that
+    ///   are covered by the words below are set (infinite bitmap). Other bits
+    ///   are reserved and must be zero.
+    /// - Number of words N, as a little-endian `u32`
+    /// - N words, as little-endian `u64`s, where bit `j` of word `i` tells if
+    ///   index `64 * i + j` of the bitmap is set.
+    ///
+    /// The encoding is canonical: the last word always contains an index that
+    /// differs from the tail, so equal bitmaps always have equal encodings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hwlocality::bitmaps::Bitmap;
+    ///
+    /// let bitmap = Bitmap::from_range(12..=34);
+    /// let bytes = bitmap.to_bytes();
+    /// assert_eq!(bytes.len(), 2 + 4 + 8);
+    /// assert_eq!(Bitmap::from_bytes(&bytes)?, bitmap);
+    ///
+    /// let infinite = Bitmap::from_range(1..);
+    /// assert_eq!(Bitmap::from_bytes(&infinite.to_bytes())?, infinite);
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        // Figure out which indices must be covered by words
+        let infinite = self.weight().is_none();
+        let last_covered = if infinite {
+            self.last_unset()
+        } else {
+            self.last_set()
+        };
+        let num_words = last_covered.map_or(0, |idx| usize::from(idx) / 64 + 1);
+
+        // Collect the words
+        let mut words = vec![0u64; num_words];
+        for idx in self
+            .iter_set()
+            .map(usize::from)
+            .take_while(|&idx| idx < num_words * 64)
+        {
+            words[idx / 64] |= 1 << (idx % 64);
+        }
+
+        // Emit the encoding
+        let mut bytes = Vec::with_capacity(BITMAP_BYTES_HEADER_LEN + num_words * 8);
+        bytes.push(BITMAP_BYTES_VERSION);
+        bytes.push(if infinite { BITMAP_BYTES_INFINITE } else { 0 });
+        bytes.extend_from_slice(
+            &u32::try_from(num_words)
+                .expect("Bitmap indices should fit in u32 words")
+                .to_le_bytes(),
+        );
+        for word in words {
+            bytes.extend_from_slice(&word.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Deserialize a bitmap from the binary format of [`Bitmap::to_bytes()`]
+    ///
+    /// # Errors
+    ///
+    /// - [`BadVersion`] if `bytes` was emitted by an unknown format version.
+    /// - [`BadFlags`] if reserved flags are set.
+    /// - [`BadLength`] if `bytes` is truncated or has trailing data.
+    /// - [`TooLarge`] if `bytes` encodes bitmap indices above
+    ///   [`BitmapIndex::MAX`].
+    ///
+    /// [`BadFlags`]: BitmapBytesError::BadFlags
+    /// [`BadLength`]: BitmapBytesError::BadLength
+    /// [`BadVersion`]: BitmapBytesError::BadVersion
+    /// [`TooLarge`]: BitmapBytesError::TooLarge
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BitmapBytesError> {
+        // Decode the header
+        if bytes.len() < BITMAP_BYTES_HEADER_LEN {
+            return Err(BitmapBytesError::BadLength);
+        }
+        let (header, body) = bytes.split_at(BITMAP_BYTES_HEADER_LEN);
+        let version = header[0];
+        if version != BITMAP_BYTES_VERSION {
+            return Err(BitmapBytesError::BadVersion(version));
+        }
+        let flags = header[1];
+        if flags & !BITMAP_BYTES_INFINITE != 0 {
+            return Err(BitmapBytesError::BadFlags(flags));
+        }
+        let num_words = u32::from_le_bytes([header[2], header[3], header[4], header[5]]);
+        let num_words = usize::try_from(num_words).map_err(|_| BitmapBytesError::TooLarge)?;
+        if body.len() / 8 != num_words || body.len() % 8 != 0 {
+            return Err(BitmapBytesError::BadLength);
+        }
+
+        // Decode the words
+        let mut result = Self::new();
+        for (word_idx, word) in body.chunks_exact(8).enumerate() {
+            let mut word = u64::from_le_bytes(word.try_into().expect("Chunks have 8 bytes"));
+            while word != 0 {
+                let idx = word_idx * 64 + word.trailing_zeros() as usize;
+                let idx = BitmapIndex::try_from(idx).map_err(|_| BitmapBytesError::TooLarge)?;
+                result.set(idx);
+                word &= word - 1;
+            }
+        }
+        if flags & BITMAP_BYTES_INFINITE != 0 {
+            let tail_start =
+                BitmapIndex::try_from(num_words * 64).map_err(|_| BitmapBytesError::TooLarge)?;
+            result.set_range(tail_start..);
+        }
+        Ok(result)
+    }
+}
+
+/// Version byte of the [`Bitmap::to_bytes()`] binary format
+const BITMAP_BYTES_VERSION: u8 = 1;
+
+/// Infinite tail flag of the [`Bitmap::to_bytes()`] binary format
+const BITMAP_BYTES_INFINITE: u8 = 1;
+
+/// Header length of the [`Bitmap::to_bytes()`] binary format
+const BITMAP_BYTES_HEADER_LEN: usize = 6;
+
+/// Error while decoding the output of [`Bitmap::to_bytes()`]
+#[derive(Copy, Clone, Debug, Eq, Error, Hash, PartialEq)]
+pub enum BitmapBytesError {
+    /// Unsupported format version
+    #[error("unsupported bitmap binary format version {0}")]
+    BadVersion(u8),
+
+    /// Reserved flags are set
+    #[error("unsupported bitmap binary format flags {0:#04x}")]
+    BadFlags(u8),
+
+    /// Input is truncated or has trailing bytes
+    #[error("bitmap binary data has an unexpected length")]
+    BadLength,
+
+    /// Input contains indices above [`BitmapIndex::MAX`]
+    #[error("bitmap binary data contains indices above BitmapIndex::MAX")]
+    TooLarge,
+}
+
+#[cfg(any(test, feature = "quickcheck"))]
+impl Arbitrary for Bitmap {
+    fn arbitrary(g: &mut Gen) -> Self {
+        use std::collections::HashSet;
+
+        // Start with an arbitrary finite bitmap
+        let mut result = HashSet::<BitmapIndex>::arbitrary(g)
+            .into_iter()
+            .collect::<Bitmap>();
+
+        // Decide by coin flip to extend infinitely on the right or not
+        if bool::arbitrary(g) {
+            let last = result.last_set().unwrap_or(BitmapIndex::MIN);
+            result.set_range(last..);
+        }
+
+        result
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        // If this is infinite, start by removing the infinite part
+        let mut local = self.clone();
+        if local.weight().is_none() {
+            local.unset_range(self.last_unset().unwrap_or(BitmapIndex::MIN)..);
+        }
+
+        // Now this is finite, can convert to Vec<BitmapIndex> and use Vec's shrinker
+        let vec = local.into_iter().collect::<Vec<_>>();
+        Box::new(vec.shrink().map(|vec| vec.into_iter().collect::<Bitmap>()))
+    }
+}
@@ -1,42 +1,42 @@
 //! Bitmap API
 
-// Main docs: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__bitmap.html
+// Main docs: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__bitmap.html
 
+mod fmt;
 mod indices;
+mod interop;
+mod ops;
 
 #[cfg(doc)]
 use crate::{
-    cpu::cpusets::CpuSet,
-    memory::nodesets::NodeSet,
-    objects::TopologyObject,
-    topology::{builder::BuildFlags, Topology},
+    cpu::cpusets::CpuSet, memory::nodesets::NodeSet, objects::TopologyObject,
+    topology::builder::BuildFlags,
 };
 use crate::{
     errors,
     ffi::{self, IncompleteType},
+    topology::Topology,
     Sealed,
 };
-#[cfg(any(test, feature = "quickcheck"))]
-use quickcheck::{Arbitrary, Gen};
 use std::{
     borrow::Borrow,
     clone::Clone,
     cmp::Ordering,
     convert::TryFrom,
-    ffi::{c_int, c_uint},
-    fmt::{self, Debug, Display},
-    iter::{FromIterator, FusedIterator},
+    ffi::{c_int, c_uint, c_ulong},
+    fmt::{Debug, Display},
+    hash::{Hash, Hasher},
+    iter::{Cycle, FromIterator, FusedIterator},
     marker::PhantomData,
-    ops::{
-        BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Bound, Deref, Not,
-        RangeBounds, Sub, SubAssign,
-    },
+    ops::{BitAnd, BitOr, BitXor, Bound, Deref, Not, RangeBounds, Sub},
     ptr::NonNull,
+    sync::Arc,
 };
 
-// Re-export BitmapIndex, the fact that it's in a separate module is an
-// implementation detail / valiant attempt to fight source file growth
+// Re-export items from submodules, the fact that they are in separate modules
+// is an implementation detail / valiant attempt to fight source file growth
 pub use indices::BitmapIndex;
+pub use interop::BitmapBytesError;
 
 /// Opaque bitmap struct
 ///
@@ -531,6 +531,63 @@ impl Bitmap {
         .expect("Bitmap operation failures are handled via panics");
     }
 
+    /// Keep the `n` lowest set indices, clear the other ones
+    ///
+    /// If fewer than `n` indices are set, the bitmap is left unchanged.
+    ///
+    /// This is useful for picking the first few PUs of a [`CpuSet`]. It is
+    /// faster than clearing indices one by one, since it only needs to locate
+    /// the `n`-th set index with [`nth_set()`](Self::nth_set()) before
+    /// clearing everything above it at once.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hwlocality::bitmaps::Bitmap;
+    ///
+    /// let mut bitmap = Bitmap::from_range(12..=34);
+    /// bitmap.unset(13);
+    /// bitmap.keep_first_n(3);
+    /// assert_eq!(format!("{bitmap}"), "12,14-15");
+    ///
+    /// let mut bitmap = Bitmap::full();
+    /// bitmap.keep_first_n(4);
+    /// assert_eq!(format!("{bitmap}"), "0-3");
+    /// ```
+    pub fn keep_first_n(&mut self, n: usize) {
+        if let Some(first_cleared) = self.nth_set(n) {
+            self.truncate_at(first_cleared);
+        }
+    }
+
+    /// Clear all indices greater than or equal to `idx`
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hwlocality::bitmaps::Bitmap;
+    ///
+    /// let mut bitmap = Bitmap::from_range(12..=34);
+    /// bitmap.truncate_at(20);
+    /// assert_eq!(format!("{bitmap}"), "12-19");
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `idx` is above the implementation-defined maximum index (at least
+    /// 2^15-1, usually 2^31-1).
+    pub fn truncate_at<Idx>(&mut self, idx: Idx)
+    where
+        Idx: Copy + PartialEq + TryInto<BitmapIndex>,
+        <Idx as TryInto<BitmapIndex>>::Error: Debug,
+    {
+        self.unset_range(idx..);
+    }
+
     /// Check if index `idx` is set
     ///
     /// # Examples
@@ -641,6 +698,75 @@ impl Bitmap {
         BitmapIterator::new(self, Bitmap::next_set)
     }
 
+    /// Check the `n`-th set index (starting from 0), if any
+    ///
+    /// This is equivalent to `self.iter_set().nth(n)`, but works one machine
+    /// word at a time, so its cost is proportional to the number of words
+    /// that precede the result rather than to `n`.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hwlocality::bitmaps::Bitmap;
+    ///
+    /// let nth_set_usize = |b: &Bitmap, n| b.nth_set(n).map(usize::from);
+    /// let bitmap = Bitmap::from_range(12..=34);
+    /// assert_eq!(nth_set_usize(&bitmap, 0), Some(12));
+    /// assert_eq!(nth_set_usize(&bitmap, 22), Some(34));
+    /// assert_eq!(bitmap.nth_set(23), None);
+    /// assert_eq!(nth_set_usize(&Bitmap::full(), 1000), Some(1000));
+    /// ```
+    pub fn nth_set(&self, mut n: usize) -> Option<BitmapIndex> {
+        // Finite bitmaps may not have enough set indices
+        if self.weight().map_or(false, |weight| n >= weight) {
+            return None;
+        }
+
+        // Otherwise, look for the word that contains the n-th set index
+        let word_bits = usize::try_from(c_ulong::BITS).expect("Should fit in usize");
+        let num_words = usize::from(BitmapIndex::MAX) / word_bits + 1;
+        for word_idx in 0..num_words {
+            let raw_word_idx = c_uint::try_from(word_idx).expect("Should fit in c_uint");
+            let mut word = self.word(raw_word_idx);
+            let word_weight = usize::try_from(word.count_ones()).expect("Should fit in usize");
+            if n < word_weight {
+                // Clear the lower set bits to find the n-th one
+                for _ in 0..n {
+                    word &= word - 1;
+                }
+                let bit_idx = usize::try_from(word.trailing_zeros()).expect("Should fit in usize");
+                return BitmapIndex::try_from(word_idx * word_bits + bit_idx).ok();
+            }
+            n -= word_weight;
+        }
+        None
+    }
+
+    /// Cycle through set indices forever
+    ///
+    /// This is useful for spreading work items like connections or queues
+    /// over the indices of a bitmap in round-robin fashion. If no index is
+    /// set, the iterator yields nothing. If the bitmap is infinite, this is
+    /// equivalent to [`Bitmap::iter_set()`].
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hwlocality::bitmaps::Bitmap;
+    ///
+    /// let bitmap = Bitmap::from_range(12..=13);
+    /// let indices = bitmap.cycle_set().take(5).map(usize::from).collect::<Vec<_>>();
+    /// assert_eq!(indices, &[12, 13, 12, 13, 12]);
+    /// assert_eq!(Bitmap::new().cycle_set().next(), None);
+    /// ```
+    pub fn cycle_set(&self) -> Cycle<BitmapIterator<&Bitmap>> {
+        self.iter_set().cycle()
+    }
+
     /// Check the last set index, if any
     ///
     /// # Examples
@@ -748,6 +874,95 @@ impl Bitmap {
         BitmapIndex::try_from_c_int(result).ok()
     }
 
+    /// Classify the bitmap according to which indices are set
+    ///
+    /// Since bitmaps which are infinite always have all indices set after some
+    /// point, they only have a finite number of unset indices, which is
+    /// reported by this method.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hwlocality::bitmaps::{Bitmap, BitmapClass};
+    ///
+    /// assert_eq!(Bitmap::new().classify(), BitmapClass::Empty);
+    /// assert_eq!(Bitmap::from_range(12..34).classify(), BitmapClass::Finite(34-12));
+    /// assert_eq!(
+    ///     Bitmap::from_range(12..).classify(),
+    ///     BitmapClass::CoFinite { unset_weight: 12 }
+    /// );
+    /// assert_eq!(Bitmap::full().classify(), BitmapClass::Full);
+    /// ```
+    pub fn classify(&self) -> BitmapClass {
+        match self.weight() {
+            Some(0) => BitmapClass::Empty,
+            Some(weight) => BitmapClass::Finite(weight),
+            None if self.is_full() => BitmapClass::Full,
+            None => BitmapClass::CoFinite {
+                unset_weight: (!self)
+                    .weight()
+                    .expect("Inverse of an infinite bitmap should be finite"),
+            },
+        }
+    }
+
+    /// Set indices that come before the infinite range of set indices
+    ///
+    /// For finite bitmaps, this is a copy of the bitmap. For infinite bitmaps,
+    /// this is a copy of the bitmap where the indices starting at
+    /// [`infinite_start()`](Self::infinite_start()) are unset.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hwlocality::bitmaps::Bitmap;
+    ///
+    /// let mut bitmap = Bitmap::from_range(12..=34);
+    /// assert_eq!(bitmap.finite_part(), bitmap);
+    /// bitmap.set_range(56..);
+    /// assert_eq!(bitmap.finite_part(), Bitmap::from_range(12..=34));
+    /// assert_eq!(Bitmap::full().finite_part(), Bitmap::new());
+    /// ```
+    pub fn finite_part(&self) -> Self {
+        let mut result = self.clone();
+        if let Some(start) = self.infinite_start() {
+            result.unset_range(start..);
+        }
+        result
+    }
+
+    /// First index of the infinite range of set indices, if any
+    ///
+    /// All indices starting at this one are set, and the index right before
+    /// it (if any) is unset. Finite bitmaps return `None`.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hwlocality::bitmaps::{Bitmap, BitmapIndex};
+    ///
+    /// let infinite_start_usize = |b: Bitmap| b.infinite_start().map(usize::from);
+    /// assert_eq!(Bitmap::from_range(12..=34).infinite_start(), None);
+    /// assert_eq!(infinite_start_usize(Bitmap::from_range(12..)), Some(12));
+    /// assert_eq!(Bitmap::full().infinite_start(), Some(BitmapIndex::MIN));
+    /// ```
+    pub fn infinite_start(&self) -> Option<BitmapIndex> {
+        if self.weight().is_some() {
+            return None;
+        }
+        Some(self.last_unset().map_or(BitmapIndex::MIN, |last_unset| {
+            last_unset
+                .checked_succ()
+                .expect("Infinite bitmaps should have a set index after the last unset one")
+        }))
+    }
+
     /// Inverts the current `Bitmap`.
     ///
     /// # Examples
@@ -789,6 +1004,47 @@ impl Bitmap {
         .expect("Should not involve faillible syscalls")
     }
 
+    /// Number of indices that are set in both `self` and `rhs`
+    ///
+    /// This is equivalent to `(self & rhs).weight()`, but does not allocate a
+    /// temporary bitmap. None means that an infinite number of indices are set
+    /// in both bitmaps.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hwlocality::bitmaps::Bitmap;
+    ///
+    /// let bitmap1 = Bitmap::from_range(12..=34);
+    /// let bitmap2 = Bitmap::from_range(30..=78);
+    /// assert_eq!(bitmap1.intersection_weight(&bitmap2), Some(5));
+    /// assert_eq!(bitmap1.intersection_weight(&Bitmap::full()), Some(23));
+    /// assert_eq!(Bitmap::full().intersection_weight(&Bitmap::full()), None);
+    /// ```
+    pub fn intersection_weight(&self, rhs: &Self) -> Option<usize> {
+        self.combined_weight(rhs, |word1, word2| word1 & word2)
+    }
+
+    /// Number of indices that are set in `self`, `rhs` or both
+    ///
+    /// This is equivalent to `(self | rhs).weight()`, but does not allocate a
+    /// temporary bitmap. None means that an infinite number of indices are set
+    /// in one of the bitmaps.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hwlocality::bitmaps::Bitmap;
+    ///
+    /// let bitmap1 = Bitmap::from_range(12..=34);
+    /// let bitmap2 = Bitmap::from_range(30..=78);
+    /// assert_eq!(bitmap1.union_weight(&bitmap2), Some(67));
+    /// assert_eq!(bitmap1.union_weight(&Bitmap::from_range(100..)), None);
+    /// ```
+    pub fn union_weight(&self, rhs: &Self) -> Option<usize> {
+        self.combined_weight(rhs, |word1, word2| word1 | word2)
+    }
+
     /// Truth that the indices set in `inner` are a subset of those set in `self`.
     ///
     /// The empty bitmap is considered included in any other bitmap.
@@ -878,139 +1134,54 @@ impl Bitmap {
         })
     }
 
-    /// Unset index iterator building block
-    fn next_unset(&self, index: Option<BitmapIndex>) -> Option<BitmapIndex> {
-        self.next(index, |bitmap, prev| unsafe {
-            ffi::hwloc_bitmap_next_unset(bitmap, prev)
-        })
-    }
-}
-
-#[cfg(any(test, feature = "quickcheck"))]
-impl Arbitrary for Bitmap {
-    fn arbitrary(g: &mut Gen) -> Self {
-        use std::collections::HashSet;
-
-        // Start with an arbitrary finite bitmap
-        let mut result = HashSet::<BitmapIndex>::arbitrary(g)
-            .into_iter()
-            .collect::<Bitmap>();
-
-        // Decide by coin flip to extend infinitely on the right or not
-        if bool::arbitrary(g) {
-            let last = result.last_set().unwrap_or(BitmapIndex::MIN);
-            result.set_range(last..);
+    /// Weight of a bitwise combination of `self` and `rhs`, computed one
+    /// machine word at a time without allocating the combined bitmap
+    ///
+    /// `combine` must map zero words to a zero word.
+    fn combined_weight(
+        &self,
+        rhs: &Self,
+        combine: impl Fn(c_ulong, c_ulong) -> c_ulong,
+    ) -> Option<usize> {
+        // Words beyond the covered words are equal to the infinite tail
+        let tail_idx = self.num_covered_words().max(rhs.num_covered_words());
+
+        // An infinite tail in the combination means infinite weight
+        if combine(self.word(tail_idx), rhs.word(tail_idx)) != 0 {
+            return None;
         }
 
-        result
-    }
-
-    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
-        // If this is infinite, start by removing the infinite part
-        let mut local = self.clone();
-        if local.weight().is_none() {
-            local.unset_range(self.last_unset().unwrap_or(BitmapIndex::MIN)..);
+        // Otherwise, sum up the weight of the covered words
+        let mut weight = 0;
+        for idx in 0..tail_idx {
+            let combined = combine(self.word(idx), rhs.word(idx));
+            weight += usize::try_from(combined.count_ones()).expect("Should fit in usize");
         }
-
-        // Now this is finite, can convert to Vec<BitmapIndex> and use Vec's shrinker
-        let vec = local.into_iter().collect::<Vec<_>>();
-        Box::new(vec.shrink().map(|vec| vec.into_iter().collect::<Bitmap>()))
-    }
-}
-
-impl<B: Borrow<Bitmap>> BitAnd<B> for &Bitmap {
-    type Output = Bitmap;
-
-    #[doc(alias = "hwloc_bitmap_and")]
-    fn bitand(self, rhs: B) -> Bitmap {
-        let mut result = Bitmap::new();
-        errors::call_hwloc_int_normal("hwloc_bitmap_and", || unsafe {
-            ffi::hwloc_bitmap_and(result.as_mut_ptr(), self.as_ptr(), rhs.borrow().as_ptr())
-        })
-        .expect("Bitmap operation failures are handled via panics");
-        result
-    }
-}
-
-impl<B: Borrow<Bitmap>> BitAnd<B> for Bitmap {
-    type Output = Bitmap;
-
-    fn bitand(mut self, rhs: B) -> Bitmap {
-        self &= rhs.borrow();
-        self
-    }
-}
-
-impl<B: Borrow<Bitmap>> BitAndAssign<B> for Bitmap {
-    fn bitand_assign(&mut self, rhs: B) {
-        errors::call_hwloc_int_normal("hwloc_bitmap_and", || unsafe {
-            ffi::hwloc_bitmap_and(self.as_mut_ptr(), self.as_ptr(), rhs.borrow().as_ptr())
-        })
-        .expect("Bitmap operation failures are handled via panics");
-    }
-}
-
-impl<B: Borrow<Bitmap>> BitOr<B> for &Bitmap {
-    type Output = Bitmap;
-
-    #[doc(alias = "hwloc_bitmap_or")]
-    fn bitor(self, rhs: B) -> Bitmap {
-        let mut result = Bitmap::new();
-        errors::call_hwloc_int_normal("hwloc_bitmap_or", || unsafe {
-            ffi::hwloc_bitmap_or(result.as_mut_ptr(), self.as_ptr(), rhs.borrow().as_ptr())
-        })
-        .expect("Bitmap operation failures are handled via panics");
-        result
-    }
-}
-
-impl<B: Borrow<Bitmap>> BitOr<B> for Bitmap {
-    type Output = Bitmap;
-
-    fn bitor(mut self, rhs: B) -> Bitmap {
-        self |= rhs.borrow();
-        self
-    }
-}
-
-impl<B: Borrow<Bitmap>> BitOrAssign<B> for Bitmap {
-    fn bitor_assign(&mut self, rhs: B) {
-        errors::call_hwloc_int_normal("hwloc_bitmap_or", || unsafe {
-            ffi::hwloc_bitmap_or(self.as_mut_ptr(), self.as_ptr(), rhs.borrow().as_ptr())
-        })
-        .expect("Bitmap operation failures are handled via panics");
+        Some(weight)
     }
-}
-
-impl<B: Borrow<Bitmap>> BitXor<B> for &Bitmap {
-    type Output = Bitmap;
 
-    #[doc(alias = "hwloc_bitmap_xor")]
-    fn bitxor(self, rhs: B) -> Bitmap {
-        let mut result = Bitmap::new();
-        errors::call_hwloc_int_normal("hwloc_bitmap_xor", || unsafe {
-            ffi::hwloc_bitmap_xor(result.as_mut_ptr(), self.as_ptr(), rhs.borrow().as_ptr())
-        })
-        .expect("Bitmap operation failures are handled via panics");
-        result
+    /// Number of machine words needed to cover all indices that differ from
+    /// the infinite tail of the bitmap (all set or all unset)
+    fn num_covered_words(&self) -> c_uint {
+        // last_set() is only None for empty and infinite bitmaps, and then
+        // last_unset() is None for the empty and full bitmaps. Checking this
+        // way avoids computing the weight, which scans the whole bitmap.
+        let last_covered = self.last_set().or_else(|| self.last_unset());
+        let word_bits = usize::try_from(c_ulong::BITS).expect("Should fit in usize");
+        let num_words = last_covered.map_or(0, |idx| usize::from(idx) / word_bits + 1);
+        c_uint::try_from(num_words).expect("Should fit in c_uint since bitmap indices do")
     }
-}
 
-impl<B: Borrow<Bitmap>> BitXor<B> for Bitmap {
-    type Output = Bitmap;
-
-    fn bitxor(mut self, rhs: B) -> Bitmap {
-        self ^= rhs.borrow();
-        self
+    /// Machine word number `idx` of the bitmap
+    fn word(&self, idx: c_uint) -> c_ulong {
+        unsafe { ffi::hwloc_bitmap_to_ith_ulong(self.as_ptr(), idx) }
     }
-}
 
-impl<B: Borrow<Bitmap>> BitXorAssign<B> for Bitmap {
-    fn bitxor_assign(&mut self, rhs: B) {
-        errors::call_hwloc_int_normal("hwloc_bitmap_xor", || unsafe {
-            ffi::hwloc_bitmap_xor(self.as_mut_ptr(), self.as_ptr(), rhs.borrow().as_ptr())
+    /// Unset index iterator building block
+    fn next_unset(&self, index: Option<BitmapIndex>) -> Option<BitmapIndex> {
+        self.next(index, |bitmap, prev| unsafe {
+            ffi::hwloc_bitmap_next_unset(bitmap, prev)
         })
-        .expect("Bitmap operation failures are handled via panics");
     }
 }
 
@@ -1027,27 +1198,12 @@ impl Clone for Bitmap {
     }
 }
 
-impl Debug for Bitmap {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        <Self as Display>::fmt(self, f)
-    }
-}
-
 impl Default for Bitmap {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl Display for Bitmap {
-    #[doc(alias = "hwloc_bitmap_list_snprintf")]
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        ffi::write_snprintf(f, |buf, len| unsafe {
-            ffi::hwloc_bitmap_list_snprintf(buf, len, self.as_ptr())
-        })
-    }
-}
-
 impl Drop for Bitmap {
     #[doc(alias = "hwloc_bitmap_free")]
     fn drop(&mut self) {
@@ -1079,6 +1235,38 @@ impl<BI: Borrow<BitmapIndex>> FromIterator<BI> for Bitmap {
     }
 }
 
+impl Hash for Bitmap {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Equal bitmaps have the same covered words and infinite tail
+        for idx in 0..=self.num_covered_words() {
+            self.word(idx).hash(state);
+        }
+    }
+}
+
+/// Summary of which indices are set in a [`Bitmap`]
+///
+/// Returned by [`Bitmap::classify()`].
+///
+/// This functionality is unique to the Rust hwloc bindings.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum BitmapClass {
+    /// No index is set
+    Empty,
+
+    /// A finite, nonzero number of indices is set
+    Finite(usize),
+
+    /// All indices are set, except for a finite, nonzero number of them
+    CoFinite {
+        /// Number of unset indices
+        unset_weight: usize,
+    },
+
+    /// All indices are set
+    Full,
+}
+
 /// Iterator over set or unset [`Bitmap`] indices
 #[derive(Copy, Clone)]
 pub struct BitmapIterator<B> {
@@ -1131,29 +1319,6 @@ impl IntoIterator for Bitmap {
     }
 }
 
-impl Not for &Bitmap {
-    type Output = Bitmap;
-
-    #[doc(alias = "hwloc_bitmap_not")]
-    fn not(self) -> Bitmap {
-        let mut result = Bitmap::new();
-        errors::call_hwloc_int_normal("hwloc_bitmap_not", || unsafe {
-            ffi::hwloc_bitmap_not(result.as_mut_ptr(), self.as_ptr())
-        })
-        .expect("Bitmap operation failures are handled via panics");
-        result
-    }
-}
-
-impl Not for Bitmap {
-    type Output = Bitmap;
-
-    fn not(mut self) -> Self {
-        self.invert();
-        self
-    }
-}
-
 impl Ord for Bitmap {
     #[doc(alias = "hwloc_bitmap_compare")]
     fn cmp(&self, other: &Self) -> Ordering {
@@ -1185,38 +1350,6 @@ impl<B: Borrow<Bitmap>> PartialOrd<B> for Bitmap {
 
 unsafe impl Send for Bitmap {}
 
-impl<B: Borrow<Bitmap>> Sub<B> for &Bitmap {
-    type Output = Bitmap;
-
-    #[doc(alias = "hwloc_bitmap_andnot")]
-    fn sub(self, rhs: B) -> Bitmap {
-        let mut result = Bitmap::new();
-        errors::call_hwloc_int_normal("hwloc_bitmap_andnot", || unsafe {
-            ffi::hwloc_bitmap_andnot(result.as_mut_ptr(), self.as_ptr(), rhs.borrow().as_ptr())
-        })
-        .expect("Bitmap operation failures are handled via panics");
-        result
-    }
-}
-
-impl<B: Borrow<Bitmap>> Sub<B> for Bitmap {
-    type Output = Bitmap;
-
-    fn sub(mut self, rhs: B) -> Bitmap {
-        self -= rhs.borrow();
-        self
-    }
-}
-
-impl<B: Borrow<Bitmap>> SubAssign<B> for Bitmap {
-    fn sub_assign(&mut self, rhs: B) {
-        errors::call_hwloc_int_normal("hwloc_bitmap_andnot", || unsafe {
-            ffi::hwloc_bitmap_andnot(self.as_mut_ptr(), self.as_ptr(), rhs.borrow().as_ptr())
-        })
-        .expect("Bitmap operation failures are handled via panics");
-    }
-}
-
 unsafe impl Sync for Bitmap {}
 
 /// Bitmap or a specialized form thereof
@@ -1374,12 +1507,6 @@ impl<'target> Borrow<BitmapRef<'target, Bitmap>> for Bitmap {
 
 // SAFETY: Do not implement Clone, or the Borrow impl above will open the door to UB.
 
-impl<Target: BitmapLike + Debug> Debug for BitmapRef<'_, Target> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        <Target as Debug>::fmt(self.as_ref(), f)
-    }
-}
-
 impl<Target: BitmapLike> Deref for BitmapRef<'_, Target> {
     type Target = Target;
 
@@ -1388,14 +1515,14 @@ impl<Target: BitmapLike> Deref for BitmapRef<'_, Target> {
     }
 }
 
-impl<Target: BitmapLike + Display> Display for BitmapRef<'_, Target> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        <Target as Display>::fmt(self.as_ref(), f)
+impl<Target: BitmapLike + Eq + PartialEq<Self>> Eq for BitmapRef<'_, Target> {}
+
+impl<Target: BitmapLike + Hash> Hash for BitmapRef<'_, Target> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_ref().hash(state)
     }
 }
 
-impl<Target: BitmapLike + Eq + PartialEq<Self>> Eq for BitmapRef<'_, Target> {}
-
 impl<'target, Target: BitmapLike> From<&'target Target> for BitmapRef<'target, Target> {
     fn from(input: &'target Target) -> Self {
         Self(input.as_raw(), PhantomData)
@@ -1517,6 +1644,150 @@ where
     }
 }
 
+/// Read-only reference to a [`Bitmap`]-like `Target` that is owned by a
+/// reference-counted [`Topology`]
+///
+/// A [`BitmapRef`] borrows from the [`Topology`] that owns the target bitmap,
+/// which makes it hard to store in long-lived structs alongside the topology.
+/// This type avoids the issue by keeping the topology alive through an
+/// `Arc<Topology>`, which also guarantees that the topology cannot be modified
+/// while the bitmap is being referenced.
+///
+/// The target bitmap is selected by a projection from the topology, much like
+/// a `yoke`:
+///
+/// ```
+/// # use hwlocality::{
+/// #     bitmaps::OwningBitmapRef,
+/// #     cpu::cpusets::CpuSet,
+/// #     objects::types::ObjectType,
+/// #     Topology,
+/// # };
+/// # use std::sync::Arc;
+/// let topology = Arc::new(Topology::new()?);
+///
+/// // Topology-wide CPU set
+/// let cpuset = OwningBitmapRef::new(topology.clone(), Topology::cpuset);
+///
+/// // CPU set of some topology object
+/// let first_pu_cpuset: Option<OwningBitmapRef<CpuSet>> =
+///     OwningBitmapRef::try_new(topology.clone(), |topology| {
+///         topology.objects_with_type(ObjectType::PU).next()?.cpuset()
+///     });
+/// assert!(cpuset.includes(&first_pu_cpuset.unwrap()));
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub struct OwningBitmapRef<Target> {
+    /// Topology that owns the target bitmap
+    topology: Arc<Topology>,
+
+    /// Target bitmap
+    bitmap: NonNull<RawBitmap>,
+
+    /// Bitmap type marker
+    _target: PhantomData<Target>,
+}
+
+impl<Target: BitmapLike> OwningBitmapRef<Target> {
+    /// Reference a bitmap that is owned by `topology`
+    pub fn new(
+        topology: Arc<Topology>,
+        project: impl FnOnce(&Topology) -> BitmapRef<'_, Target>,
+    ) -> Self {
+        let bitmap = project(&topology).0;
+        Self {
+            topology,
+            bitmap,
+            _target: PhantomData,
+        }
+    }
+
+    /// Reference a bitmap that may or may not be owned by `topology`
+    ///
+    /// This is typically used to reference the CPU or node set of a
+    /// [`TopologyObject`], as not all objects have such sets.
+    pub fn try_new(
+        topology: Arc<Topology>,
+        project: impl FnOnce(&Topology) -> Option<BitmapRef<'_, Target>>,
+    ) -> Option<Self> {
+        let bitmap = project(&topology)?.0;
+        Some(Self {
+            topology,
+            bitmap,
+            _target: PhantomData,
+        })
+    }
+
+    /// Topology that owns the target bitmap
+    pub fn topology(&self) -> &Arc<Topology> {
+        &self.topology
+    }
+
+    /// Borrow the target bitmap as a [`BitmapRef`]
+    pub fn as_bitmap_ref(&self) -> BitmapRef<'_, Target> {
+        BitmapRef(self.bitmap, PhantomData)
+    }
+}
+
+impl<Target: BitmapLike> AsRef<Target> for OwningBitmapRef<Target> {
+    fn as_ref(&self) -> &Target {
+        // This is safe because...
+        // - Target is effectively a repr(transparent) newtype of
+        //   NonNull<RawBitmap>, so &NonNull<RawBitmap> and &Target are
+        //   effectively the same thing after compilation.
+        // - The target bitmap is owned by the topology, which is kept alive
+        //   and cannot be modified as long as self exists since we hold an
+        //   Arc to it.
+        // - The borrow checker ensures that one cannot construct an
+        //   excessively long-lived &'a Target from &'a self.
+        unsafe { std::mem::transmute::<&NonNull<RawBitmap>, &Target>(&self.bitmap) }
+    }
+}
+
+impl<Target: BitmapLike> Borrow<Target> for OwningBitmapRef<Target> {
+    fn borrow(&self) -> &Target {
+        self.as_ref()
+    }
+}
+
+impl<Target> Clone for OwningBitmapRef<Target> {
+    fn clone(&self) -> Self {
+        Self {
+            topology: self.topology.clone(),
+            bitmap: self.bitmap,
+            _target: PhantomData,
+        }
+    }
+}
+
+impl<Target: BitmapLike> Deref for OwningBitmapRef<Target> {
+    type Target = Target;
+
+    fn deref(&self) -> &Target {
+        self.as_ref()
+    }
+}
+
+impl<Target: BitmapLike + Eq + PartialEq<Self>> Eq for OwningBitmapRef<Target> {}
+
+impl<Target: BitmapLike + Hash> Hash for OwningBitmapRef<Target> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_ref().hash(state)
+    }
+}
+
+impl<Target, Rhs> PartialEq<Rhs> for OwningBitmapRef<Target>
+where
+    Target: BitmapLike + PartialEq<Rhs>,
+{
+    fn eq(&self, other: &Rhs) -> bool {
+        self.as_ref() == other
+    }
+}
+
+unsafe impl<Target: BitmapLike + Send> Send for OwningBitmapRef<Target> {}
+unsafe impl<Target: BitmapLike + Sync> Sync for OwningBitmapRef<Target> {}
+
 /// Trait for manipulating specialized bitmaps (CpuSet, NodeSet) in a homogeneous way
 pub trait SpecializedBitmap:
     AsRef<Bitmap>
@@ -1559,6 +1830,7 @@ macro_rules! impl_bitmap_newtype {
             Default,
             Eq,
             derive_more::From,
+            Hash,
             derive_more::Into,
             derive_more::IntoIterator,
             derive_more::Not,
@@ -1771,6 +2043,24 @@ macro_rules! impl_bitmap_newtype {
                 self.0.singlify()
             }
 
+            /// Keep the `n` lowest set indices, clear the other ones
+            ///
+            /// See [`Bitmap::keep_first_n`](crate::bitmaps::Bitmap::keep_first_n).
+            pub fn keep_first_n(&mut self, n: usize) {
+                self.0.keep_first_n(n)
+            }
+
+            /// Clear all indices greater than or equal to `idx`
+            ///
+            /// See [`Bitmap::truncate_at`](crate::bitmaps::Bitmap::truncate_at).
+            pub fn truncate_at<Idx>(&mut self, idx: Idx)
+            where
+                Idx: Copy + PartialEq + TryInto<$crate::bitmaps::BitmapIndex>,
+                <Idx as TryInto<$crate::bitmaps::BitmapIndex>>::Error: std::fmt::Debug,
+            {
+                self.0.truncate_at(idx)
+            }
+
             /// Check if index `idx` is set
             ///
             /// See [`Bitmap::is_set`](crate::bitmaps::Bitmap::is_set).
@@ -1812,6 +2102,22 @@ macro_rules! impl_bitmap_newtype {
                 self.0.iter_set()
             }
 
+            /// Check the `n`-th set index (starting from 0), if any
+            ///
+            /// See [`Bitmap::nth_set`](crate::bitmaps::Bitmap::nth_set).
+            pub fn nth_set(&self, n: usize) -> Option<$crate::bitmaps::BitmapIndex> {
+                self.0.nth_set(n)
+            }
+
+            /// Cycle through set indices forever
+            ///
+            /// See [`Bitmap::cycle_set`](crate::bitmaps::Bitmap::cycle_set).
+            pub fn cycle_set(
+                &self
+            ) -> std::iter::Cycle<$crate::bitmaps::BitmapIterator<&$crate::bitmaps::Bitmap>> {
+                self.0.cycle_set()
+            }
+
             /// Check the last set index, if any
             ///
             /// See [`Bitmap::last_set`](crate::bitmaps::Bitmap::last_set).
@@ -1849,6 +2155,34 @@ macro_rules! impl_bitmap_newtype {
                 self.0.last_unset()
             }
 
+            /// Classify the bitmap according to which indices are set
+            ///
+            /// See [`Bitmap::classify`](crate::bitmaps::Bitmap::classify).
+            pub fn classify(&self) -> $crate::bitmaps::BitmapClass {
+                self.0.classify()
+            }
+
+            /// Set indices that come before the infinite range of set indices
+            ///
+            /// See [`Bitmap::finite_part`](crate::bitmaps::Bitmap::finite_part).
+            pub fn finite_part(&self) -> Self {
+                Self::from(self.0.finite_part())
+            }
+
+            /// First index of the infinite range of set indices, if any
+            ///
+            /// See [`Bitmap::infinite_start`](crate::bitmaps::Bitmap::infinite_start).
+            pub fn infinite_start(&self) -> Option<$crate::bitmaps::BitmapIndex> {
+                self.0.infinite_start()
+            }
+
+            /// Append the textual representation of this bitmap to `out`
+            ///
+            /// See [`Bitmap::write_list_string`](crate::bitmaps::Bitmap::write_list_string).
+            pub fn write_list_string(&self, out: &mut String) {
+                self.0.write_list_string(out)
+            }
+
             /// Inverts the current `Bitmap`.
             ///
             /// See [`Bitmap::invert`](crate::bitmaps::Bitmap::invert).
@@ -1863,12 +2197,42 @@ macro_rules! impl_bitmap_newtype {
                 self.0.intersects(&rhs.0)
             }
 
+            /// Number of indices that are set in both `self` and `rhs`
+            ///
+            /// See [`Bitmap::intersection_weight`](crate::bitmaps::Bitmap::intersection_weight).
+            pub fn intersection_weight(&self, rhs: &Self) -> Option<usize> {
+                self.0.intersection_weight(&rhs.0)
+            }
+
+            /// Number of indices that are set in `self`, `rhs` or both
+            ///
+            /// See [`Bitmap::union_weight`](crate::bitmaps::Bitmap::union_weight).
+            pub fn union_weight(&self, rhs: &Self) -> Option<usize> {
+                self.0.union_weight(&rhs.0)
+            }
+
             /// Truth that the indices set in `inner` are a subset of those set in `self`
             ///
             /// See [`Bitmap::includes`](crate::bitmaps::Bitmap::includes).
             pub fn includes(&self, inner: &Self) -> bool {
                 self.0.includes(&inner.0)
             }
+
+            /// Serialize this bitmap into a compact binary format
+            ///
+            /// See [`Bitmap::to_bytes`](crate::bitmaps::Bitmap::to_bytes).
+            pub fn to_bytes(&self) -> Vec<u8> {
+                self.0.to_bytes()
+            }
+
+            /// Deserialize a bitmap from the binary format of `to_bytes()`
+            ///
+            /// See [`Bitmap::from_bytes`](crate::bitmaps::Bitmap::from_bytes).
+            pub fn from_bytes(
+                bytes: &[u8]
+            ) -> Result<Self, $crate::bitmaps::BitmapBytesError> {
+                $crate::bitmaps::Bitmap::from_bytes(bytes).map(Self::from)
+            }
         }
 
         unsafe impl $crate::bitmaps::BitmapLike for $newtype {
@@ -2196,6 +2560,169 @@ mod tests {
         test_basic_inplace(&empty, &inverse);
     }
 
+    #[quickcheck]
+    fn classify(bitmap: Bitmap) {
+        let class = bitmap.classify();
+        match bitmap.weight() {
+            Some(0) => assert_eq!(class, BitmapClass::Empty),
+            Some(weight) => assert_eq!(class, BitmapClass::Finite(weight)),
+            None => {
+                let unset_weight = bitmap.iter_unset().count();
+                if unset_weight == 0 {
+                    assert_eq!(class, BitmapClass::Full);
+                } else {
+                    assert_eq!(class, BitmapClass::CoFinite { unset_weight });
+                }
+            }
+        }
+    }
+
+    #[quickcheck]
+    fn finite_part_and_infinite_start(bitmap: Bitmap) {
+        let (finite, infinite) = split_infinite_bitmap(bitmap.clone());
+        assert_eq!(bitmap.finite_part(), finite);
+        assert_eq!(
+            bitmap.infinite_start(),
+            infinite.clone().map(|range| range.start)
+        );
+
+        // The finite part and the infinite range make up the original bitmap
+        let mut rebuilt = bitmap.finite_part();
+        if let Some(start) = bitmap.infinite_start() {
+            assert!(!rebuilt.is_set(start));
+            if let Some(before) = start.checked_pred() {
+                assert!(!bitmap.is_set(before));
+            }
+            rebuilt.set_range(start..);
+        }
+        assert_eq!(rebuilt, bitmap);
+    }
+
+    #[quickcheck]
+    fn hash_consistent_with_eq(bitmap: Bitmap, other: Bitmap, index: BitmapIndex) {
+        use std::collections::hash_map::DefaultHasher;
+        fn hash(bitmap: &Bitmap) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            bitmap.hash(&mut hasher);
+            hasher.finish()
+        }
+        if bitmap == other {
+            assert_eq!(hash(&bitmap), hash(&other));
+        }
+
+        // Equal bitmaps with a different internal allocation size
+        let mut grown = bitmap.clone();
+        if bitmap.is_set(index) {
+            grown.unset(index);
+            grown.set(index);
+        } else {
+            grown.set(index);
+            grown.unset(index);
+        }
+        assert_eq!(grown, bitmap);
+        assert_eq!(hash(&grown), hash(&bitmap));
+    }
+
+    #[quickcheck]
+    fn bytes_roundtrip(bitmap: Bitmap) {
+        let bytes = bitmap.to_bytes();
+        assert_eq!(Bitmap::from_bytes(&bytes), Ok(bitmap));
+    }
+
+    #[quickcheck]
+    fn combined_weights(bitmap: Bitmap, other: Bitmap) {
+        assert_eq!(
+            bitmap.intersection_weight(&other),
+            (&bitmap & &other).weight()
+        );
+        assert_eq!(bitmap.union_weight(&other), (&bitmap | &other).weight());
+    }
+
+    // Number of set indices that can be enumerated in a reasonable time,
+    // including some of the infinite tail of infinite bitmaps
+    fn explorable_weight(bitmap: &Bitmap) -> usize {
+        bitmap.weight().unwrap_or_else(|| {
+            let finite_weight = bitmap.finite_part().weight().unwrap();
+            finite_weight + INFINITE_EXPLORE_ITERS
+        })
+    }
+
+    #[quickcheck]
+    fn nth_set_matches_iter_set(bitmap: Bitmap, n: usize) {
+        // Cover the first unreachable index of finite bitmaps too
+        let n = n % (explorable_weight(&bitmap) + 1);
+        assert_eq!(bitmap.nth_set(n), bitmap.iter_set().nth(n));
+        if bitmap.weight().is_some() {
+            assert_eq!(bitmap.nth_set(usize::MAX), None);
+        }
+    }
+
+    #[quickcheck]
+    fn keep_first_n(bitmap: Bitmap, n: usize) {
+        let n = if bitmap.weight().is_some() {
+            n
+        } else {
+            n % (explorable_weight(&bitmap) + 1)
+        };
+        let mut kept = bitmap.clone();
+        kept.keep_first_n(n);
+        assert_eq!(
+            kept.weight(),
+            Some(bitmap.weight().map_or(n, |weight| n.min(weight)))
+        );
+        assert!(kept.iter_set().eq(bitmap.iter_set().take(n)));
+    }
+
+    #[quickcheck]
+    fn truncate_at(bitmap: Bitmap, index: BitmapIndex) {
+        let mut truncated = bitmap.clone();
+        truncated.truncate_at(index);
+
+        // Only indices below `index` are kept...
+        assert!(truncated.weight().is_some());
+        assert!(truncated.last_set().map_or(true, |last| last < index));
+        assert!((&truncated - &bitmap).is_empty());
+
+        // ...and all of them are kept
+        let cleared = &bitmap - &truncated;
+        assert!(cleared.first_set().map_or(true, |first| first >= index));
+        let window_start = usize::from(index).saturating_sub(INFINITE_EXPLORE_ITERS);
+        let window_end = usize::from(index) + INFINITE_EXPLORE_ITERS;
+        for idx in (window_start..window_end).filter_map(|idx| BitmapIndex::try_from(idx).ok()) {
+            assert_eq!(truncated.is_set(idx), bitmap.is_set(idx) && idx < index);
+        }
+    }
+
+    #[test]
+    fn bytes_errors() {
+        let bytes = Bitmap::from_range(3..=70).to_bytes();
+        assert_eq!(bytes.len(), 6 + 2 * 8);
+        assert_eq!(
+            Bitmap::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(BitmapBytesError::BadLength)
+        );
+        let mut bad_version = bytes.clone();
+        bad_version[0] = 2;
+        assert_eq!(
+            Bitmap::from_bytes(&bad_version),
+            Err(BitmapBytesError::BadVersion(2))
+        );
+        let mut bad_flags = bytes;
+        bad_flags[1] = 0x80;
+        assert_eq!(
+            Bitmap::from_bytes(&bad_flags),
+            Err(BitmapBytesError::BadFlags(0x80))
+        );
+        assert_eq!(
+            Bitmap::from_bytes(&Bitmap::new().to_bytes()),
+            Ok(Bitmap::new())
+        );
+        assert_eq!(
+            Bitmap::from_bytes(&Bitmap::full().to_bytes()),
+            Ok(Bitmap::full())
+        );
+    }
+
     #[quickcheck]
     fn empty_extend(extra: HashSet<BitmapIndex>) {
         let mut extended = Bitmap::new();
@@ -0,0 +1,159 @@
+//! Set operators for [`Bitmap`]
+
+use super::Bitmap;
+use crate::{errors, ffi};
+use std::{
+    borrow::Borrow,
+    ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not, Sub, SubAssign},
+};
+
+impl<B: Borrow<Bitmap>> BitAnd<B> for &Bitmap {
+    type Output = Bitmap;
+
+    #[doc(alias = "hwloc_bitmap_and")]
+    fn bitand(self, rhs: B) -> Bitmap {
+        let mut result = Bitmap::new();
+        errors::call_hwloc_int_normal("hwloc_bitmap_and", || unsafe {
+            ffi::hwloc_bitmap_and(result.as_mut_ptr(), self.as_ptr(), rhs.borrow().as_ptr())
+        })
+        .expect("Bitmap operation failures are handled via panics");
+        result
+    }
+}
+
+impl<B: Borrow<Bitmap>> BitAnd<B> for Bitmap {
+    type Output = Bitmap;
+
+    fn bitand(mut self, rhs: B) -> Bitmap {
+        self &= rhs.borrow();
+        self
+    }
+}
+
+impl<B: Borrow<Bitmap>> BitAndAssign<B> for Bitmap {
+    fn bitand_assign(&mut self, rhs: B) {
+        errors::call_hwloc_int_normal("hwloc_bitmap_and", || unsafe {
+            ffi::hwloc_bitmap_and(self.as_mut_ptr(), self.as_ptr(), rhs.borrow().as_ptr())
+        })
+        .expect("Bitmap operation failures are handled via panics");
+    }
+}
+
+impl<B: Borrow<Bitmap>> BitOr<B> for &Bitmap {
+    type Output = Bitmap;
+
+    #[doc(alias = "hwloc_bitmap_or")]
+    fn bitor(self, rhs: B) -> Bitmap {
+        let mut result = Bitmap::new();
+        errors::call_hwloc_int_normal("hwloc_bitmap_or", || unsafe {
+            ffi::hwloc_bitmap_or(result.as_mut_ptr(), self.as_ptr(), rhs.borrow().as_ptr())
+        })
+        .expect("Bitmap operation failures are handled via panics");
+        result
+    }
+}
+
+impl<B: Borrow<Bitmap>> BitOr<B> for Bitmap {
+    type Output = Bitmap;
+
+    fn bitor(mut self, rhs: B) -> Bitmap {
+        self |= rhs.borrow();
+        self
+    }
+}
+
+impl<B: Borrow<Bitmap>> BitOrAssign<B> for Bitmap {
+    fn bitor_assign(&mut self, rhs: B) {
+        errors::call_hwloc_int_normal("hwloc_bitmap_or", || unsafe {
+            ffi::hwloc_bitmap_or(self.as_mut_ptr(), self.as_ptr(), rhs.borrow().as_ptr())
+        })
+        .expect("Bitmap operation failures are handled via panics");
+    }
+}
+
+impl<B: Borrow<Bitmap>> BitXor<B> for &Bitmap {
+    type Output = Bitmap;
+
+    #[doc(alias = "hwloc_bitmap_xor")]
+    fn bitxor(self, rhs: B) -> Bitmap {
+        let mut result = Bitmap::new();
+        errors::call_hwloc_int_normal("hwloc_bitmap_xor", || unsafe {
+            ffi::hwloc_bitmap_xor(result.as_mut_ptr(), self.as_ptr(), rhs.borrow().as_ptr())
+        })
+        .expect("Bitmap operation failures are handled via panics");
+        result
+    }
+}
+
+impl<B: Borrow<Bitmap>> BitXor<B> for Bitmap {
+    type Output = Bitmap;
+
+    fn bitxor(mut self, rhs: B) -> Bitmap {
+        self ^= rhs.borrow();
+        self
+    }
+}
+
+impl<B: Borrow<Bitmap>> BitXorAssign<B> for Bitmap {
+    fn bitxor_assign(&mut self, rhs: B) {
+        errors::call_hwloc_int_normal("hwloc_bitmap_xor", || unsafe {
+            ffi::hwloc_bitmap_xor(self.as_mut_ptr(), self.as_ptr(), rhs.borrow().as_ptr())
+        })
+        .expect("Bitmap operation failures are handled via panics");
+    }
+}
+
+impl Not for &Bitmap {
+    type Output = Bitmap;
+
+    #[doc(alias = "hwloc_bitmap_not")]
+    fn not(self) -> Bitmap {
+        let mut result = Bitmap::new();
+        errors::call_hwloc_int_normal("hwloc_bitmap_not", || unsafe {
+            ffi::hwloc_bitmap_not(result.as_mut_ptr(), self.as_ptr())
+        })
+        .expect("Bitmap operation failures are handled via panics");
+        result
+    }
+}
+
+impl Not for Bitmap {
+    type Output = Bitmap;
+
+    fn not(mut self) -> Self {
+        self.invert();
+        self
+    }
+}
+
+impl<B: Borrow<Bitmap>> Sub<B> for &Bitmap {
+    type Output = Bitmap;
+
+    #[doc(alias = "hwloc_bitmap_andnot")]
+    fn sub(self, rhs: B) -> Bitmap {
+        let mut result = Bitmap::new();
+        errors::call_hwloc_int_normal("hwloc_bitmap_andnot", || unsafe {
+            ffi::hwloc_bitmap_andnot(result.as_mut_ptr(), self.as_ptr(), rhs.borrow().as_ptr())
+        })
+        .expect("Bitmap operation failures are handled via panics");
+        result
+    }
+}
+
+impl<B: Borrow<Bitmap>> Sub<B> for Bitmap {
+    type Output = Bitmap;
+
+    fn sub(mut self, rhs: B) -> Bitmap {
+        self -= rhs.borrow();
+        self
+    }
+}
+
+impl<B: Borrow<Bitmap>> SubAssign<B> for Bitmap {
+    fn sub_assign(&mut self, rhs: B) {
+        errors::call_hwloc_int_normal("hwloc_bitmap_andnot", || unsafe {
+            ffi::hwloc_bitmap_andnot(self.as_mut_ptr(), self.as_ptr(), rhs.borrow().as_ptr())
+        })
+        .expect("Bitmap operation failures are handled via panics");
+    }
+}
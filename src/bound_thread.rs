@@ -0,0 +1,409 @@
+//! Spawning threads with CPU and memory bindings
+//!
+//! Binding a new thread to some CPUs and NUMA nodes must be done by the thread
+//! itself before it starts doing any work, which requires sending it a
+//! reference to the [`Topology`] and reporting binding errors back to the
+//! spawning thread. [`BoundThreadBuilder`] takes care of this, in a manner that
+//! mirrors [`std::thread::Builder`].
+
+use crate::{
+    cpu::{
+        binding::{CpuBindingError, CpuBindingFlags},
+        cpusets::CpuSet,
+    },
+    errors::HybridError,
+    memory::{
+        binding::{MemoryBindingError, MemoryBindingFlags, MemoryBindingPolicy},
+        nodesets::NodeSet,
+    },
+    topology::Topology,
+};
+use std::{
+    io,
+    sync::mpsc,
+    thread::{self, JoinHandle, Scope, ScopedJoinHandle, Thread},
+};
+use thiserror::Error;
+
+/// # Spawning bound threads
+impl Topology {
+    /// Spawn a thread that is bound to the CPUs of `cpuset`
+    ///
+    /// If `memory_policy` is set, the memory allocations of the new thread are
+    /// also bound to the NUMA nodes that are local to `cpuset`, using that
+    /// policy.
+    ///
+    /// Bindings are set up before `f` starts running. Use
+    /// [`BoundThreadBuilder`] for more control over the new thread.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Errors
+    ///
+    /// See [`BoundThreadBuilder::spawn()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::{cpu::cpusets::CpuSet, memory::binding::MemoryBindingPolicy};
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// let last_pu = CpuSet::from(topology.cpuset().last_set().unwrap());
+    /// let handle = topology.spawn_bound(&last_pu, Some(MemoryBindingPolicy::Bind), || {
+    ///     // ...this runs on the last PU, with memory allocated nearby...
+    /// });
+    /// match handle {
+    ///     Ok(handle) => handle.join().unwrap(),
+    ///     Err(e) => eprintln!("Could not spawn a bound thread: {e}"),
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn spawn_bound<F, T>(
+        &self,
+        cpuset: &CpuSet,
+        memory_policy: Option<MemoryBindingPolicy>,
+        f: F,
+    ) -> Result<BoundJoinHandle<T>, SpawnBoundError>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let mut builder = BoundThreadBuilder::new(self).bind_cpu(cpuset.clone());
+        if let Some(policy) = memory_policy {
+            builder = builder.bind_memory(NodeSet::from_cpuset(self, cpuset), policy);
+        }
+        builder.spawn(f)
+    }
+}
+
+/// Thread factory that sets up CPU and memory bindings
+///
+/// This mirrors [`std::thread::Builder`], with extra methods to configure the
+/// CPU and memory bindings of the new thread. Bindings are set up by the new
+/// thread before it runs any user code, and are not restored when the thread
+/// exits since the thread is gone by then.
+///
+/// This functionality is unique to the Rust hwloc bindings.
+///
+/// # Examples
+///
+/// ```
+/// # use hwlocality::{bound_thread::BoundThreadBuilder, objects::types::ObjectType};
+/// # let topology = hwlocality::Topology::test_instance();
+/// // Spawn one named worker per CPU core
+/// std::thread::scope(|scope| {
+///     for (idx, core) in topology.objects_with_type(ObjectType::Core).enumerate() {
+///         let cpuset = core.cpuset().unwrap().to_owned();
+///         let result = BoundThreadBuilder::new(&topology)
+///             .name(format!("worker{idx}"))
+///             .bind_cpu(cpuset)
+///             .spawn_scoped(scope, move || {
+///                 // ...do some work on this core...
+///             });
+///         if let Err(e) = result {
+///             eprintln!("Failed to spawn worker {idx}: {e}");
+///         }
+///     }
+/// });
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct BoundThreadBuilder<'topology> {
+    /// Topology used for binding
+    topology: &'topology Topology,
+
+    /// Underlying standard thread builder
+    builder: thread::Builder,
+
+    /// CPUs that the thread should be bound to, if any
+    cpuset: Option<CpuSet>,
+
+    /// NUMA nodes and policy that the thread's memory should be bound to, if any
+    memory: Option<(NodeSet, MemoryBindingPolicy)>,
+}
+//
+impl<'topology> BoundThreadBuilder<'topology> {
+    /// Generate a thread builder that does not set up any binding yet
+    pub fn new(topology: &'topology Topology) -> Self {
+        Self {
+            topology,
+            builder: thread::Builder::new(),
+            cpuset: None,
+            memory: None,
+        }
+    }
+
+    /// Name the thread, see [`std::thread::Builder::name()`]
+    pub fn name(mut self, name: String) -> Self {
+        self.builder = self.builder.name(name);
+        self
+    }
+
+    /// Set the thread's stack size, see [`std::thread::Builder::stack_size()`]
+    pub fn stack_size(mut self, size: usize) -> Self {
+        self.builder = self.builder.stack_size(size);
+        self
+    }
+
+    /// Bind the thread to the CPUs of `cpuset`
+    pub fn bind_cpu(mut self, cpuset: CpuSet) -> Self {
+        self.cpuset = Some(cpuset);
+        self
+    }
+
+    /// Bind the thread's memory allocations to the NUMA nodes of `nodeset`
+    /// using `policy`
+    pub fn bind_memory(mut self, nodeset: NodeSet, policy: MemoryBindingPolicy) -> Self {
+        self.memory = Some((nodeset, policy));
+        self
+    }
+
+    /// Spawn the thread, see [`std::thread::Builder::spawn()`]
+    ///
+    /// This waits for the new thread to set up its bindings.
+    ///
+    /// # Errors
+    ///
+    /// - [`Spawn`] if the operating system failed to create the thread
+    /// - [`CpuBinding`] if the thread could not be bound to the requested CPUs
+    /// - [`MemoryBinding`] if the thread's memory could not be bound to the
+    ///   requested NUMA nodes
+    ///
+    /// In the latter two cases, the thread exits without running `f`.
+    ///
+    /// [`CpuBinding`]: SpawnBoundError::CpuBinding
+    /// [`MemoryBinding`]: SpawnBoundError::MemoryBinding
+    /// [`Spawn`]: SpawnBoundError::Spawn
+    pub fn spawn<F, T>(self, f: F) -> Result<BoundJoinHandle<T>, SpawnBoundError>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let (binder, report) = self.binder();
+        let handle = self.builder.spawn(move || binder.bind().then(f))?;
+        if let Err(e) = Self::wait_for_bindings(&report) {
+            let _ = handle.join();
+            return Err(e);
+        }
+        Ok(BoundJoinHandle(handle))
+    }
+
+    /// Spawn a scoped thread, see [`std::thread::Builder::spawn_scoped()`]
+    ///
+    /// This waits for the new thread to set up its bindings.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`spawn()`](Self::spawn()).
+    pub fn spawn_scoped<'scope, 'env, F, T>(
+        self,
+        scope: &'scope Scope<'scope, 'env>,
+        f: F,
+    ) -> Result<BoundScopedJoinHandle<'scope, T>, SpawnBoundError>
+    where
+        F: FnOnce() -> T + Send + 'scope,
+        T: Send + 'scope,
+    {
+        let (binder, report) = self.binder();
+        let handle = self
+            .builder
+            .spawn_scoped(scope, move || binder.bind().then(f))?;
+        if let Err(e) = Self::wait_for_bindings(&report) {
+            let _ = handle.join();
+            return Err(e);
+        }
+        Ok(BoundScopedJoinHandle(handle))
+    }
+
+    /// Split out the binding configuration, to be sent to the new thread
+    fn binder(&self) -> (Binder, mpsc::Receiver<Result<(), SpawnBoundError>>) {
+        let (sender, receiver) = mpsc::sync_channel(1);
+        let binder = Binder {
+            topology: TopologyPtr(self.topology),
+            cpuset: self.cpuset.clone(),
+            memory: self.memory.clone(),
+            sender,
+        };
+        (binder, receiver)
+    }
+
+    /// Wait for the new thread to report the outcome of binding
+    fn wait_for_bindings(
+        report: &mpsc::Receiver<Result<(), SpawnBoundError>>,
+    ) -> Result<(), SpawnBoundError> {
+        report
+            .recv()
+            .map_err(|_| SpawnBoundError::BindingPanicked)?
+    }
+}
+
+/// Binding configuration, sent to the new thread
+struct Binder {
+    /// Topology used for binding
+    topology: TopologyPtr,
+
+    /// CPUs that the thread should be bound to, if any
+    cpuset: Option<CpuSet>,
+
+    /// NUMA nodes and policy that the thread's memory should be bound to, if any
+    memory: Option<(NodeSet, MemoryBindingPolicy)>,
+
+    /// Channel used to report the outcome of binding to the spawning thread
+    sender: mpsc::SyncSender<Result<(), SpawnBoundError>>,
+}
+//
+impl Binder {
+    /// Bind the current thread, report the outcome to the spawning thread,
+    /// and tell if binding succeeded
+    ///
+    /// If binding fails, the current thread must exit without running user
+    /// code. The spawning thread then joins it and reports the binding error.
+    fn bind(self) -> bool {
+        // SAFETY: The spawning thread keeps the topology borrowed until the
+        //         outcome of binding is reported (or the Binder is dropped due
+        //         to a panic), and we only use it until then.
+        let topology = unsafe { &*self.topology.0 };
+        let mut result = Ok(());
+        if let Some(cpuset) = &self.cpuset {
+            result = topology
+                .bind_cpu(cpuset, CpuBindingFlags::THREAD)
+                .map_err(SpawnBoundError::CpuBinding);
+        }
+        if let (Ok(()), Some((nodeset, policy))) = (&result, &self.memory) {
+            result = topology
+                .bind_memory(nodeset, *policy, MemoryBindingFlags::THREAD)
+                .map_err(SpawnBoundError::MemoryBinding);
+        }
+        let succeeded = result.is_ok();
+        let _ = self.sender.send(result);
+        succeeded
+    }
+}
+
+/// Owned permission to join on a bound thread
+///
+/// Returned by [`BoundThreadBuilder::spawn()`] and [`Topology::spawn_bound()`],
+/// this is the equivalent of [`JoinHandle`] for threads whose bindings were
+/// successfully set up.
+#[derive(Debug)]
+pub struct BoundJoinHandle<T>(JoinHandle<Option<T>>);
+//
+impl<T> BoundJoinHandle<T> {
+    /// Handle to the underlying thread, see [`JoinHandle::thread()`]
+    pub fn thread(&self) -> &Thread {
+        self.0.thread()
+    }
+
+    /// Wait for the thread to finish, see [`JoinHandle::join()`]
+    ///
+    /// # Errors
+    ///
+    /// If the thread panicked, the panic payload is returned.
+    pub fn join(self) -> thread::Result<T> {
+        self.0
+            .join()
+            .map(|result| result.expect("Bound threads always run user code"))
+    }
+
+    /// Truth that the thread has finished running, see
+    /// [`JoinHandle::is_finished()`]
+    pub fn is_finished(&self) -> bool {
+        self.0.is_finished()
+    }
+}
+
+/// Owned permission to join on a scoped bound thread
+///
+/// Returned by [`BoundThreadBuilder::spawn_scoped()`], this is the equivalent
+/// of [`ScopedJoinHandle`] for threads whose bindings were successfully set up.
+#[derive(Debug)]
+pub struct BoundScopedJoinHandle<'scope, T>(ScopedJoinHandle<'scope, Option<T>>);
+//
+impl<T> BoundScopedJoinHandle<'_, T> {
+    /// Handle to the underlying thread, see [`ScopedJoinHandle::thread()`]
+    pub fn thread(&self) -> &Thread {
+        self.0.thread()
+    }
+
+    /// Wait for the thread to finish, see [`ScopedJoinHandle::join()`]
+    ///
+    /// # Errors
+    ///
+    /// If the thread panicked, the panic payload is returned.
+    pub fn join(self) -> thread::Result<T> {
+        self.0
+            .join()
+            .map(|result| result.expect("Bound threads always run user code"))
+    }
+
+    /// Truth that the thread has finished running, see
+    /// [`ScopedJoinHandle::is_finished()`]
+    pub fn is_finished(&self) -> bool {
+        self.0.is_finished()
+    }
+}
+
+/// Topology pointer that can be sent to the new thread
+///
+/// Using a pointer rather than a reference lets the new thread use the
+/// topology without requiring it to outlive the thread. See [`Binder::bind()`]
+/// for the safety argument.
+struct TopologyPtr(*const Topology);
+//
+// SAFETY: Topology is Sync, so sharing it with another thread is fine
+unsafe impl Send for TopologyPtr {}
+
+/// Error returned when spawning a bound thread fails
+#[derive(Debug, Error)]
+pub enum SpawnBoundError {
+    /// The operating system failed to create the thread
+    #[error("failed to spawn thread")]
+    Spawn(#[from] io::Error),
+
+    /// The thread could not be bound to the requested CPUs
+    #[error("failed to bind thread to CPUs")]
+    CpuBinding(#[source] HybridError<CpuBindingError>),
+
+    /// The thread's memory could not be bound to the requested NUMA nodes
+    #[error("failed to bind thread memory to NUMA nodes")]
+    MemoryBinding(#[source] MemoryBindingError<NodeSet>),
+
+    /// The thread panicked while setting up bindings
+    #[error("thread panicked while setting up bindings")]
+    BindingPanicked,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+
+    #[test]
+    fn failed_binding_should_not_run_user_code() {
+        let topology = Topology::test_instance();
+        let ran = Arc::new(AtomicBool::new(false));
+        let ran2 = ran.clone();
+        let result = BoundThreadBuilder::new(topology)
+            .bind_cpu(CpuSet::new())
+            .spawn(move || ran2.store(true, Ordering::Relaxed));
+        assert!(matches!(result, Err(SpawnBoundError::CpuBinding(_))));
+        assert!(!ran.load(Ordering::Relaxed));
+
+        thread::scope(|scope| {
+            let result = BoundThreadBuilder::new(topology)
+                .bind_cpu(CpuSet::new())
+                .spawn_scoped(scope, || ran.store(true, Ordering::Relaxed));
+            assert!(matches!(result, Err(SpawnBoundError::CpuBinding(_))));
+        });
+        assert!(!ran.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn unbound_thread_should_return_its_result() {
+        let topology = Topology::test_instance();
+        let handle = BoundThreadBuilder::new(topology).spawn(|| 42).unwrap();
+        assert_eq!(handle.join().unwrap(), 42);
+    }
+}
@@ -0,0 +1,849 @@
+//! Coverage of the hwloc C API
+//!
+//! This module provides a table of the hwloc C entry points that hwlocality
+//! knows about, along with the Rust function or trait implementation that
+//! exposes each of them. It can be used to quickly find the Rust counterpart
+//! of some hwloc call, or by tooling that needs to detect coverage gaps.
+//!
+//! Note that many hwloc entry points are inline functions, macros or plain
+//! struct member accesses on the C side, which hwlocality reimplements in
+//! Rust. These are listed here as long as hwlocality has a direct
+//! counterpart for them.
+//!
+//! [`Bitmap`] methods listed here are also available on [`CpuSet`] and
+//! [`NodeSet`].
+//!
+//! [`Bitmap`]: crate::bitmaps::Bitmap
+//! [`CpuSet`]: crate::cpu::cpusets::CpuSet
+//! [`NodeSet`]: crate::memory::nodesets::NodeSet
+
+use Binding::{Function, Internal, TraitImpl};
+
+/// How an hwloc C entry point is exposed by hwlocality
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Binding {
+    /// Exposed as a function or method, whose path relative to the module
+    /// where it is defined is given (e.g. `"Topology::bind_cpu"`)
+    Function(&'static str),
+
+    /// Exposed as an implementation of a standard trait, which is given
+    /// (e.g. `"impl Drop for Topology"`)
+    TraitImpl(&'static str),
+
+    /// Only used internally, with no public counterpart
+    Internal,
+}
+
+/// An hwloc C entry point and how hwlocality exposes it
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ApiEntry {
+    /// Name of the hwloc entry point (e.g. `"hwloc_set_cpubind"`)
+    pub hwloc_name: &'static str,
+
+    /// How this entry point is exposed by hwlocality
+    pub binding: Binding,
+
+    /// Cargo feature that must be enabled for this binding to be available
+    pub feature: Option<&'static str>,
+
+    /// Operating system to which this binding is specific
+    pub target_os: Option<&'static str>,
+}
+
+impl ApiEntry {
+    /// Entry that is available with any feature set, on any OS
+    const fn new(hwloc_name: &'static str, binding: Binding) -> Self {
+        Self {
+            hwloc_name,
+            binding,
+            feature: None,
+            target_os: None,
+        }
+    }
+
+    /// Require a cargo feature
+    const fn feature(mut self, feature: &'static str) -> Self {
+        self.feature = Some(feature);
+        self
+    }
+
+    /// Restrict to a certain operating system
+    const fn target_os(mut self, target_os: &'static str) -> Self {
+        self.target_os = Some(target_os);
+        self
+    }
+}
+
+/// Table of all known hwloc entry points, sorted by hwloc name
+///
+/// # Examples
+///
+/// ```
+/// # use hwlocality::coverage::Binding;
+/// let internal = hwlocality::coverage()
+///     .iter()
+///     .filter(|entry| entry.binding == Binding::Internal)
+///     .count();
+/// println!("{internal} hwloc entry points have no public counterpart");
+/// ```
+pub fn coverage() -> &'static [ApiEntry] {
+    ENTRIES
+}
+
+/// Look up how a particular hwloc entry point is exposed by hwlocality
+///
+/// # Examples
+///
+/// ```
+/// # use hwlocality::coverage::{self, Binding};
+/// let entry = coverage::lookup("hwloc_set_cpubind").unwrap();
+/// assert_eq!(entry.binding, Binding::Function("Topology::bind_cpu"));
+/// ```
+pub fn lookup(hwloc_name: &str) -> Option<&'static ApiEntry> {
+    ENTRIES
+        .binary_search_by(|entry| entry.hwloc_name.cmp(hwloc_name))
+        .ok()
+        .map(|idx| &ENTRIES[idx])
+}
+
+/// Known hwloc entry points, sorted by hwloc name
+//
+// NOTE: When binding a new hwloc entry point, add it here. The unit tests
+//       below check that every entry point declared in the ffi module is
+//       listed.
+static ENTRIES: &[ApiEntry] = &[
+    ApiEntry::new("hwloc_alloc", Function("Topology::allocate_memory")),
+    ApiEntry::new(
+        "hwloc_alloc_membind",
+        Function("Topology::allocate_bound_memory"),
+    ),
+    ApiEntry::new(
+        "hwloc_alloc_membind_policy",
+        Function("Topology::binding_allocate_memory"),
+    ),
+    ApiEntry::new("hwloc_bitmap_allbut", Function("Bitmap::set_all_but")),
+    ApiEntry::new("hwloc_bitmap_alloc", Function("Bitmap::new")),
+    ApiEntry::new("hwloc_bitmap_alloc_full", Function("Bitmap::full")),
+    ApiEntry::new("hwloc_bitmap_and", TraitImpl("impl BitAnd for Bitmap")),
+    ApiEntry::new("hwloc_bitmap_andnot", TraitImpl("impl Sub for Bitmap")),
+    ApiEntry::new("hwloc_bitmap_clr", Function("Bitmap::unset")),
+    ApiEntry::new("hwloc_bitmap_clr_range", Function("Bitmap::unset_range")),
+    ApiEntry::new("hwloc_bitmap_compare", TraitImpl("impl Ord for Bitmap")),
+    ApiEntry::new("hwloc_bitmap_copy", Function("Bitmap::copy_from")),
+    ApiEntry::new("hwloc_bitmap_dup", TraitImpl("impl Clone for Bitmap")),
+    ApiEntry::new("hwloc_bitmap_fill", Function("Bitmap::fill")),
+    ApiEntry::new("hwloc_bitmap_first", Function("Bitmap::first_set")),
+    ApiEntry::new("hwloc_bitmap_first_unset", Function("Bitmap::first_unset")),
+    ApiEntry::new("hwloc_bitmap_foreach_begin", Function("Bitmap::iter_set")),
+    ApiEntry::new("hwloc_bitmap_foreach_end", Function("Bitmap::iter_set")),
+    ApiEntry::new("hwloc_bitmap_free", TraitImpl("impl Drop for Bitmap")),
+    ApiEntry::new("hwloc_bitmap_intersects", Function("Bitmap::intersects")),
+    ApiEntry::new(
+        "hwloc_bitmap_isequal",
+        TraitImpl("impl PartialEq for Bitmap"),
+    ),
+    ApiEntry::new("hwloc_bitmap_isfull", Function("Bitmap::is_full")),
+    ApiEntry::new("hwloc_bitmap_isincluded", Function("Bitmap::includes")),
+    ApiEntry::new("hwloc_bitmap_isset", Function("Bitmap::is_set")),
+    ApiEntry::new("hwloc_bitmap_iszero", Function("Bitmap::is_empty")),
+    ApiEntry::new("hwloc_bitmap_last", Function("Bitmap::last_set")),
+    ApiEntry::new("hwloc_bitmap_last_unset", Function("Bitmap::last_unset")),
+    ApiEntry::new(
+        "hwloc_bitmap_list_snprintf",
+        TraitImpl("impl Display for Bitmap"),
+    ),
+    ApiEntry::new("hwloc_bitmap_next", Function("Bitmap::iter_set")),
+    ApiEntry::new("hwloc_bitmap_next_unset", Function("Bitmap::iter_unset")),
+    ApiEntry::new("hwloc_bitmap_not", TraitImpl("impl Not for Bitmap")),
+    ApiEntry::new("hwloc_bitmap_only", Function("Bitmap::set_only")),
+    ApiEntry::new("hwloc_bitmap_or", TraitImpl("impl BitOr for Bitmap")),
+    ApiEntry::new("hwloc_bitmap_set", Function("Bitmap::set")),
+    ApiEntry::new("hwloc_bitmap_set_range", Function("Bitmap::set_range")),
+    ApiEntry::new("hwloc_bitmap_singlify", Function("Bitmap::singlify")),
+    ApiEntry::new(
+        "hwloc_bitmap_singlify_per_core",
+        Function("CpuSet::singlify_per_core"),
+    )
+    .feature("hwloc-2_2_0"),
+    ApiEntry::new("hwloc_bitmap_to_ith_ulong", Internal),
+    ApiEntry::new("hwloc_bitmap_weight", Function("Bitmap::weight")),
+    ApiEntry::new("hwloc_bitmap_xor", TraitImpl("impl BitXor for Bitmap")),
+    ApiEntry::new("hwloc_bitmap_zero", Function("Bitmap::clear")),
+    ApiEntry::new(
+        "hwloc_bridge_covers_pcibus",
+        Function("TopologyObject::is_bridge_covering_pci_bus"),
+    ),
+    ApiEntry::new(
+        "hwloc_compare_types",
+        TraitImpl("impl PartialOrd for ObjectType"),
+    ),
+    ApiEntry::new(
+        "hwloc_cpukinds_get_by_cpuset",
+        Function("Topology::cpu_kind_from_set"),
+    )
+    .feature("hwloc-2_4_0"),
+    ApiEntry::new("hwloc_cpukinds_get_info", Function("Topology::cpu_kinds"))
+        .feature("hwloc-2_4_0"),
+    ApiEntry::new("hwloc_cpukinds_get_nr", Function("Topology::num_cpu_kinds"))
+        .feature("hwloc-2_4_0"),
+    ApiEntry::new(
+        "hwloc_cpukinds_register",
+        Function("TopologyEditor::register_cpu_kind"),
+    )
+    .feature("hwloc-2_4_0"),
+    ApiEntry::new(
+        "hwloc_cpuset_from_glibc_sched_affinity",
+        Function("CpuSet::from_libc_cpuset"),
+    )
+    .target_os("linux"),
+    ApiEntry::new(
+        "hwloc_cpuset_from_nodeset",
+        Function("CpuSet::from_nodeset"),
+    ),
+    ApiEntry::new(
+        "hwloc_cpuset_to_glibc_sched_affinity",
+        Function("CpuSet::to_libc_cpuset"),
+    )
+    .target_os("linux"),
+    ApiEntry::new("hwloc_cpuset_to_nodeset", Function("NodeSet::from_cpuset")),
+    ApiEntry::new(
+        "hwloc_cuda_get_device_cpuset",
+        Function("Topology::cuda_device_cpuset"),
+    )
+    .feature("cuda"),
+    ApiEntry::new(
+        "hwloc_cuda_get_device_osdev_by_index",
+        Function("Topology::cuda_device_os_device"),
+    )
+    .feature("cuda"),
+    ApiEntry::new(
+        "hwloc_cuda_get_device_pcidev",
+        Function("Topology::cuda_device_pci_device"),
+    )
+    .feature("cuda"),
+    ApiEntry::new(
+        "hwloc_distances_add_commit",
+        Function("TopologyEditor::add_distances"),
+    )
+    .feature("hwloc-2_5_0"),
+    ApiEntry::new(
+        "hwloc_distances_add_create",
+        Function("TopologyEditor::add_distances"),
+    )
+    .feature("hwloc-2_5_0"),
+    ApiEntry::new(
+        "hwloc_distances_add_values",
+        Function("TopologyEditor::add_distances"),
+    )
+    .feature("hwloc-2_5_0"),
+    ApiEntry::new("hwloc_distances_get", Function("Topology::distances")),
+    ApiEntry::new(
+        "hwloc_distances_get_by_depth",
+        Function("Topology::distances_at_depth"),
+    ),
+    ApiEntry::new(
+        "hwloc_distances_get_by_name",
+        Function("Topology::distances_with_name"),
+    )
+    .feature("hwloc-2_1_0"),
+    ApiEntry::new(
+        "hwloc_distances_get_by_type",
+        Function("Topology::distances_with_type"),
+    ),
+    ApiEntry::new("hwloc_distances_get_name", Function("Distances::name")).feature("hwloc-2_1_0"),
+    ApiEntry::new(
+        "hwloc_distances_obj_index",
+        Function("Distances::object_idx"),
+    ),
+    ApiEntry::new(
+        "hwloc_distances_obj_pair_values",
+        Function("Distances::object_pair_distance"),
+    ),
+    ApiEntry::new(
+        "hwloc_distances_release",
+        TraitImpl("impl Drop for Distances"),
+    ),
+    ApiEntry::new(
+        "hwloc_distances_release_remove",
+        Function("TopologyEditor::remove_distances"),
+    )
+    .feature("hwloc-2_3_0"),
+    ApiEntry::new(
+        "hwloc_distances_remove",
+        Function("TopologyEditor::remove_all_distances"),
+    )
+    .feature("hwloc-2_3_0"),
+    ApiEntry::new(
+        "hwloc_distances_remove_by_depth",
+        Function("TopologyEditor::remove_distances_at_depth"),
+    )
+    .feature("hwloc-2_3_0"),
+    ApiEntry::new(
+        "hwloc_distances_remove_by_type",
+        Function("TopologyEditor::remove_distances_with_type"),
+    )
+    .feature("hwloc-2_3_0"),
+    ApiEntry::new(
+        "hwloc_distances_transform",
+        Function("Distances::transform"),
+    )
+    .feature("hwloc-2_5_0"),
+    ApiEntry::new("hwloc_distrib", Function("Topology::distribute_items")),
+    ApiEntry::new("hwloc_free", TraitImpl("impl Drop for Bytes")),
+    ApiEntry::new("hwloc_free_xmlbuffer", TraitImpl("impl Drop for XML")),
+    ApiEntry::new(
+        "hwloc_get_ancestor_obj_by_depth",
+        Function("TopologyObject::ancestor_at_depth"),
+    ),
+    ApiEntry::new(
+        "hwloc_get_ancestor_obj_by_type",
+        Function("TopologyObject::first_ancestor_with_type"),
+    ),
+    ApiEntry::new("hwloc_get_api_version", Function("get_api_version")),
+    ApiEntry::new(
+        "hwloc_get_area_membind",
+        Function("Topology::area_memory_binding"),
+    ),
+    ApiEntry::new(
+        "hwloc_get_area_memlocation",
+        Function("Topology::area_memory_location"),
+    ),
+    ApiEntry::new(
+        "hwloc_get_cache_covering_cpuset",
+        Function("Topology::first_cache_covering_cpuset"),
+    ),
+    ApiEntry::new(
+        "hwloc_get_cache_type_depth",
+        Function("Topology::depth_for_cache"),
+    ),
+    ApiEntry::new(
+        "hwloc_get_child_covering_cpuset",
+        Function("TopologyObject::normal_child_covering_cpuset"),
+    ),
+    ApiEntry::new(
+        "hwloc_get_closest_objs",
+        Function("Topology::closest_objects"),
+    ),
+    ApiEntry::new(
+        "hwloc_get_common_ancestor_obj",
+        Function("TopologyObject::common_ancestor"),
+    ),
+    ApiEntry::new("hwloc_get_cpubind", Function("Topology::cpu_binding")),
+    ApiEntry::new("hwloc_get_depth_type", Function("Topology::type_at_depth")),
+    ApiEntry::new(
+        "hwloc_get_first_largest_obj_inside_cpuset",
+        Function("Topology::first_largest_object_inside_cpuset"),
+    ),
+    ApiEntry::new(
+        "hwloc_get_largest_objs_inside_cpuset",
+        Function("Topology::coarsest_cpuset_partition"),
+    ),
+    ApiEntry::new(
+        "hwloc_get_last_cpu_location",
+        Function("Topology::last_cpu_location"),
+    ),
+    ApiEntry::new(
+        "hwloc_get_local_numanode_objs",
+        Function("Topology::local_numa_nodes"),
+    )
+    .feature("hwloc-2_3_0"),
+    ApiEntry::new("hwloc_get_membind", Function("Topology::memory_binding")),
+    ApiEntry::new(
+        "hwloc_get_memory_parents_depth",
+        Function("Topology::memory_parents_depth"),
+    ),
+    ApiEntry::new(
+        "hwloc_get_nbobjs_by_depth",
+        Function("Topology::size_at_depth"),
+    ),
+    ApiEntry::new(
+        "hwloc_get_nbobjs_by_type",
+        Function("Topology::objects_with_type"),
+    ),
+    ApiEntry::new(
+        "hwloc_get_nbobjs_inside_cpuset_by_depth",
+        Function("Topology::objects_inside_cpuset_at_depth"),
+    ),
+    ApiEntry::new(
+        "hwloc_get_nbobjs_inside_cpuset_by_type",
+        Function("Topology::objects_inside_cpuset_with_type"),
+    ),
+    ApiEntry::new("hwloc_get_next_bridge", Function("Topology::bridges")),
+    ApiEntry::new(
+        "hwloc_get_next_child",
+        Function("TopologyObject::all_children"),
+    ),
+    ApiEntry::new(
+        "hwloc_get_next_obj_by_depth",
+        Function("Topology::objects_at_depth"),
+    ),
+    ApiEntry::new(
+        "hwloc_get_next_obj_by_type",
+        Function("Topology::objects_with_type"),
+    ),
+    ApiEntry::new(
+        "hwloc_get_next_obj_covering_cpuset_by_depth",
+        Function("Topology::objects_covering_cpuset_at_depth"),
+    ),
+    ApiEntry::new(
+        "hwloc_get_next_obj_covering_cpuset_by_type",
+        Function("Topology::objects_covering_cpuset_with_type"),
+    ),
+    ApiEntry::new(
+        "hwloc_get_next_obj_inside_cpuset_by_depth",
+        Function("Topology::objects_inside_cpuset_at_depth"),
+    ),
+    ApiEntry::new(
+        "hwloc_get_next_obj_inside_cpuset_by_type",
+        Function("Topology::objects_inside_cpuset_with_type"),
+    ),
+    ApiEntry::new("hwloc_get_next_osdev", Function("Topology::os_devices")),
+    ApiEntry::new("hwloc_get_next_pcidev", Function("Topology::pci_devices")),
+    ApiEntry::new(
+        "hwloc_get_non_io_ancestor_obj",
+        Function("TopologyObject::non_io_ancestor"),
+    ),
+    ApiEntry::new(
+        "hwloc_get_numanode_obj_by_os_index",
+        Function("Topology::node_with_os_index"),
+    ),
+    ApiEntry::new(
+        "hwloc_get_obj_below_array_by_type",
+        Function("Topology::object_by_type_index_path"),
+    ),
+    ApiEntry::new(
+        "hwloc_get_obj_below_by_type",
+        Function("Topology::object_by_type_index_path"),
+    ),
+    ApiEntry::new(
+        "hwloc_get_obj_by_depth",
+        Function("Topology::objects_at_depth"),
+    ),
+    ApiEntry::new(
+        "hwloc_get_obj_by_type",
+        Function("Topology::objects_with_type"),
+    ),
+    ApiEntry::new(
+        "hwloc_get_obj_covering_cpuset",
+        Function("Topology::smallest_object_covering_cpuset"),
+    ),
+    ApiEntry::new(
+        "hwloc_get_obj_index_inside_cpuset",
+        Function("Topology::object_index_inside_cpuset"),
+    ),
+    ApiEntry::new(
+        "hwloc_get_obj_inside_cpuset_by_depth",
+        Function("Topology::objects_inside_cpuset_at_depth"),
+    ),
+    ApiEntry::new(
+        "hwloc_get_obj_inside_cpuset_by_type",
+        Function("Topology::objects_inside_cpuset_with_type"),
+    ),
+    ApiEntry::new(
+        "hwloc_get_obj_with_same_locality",
+        Function("Topology::object_with_same_locality"),
+    )
+    .feature("hwloc-2_5_0"),
+    ApiEntry::new(
+        "hwloc_get_pcidev_by_busid",
+        Function("Topology::pci_device_by_bus_id"),
+    ),
+    ApiEntry::new(
+        "hwloc_get_pcidev_by_busidstring",
+        Function("Topology::pci_device_by_bus_id_string"),
+    ),
+    ApiEntry::new(
+        "hwloc_get_proc_cpubind",
+        Function("Topology::process_cpu_binding"),
+    ),
+    ApiEntry::new(
+        "hwloc_get_proc_last_cpu_location",
+        Function("Topology::last_process_cpu_location"),
+    ),
+    ApiEntry::new(
+        "hwloc_get_proc_membind",
+        Function("Topology::process_memory_binding"),
+    ),
+    ApiEntry::new(
+        "hwloc_get_pu_obj_by_os_index",
+        Function("Topology::pu_with_os_index"),
+    ),
+    ApiEntry::new("hwloc_get_root_obj", Function("Topology::root_object")),
+    ApiEntry::new(
+        "hwloc_get_shared_cache_covering_obj",
+        Function("TopologyObject::first_shared_cache"),
+    ),
+    ApiEntry::new(
+        "hwloc_get_thread_cpubind",
+        Function("Topology::thread_cpu_binding"),
+    ),
+    ApiEntry::new("hwloc_get_type_depth", Function("Topology::depth_for_type")),
+    ApiEntry::new(
+        "hwloc_get_type_or_above_depth",
+        Function("Topology::depth_or_above_for_type"),
+    ),
+    ApiEntry::new(
+        "hwloc_get_type_or_below_depth",
+        Function("Topology::depth_or_below_for_type"),
+    ),
+    ApiEntry::new(
+        "hwloc_ibv_get_device_cpuset",
+        Function("Topology::ibv_device_cpuset"),
+    )
+    .feature("ofed"),
+    ApiEntry::new(
+        "hwloc_ibv_get_device_osdev",
+        Function("Topology::ibv_device_os_device"),
+    )
+    .feature("ofed"),
+    ApiEntry::new(
+        "hwloc_ibv_get_device_osdev_by_name",
+        Function("Topology::ibv_device_os_device"),
+    )
+    .feature("ofed"),
+    ApiEntry::new(
+        "hwloc_levelzero_get_device_cpuset",
+        Function("Topology::levelzero_device_cpuset"),
+    )
+    .feature("ze"),
+    ApiEntry::new(
+        "hwloc_levelzero_get_device_osdev",
+        Function("Topology::levelzero_device_os_device"),
+    )
+    .feature("ze"),
+    ApiEntry::new(
+        "hwloc_linux_get_tid_cpubind",
+        Function("Topology::tid_cpu_binding"),
+    )
+    .target_os("linux"),
+    ApiEntry::new(
+        "hwloc_linux_get_tid_last_cpu_location",
+        Function("Topology::tid_last_cpu_location"),
+    )
+    .target_os("linux"),
+    ApiEntry::new(
+        "hwloc_linux_read_path_as_cpumask",
+        Function("Topology::read_path_as_cpumask"),
+    )
+    .target_os("linux"),
+    ApiEntry::new(
+        "hwloc_linux_set_tid_cpubind",
+        Function("Topology::bind_tid_cpu"),
+    )
+    .target_os("linux"),
+    ApiEntry::new(
+        "hwloc_memattr_get_best_initiator",
+        Function("MemoryAttribute::best_initiator"),
+    )
+    .feature("hwloc-2_3_0"),
+    ApiEntry::new(
+        "hwloc_memattr_get_best_target",
+        Function("MemoryAttribute::best_target"),
+    )
+    .feature("hwloc-2_3_0"),
+    ApiEntry::new(
+        "hwloc_memattr_get_by_name",
+        Function("Topology::memory_attribute_named"),
+    )
+    .feature("hwloc-2_3_0"),
+    ApiEntry::new(
+        "hwloc_memattr_get_flags",
+        Function("MemoryAttribute::flags"),
+    )
+    .feature("hwloc-2_3_0"),
+    ApiEntry::new(
+        "hwloc_memattr_get_initiators",
+        Function("MemoryAttribute::initiators"),
+    )
+    .feature("hwloc-2_3_0"),
+    ApiEntry::new("hwloc_memattr_get_name", Function("MemoryAttribute::name"))
+        .feature("hwloc-2_3_0"),
+    ApiEntry::new(
+        "hwloc_memattr_get_targets",
+        Function("MemoryAttribute::targets"),
+    )
+    .feature("hwloc-2_3_0"),
+    ApiEntry::new(
+        "hwloc_memattr_get_value",
+        Function("MemoryAttribute::value"),
+    )
+    .feature("hwloc-2_3_0"),
+    ApiEntry::new(
+        "hwloc_memattr_register",
+        Function("TopologyEditor::register_memory_attribute"),
+    )
+    .feature("hwloc-2_3_0"),
+    ApiEntry::new(
+        "hwloc_memattr_set_value",
+        Function("MemoryAttributeBuilder::set_values"),
+    )
+    .feature("hwloc-2_3_0"),
+    ApiEntry::new(
+        "hwloc_nvml_get_device_cpuset",
+        Function("Topology::nvml_device_cpuset"),
+    )
+    .feature("nvml"),
+    ApiEntry::new(
+        "hwloc_nvml_get_device_osdev",
+        Function("Topology::nvml_device_os_device_by_uuid"),
+    )
+    .feature("nvml"),
+    ApiEntry::new(
+        "hwloc_nvml_get_device_osdev_by_index",
+        Function("Topology::nvml_device_os_device"),
+    )
+    .feature("nvml"),
+    ApiEntry::new("hwloc_obj_add_info", Function("TopologyObject::add_info")),
+    ApiEntry::new(
+        "hwloc_obj_add_other_obj_sets",
+        Function("TopologyEditor::insert_group_object"),
+    )
+    .feature("hwloc-2_3_0"),
+    ApiEntry::new(
+        "hwloc_obj_attr_snprintf",
+        TraitImpl("impl Debug for TopologyObject"),
+    ),
+    ApiEntry::new(
+        "hwloc_obj_get_info_by_name",
+        Function("TopologyObject::info"),
+    ),
+    ApiEntry::new(
+        "hwloc_obj_is_in_subtree",
+        Function("TopologyObject::is_in_subtree"),
+    ),
+    ApiEntry::new(
+        "hwloc_obj_type_is_cache",
+        Function("ObjectType::is_cpu_cache"),
+    ),
+    ApiEntry::new(
+        "hwloc_obj_type_is_dcache",
+        Function("ObjectType::is_cpu_data_cache"),
+    ),
+    ApiEntry::new(
+        "hwloc_obj_type_is_icache",
+        Function("ObjectType::is_cpu_instruction_cache"),
+    ),
+    ApiEntry::new("hwloc_obj_type_is_io", Function("ObjectType::is_io")),
+    ApiEntry::new(
+        "hwloc_obj_type_is_memory",
+        Function("ObjectType::is_memory"),
+    ),
+    ApiEntry::new(
+        "hwloc_obj_type_is_normal",
+        Function("ObjectType::is_normal"),
+    ),
+    ApiEntry::new(
+        "hwloc_obj_type_snprintf",
+        TraitImpl("impl Debug for TopologyObject"),
+    ),
+    ApiEntry::new("hwloc_obj_type_string", Internal),
+    ApiEntry::new(
+        "hwloc_rsmi_get_device_cpuset",
+        Function("Topology::rsmi_device_cpuset"),
+    )
+    .feature("rsmi"),
+    ApiEntry::new(
+        "hwloc_rsmi_get_device_osdev",
+        Function("Topology::rsmi_device_os_device"),
+    )
+    .feature("rsmi"),
+    ApiEntry::new(
+        "hwloc_rsmi_get_device_osdev_by_index",
+        Function("Topology::rsmi_device_os_device"),
+    )
+    .feature("rsmi"),
+    ApiEntry::new(
+        "hwloc_set_area_membind",
+        Function("Topology::bind_memory_area"),
+    ),
+    ApiEntry::new("hwloc_set_cpubind", Function("Topology::bind_cpu")),
+    ApiEntry::new("hwloc_set_membind", Function("Topology::bind_memory")),
+    ApiEntry::new(
+        "hwloc_set_proc_cpubind",
+        Function("Topology::bind_process_cpu"),
+    ),
+    ApiEntry::new(
+        "hwloc_set_proc_membind",
+        Function("Topology::bind_process_memory"),
+    ),
+    ApiEntry::new(
+        "hwloc_set_thread_cpubind",
+        Function("Topology::bind_thread_cpu"),
+    ),
+    ApiEntry::new(
+        "hwloc_topology_abi_check",
+        Function("Topology::is_abi_compatible"),
+    ),
+    ApiEntry::new(
+        "hwloc_topology_alloc_group_object",
+        Function("TopologyEditor::insert_group_object"),
+    )
+    .feature("hwloc-2_3_0"),
+    ApiEntry::new("hwloc_topology_allow", Function("TopologyEditor::allow")).feature("hwloc-2_3_0"),
+    ApiEntry::new("hwloc_topology_check", Internal),
+    ApiEntry::new(
+        "hwloc_topology_destroy",
+        TraitImpl("impl Drop for Topology"),
+    ),
+    ApiEntry::new("hwloc_topology_dup", TraitImpl("impl Clone for Topology")),
+    ApiEntry::new(
+        "hwloc_topology_export_synthetic",
+        Function("Topology::export_synthetic"),
+    ),
+    ApiEntry::new(
+        "hwloc_topology_export_xml",
+        Function("Topology::export_xml_file"),
+    ),
+    ApiEntry::new(
+        "hwloc_topology_export_xmlbuffer",
+        Function("Topology::export_xml"),
+    ),
+    ApiEntry::new(
+        "hwloc_topology_get_allowed_cpuset",
+        Function("Topology::allowed_cpuset"),
+    ),
+    ApiEntry::new(
+        "hwloc_topology_get_allowed_nodeset",
+        Function("Topology::allowed_nodeset"),
+    ),
+    ApiEntry::new(
+        "hwloc_topology_get_complete_cpuset",
+        Function("Topology::complete_cpuset"),
+    ),
+    ApiEntry::new(
+        "hwloc_topology_get_complete_nodeset",
+        Function("Topology::complete_nodeset"),
+    ),
+    ApiEntry::new(
+        "hwloc_topology_get_default_nodeset",
+        Function("Topology::default_nodeset"),
+    )
+    .feature("hwloc-2_10_0"),
+    ApiEntry::new("hwloc_topology_get_depth", Function("Topology::depth")),
+    ApiEntry::new(
+        "hwloc_topology_get_flags",
+        Function("Topology::build_flags"),
+    ),
+    ApiEntry::new(
+        "hwloc_topology_get_support",
+        Function("Topology::feature_support"),
+    ),
+    ApiEntry::new(
+        "hwloc_topology_get_topology_cpuset",
+        Function("Topology::cpuset"),
+    ),
+    ApiEntry::new(
+        "hwloc_topology_get_topology_nodeset",
+        Function("Topology::nodeset"),
+    ),
+    ApiEntry::new(
+        "hwloc_topology_get_type_filter",
+        Function("Topology::type_filter"),
+    ),
+    ApiEntry::new("hwloc_topology_init", Function("Topology::builder")),
+    ApiEntry::new(
+        "hwloc_topology_insert_group_object",
+        Function("TopologyEditor::insert_group_object"),
+    )
+    .feature("hwloc-2_3_0"),
+    ApiEntry::new(
+        "hwloc_topology_insert_misc_object",
+        Function("TopologyEditor::insert_misc_object"),
+    )
+    .feature("hwloc-2_3_0"),
+    ApiEntry::new(
+        "hwloc_topology_is_thissystem",
+        Function("Topology::is_this_system"),
+    ),
+    ApiEntry::new("hwloc_topology_load", Function("TopologyBuilder::build")),
+    ApiEntry::new("hwloc_topology_refresh", Function("Topology::edit")).feature("hwloc-2_3_0"),
+    ApiEntry::new(
+        "hwloc_topology_restrict",
+        Function("TopologyEditor::restrict"),
+    )
+    .feature("hwloc-2_3_0"),
+    ApiEntry::new(
+        "hwloc_topology_set_all_types_filter",
+        Function("TopologyBuilder::with_common_type_filter"),
+    ),
+    ApiEntry::new(
+        "hwloc_topology_set_cache_types_filter",
+        Function("TopologyBuilder::with_cpu_cache_type_filter"),
+    ),
+    ApiEntry::new(
+        "hwloc_topology_set_components",
+        Function("TopologyBuilder::blacklist_component"),
+    )
+    .feature("hwloc-2_1_0"),
+    ApiEntry::new(
+        "hwloc_topology_set_flags",
+        Function("TopologyBuilder::with_flags"),
+    ),
+    ApiEntry::new(
+        "hwloc_topology_set_icache_types_filter",
+        Function("TopologyBuilder::with_cpu_icache_type_filter"),
+    ),
+    ApiEntry::new(
+        "hwloc_topology_set_io_types_filter",
+        Function("TopologyBuilder::with_io_type_filter"),
+    ),
+    ApiEntry::new(
+        "hwloc_topology_set_pid",
+        Function("TopologyBuilder::from_pid"),
+    ),
+    ApiEntry::new(
+        "hwloc_topology_set_synthetic",
+        Function("TopologyBuilder::from_synthetic"),
+    ),
+    ApiEntry::new(
+        "hwloc_topology_set_type_filter",
+        Function("TopologyBuilder::with_type_filter"),
+    ),
+    ApiEntry::new(
+        "hwloc_topology_set_xml",
+        Function("TopologyBuilder::from_xml_file"),
+    ),
+    ApiEntry::new(
+        "hwloc_topology_set_xmlbuffer",
+        Function("TopologyBuilder::from_xml"),
+    ),
+    ApiEntry::new(
+        "hwloc_windows_get_nr_processor_groups",
+        Function("Topology::num_processor_groups"),
+    )
+    .feature("hwloc-2_5_0")
+    .target_os("windows"),
+    ApiEntry::new(
+        "hwloc_windows_get_processor_group_cpuset",
+        Function("Topology::processor_groups"),
+    )
+    .feature("hwloc-2_5_0")
+    .target_os("windows"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_should_be_sorted_and_unique() {
+        for window in ENTRIES.windows(2) {
+            assert!(window[0].hwloc_name < window[1].hwloc_name, "{window:?}");
+        }
+    }
+
+    #[test]
+    fn ffi_entry_points_should_be_covered() {
+        let ffi_source = include_str!("ffi.rs");
+        for line in ffi_source.lines() {
+            let Some((_, rest)) = line.split_once("fn hwloc_") else {
+                continue;
+            };
+            let name_len = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            let name = format!("hwloc_{}", &rest[..name_len]);
+            assert!(lookup(&name).is_some(), "{name} is missing from ENTRIES");
+        }
+    }
+}
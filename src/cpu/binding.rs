@@ -7,13 +7,15 @@ use crate::{
     cpu::cpusets::CpuSet,
     errors::{self, FlagsError, HybridError, RawHwlocError},
     ffi,
+    memory::nodesets::NodeSet,
+    objects::{types::ObjectType, TopologyObject},
     topology::{RawTopology, Topology},
     ProcessId, ThreadId,
 };
 use bitflags::bitflags;
 use derive_more::Display;
 use libc::{ENOSYS, EXDEV};
-use std::{ffi::c_int, fmt::Display};
+use std::{ffi::c_int, fmt::Display, marker::PhantomData};
 use thiserror::Error;
 
 /// # CPU binding
@@ -34,12 +36,19 @@ use thiserror::Error;
 /// binding set, etc). You can inhibit this with flag [`STRICT`], at the
 /// expense of reducing portability across operating systems.
 ///
+/// In addition to the errors listed by individual functions, all CPU binding
+/// functions may report a [`HybridError::Hwloc`] error with errno `EPERM` if
+/// the operating system does not allow the caller to query or modify the
+/// target's binding, typically because it belongs to another user, and with
+/// errno `EINTR` if the underlying system call kept being interrupted by
+/// signals (see [`errors::set_interrupted_call_retries()`]).
+///
 /// [`ASSUME_SINGLE_THREAD`]: CpuBindingFlags::ASSUME_SINGLE_THREAD
 /// [`PROCESS`]: CpuBindingFlags::PROCESS
 /// [`THREAD`]: CpuBindingFlags::THREAD
 /// [`STRICT`]: CpuBindingFlags::STRICT
 //
-// Upstream docs: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__cpubinding.html
+// Upstream docs: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__cpubinding.html
 impl Topology {
     /// Binds the current process or thread on given CPUs
     ///
@@ -74,6 +83,33 @@ impl Topology {
     ///   to the requested CPU set, specifically.
     /// - [`BadFlags`] if flags [`PROCESS`] and [`THREAD`] were both specified.
     ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::{
+    /// #     cpu::binding::CpuBindingFlags,
+    /// #     objects::types::ObjectType,
+    /// #     topology::support::{CpuBindingSupport, FeatureSupport},
+    /// # };
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// if topology.supports(
+    ///     FeatureSupport::cpu_binding,
+    ///     CpuBindingSupport::set_current_thread,
+    /// ) {
+    ///     // Bind the current thread to the last PU
+    ///     let last_pu = topology
+    ///         .objects_with_type(ObjectType::PU)
+    ///         .next_back()
+    ///         .expect("There should be at least one PU");
+    ///     let cpuset = last_pu.cpuset().expect("PUs should have a cpuset");
+    ///     topology.bind_cpu(&cpuset, CpuBindingFlags::THREAD)?;
+    ///
+    ///     // Unbind by binding to all CPUs again
+    ///     topology.bind_cpu(&topology.cpuset(), CpuBindingFlags::THREAD)?;
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    ///
     /// [`BadCpuSet`]: CpuBindingError::BadCpuSet
     /// [`BadFlags`]: CpuBindingError::BadFlags
     /// [`BadObject(ThisProgram)`]: CpuBindingError::BadObject
@@ -96,6 +132,140 @@ impl Topology {
         )
     }
 
+    /// Temporarily bind the current process or thread to CPUs
+    ///
+    /// This records the current CPU binding, then binds the current process
+    /// or thread to `set` as [`bind_cpu()`](Self::bind_cpu()) would. The
+    /// previous binding is restored when the returned guard is dropped,
+    /// including when unwinding from a panic, or when it is explicitly
+    /// [restored](CpuBindingGuard::restore()).
+    ///
+    /// Since the guard restores the binding of whichever thread drops it, it
+    /// cannot be sent to another thread.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Errors
+    ///
+    /// Errors from [`cpu_binding()`](Self::cpu_binding()) when querying the
+    /// current binding, and from [`bind_cpu()`](Self::bind_cpu()) when
+    /// binding to `set`. In both cases, the binding is left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::{
+    /// #     cpu::binding::CpuBindingFlags,
+    /// #     topology::support::{CpuBindingSupport, FeatureSupport},
+    /// # };
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// if topology.supports(
+    ///     FeatureSupport::cpu_binding,
+    ///     CpuBindingSupport::set_current_thread,
+    /// ) && topology.supports(
+    ///     FeatureSupport::cpu_binding,
+    ///     CpuBindingSupport::get_current_thread,
+    /// ) {
+    ///     let before = topology.cpu_binding(CpuBindingFlags::THREAD)?;
+    ///     {
+    ///         // Pin the current thread to the first PU for a benchmark
+    ///         let first_pu = topology.cpuset().first_set().unwrap();
+    ///         let cpuset = hwlocality::cpu::cpusets::CpuSet::from(first_pu);
+    ///         let _guard = topology.bind_cpu_scoped(&cpuset, CpuBindingFlags::THREAD)?;
+    ///         // ...run the benchmark...
+    ///     }
+    ///     assert_eq!(topology.cpu_binding(CpuBindingFlags::THREAD)?, before);
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn bind_cpu_scoped(
+        &self,
+        set: &CpuSet,
+        flags: CpuBindingFlags,
+    ) -> Result<CpuBindingGuard<'_>, HybridError<CpuBindingError>> {
+        // Some flags are only meaningful when setting bindings
+        let query_flags = flags - CpuBindingFlags::NO_MEMORY_BINDING - CpuBindingFlags::STRICT;
+        let previous = self.cpu_binding(query_flags)?;
+        self.bind_cpu(set, flags)?;
+        Ok(CpuBindingGuard {
+            topology: self,
+            previous: Some(previous),
+            flags,
+            _not_send: PhantomData,
+        })
+    }
+
+    /// Bind the current process or thread on CPUs from `set`, as well as
+    /// possible
+    ///
+    /// Most applications want best-effort placement rather than hard failures
+    /// when the operating system cannot honor a CPU binding exactly. This
+    /// function tries increasingly lenient binding strategies until one
+    /// succeeds:
+    ///
+    /// 1. Binding to `set` with [`CpuBindingFlags::STRICT`]
+    /// 2. Binding to `set` without [`CpuBindingFlags::STRICT`], letting the
+    ///    operating system bind to a slightly different set
+    /// 3. Binding to a [single PU](CpuSet::singlify()) of `set`, for operating
+    ///    systems which can only bind to one CPU
+    ///
+    /// `flags` are used for every attempt, and select which of the current
+    /// process or thread gets bound like in [`Topology::bind_cpu()`]. The
+    /// [`STRICT`](CpuBindingFlags::STRICT) flag is managed by this function
+    /// and should not be specified.
+    ///
+    /// The returned [`BindingOutcome`] tells which strategy succeeded, if any,
+    /// what the binding looks like afterwards, and why the stricter strategies
+    /// failed.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::cpu::binding::CpuBindingFlags;
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// let outcome = topology.prefer_cpu(&topology.cpuset(), CpuBindingFlags::THREAD);
+    /// match outcome.level() {
+    ///     Some(level) => println!("Bound this thread with {level:?} strategy"),
+    ///     None => println!("Could not bind this thread: {:?}", outcome.errors()),
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn prefer_cpu(&self, set: &CpuSet, flags: CpuBindingFlags) -> BindingOutcome {
+        let flags = flags - CpuBindingFlags::STRICT;
+        let mut errors = Vec::new();
+        let mut single_pu = set.clone();
+        single_pu.singlify();
+        let attempts = [
+            (BindingLevel::Strict, set, flags | CpuBindingFlags::STRICT),
+            (BindingLevel::NonStrict, set, flags),
+            (BindingLevel::SinglePU, &single_pu, flags),
+        ];
+        for (level, set, flags) in attempts {
+            match self.bind_cpu(set, flags) {
+                Ok(()) => {
+                    // Some flags are only meaningful when setting bindings
+                    let query_flags =
+                        flags - CpuBindingFlags::NO_MEMORY_BINDING - CpuBindingFlags::STRICT;
+                    return BindingOutcome {
+                        level: Some(level),
+                        bound_set: Some(set.clone()),
+                        actual_binding: self.cpu_binding(query_flags).ok(),
+                        errors,
+                    };
+                }
+                Err(e) => errors.push(e),
+            }
+        }
+        BindingOutcome {
+            level: None,
+            bound_set: None,
+            actual_binding: None,
+            errors,
+        }
+    }
+
     /// Get the current process or thread CPU binding
     ///
     /// Flag [`NO_MEMORY_BINDING`] should not be used with this function.
@@ -113,6 +283,24 @@ impl Topology {
     /// - [`BadFlags`] if flag [`NO_MEMORY_BINDING`] was specified or if
     ///   flags [`PROCESS`] and [`THREAD`] were both specified.
     ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::{
+    /// #     cpu::binding::CpuBindingFlags,
+    /// #     topology::support::{CpuBindingSupport, FeatureSupport},
+    /// # };
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// if topology.supports(
+    ///     FeatureSupport::cpu_binding,
+    ///     CpuBindingSupport::get_current_thread,
+    /// ) {
+    ///     let binding = topology.cpu_binding(CpuBindingFlags::THREAD)?;
+    ///     println!("Current thread is bound to {binding}");
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    ///
     /// [`BadFlags`]: CpuBindingError::BadFlags
     /// [`BadObject(ThisProgram)`]: CpuBindingError::BadObject
     /// [`NO_MEMORY_BINDING`]: CpuBindingFlags::NO_MEMORY_BINDING
@@ -335,6 +523,92 @@ impl Topology {
         )
     }
 
+    /// PU where the current thread last ran
+    ///
+    /// This resolves the output of [`last_cpu_location()`] for the current
+    /// thread into a [`PU`] object. As with [`last_cpu_location()`], the
+    /// result may already be outdated by the time it is returned, unless the
+    /// current thread is bound to a single PU.
+    ///
+    /// Returns `Ok(None)` if the current thread last ran on a PU that is not
+    /// part of this topology, e.g. one that was removed by restricting the
+    /// topology.
+    ///
+    /// Requires [`CpuBindingSupport::get_current_thread_last_cpu_location()`].
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Errors
+    ///
+    /// See [`last_cpu_location()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::{
+    /// #     objects::types::ObjectType,
+    /// #     topology::support::{CpuBindingSupport, FeatureSupport},
+    /// # };
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// if topology.supports(
+    ///     FeatureSupport::cpu_binding,
+    ///     CpuBindingSupport::get_current_thread_last_cpu_location,
+    /// ) {
+    ///     if let Some(pu) = topology.current_pu()? {
+    ///         assert_eq!(pu.object_type(), ObjectType::PU);
+    ///         println!("Last ran on {pu}");
+    ///     }
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    ///
+    /// [`last_cpu_location()`]: Self::last_cpu_location()
+    /// [`PU`]: ObjectType::PU
+    pub fn current_pu(&self) -> Result<Option<&TopologyObject>, HybridError<CpuBindingError>> {
+        let location = self.last_cpu_location(CpuBindingFlags::THREAD)?;
+        Ok(location
+            .first_set()
+            .and_then(|pu| self.pu_with_os_index(usize::from(pu))))
+    }
+
+    /// NUMA node that is local to the PU where the current thread last ran
+    ///
+    /// This is meant for cheap first-touch placement decisions in NUMA-aware
+    /// data structures. The NUMA node is found by walking up the ancestors of
+    /// the PU until one of them has memory attached. If several NUMA nodes are
+    /// attached there (e.g. DRAM and high-bandwidth memory), the first one is
+    /// returned, and [`Topology::local_numa_nodes()`] can be used to list
+    /// all of them.
+    ///
+    /// Returns `Ok(None)` if [`current_pu()`](Self::current_pu()) does or if
+    /// no NUMA node is local to that PU.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Errors
+    ///
+    /// See [`last_cpu_location()`](Self::last_cpu_location()).
+    pub fn current_numa_node(
+        &self,
+    ) -> Result<Option<&TopologyObject>, HybridError<CpuBindingError>> {
+        let Some(pu) = self.current_pu()? else {
+            return Ok(None);
+        };
+        // NUMA nodes may be attached behind memory-side caches
+        fn first_numa_node(object: &TopologyObject) -> Option<&TopologyObject> {
+            object.memory_children().find_map(|child| {
+                if child.object_type() == ObjectType::NUMANode {
+                    Some(child)
+                } else {
+                    first_numa_node(child)
+                }
+            })
+        }
+        Ok(std::iter::once(pu)
+            .chain(pu.ancestors())
+            .find_map(first_numa_node))
+    }
+
     /// Get the last physical CPU where a process ran.
     ///
     /// As a special case on Linux, if a tid (thread ID) is supplied instead of
@@ -375,6 +649,94 @@ impl Topology {
         )
     }
 
+    /// Number of PUs that the current thread may run on, in the spirit of
+    /// [`std::thread::available_parallelism()`] and `num_cpus::get()`
+    ///
+    /// This is the number of PUs in [`Topology::allowed_cpuset()`] that the
+    /// current thread is bound to (or, if the thread's binding cannot be
+    /// queried, that the current process is bound to). It is meant to help
+    /// projects that size their thread pools using the aforementioned
+    /// functions to migrate to hwlocality incrementally.
+    ///
+    /// The result may differ from that of the standard library because...
+    ///
+    /// - The topology is a snapshot. PUs that were allowed when the topology
+    ///   was loaded are counted even if they have since been disallowed, and
+    ///   vice versa.
+    /// - CPU quotas (e.g. Linux cgroup `cpu.max`) are not taken into account,
+    ///   only restrictions on the set of PUs that may be used are.
+    /// - The standard library falls back to the total number of CPUs when the
+    ///   binding cannot be queried, whereas this falls back to the number of
+    ///   allowed PUs, which can be smaller.
+    ///
+    /// If you are looking for an equivalent of `num_cpus::get_physical()`,
+    /// count the [`Core`] objects of the topology instead, or use
+    /// `CpuSet::singlify_per_core()` (hwloc 2.2+) to discard SMT siblings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// let parallelism = topology.available_parallelism_like();
+    /// assert!(parallelism >= 1);
+    /// assert!(parallelism <= topology.cpuset().weight().unwrap());
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    ///
+    /// [`Core`]: crate::objects::types::ObjectType::Core
+    pub fn available_parallelism_like(&self) -> usize {
+        let mut cpuset = self.allowed_cpuset().to_owned();
+        if let Ok(binding) = self
+            .cpu_binding(CpuBindingFlags::THREAD)
+            .or_else(|_| self.cpu_binding(CpuBindingFlags::PROCESS))
+        {
+            let bound = &cpuset & &binding;
+            if !bound.is_empty() {
+                cpuset = bound;
+            }
+        }
+        cpuset.weight().expect("Topology cpusets should be finite")
+    }
+
+    /// Compute the CPU binding of a task spawned by a task that is bound to
+    /// `parent`, according to some inheritance `policy`
+    ///
+    /// This is meant to be used by thread pools and other task schedulers
+    /// that support nested parallelism, where pinning every subtask to its
+    /// parent's exact CPUs can leave the rest of the machine idle.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::cpu::binding::BindingInheritance;
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// let first_pu = topology.cpuset().first_set().unwrap();
+    /// let parent = hwlocality::cpu::cpusets::CpuSet::from(first_pu);
+    ///
+    /// let inherited = topology.inherited_cpu_binding(&parent, BindingInheritance::Inherit);
+    /// assert_eq!(inherited, parent);
+    ///
+    /// let widened = topology.inherited_cpu_binding(&parent, BindingInheritance::WidenToNUMANode);
+    /// assert!(widened.includes(&parent));
+    ///
+    /// let unbound = topology.inherited_cpu_binding(&parent, BindingInheritance::Unbind);
+    /// assert_eq!(unbound, topology.allowed_cpuset());
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn inherited_cpu_binding(&self, parent: &CpuSet, policy: BindingInheritance) -> CpuSet {
+        match policy {
+            BindingInheritance::Inherit => parent.clone(),
+            BindingInheritance::WidenToNUMANode => {
+                let nodeset = NodeSet::from_cpuset(self, parent);
+                let mut widened = CpuSet::from_nodeset(self, &nodeset);
+                widened &= self.allowed_cpuset();
+                widened |= parent;
+                widened
+            }
+            BindingInheritance::Unbind => self.allowed_cpuset().to_owned(),
+        }
+    }
+
     /// Binding for set_cpubind style functions
     fn bind_cpu_impl(
         &self,
@@ -382,18 +744,33 @@ impl Topology {
         flags: CpuBindingFlags,
         target: CpuBoundObject,
         api: &'static str,
-        ffi: impl FnOnce(*const RawTopology, *const RawBitmap, c_int) -> c_int,
+        mut ffi: impl FnMut(*const RawTopology, *const RawBitmap, c_int) -> c_int,
     ) -> Result<(), HybridError<CpuBindingError>> {
         if !flags.is_valid(target, CpuBindingOperation::SetBinding) {
             return Err(CpuBindingError::BadFlags(flags.into()).into());
         }
-        call_hwloc(api, target, Some(set), || {
+        log_event!(
+            Trace,
+            "Binding {target} to CPUs {set} with flags {flags:?} via {api}"
+        );
+        let result = call_hwloc(api, target, Some(set), || {
             ffi(
                 self.as_ptr(),
                 set.as_ptr(),
                 i32::try_from(flags.bits()).expect("Unexpected high order bit in flags"),
             )
-        })
+        });
+        match &result {
+            Ok(()) => log_event!(
+                Debug,
+                "Bound {target} to CPUs {set} with flags {flags:?} via {api}"
+            ),
+            Err(e) => log_event!(
+                Warn,
+                "Failed to bind {target} to CPUs {set} with flags {flags:?} via {api}: {e}"
+            ),
+        }
+        result
     }
 
     /// Binding for get_cpubind style functions
@@ -402,7 +779,7 @@ impl Topology {
         flags: CpuBindingFlags,
         target: CpuBoundObject,
         api: &'static str,
-        ffi: impl FnOnce(*const RawTopology, *mut RawBitmap, c_int) -> c_int,
+        ffi: impl FnMut(*const RawTopology, *mut RawBitmap, c_int) -> c_int,
     ) -> Result<CpuSet, HybridError<CpuBindingError>> {
         self.get_cpuset(flags, target, CpuBindingOperation::GetBinding, api, ffi)
     }
@@ -413,7 +790,7 @@ impl Topology {
         flags: CpuBindingFlags,
         target: CpuBoundObject,
         api: &'static str,
-        ffi: impl FnOnce(*const RawTopology, *mut RawBitmap, c_int) -> c_int,
+        ffi: impl FnMut(*const RawTopology, *mut RawBitmap, c_int) -> c_int,
     ) -> Result<CpuSet, HybridError<CpuBindingError>> {
         self.get_cpuset(
             flags,
@@ -431,7 +808,7 @@ impl Topology {
         target: CpuBoundObject,
         operation: CpuBindingOperation,
         api: &'static str,
-        ffi: impl FnOnce(*const RawTopology, *mut RawBitmap, c_int) -> c_int,
+        mut ffi: impl FnMut(*const RawTopology, *mut RawBitmap, c_int) -> c_int,
     ) -> Result<CpuSet, HybridError<CpuBindingError>> {
         if !flags.is_valid(target, operation) {
             return Err(CpuBindingError::BadFlags(flags.into()).into());
@@ -519,9 +896,17 @@ bitflags! {
         /// result in the binding function erroring out with
         /// [`CpuBindingError::Unsupported`].
         ///
+        /// Whether this flag is set or not, CPU binding functions do not ask the
+        /// OS to migrate memory that has already been allocated. To move
+        /// existing memory along with the CPU binding, use
+        /// [`Topology::bind_memory()`] with [`MemoryBindingFlags::MIGRATE`]
+        /// after binding CPUs.
+        ///
         /// This flag is only meaningful when used with functions that set the
-        /// CPU binding. It is ignored when used with functions that get CPU
-        /// binding information.
+        /// CPU binding. Functions that get CPU binding information reject it
+        /// with a [`CpuBindingError::BadFlags`] error.
+        ///
+        /// [`MemoryBindingFlags::MIGRATE`]: crate::memory::binding::MemoryBindingFlags::MIGRATE
         #[doc(alias = "HWLOC_CPUBIND_NOMEMBIND")]
         const NO_MEMORY_BINDING = (1<<3);
     }
@@ -559,6 +944,143 @@ impl CpuBindingFlags {
     }
 }
 //
+/// How the CPU binding of a task is derived from that of the task that
+/// spawned it
+///
+/// See [`Topology::inherited_cpu_binding()`] for more information.
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub enum BindingInheritance {
+    /// Run on the same CPUs as the parent task
+    #[default]
+    Inherit,
+
+    /// Run on any CPU that is local to the parent task's NUMA node(s)
+    ///
+    /// This keeps subtasks close to the memory that their parent is likely to
+    /// share with them, while letting them spread out across that NUMA node.
+    WidenToNUMANode,
+
+    /// Run on any CPU that the topology allows
+    Unbind,
+}
+
+/// Guard that restores a previous CPU binding when dropped
+///
+/// See [`Topology::bind_cpu_scoped()`] for more information.
+#[derive(Debug)]
+#[must_use = "The previous CPU binding is restored as soon as the guard is dropped"]
+pub struct CpuBindingGuard<'topology> {
+    /// Topology that was used for binding
+    topology: &'topology Topology,
+
+    /// CPU binding to be restored, if not restored yet
+    previous: Option<CpuSet>,
+
+    /// Flags that were used for binding
+    flags: CpuBindingFlags,
+
+    /// The binding must be restored by the thread that set it
+    _not_send: PhantomData<*const ()>,
+}
+//
+impl CpuBindingGuard<'_> {
+    /// CPU binding that will be restored
+    pub fn previous_binding(&self) -> &CpuSet {
+        self.previous
+            .as_ref()
+            .expect("Only restore() takes the previous binding out")
+    }
+
+    /// Restore the previous CPU binding, reporting errors
+    ///
+    /// Dropping the guard restores the previous binding too, but ignores
+    /// errors since they cannot be reported from a destructor.
+    ///
+    /// # Errors
+    ///
+    /// See [`Topology::bind_cpu()`].
+    pub fn restore(mut self) -> Result<(), HybridError<CpuBindingError>> {
+        self.restore_impl()
+    }
+
+    /// Restore the previous CPU binding, if not done already
+    fn restore_impl(&mut self) -> Result<(), HybridError<CpuBindingError>> {
+        match self.previous.take() {
+            Some(previous) => self.topology.bind_cpu(&previous, self.flags),
+            None => Ok(()),
+        }
+    }
+}
+//
+impl Drop for CpuBindingGuard<'_> {
+    fn drop(&mut self) {
+        let _ = self.restore_impl();
+    }
+}
+
+/// Binding strategy that succeeded in [`Topology::prefer_cpu()`]
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum BindingLevel {
+    /// Bound exactly to the requested set, with [`CpuBindingFlags::STRICT`]
+    Strict,
+
+    /// Bound to the requested set without [`CpuBindingFlags::STRICT`], so the
+    /// operating system may have bound to a slightly different set
+    NonStrict,
+
+    /// Bound to a single PU from the requested set
+    SinglePU,
+}
+
+/// What [`Topology::prefer_cpu()`] managed to achieve
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BindingOutcome {
+    /// Strategy that succeeded, if any
+    level: Option<BindingLevel>,
+
+    /// Set that was successfully bound to, if any
+    bound_set: Option<CpuSet>,
+
+    /// Binding after the operation, if it can be queried
+    actual_binding: Option<CpuSet>,
+
+    /// Errors from the strategies that failed, in the order they were tried
+    errors: Vec<HybridError<CpuBindingError>>,
+}
+//
+impl BindingOutcome {
+    /// Truth that some binding strategy succeeded
+    pub fn is_bound(&self) -> bool {
+        self.level.is_some()
+    }
+
+    /// Binding strategy that succeeded, if any
+    pub fn level(&self) -> Option<BindingLevel> {
+        self.level
+    }
+
+    /// Set that was successfully bound to, if any
+    ///
+    /// This is the requested set, or a single PU from it if the
+    /// [`SinglePU`](BindingLevel::SinglePU) strategy was used.
+    pub fn bound_set(&self) -> Option<&CpuSet> {
+        self.bound_set.as_ref()
+    }
+
+    /// CPU binding of the current process or thread after binding
+    ///
+    /// This is `None` if binding failed, or if the operating system does not
+    /// support querying the CPU binding.
+    pub fn actual_binding(&self) -> Option<&CpuSet> {
+        self.actual_binding.as_ref()
+    }
+
+    /// Why stricter binding strategies failed, in the order they were tried
+    pub fn errors(&self) -> &[HybridError<CpuBindingError>] {
+        &self.errors
+    }
+}
+
 /// Object that is being bound to particular CPUs
 #[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
 pub enum CpuBoundObject {
@@ -627,18 +1149,70 @@ pub enum CpuBindingError {
     BadCpuSet(CpuBoundObject, CpuSet),
 }
 
+/// Errors that can occur when binding Windows threads within a processor group
+#[cfg(any(doc, all(feature = "hwloc-2_5_0", target_os = "windows")))]
+#[derive(Clone, Debug, Error, Eq, PartialEq)]
+pub enum ProcessorGroupBindingError {
+    /// Failed to query the Windows processor groups
+    #[error(transparent)]
+    Hwloc(#[from] RawHwlocError),
+
+    /// Cannot bind a thread to an empty CPU set
+    #[error("cannot bind a thread to an empty CPU set")]
+    EmptySet,
+
+    /// The target CPU set spans multiple processor groups
+    ///
+    /// Windows threads can only be bound inside of a single processor group.
+    /// Consider splitting the set with
+    /// `Topology::split_by_processor_group()` and binding one thread to each
+    /// part instead.
+    #[error("{set} spans processor groups {groups:?}")]
+    MultipleGroups {
+        /// CPU set that was requested
+        set: CpuSet,
+
+        /// Indices of the processor groups that this CPU set spans
+        groups: Vec<usize>,
+    },
+
+    /// The target CPU set contains PUs that are not part of any processor
+    /// group
+    #[error("{0} contains PUs that are not part of any processor group")]
+    OutsideGroups(CpuSet),
+
+    /// The PUs of the target processor group cannot be expressed as an
+    /// affinity mask
+    ///
+    /// hwloc normally numbers the PUs of each processor group within a
+    /// window of `usize::BITS` consecutive indices, which Windows affinity
+    /// masks are relative to. This error is reported if the cpuset of the
+    /// processor group with this index does not fit in such a window.
+    #[error("processor group {0} cannot be expressed as an affinity mask")]
+    UnsupportedGroupLayout(usize),
+
+    /// The operating system rejected the thread binding
+    #[error("SetThreadGroupAffinity failed: {0}")]
+    Os(errno::Errno),
+}
+
 /// Call an hwloc API that is about getting or setting CPU bindings, translate
 /// known errors into higher-level `CpuBindingError`s.
 ///
 /// Validating flags is left up to the caller, to avoid allocating result
 /// objects when it can be proved upfront that the request is invalid.
+///
+/// Calls that are interrupted by a signal are transparently retried, up to the
+/// limit set by [`errors::set_interrupted_call_retries()`], after which the
+/// raw `EINTR` error is reported.
 pub(crate) fn call_hwloc(
     api: &'static str,
     object: CpuBoundObject,
     cpuset: Option<&CpuSet>,
-    ffi: impl FnOnce() -> c_int,
+    mut ffi: impl FnMut() -> c_int,
 ) -> Result<(), HybridError<CpuBindingError>> {
-    match errors::call_hwloc_int_normal(api, ffi) {
+    let max_retries = Some(errors::interrupted_call_retries());
+    match errors::retry_on_eintr(max_retries, || errors::call_hwloc_int_normal(api, &mut ffi)) {
         Ok(_positive) => Ok(()),
         Err(
             raw_err @ RawHwlocError {
@@ -658,3 +1232,36 @@ pub(crate) fn call_hwloc(
         Err(raw_err) => Err(HybridError::Hwloc(raw_err)),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::topology::support::{CpuBindingSupport, FeatureSupport};
+
+    #[test]
+    fn no_memory_binding_is_only_valid_when_binding() {
+        let flags = CpuBindingFlags::THREAD | CpuBindingFlags::NO_MEMORY_BINDING;
+        assert!(flags.is_valid(CpuBoundObject::ThisProgram, CpuBindingOperation::SetBinding));
+        assert!(!flags.is_valid(CpuBoundObject::ThisProgram, CpuBindingOperation::GetBinding));
+        assert!(!flags.is_valid(
+            CpuBoundObject::ThisProgram,
+            CpuBindingOperation::GetLastLocation
+        ));
+    }
+
+    #[test]
+    fn current_numa_node_should_be_local_to_current_pu() {
+        let topology = Topology::test_instance();
+        if !topology.supports(
+            FeatureSupport::cpu_binding,
+            CpuBindingSupport::get_current_thread_last_cpu_location,
+        ) {
+            return;
+        }
+        let Ok(Some(node)) = topology.current_numa_node() else {
+            return;
+        };
+        assert_eq!(node.object_type(), ObjectType::NUMANode);
+        assert!(!node.cpuset().unwrap().is_empty());
+    }
+}
@@ -41,7 +41,7 @@ impl Topology {
 }
 
 /// Data (or unified) caches levels supported by hwloc
-const DATA_CACHE_LEVELS: &[ObjectType] = &[
+pub(crate) const DATA_CACHE_LEVELS: &[ObjectType] = &[
     ObjectType::L1Cache,
     ObjectType::L2Cache,
     ObjectType::L3Cache,
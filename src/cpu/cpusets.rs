@@ -5,6 +5,7 @@ use crate::errors;
 #[cfg(doc)]
 use crate::{bitmaps::Bitmap, topology::support::DiscoverySupport};
 use crate::{
+    bitmaps::BitmapIndex,
     impl_bitmap_newtype,
     memory::nodesets::NodeSet,
     objects::{depth::Depth, types::ObjectType, TopologyObject},
@@ -18,7 +19,7 @@ use thiserror::Error;
 /// # Finding objects inside a CPU set
 //
 // This is inspired by the upstream functionality described at
-// https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__helper__find__inside.html
+// https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__helper__find__inside.html
 // but the code had to be ported to Rust as most C code is inline and thus
 // cannot be called from Rust, and the only function that's not inline does not
 // fit Rust's design (assumes caller has allocated large enough storage with no
@@ -33,8 +34,25 @@ impl Topology {
     /// iteration can be more efficiently performed by using
     /// [`coarsest_cpuset_partition()`].
     ///
+    /// The iterator takes ownership of `set`, which it uses as a scratchpad to
+    /// keep track of the CPUs that have not been covered yet.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// let set = topology.cpuset().to_owned();
+    /// let mut covered = hwlocality::cpu::cpusets::CpuSet::new();
+    /// for obj in topology.largest_objects_inside_cpuset(set.clone()) {
+    ///     let obj_set = obj.cpuset().expect("Normal objects have a cpuset");
+    ///     assert!(!covered.intersects(&obj_set));
+    ///     covered |= obj_set;
+    /// }
+    /// assert_eq!(covered, set);
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    ///
     /// [`coarsest_cpuset_partition()`]: Topology::coarsest_cpuset_partition()
-    #[doc(alias = "hwloc_get_first_largest_obj_inside_cpuset")]
     pub fn largest_objects_inside_cpuset(
         &self,
         set: CpuSet,
@@ -60,6 +78,44 @@ impl Topology {
         &self,
         set: &CpuSet,
     ) -> Result<Vec<&TopologyObject>, CoarsestPartitionError> {
+        let mut result = Vec::new();
+        self.coarsest_cpuset_partition_into(set, &mut result)?;
+        Ok(result)
+    }
+
+    /// Like [`coarsest_cpuset_partition()`], but writes the objects into a
+    /// caller-provided buffer
+    ///
+    /// `result` is cleared before use. Reusing the same buffer across calls
+    /// avoids allocating a new `Vec` every time, which matters on hot paths.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Errors
+    ///
+    /// See [`coarsest_cpuset_partition()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// let mut objects = Vec::new();
+    /// for pu in topology.objects_with_type(hwlocality::objects::types::ObjectType::PU) {
+    ///     let cpuset = pu.cpuset().unwrap();
+    ///     topology.coarsest_cpuset_partition_into(&cpuset, &mut objects)?;
+    ///     assert_eq!(objects.len(), 1);
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    ///
+    /// [`coarsest_cpuset_partition()`]: Topology::coarsest_cpuset_partition()
+    pub fn coarsest_cpuset_partition_into<'self_>(
+        &'self_ self,
+        set: &CpuSet,
+        result: &mut Vec<&'self_ TopologyObject>,
+    ) -> Result<(), CoarsestPartitionError> {
+        result.clear();
+
         // Make sure each set index actually maps into a hardware PU
         let root = self.root_object();
         let root_cpuset = root.cpuset().expect("Root should have a CPU set");
@@ -71,7 +127,6 @@ impl Topology {
         }
 
         // Start recursion
-        let mut result = Vec::new();
         let mut cpusets = Vec::new();
         fn process_object<'a>(
             parent: &'a TopologyObject,
@@ -109,8 +164,8 @@ impl Topology {
             }
             cpusets.push(subset);
         }
-        process_object(root, set, &mut result, &mut cpusets);
-        Ok(result)
+        process_object(root, set, result, &mut cpusets);
+        Ok(())
     }
 
     /// Enumerate objects included in the given cpuset `set` at a certain depth
@@ -178,17 +233,34 @@ impl Topology {
     /// This is convenient for iterating over all largest objects within a CPU
     /// set by doing a loop getting the first largest object and clearing its
     /// CPU set from the remaining CPU set. This very pattern is exposed by
-    /// the `largest_objects_inside_cpuset` method, which is why this method is
-    /// not publicly exposed.
+    /// the [`largest_objects_inside_cpuset()`] method.
     ///
     /// That being said, if the cpuset is a strict subset of the root cpuset of
     /// this `Topology`, the work may be more efficiently done by
-    /// `largest_cpuset_partition()`, which only needs to walk the topology
+    /// [`coarsest_cpuset_partition()`], which only needs to walk the topology
     /// tree once.
     ///
     /// Objects with empty CPU sets are ignored (otherwise they would be
     /// considered included in any given set).
-    fn first_largest_object_inside_cpuset(&self, set: &CpuSet) -> Option<&TopologyObject> {
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// // The whole topology cpuset is covered by the root object
+    /// let set = topology.cpuset().to_owned();
+    /// let root = topology.root_object();
+    /// assert!(std::ptr::eq(
+    ///     topology.first_largest_object_inside_cpuset(&set).unwrap(),
+    ///     root
+    /// ));
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    ///
+    /// [`coarsest_cpuset_partition()`]: Topology::coarsest_cpuset_partition()
+    /// [`largest_objects_inside_cpuset()`]: Topology::largest_objects_inside_cpuset()
+    #[doc(alias = "hwloc_get_first_largest_obj_inside_cpuset")]
+    pub fn first_largest_object_inside_cpuset(&self, set: &CpuSet) -> Option<&TopologyObject> {
         // If root object doesn't intersect this CPU set then no child will
         let root = self.root_object();
         let root_cpuset = root.cpuset().expect("Root should have a CPU set");
@@ -263,7 +335,7 @@ pub struct CoarsestPartitionError {
 /// # Finding objects covering at least a CPU set
 //
 // This is inspired by the upstream functionality described at
-// https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__helper__find__covering.html
+// https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__helper__find__covering.html
 // but the code had to be ported to Rust because it's inline
 impl Topology {
     /// Get the lowest object covering at least the given cpuset `set`, if any
@@ -372,7 +444,21 @@ impl CpuSet {
     /// Hence the entire topology node set, that one can query via
     /// [`Topology::nodeset()`], would be converted by this function into the
     /// set of all CPUs that have some local NUMA nodes.
+    ///
+    /// The reverse conversion is performed by [`NodeSet::from_cpuset()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::cpu::cpusets::CpuSet;
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// // Every PU of the test topology has a local NUMA node
+    /// let cpuset = CpuSet::from_nodeset(&topology, &topology.nodeset());
+    /// assert_eq!(cpuset, topology.cpuset());
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
     #[doc(alias = "hwloc_cpuset_from_nodeset")]
+    #[doc(alias = "convert_nodeset_to_cpuset")]
     pub fn from_nodeset(topology: &Topology, nodeset: &NodeSet) -> CpuSet {
         let mut cpuset = CpuSet::new();
         for obj in topology.objects_at_depth(Depth::NUMANode) {
@@ -382,13 +468,290 @@ impl CpuSet {
         }
         cpuset
     }
+
+    /// Build a CPU set from the logical indices of some [`PU`]s
+    ///
+    /// CPU sets are indexed by OS (physical) PU index, whereas many
+    /// applications and tools like `lstopo -l` number PUs by logical index.
+    /// This converts the latter into the former. The reverse conversion is
+    /// performed by [`Topology::logical_indices()`].
+    ///
+    /// # Errors
+    ///
+    /// - [`BadLogicalIndex`] if one of the `logical_indices` does not match a
+    ///   [`PU`] of `topology`.
+    ///
+    /// [`PU`]: ObjectType::PU
+    pub fn from_logical_indices(
+        topology: &Topology,
+        logical_indices: impl IntoIterator<Item = usize>,
+    ) -> Result<CpuSet, BadLogicalIndex> {
+        let pus = topology
+            .objects_with_type(ObjectType::PU)
+            .collect::<Vec<_>>();
+        let mut cpuset = CpuSet::new();
+        for logical_index in logical_indices {
+            let pu = pus
+                .get(logical_index)
+                .ok_or(BadLogicalIndex(logical_index))?;
+            cpuset.set(pu.os_index().expect("PUs should have an OS index"));
+        }
+        Ok(cpuset)
+    }
+
+    /// Build a CPU set from `core_affinity`-style CPU identifiers
+    ///
+    /// The `core_affinity` crate identifies CPUs using a `CoreId` whose `id`
+    /// field is the OS index of a [`PU`] (not of a [`Core`], despite the name),
+    /// which is exactly how CPU sets are indexed. This lets you feed a list of
+    /// such identifiers into hwlocality using
+    /// `CpuSet::from_core_ids(core_ids.iter().map(|core| core.id))`.
+    ///
+    /// The reverse conversion is performed by [`CpuSet::core_ids()`].
+    ///
+    /// # Errors
+    ///
+    /// - [`BadCoreId`] if one of the `core_ids` is too large to be a valid
+    ///   bitmap index.
+    ///
+    /// [`Core`]: ObjectType::Core
+    /// [`PU`]: ObjectType::PU
+    pub fn from_core_ids(core_ids: impl IntoIterator<Item = usize>) -> Result<CpuSet, BadCoreId> {
+        let mut cpuset = CpuSet::new();
+        for core_id in core_ids {
+            let idx = BitmapIndex::try_from(core_id).map_err(|_| BadCoreId(core_id))?;
+            cpuset.set(idx);
+        }
+        Ok(cpuset)
+    }
+
+    /// Enumerate the `core_affinity`-style identifiers of the CPUs in this set
+    ///
+    /// This is the reverse of [`CpuSet::from_core_ids()`], and can be used to
+    /// build a list of `core_affinity::CoreId { id }` for code that has not
+    /// been migrated to hwlocality yet.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::cpu::cpusets::CpuSet;
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// let core_ids = topology.cpuset().core_ids().collect::<Vec<_>>();
+    /// assert_eq!(CpuSet::from_core_ids(core_ids)?, topology.cpuset());
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn core_ids(&self) -> impl FusedIterator<Item = usize> + '_ {
+        self.iter_set().map(usize::from)
+    }
+
+    /// Enumerate the [`Core`]s of `topology` that have PUs in this set
+    ///
+    /// Unlike [`Topology::objects_inside_cpuset_with_type()`], this also
+    /// lists cores of which this set only contains some PUs, which is what
+    /// you want when e.g. figuring out which cores a thread pool may run on.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::objects::types::ObjectType;
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// let first_pu = topology.objects_with_type(ObjectType::PU).next().unwrap();
+    /// let cpuset = first_pu.cpuset().unwrap().to_owned();
+    /// assert_eq!(cpuset.cores(&topology).count(), 1);
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    ///
+    /// [`Core`]: ObjectType::Core
+    pub fn cores<'result>(
+        &'result self,
+        topology: &'result Topology,
+    ) -> impl DoubleEndedIterator<Item = &'result TopologyObject> + Clone + FusedIterator + 'result
+    {
+        self.intersecting_objects(topology, ObjectType::Core)
+    }
+
+    /// Enumerate the [`Package`]s of `topology` that have PUs in this set
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// [`Package`]: ObjectType::Package
+    pub fn packages<'result>(
+        &'result self,
+        topology: &'result Topology,
+    ) -> impl DoubleEndedIterator<Item = &'result TopologyObject> + Clone + FusedIterator + 'result
+    {
+        self.intersecting_objects(topology, ObjectType::Package)
+    }
+
+    /// Enumerate the [`NUMANode`]s of `topology` that are local to some PUs in
+    /// this set
+    ///
+    /// The [`NodeSet`] of these NUMA nodes can be computed more directly using
+    /// [`NodeSet::from_cpuset()`].
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// [`NUMANode`]: ObjectType::NUMANode
+    pub fn numa_nodes<'result>(
+        &'result self,
+        topology: &'result Topology,
+    ) -> impl DoubleEndedIterator<Item = &'result TopologyObject> + Clone + FusedIterator + 'result
+    {
+        self.intersecting_objects(topology, ObjectType::NUMANode)
+    }
+
+    /// Objects of type `object_type` whose cpuset intersects this set
+    fn intersecting_objects<'result>(
+        &'result self,
+        topology: &'result Topology,
+        object_type: ObjectType,
+    ) -> impl DoubleEndedIterator<Item = &'result TopologyObject> + Clone + FusedIterator + 'result
+    {
+        topology
+            .objects_with_type(object_type)
+            .filter(move |object| {
+                object
+                    .cpuset()
+                    .map_or(false, |object_cpuset| object_cpuset.intersects(self))
+            })
+    }
+
+    /// PUs of `allowed` that are not in this set
+    ///
+    /// This is typically used to find the CPUs that are left for other tasks
+    /// once some have been reserved, e.g. by passing the
+    /// [allowed cpuset](Topology::allowed_cpuset()) of the topology as
+    /// `allowed`. Unlike the `!` operator, this never produces an infinite
+    /// set.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::cpu::cpusets::CpuSet;
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// let allowed = topology.allowed_cpuset();
+    /// let reserved = CpuSet::from(allowed.first_set().unwrap());
+    /// let others = reserved.complement_within(&allowed);
+    /// assert_eq!(others.weight(), allowed.weight().map(|w| w - 1));
+    /// assert!(!others.intersects(&reserved));
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn complement_within(&self, allowed: &CpuSet) -> CpuSet {
+        allowed - self
+    }
 }
 
+/// # glibc `sched_setaffinity` interop
+///
+/// These conversions let hwlocality interoperate with code that calls
+/// `sched_setaffinity()` or `pthread_setaffinity_np()` directly, e.g. via
+/// the `libc` or `nix` crates.
+//
+// Upstream docs: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__glibc__sched.html
+#[cfg(target_os = "linux")]
+impl CpuSet {
+    /// Convert this CPU set into a libc `cpu_set_t`
+    ///
+    /// `topology` is not used by the current implementation, but is required
+    /// for consistency with hwloc's `hwloc_cpuset_to_glibc_sched_affinity()`.
+    ///
+    /// # Errors
+    ///
+    /// - [`BadLibcCpu`] if this set contains a PU whose OS index is too large
+    ///   to be stored in a `cpu_set_t`, which includes infinite sets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::cpu::cpusets::CpuSet;
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// let cpuset = topology.cpuset();
+    /// let libc_cpuset = cpuset.to_libc_cpuset(topology)?;
+    /// let size = std::mem::size_of::<libc::cpu_set_t>();
+    /// assert_eq!(
+    ///     unsafe { libc::CPU_COUNT_S(size, &libc_cpuset) },
+    ///     cpuset.weight().unwrap() as i32
+    /// );
+    /// assert_eq!(CpuSet::from_libc_cpuset(topology, &libc_cpuset), cpuset);
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    #[doc(alias = "hwloc_cpuset_to_glibc_sched_affinity")]
+    pub fn to_libc_cpuset(&self, _topology: &Topology) -> Result<libc::cpu_set_t, BadLibcCpu> {
+        // SAFETY: cpu_set_t is a plain array of integers, so all-zeroes is a
+        //         valid (empty) set
+        let mut result: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+        for pu in self.iter_set() {
+            let pu = usize::from(pu);
+            if pu >= LIBC_CPU_SET_CAPACITY {
+                return Err(BadLibcCpu(pu));
+            }
+            // SAFETY: pu was checked to be in bounds above
+            unsafe { libc::CPU_SET(pu, &mut result) };
+        }
+        Ok(result)
+    }
+
+    /// Convert a libc `cpu_set_t` into a CPU set
+    ///
+    /// `topology` is not used by the current implementation, but is required
+    /// for consistency with hwloc's `hwloc_cpuset_from_glibc_sched_affinity()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::cpu::cpusets::CpuSet;
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// let mut libc_cpuset: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    /// unsafe { libc::CPU_SET(0, &mut libc_cpuset) };
+    /// assert_eq!(
+    ///     CpuSet::from_libc_cpuset(topology, &libc_cpuset),
+    ///     CpuSet::from_range(0..=0)
+    /// );
+    /// ```
+    #[doc(alias = "hwloc_cpuset_from_glibc_sched_affinity")]
+    pub fn from_libc_cpuset(_topology: &Topology, set: &libc::cpu_set_t) -> CpuSet {
+        let mut result = CpuSet::new();
+        for pu in 0..LIBC_CPU_SET_CAPACITY {
+            // SAFETY: pu is in bounds by construction
+            if unsafe { libc::CPU_ISSET(pu, set) } {
+                result.set(pu);
+            }
+        }
+        result
+    }
+}
+
+/// Number of CPUs that a libc `cpu_set_t` can hold
+#[cfg(target_os = "linux")]
+const LIBC_CPU_SET_CAPACITY: usize = 8 * std::mem::size_of::<libc::cpu_set_t>();
+
 #[cfg(feature = "hwloc-2_2_0")]
 #[derive(Copy, Clone, Debug, Default, Error, Eq, Hash, PartialEq)]
 #[error("{0} is not a valid hwloc PU index")]
 pub struct BadPUIndex(usize);
 
+/// Error returned when a logical index does not match any PU of the topology
+#[derive(Copy, Clone, Debug, Default, Error, Eq, Hash, PartialEq)]
+#[error("{0} is not a valid PU logical index")]
+pub struct BadLogicalIndex(pub usize);
+
+/// Error returned when a `core_affinity`-style CPU identifier cannot be stored
+/// in a [`CpuSet`]
+#[derive(Copy, Clone, Debug, Default, Error, Eq, Hash, PartialEq)]
+#[error("{0} is not a valid CPU identifier")]
+pub struct BadCoreId(pub usize);
+
+/// Error returned when a [`CpuSet`] contains a PU that cannot be stored in a
+/// libc `cpu_set_t`
+#[cfg(target_os = "linux")]
+#[derive(Copy, Clone, Debug, Default, Error, Eq, Hash, PartialEq)]
+#[error("PU {0} cannot be stored in a libc cpu_set_t")]
+pub struct BadLibcCpu(pub usize);
+
 impl_bitmap_newtype!(
     /// A `CpuSet` is a [`Bitmap`] whose bits are set according to CPU physical
     /// OS indexes
@@ -1,5 +1,7 @@
 //! Kinds of CPU cores
 
+#[cfg(feature = "hwloc-2_8_0")]
+use crate::topology::builder::BuildFlags;
 #[cfg(doc)]
 use crate::topology::support::DiscoverySupport;
 use crate::{
@@ -7,7 +9,7 @@ use crate::{
     errors::{self, HybridError, RawHwlocError},
     ffi::{self, LibcString},
     info::TextualInfo,
-    topology::{editor::TopologyEditor, Topology},
+    topology::{builder::FeatureDisabledAtBuild, editor::TopologyEditor, Topology},
 };
 use libc::{EINVAL, ENOENT, EXDEV};
 use std::{
@@ -31,7 +33,7 @@ use thiserror::Error;
 ///
 /// Information about CPU kinds can also be enumerated using [`cpu_kinds()`].
 /// For each CPU kind, an abstracted efficiency value is provided, along with
-/// [info attributes](https://hwloc.readthedocs.io/en/v2.9/topoattrs.html#topoattrs_cpukinds)
+/// [info attributes](https://hwloc.readthedocs.io/en/v2.11/topoattrs.html#topoattrs_cpukinds)
 /// such as "CoreType" or "FrequencyMaxMHz".
 ///
 /// A higher efficiency value means greater intrinsic performance (and possibly
@@ -52,7 +54,7 @@ use thiserror::Error;
 /// (on ARM), or core types and frequencies (on other architectures). The
 /// environment variable `HWLOC_CPUKINDS_RANKING` may be used to change this
 /// heuristics, see [Environment
-/// Variables](https://hwloc.readthedocs.io/en/v2.9/envvar.html).
+/// Variables](https://hwloc.readthedocs.io/en/v2.11/envvar.html).
 ///
 /// If hwloc fails to rank any kind, for instance because the operating system
 /// does not expose efficiencies and core frequencies, all kinds will have an
@@ -61,36 +63,70 @@ use thiserror::Error;
 /// The kind that describes a given CPU set (if any, and not partially) may also
 /// be queried with [`cpu_kind_from_set()`].
 ///
+/// If the topology was built with `BuildFlags::IGNORE_CPU_KINDS`, CPU kinds
+/// are not queried from the operating system and will only be known if
+/// registered by the user. Queries report a [`FeatureDisabledAtBuild`] error
+/// instead of an absence of CPU kinds in this case.
+///
 /// [`cpu_kind_from_set()`]: Topology::cpu_kind_from_set()
 /// [`cpu_kinds()`]: Topology::cpu_kinds()
 /// [`num_cpu_kinds()`]: Topology::num_cpu_kinds()
 //
-// Upstream docs: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__cpukinds.html
+// Upstream docs: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__cpukinds.html
 impl Topology {
     /// Number of different kinds of CPU cores in the topology
     ///
     /// # Errors
     ///
-    /// - [`CpuKindsUnknown`] if no information about CPU kinds was found
+    /// - [`Unknown`] if no information about CPU kinds was found
+    /// - [`FeatureDisabledAtBuild`] if no information about CPU kinds was
+    ///   found and the topology was built with `BuildFlags::IGNORE_CPU_KINDS`
+    ///
+    /// [`FeatureDisabledAtBuild`]: CpuKindsQueryError::FeatureDisabledAtBuild
+    /// [`Unknown`]: CpuKindsQueryError::Unknown
     #[doc(alias = "hwloc_cpukinds_get_nr")]
-    pub fn num_cpu_kinds(&self) -> Result<NonZeroUsize, CpuKindsUnknown> {
+    pub fn num_cpu_kinds(&self) -> Result<NonZeroUsize, CpuKindsQueryError> {
         let count = errors::call_hwloc_int_normal("hwloc_cpukinds_get_nr", || unsafe {
             ffi::hwloc_cpukinds_get_nr(self.as_ptr(), 0)
         })
         .expect("All known failure cases are prevented by API design");
-        NonZeroUsize::new(ffi::expect_usize(count)).ok_or(CpuKindsUnknown)
+        if let Some(count) = NonZeroUsize::new(ffi::expect_usize(count)) {
+            return Ok(count);
+        }
+        #[cfg(feature = "hwloc-2_8_0")]
+        self.ensure_features_enabled(BuildFlags::IGNORE_CPU_KINDS)?;
+        Err(CpuKindsQueryError::Unknown)
     }
 
-    /// Enumerate CPU kinds, from least efficient efficient to most efficient
+    /// Enumerate CPU kinds, from least efficient to most efficient
     ///
     /// For each CPU kind, provide the [`CpuSet`] of PUs belonging to that kind,
     /// how efficient this CPU kind is (if CPU kind efficiencies are known) and
     /// [other things we know about
-    /// it](https://hwloc.readthedocs.io/en/v2.9/topoattrs.html#topoattrs_cpukinds).
+    /// it](https://hwloc.readthedocs.io/en/v2.11/topoattrs.html#topoattrs_cpukinds).
     ///
     /// # Errors
     ///
-    /// - [`CpuKindsUnknown`] if no information about CPU kinds was found
+    /// Same as [`Topology::num_cpu_kinds()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// // Run latency-critical work on the highest-performance cores, e.g.
+    /// // P-cores on Intel hybrid CPUs, and background work on the others
+    /// if let Ok(mut kinds) = topology.cpu_kinds() {
+    ///     let (fast_cpus, efficiency, infos) = kinds.next_back().unwrap();
+    ///     println!("Fastest CPUs: {fast_cpus} (efficiency {efficiency:?})");
+    ///     for info in infos {
+    ///         println!("- {:?} = {:?}", info.name(), info.value());
+    ///     }
+    ///     for (slow_cpus, _efficiency, _infos) in kinds {
+    ///         assert!(!slow_cpus.intersects(&fast_cpus));
+    ///     }
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
     #[doc(alias = "hwloc_cpukinds_get_info")]
     pub fn cpu_kinds(
         &self,
@@ -100,7 +136,7 @@ impl Topology {
             + DoubleEndedIterator
             + ExactSizeIterator
             + FusedIterator,
-        CpuKindsUnknown,
+        CpuKindsQueryError,
     > {
         // Iterate over all CPU kinds
         let num_cpu_kinds = usize::from(self.num_cpu_kinds()?);
@@ -156,12 +192,28 @@ impl Topology {
     ///   (i.e. some CPUs in the set belong to a kind, others to other kind(s))
     /// - [`NotIncluded`] if `set` is not included in any kind, even partially
     ///   (i.e. CPU kind info isn't known or CPU set does not cover real CPUs)
+    /// - [`FeatureDisabledAtBuild`] if `set` is not included in any kind and
+    ///   the topology was built with `BuildFlags::IGNORE_CPU_KINDS`
     /// - [`InvalidSet`] if the CPU set is considered invalid for another reason
     ///
+    /// [`FeatureDisabledAtBuild`]: CpuKindFromSetError::FeatureDisabledAtBuild
     /// [`InvalidSet`]: CpuKindFromSetError::InvalidSet
     /// [`NotIncluded`]: CpuKindFromSetError::NotIncluded
     /// [`PartiallyIncluded`]: CpuKindFromSetError::PartiallyIncluded
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::cpu::cpusets::CpuSet;
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// let first_pu = CpuSet::from(topology.cpuset().first_set().unwrap());
+    /// if let Ok((kind_cpus, _efficiency, _infos)) = topology.cpu_kind_from_set(&first_pu) {
+    ///     assert!(kind_cpus.includes(&first_pu));
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
     #[doc(alias = "hwloc_cpukinds_get_by_cpuset")]
+    #[doc(alias = "cpu_kind_of")]
     pub fn cpu_kind_from_set(
         &self,
         set: &CpuSet,
@@ -178,7 +230,11 @@ impl Topology {
                 },
             ) => match errno.0 {
                 EXDEV => return Err(CpuKindFromSetError::PartiallyIncluded),
-                ENOENT => return Err(CpuKindFromSetError::NotIncluded),
+                ENOENT => {
+                    #[cfg(feature = "hwloc-2_8_0")]
+                    self.ensure_features_enabled(BuildFlags::IGNORE_CPU_KINDS)?;
+                    return Err(CpuKindFromSetError::NotIncluded);
+                }
                 EINVAL => return Err(CpuKindFromSetError::InvalidSet),
                 _ => unreachable!("Unexpected hwloc error: {raw_error}"),
             },
@@ -190,7 +246,7 @@ impl Topology {
 
 /// # Kinds of CPU cores
 //
-// Upstream docs: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__cpukinds.html
+// Upstream docs: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__cpukinds.html
 impl<'topology> TopologyEditor<'topology> {
     /// Register a kind of CPU in the topology.
     ///
@@ -272,10 +328,18 @@ impl<'topology> TopologyEditor<'topology> {
 /// Efficiency ranges from 0 to the number of CPU kinds minus one.
 pub type CpuEfficiency = usize;
 
-/// No information about CPU kinds was found
-#[derive(Copy, Clone, Debug, Default, Error, Eq, Hash, PartialEq)]
-#[error("no information about CPU kinds was found")]
-pub struct CpuKindsUnknown;
+/// Error while querying CPU kinds
+#[derive(Copy, Clone, Debug, Error, Eq, Hash, PartialEq)]
+pub enum CpuKindsQueryError {
+    /// No information about CPU kinds was found
+    #[error("no information about CPU kinds was found")]
+    Unknown,
+
+    /// No information about CPU kinds was found, and CPU kinds were disabled
+    /// at build time
+    #[error(transparent)]
+    FeatureDisabledAtBuild(#[from] FeatureDisabledAtBuild),
+}
 
 /// Error while querying a CPU kind from a CPU set
 #[derive(Copy, Clone, Debug, Error, Eq, Hash, PartialEq)]
@@ -296,6 +360,11 @@ pub enum CpuKindFromSetError {
     /// non-existent CPUs)
     #[error("CPU set is invalid")]
     InvalidSet,
+
+    /// CPU set is not included in any kind, and CPU kinds were disabled at
+    /// build time
+    #[error(transparent)]
+    FeatureDisabledAtBuild(#[from] FeatureDisabledAtBuild),
 }
 
 /// Error while registering a new CPU kind
@@ -5,3 +5,7 @@ pub mod caches;
 pub mod cpusets;
 #[cfg(feature = "hwloc-2_4_0")]
 pub mod kinds;
+pub mod partition;
+pub mod per_cpu;
+pub mod thread_pool;
+pub mod tree_barrier;
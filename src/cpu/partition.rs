@@ -0,0 +1,539 @@
+//! Partitioning CPUs between groups of work
+//!
+//! [`Topology::distribute_items()`] exposes hwloc's work distribution
+//! algorithm, but thread pool authors usually need more than that: they want
+//! to choose between packing workers close to each other or scattering them
+//! across the machine, to avoid SMT siblings or slow CPU cores, and to know
+//! which NUMA nodes each group of workers should allocate memory from. This
+//! module provides a [`Partitioner`] that takes care of all of this.
+
+use super::cpusets::CpuSet;
+use crate::{
+    memory::nodesets::NodeSet,
+    objects::{types::ObjectType, TopologyObject},
+    topology::Topology,
+};
+use std::num::NonZeroUsize;
+use thiserror::Error;
+
+/// Partition the CPUs of a topology between groups of work
+///
+/// Set up with [`Partitioner::new()`], configure with the builder methods,
+/// then compute the partition with [`Partitioner::partition()`].
+///
+/// This functionality is unique to the Rust hwloc bindings.
+///
+/// # Examples
+///
+/// ```
+/// # use hwlocality::cpu::partition::{PartitionPolicy, Partitioner};
+/// # use std::num::NonZeroUsize;
+/// # let topology = hwlocality::Topology::test_instance();
+/// // Split the machine between 4 thread pools, avoiding SMT siblings
+/// let num_pools = NonZeroUsize::new(4).unwrap();
+/// let pools = Partitioner::new(&topology)
+///     .groups(num_pools)
+///     .policy(PartitionPolicy::Spread)
+///     .smt(false)
+///     .partition()?;
+/// assert_eq!(pools.len(), num_pools.get());
+/// for pool in &pools {
+///     assert!(!pool.cpuset().is_empty());
+///     assert!(topology.cpuset().includes(pool.cpuset()));
+///     println!("Pool on CPUs {} uses NUMA nodes {}", pool.cpuset(), pool.nodeset());
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct Partitioner<'topology> {
+    /// Topology whose CPUs are being partitioned
+    topology: &'topology Topology,
+
+    /// Number of groups
+    num_groups: NonZeroUsize,
+
+    /// How CPUs are assigned to groups
+    policy: PartitionPolicy,
+
+    /// Truth that several PUs of a core may be used
+    smt: bool,
+
+    /// CPUs that may be used
+    cpuset: CpuSet,
+
+    /// Truth that only the most efficient kind of CPU should be used
+    #[cfg(feature = "hwloc-2_4_0")]
+    best_cpu_kind_only: bool,
+}
+//
+impl<'topology> Partitioner<'topology> {
+    /// Prepare to partition the allowed CPUs of `topology`
+    ///
+    /// By default, all allowed CPUs are assigned to a single group using the
+    /// [`Spread`](PartitionPolicy::Spread) policy.
+    pub fn new(topology: &'topology Topology) -> Self {
+        Self {
+            topology,
+            num_groups: NonZeroUsize::new(1).expect("1 is not 0"),
+            policy: PartitionPolicy::default(),
+            smt: true,
+            cpuset: topology.cpuset() & topology.allowed_cpuset(),
+            #[cfg(feature = "hwloc-2_4_0")]
+            best_cpu_kind_only: false,
+        }
+    }
+
+    /// Set the number of groups to partition CPUs between
+    pub fn groups(mut self, num_groups: NonZeroUsize) -> Self {
+        self.num_groups = num_groups;
+        self
+    }
+
+    /// Set how CPUs are assigned to groups
+    pub fn policy(mut self, policy: PartitionPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Truth that groups may use several PUs of the same core
+    ///
+    /// This is the default. If set to `false`, only the first PU of each core
+    /// is used, which avoids simultaneous multithreading (Hyper-Threading)
+    /// and is often beneficial for compute-bound work.
+    pub fn smt(mut self, smt: bool) -> Self {
+        self.smt = smt;
+        self
+    }
+
+    /// Only use CPUs from `cpuset`
+    ///
+    /// CPUs that are not in `cpuset` are excluded from the partition. By
+    /// default, all allowed CPUs of the topology are used.
+    pub fn within(mut self, cpuset: &CpuSet) -> Self {
+        self.cpuset &= cpuset;
+        self
+    }
+
+    /// Truth that only the most efficient kind of CPU should be used
+    ///
+    /// On hybrid CPUs, this restricts the partition to the highest-performance
+    /// cores (e.g. P-cores on Intel hybrid CPUs). This has no effect if
+    /// [CPU kinds](Topology::cpu_kinds()) are not known.
+    #[cfg(feature = "hwloc-2_4_0")]
+    pub fn best_cpu_kind_only(mut self, best_cpu_kind_only: bool) -> Self {
+        self.best_cpu_kind_only = best_cpu_kind_only;
+        self
+    }
+
+    /// Compute the partition
+    ///
+    /// One [`Partition`] is emitted per group, in group order.
+    ///
+    /// # Errors
+    ///
+    /// - [`NoCpusError`] if no CPU is left to partition after applying the
+    ///   configured restrictions
+    pub fn partition(&self) -> Result<Vec<Partition>, NoCpusError> {
+        let cpuset = self.available_cpus();
+        if cpuset.is_empty() {
+            return Err(NoCpusError);
+        }
+        let num_groups = self.num_groups.get();
+        let cpusets = match self.policy {
+            PartitionPolicy::Spread => {
+                let mut result = Vec::with_capacity(num_groups);
+                let root = self.topology.root_object();
+                let roots = restrict(std::iter::once(root), &cpuset);
+                spread(roots, num_groups, &cpuset, &mut result);
+                result
+            }
+            PartitionPolicy::Compact => {
+                let pus = self.topology.pus_from_cpuset(&cpuset).collect::<Vec<_>>();
+                assign_pus(&pus, num_groups)
+            }
+            PartitionPolicy::Scatter => {
+                let pus = scatter_order(self.topology.root_object(), &cpuset);
+                assign_pus(&pus, num_groups)
+            }
+        };
+        debug_assert_eq!(cpusets.len(), num_groups);
+        Ok(cpusets
+            .into_iter()
+            .map(|cpuset| Partition {
+                nodeset: NodeSet::from_cpuset(self.topology, &cpuset),
+                cpuset,
+            })
+            .collect())
+    }
+
+    /// CPUs that can be used after applying all restrictions
+    fn available_cpus(&self) -> CpuSet {
+        let mut cpuset = self.cpuset.clone();
+        #[cfg(feature = "hwloc-2_4_0")]
+        if self.best_cpu_kind_only {
+            if let Ok(kinds) = self.topology.cpu_kinds() {
+                if let Some((best_cpus, _efficiency, _infos)) = kinds.last() {
+                    cpuset &= best_cpus;
+                }
+            }
+        }
+        if !self.smt {
+            for core in self.topology.objects_with_type(ObjectType::Core) {
+                let core_cpuset = core.cpuset().expect("Cores should have a cpuset");
+                if let Some(first_pu) = (&cpuset & &core_cpuset).first_set() {
+                    cpuset -= core_cpuset;
+                    cpuset.set(first_pu);
+                }
+            }
+        }
+        cpuset
+    }
+}
+
+/// How CPUs are assigned to groups by a [`Partitioner`]
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub enum PartitionPolicy {
+    /// Split all CPUs between groups, following the topology hierarchy
+    ///
+    /// This is the algorithm of [`Topology::distribute_items()`]: each group
+    /// gets a share of the CPUs that is proportional to their number, and
+    /// groups with neighboring indices get neighbouring CPUs, which have a
+    /// high chance of sharing resources like CPU caches. If there are more
+    /// groups than CPUs, some groups share a CPU.
+    #[default]
+    Spread,
+
+    /// Give each group a single PU, packing groups close to each other
+    ///
+    /// Groups are assigned PUs in logical order, so that groups with
+    /// neighboring indices are as close to each other as possible and use a
+    /// minimal fraction of the machine. If there are more groups than PUs,
+    /// PUs are reused starting from the first one.
+    Compact,
+
+    /// Give each group a single PU, scattering groups across the machine
+    ///
+    /// Groups are assigned PUs that are as far away from each other as
+    /// possible, e.g. consecutive groups go to different packages, then to
+    /// different cores of each package. This maximizes the amount of shared
+    /// resources like caches and memory bandwidth available to each group. If
+    /// there are more groups than PUs, PUs are reused starting from the first
+    /// one.
+    Scatter,
+}
+
+/// CPUs and NUMA nodes assigned to a group by a [`Partitioner`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Partition {
+    /// CPUs that the group should run on
+    cpuset: CpuSet,
+
+    /// NUMA nodes that are local to these CPUs
+    nodeset: NodeSet,
+}
+//
+impl Partition {
+    /// CPUs that the group should run on
+    pub fn cpuset(&self) -> &CpuSet {
+        &self.cpuset
+    }
+
+    /// NUMA nodes that are local to [the group's CPUs](Self::cpuset())
+    ///
+    /// This is where the group should allocate its memory from.
+    pub fn nodeset(&self) -> &NodeSet {
+        &self.nodeset
+    }
+
+    /// Extract the CPU and NUMA node sets
+    pub fn into_sets(self) -> (CpuSet, NodeSet) {
+        (self.cpuset, self.nodeset)
+    }
+}
+
+/// Error returned when there are no CPUs to partition
+///
+/// This happens when the [`Partitioner`] is restricted to a set of CPUs that
+/// contains no allowed CPU of the topology.
+#[derive(Copy, Clone, Debug, Default, Eq, Error, Hash, PartialEq)]
+#[error("there are no CPUs to partition")]
+pub struct NoCpusError;
+
+/// Pair up each object with the part of its cpuset that is in `cpuset` and
+/// the number of CPUs in there, dropping objects with no CPU in `cpuset`
+fn restrict<'object>(
+    objects: impl Iterator<Item = &'object TopologyObject>,
+    cpuset: &CpuSet,
+) -> Vec<(&'object TopologyObject, CpuSet, usize)> {
+    objects
+        .filter_map(|object| {
+            let object_cpuset = object.cpuset()? & cpuset;
+            let weight = object_cpuset
+                .weight()
+                .expect("Topology objects should not have infinite cpusets");
+            (weight > 0).then_some((object, object_cpuset, weight))
+        })
+        .collect()
+}
+
+/// Recursively split the CPUs of `objects` between `num_items` items
+///
+/// This follows the algorithm of [`Topology::distribute_items()`], with object
+/// cpusets restricted to `cpuset`.
+fn spread(
+    objects: Vec<(&TopologyObject, CpuSet, usize)>,
+    num_items: usize,
+    cpuset: &CpuSet,
+    result: &mut Vec<CpuSet>,
+) {
+    debug_assert!(!objects.is_empty());
+    debug_assert_ne!(num_items, 0);
+    let total_weight = objects.iter().map(|(_, _, weight)| weight).sum::<usize>();
+    let mut given_weight = 0;
+    let mut given_items = 0;
+    for (object, object_cpuset, weight) in objects {
+        // Give this object a number of items proportional to its weight, with
+        // a bias towards giving more items to the first objects
+        given_weight += weight;
+        let next_given_items = (given_weight * num_items + total_weight - 1) / total_weight;
+        let my_items = next_given_items - given_items;
+        given_items = next_given_items;
+
+        // Recurse until we reach the bottom of the topology or run out of items
+        let children = restrict(object.normal_children(), cpuset);
+        if my_items > 1 && !children.is_empty() {
+            spread(children, my_items, cpuset, result);
+        } else if my_items > 0 {
+            for _ in 0..my_items {
+                result.push(object_cpuset.clone());
+            }
+        } else {
+            *result.last_mut().expect("First object always gets items") |= object_cpuset;
+        }
+    }
+}
+
+/// Give one PU to each of `num_groups` groups, going through `pus` in order
+fn assign_pus(pus: &[&TopologyObject], num_groups: usize) -> Vec<CpuSet> {
+    debug_assert!(!pus.is_empty());
+    pus.iter()
+        .cycle()
+        .take(num_groups)
+        .map(|pu| pu.cpuset().expect("PUs should have a cpuset").to_owned())
+        .collect()
+}
+
+/// PUs below `object` that are in `cpuset`, ordered such that consecutive PUs
+/// are as far away from each other as possible
+fn scatter_order<'object>(
+    object: &'object TopologyObject,
+    cpuset: &CpuSet,
+) -> Vec<&'object TopologyObject> {
+    if object.object_type() == ObjectType::PU {
+        let in_cpuset = object
+            .cpuset()
+            .map_or(false, |pu_cpuset| pu_cpuset.intersects(cpuset));
+        return if in_cpuset { vec![object] } else { Vec::new() };
+    }
+    let mut children = restrict(object.normal_children(), cpuset)
+        .into_iter()
+        .map(|(child, _, _)| scatter_order(child, cpuset).into_iter())
+        .collect::<Vec<_>>();
+    let mut result = Vec::new();
+    loop {
+        let len = result.len();
+        for child in &mut children {
+            result.extend(child.next());
+        }
+        if result.len() == len {
+            break result;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quickcheck_macros::quickcheck;
+
+    /// Restrict the allowed CPUs of the test topology to the PUs selected by
+    /// `mask`, which is cycled over as needed
+    fn restricted_cpuset(mask: &[bool]) -> CpuSet {
+        let topology = Topology::test_instance();
+        let allowed = topology.cpuset() & topology.allowed_cpuset();
+        if mask.is_empty() {
+            return allowed;
+        }
+        allowed
+            .iter_set()
+            .zip(mask.iter().cycle())
+            .filter_map(|(pu, &keep)| keep.then_some(pu))
+            .collect()
+    }
+
+    /// Check that a partition splits `available` CPUs between `num_groups`
+    /// non-empty groups, which are disjoint if there are enough CPUs
+    fn check_coverage(partition: &[Partition], num_groups: usize, available: &CpuSet) {
+        let topology = Topology::test_instance();
+        assert_eq!(partition.len(), num_groups);
+        let mut union = CpuSet::new();
+        let mut num_overlaps = 0;
+        for group in partition {
+            assert!(!group.cpuset().is_empty());
+            assert!(available.includes(group.cpuset()));
+            assert_eq!(
+                group.nodeset(),
+                &NodeSet::from_cpuset(topology, group.cpuset())
+            );
+            if union.intersects(group.cpuset()) {
+                num_overlaps += 1;
+            }
+            union |= group.cpuset();
+        }
+        if num_groups <= available.weight().unwrap() {
+            assert_eq!(num_overlaps, 0);
+        }
+    }
+
+    #[quickcheck]
+    fn available_cpus(mask: Vec<bool>, smt: bool) {
+        let topology = Topology::test_instance();
+        let within = restricted_cpuset(&mask);
+        let partitioner = Partitioner::new(topology).within(&within).smt(smt);
+        let available = partitioner.available_cpus();
+        assert!(within.includes(&available));
+        for core in topology.objects_with_type(ObjectType::Core) {
+            let core_cpuset = core.cpuset().unwrap();
+            let in_within = within.intersection_weight(&core_cpuset).unwrap();
+            let in_available = available.intersection_weight(&core_cpuset).unwrap();
+            if smt {
+                assert_eq!(in_available, in_within);
+            } else {
+                assert_eq!(in_available, in_within.min(1));
+            }
+        }
+        if smt {
+            assert_eq!(available, within);
+        }
+    }
+
+    #[cfg(feature = "hwloc-2_4_0")]
+    #[test]
+    fn best_cpu_kind_only() {
+        let topology = Topology::test_instance();
+        let available = Partitioner::new(topology)
+            .best_cpu_kind_only(true)
+            .available_cpus();
+        match topology.cpu_kinds() {
+            Ok(kinds) => {
+                let (best_cpus, _efficiency, _infos) = kinds.last().unwrap();
+                assert_eq!(available, best_cpus & topology.allowed_cpuset());
+            }
+            Err(_) => assert_eq!(available, Partitioner::new(topology).available_cpus()),
+        }
+    }
+
+    #[quickcheck]
+    fn spread(num_groups: u8, mask: Vec<bool>) {
+        let num_groups = NonZeroUsize::new(usize::from(num_groups) + 1).unwrap();
+        let topology = Topology::test_instance();
+        let partitioner = Partitioner::new(topology)
+            .groups(num_groups)
+            .within(&restricted_cpuset(&mask));
+        let available = partitioner.available_cpus();
+        let Ok(partition) = partitioner.partition() else {
+            assert!(available.is_empty());
+            return;
+        };
+        check_coverage(&partition, num_groups.get(), &available);
+
+        // All available CPUs are used, in topology order
+        let union = partition
+            .iter()
+            .fold(CpuSet::new(), |acc, group| acc | group.cpuset());
+        assert_eq!(union, available);
+        for window in partition.windows(2) {
+            assert!(window[0].cpuset().first_set() <= window[1].cpuset().first_set());
+        }
+    }
+
+    #[quickcheck]
+    fn assign_pus(num_groups: u8, mask: Vec<bool>) {
+        let num_groups = NonZeroUsize::new(usize::from(num_groups) + 1).unwrap();
+        let topology = Topology::test_instance();
+        let cpuset = restricted_cpuset(&mask);
+        let pus = topology.pus_from_cpuset(&cpuset).collect::<Vec<_>>();
+        if pus.is_empty() {
+            return;
+        }
+        let cpusets = super::assign_pus(&pus, num_groups.get());
+        assert_eq!(cpusets.len(), num_groups.get());
+        for (idx, cpuset) in cpusets.iter().enumerate() {
+            assert_eq!(pus[idx % pus.len()].cpuset().unwrap(), cpuset);
+        }
+    }
+
+    #[quickcheck]
+    fn scatter_order(mask: Vec<bool>) {
+        let topology = Topology::test_instance();
+        let cpuset = restricted_cpuset(&mask);
+        let order = super::scatter_order(topology.root_object(), &cpuset);
+
+        // Every PU of the cpuset appears exactly once
+        let mut expected = topology.pus_from_cpuset(&cpuset).collect::<Vec<_>>();
+        let mut actual = order.clone();
+        expected.sort_unstable_by_key(|pu| pu.logical_index());
+        actual.sort_unstable_by_key(|pu| pu.logical_index());
+        assert!(expected
+            .iter()
+            .zip(&actual)
+            .all(|(expected, actual)| std::ptr::eq(*expected, *actual)));
+        assert_eq!(expected.len(), actual.len());
+
+        // The first PUs are spread over as many packages as possible
+        let packages = topology
+            .objects_with_type(ObjectType::Package)
+            .filter(|package| package.cpuset().unwrap().intersects(&cpuset))
+            .count();
+        let first_packages = order
+            .iter()
+            .take(packages)
+            .map(|pu| {
+                pu.ancestors()
+                    .find(|ancestor| ancestor.object_type() == ObjectType::Package)
+                    .map(TopologyObject::logical_index)
+            })
+            .collect::<std::collections::HashSet<_>>();
+        assert_eq!(first_packages.len(), packages);
+    }
+
+    #[quickcheck]
+    fn single_pu_policies(num_groups: u8, mask: Vec<bool>, scatter: bool) {
+        let num_groups = NonZeroUsize::new(usize::from(num_groups) + 1).unwrap();
+        let topology = Topology::test_instance();
+        let policy = if scatter {
+            PartitionPolicy::Scatter
+        } else {
+            PartitionPolicy::Compact
+        };
+        let partitioner = Partitioner::new(topology)
+            .groups(num_groups)
+            .policy(policy)
+            .within(&restricted_cpuset(&mask));
+        let available = partitioner.available_cpus();
+        let Ok(partition) = partitioner.partition() else {
+            assert!(available.is_empty());
+            return;
+        };
+        check_coverage(&partition, num_groups.get(), &available);
+        for group in &partition {
+            assert_eq!(group.cpuset().weight(), Some(1));
+        }
+        if num_groups.get() >= available.weight().unwrap() {
+            let union = partition
+                .iter()
+                .fold(CpuSet::new(), |acc, group| acc | group.cpuset());
+            assert_eq!(union, available);
+        }
+    }
+}
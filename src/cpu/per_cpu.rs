@@ -0,0 +1,253 @@
+//! Per-CPU data
+//!
+//! Concurrent data structures often scale better when every CPU core gets its
+//! own copy of some data, which threads update locally before the copies are
+//! aggregated. Sharded counters are the typical example. This module provides
+//! a container for this purpose, whose layout is derived from the topology.
+
+use super::{
+    binding::{CpuBindingError, CpuBindingFlags},
+    cpusets::CpuSet,
+};
+use crate::{
+    errors::HybridError,
+    objects::{types::ObjectType, TopologyObject},
+    topology::Topology,
+};
+use std::{
+    iter::FusedIterator,
+    ops::{Index, IndexMut},
+};
+
+/// One `T` per CPU core, PU, or other object with a cpuset
+///
+/// Each value is padded to a cache line boundary to avoid false sharing when
+/// values are updated by threads running on different CPUs. Values are
+/// stored in the logical order of the underlying topology objects.
+///
+/// # Examples
+///
+/// A sharded event counter:
+///
+/// ```
+/// # use hwlocality::cpu::per_cpu::PerCpu;
+/// # use std::sync::atomic::{AtomicU64, Ordering};
+/// # let topology = hwlocality::Topology::test_instance();
+/// let counter = PerCpu::per_core(topology, |_core| AtomicU64::new(0));
+///
+/// // Each thread finds its local shard once, then updates it
+/// std::thread::scope(|scope| {
+///     for _ in 0..4 {
+///         scope.spawn(|| {
+///             let shard = counter.current(topology).unwrap_or(&counter[0]);
+///             for _ in 0..1000 {
+///                 shard.fetch_add(1, Ordering::Relaxed);
+///             }
+///         });
+///     }
+/// });
+///
+/// // Shards are aggregated on read
+/// let total = counter
+///     .iter()
+///     .map(|shard| shard.load(Ordering::Relaxed))
+///     .sum::<u64>();
+/// assert_eq!(total, 4000);
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[doc(alias = "PerCore")]
+#[doc(alias = "PerPU")]
+#[derive(Clone, Debug)]
+pub struct PerCpu<T> {
+    /// Type of topology object that values are associated with
+    object_type: ObjectType,
+
+    /// Values, in logical order of the associated objects
+    values: Box<[CachePadded<T>]>,
+
+    /// Index of the value associated with each PU, by PU OS index
+    pu_to_value: Vec<Option<usize>>,
+}
+//
+impl<T> PerCpu<T> {
+    /// Set up one value per PU of the topology
+    pub fn per_pu(topology: &Topology, init: impl FnMut(&TopologyObject) -> T) -> Self {
+        Self::with_type(topology, ObjectType::PU, init)
+    }
+
+    /// Set up one value per CPU core of the topology
+    ///
+    /// If the topology does not have [`Core`] objects, there will be one value
+    /// per PU instead.
+    ///
+    /// [`Core`]: ObjectType::Core
+    pub fn per_core(topology: &Topology, init: impl FnMut(&TopologyObject) -> T) -> Self {
+        let ty = if topology.depth_for_type(ObjectType::Core).is_ok() {
+            ObjectType::Core
+        } else {
+            ObjectType::PU
+        };
+        Self::with_type(topology, ty, init)
+    }
+
+    /// Set up one value per object of type `ty` (e.g. per L3 cache)
+    ///
+    /// `init` is called on each object in logical order to produce the
+    /// associated value.
+    ///
+    /// # Panics
+    ///
+    /// If objects of type `ty` do not have a cpuset, i.e. if they are not
+    /// normal or memory objects.
+    pub fn with_type(
+        topology: &Topology,
+        ty: ObjectType,
+        mut init: impl FnMut(&TopologyObject) -> T,
+    ) -> Self {
+        let mut values = Vec::new();
+        let mut pu_to_value = Vec::new();
+        for (idx, obj) in topology.objects_with_type(ty).enumerate() {
+            let cpuset = obj
+                .cpuset()
+                .expect("PerCpu requires an object type with a cpuset");
+            for pu in cpuset.iter_set() {
+                let pu = usize::from(pu);
+                if pu >= pu_to_value.len() {
+                    pu_to_value.resize(pu + 1, None);
+                }
+                pu_to_value[pu] = Some(idx);
+            }
+            values.push(CachePadded(init(obj)));
+        }
+        Self {
+            object_type: ty,
+            values: values.into(),
+            pu_to_value,
+        }
+    }
+
+    /// Type of topology object that values are associated with
+    pub fn object_type(&self) -> ObjectType {
+        self.object_type
+    }
+
+    /// Number of values
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Truth that there are no values
+    ///
+    /// This only happens when the topology has no object of the requested
+    /// type, e.g. when asking for one value per L3 cache on a machine without
+    /// L3 cache.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Value associated with the object of logical index `idx`, if any
+    pub fn get(&self, idx: usize) -> Option<&T> {
+        self.values.get(idx).map(|value| &value.0)
+    }
+
+    /// Mutable access to the value associated with the object of logical
+    /// index `idx`, if any
+    pub fn get_mut(&mut self, idx: usize) -> Option<&mut T> {
+        self.values.get_mut(idx).map(|value| &mut value.0)
+    }
+
+    /// Logical index of the value associated with the PUs of `set`
+    ///
+    /// Returns `None` if these PUs are not all associated with the same value.
+    pub fn index_of(&self, set: &CpuSet) -> Option<usize> {
+        let mut indices = set
+            .iter_set()
+            .map(|pu| self.pu_to_value.get(usize::from(pu)).copied().flatten());
+        let first = indices.next()??;
+        indices.all(|idx| idx == Some(first)).then_some(first)
+    }
+
+    /// Logical index of the value associated with the current thread
+    ///
+    /// If the current thread is bound to CPUs that are all associated with a
+    /// single value, that value is selected. Otherwise, the value associated
+    /// with the CPU where the thread last ran is selected, which may change
+    /// over time.
+    ///
+    /// Returns `Ok(None)` if the current thread is running on a CPU that is
+    /// not part of the topology that this object was built from.
+    ///
+    /// Since this requires system calls, you should cache the result in
+    /// threads that are bound to a single value.
+    ///
+    /// # Errors
+    ///
+    /// See [`Topology::last_cpu_location()`].
+    pub fn current_index(
+        &self,
+        topology: &Topology,
+    ) -> Result<Option<usize>, HybridError<CpuBindingError>> {
+        if let Ok(binding) = topology.cpu_binding(CpuBindingFlags::THREAD) {
+            if let Some(idx) = self.index_of(&binding) {
+                return Ok(Some(idx));
+            }
+        }
+        let location = topology.last_cpu_location(CpuBindingFlags::THREAD)?;
+        Ok(location
+            .first_set()
+            .and_then(|pu| self.pu_to_value.get(usize::from(pu)).copied().flatten()))
+    }
+
+    /// Value associated with the current thread
+    ///
+    /// See [`current_index()`](Self::current_index()) for details. If the
+    /// current CPU is not part of the topology, the first value is returned.
+    ///
+    /// # Errors
+    ///
+    /// See [`Topology::last_cpu_location()`].
+    ///
+    /// # Panics
+    ///
+    /// If there are no values (see [`is_empty()`](Self::is_empty())).
+    pub fn current(&self, topology: &Topology) -> Result<&T, HybridError<CpuBindingError>> {
+        let idx = self.current_index(topology)?.unwrap_or(0);
+        Ok(&self[idx])
+    }
+
+    /// Iterate over the values, in logical order of the associated objects
+    pub fn iter(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = &T> + Clone + ExactSizeIterator + FusedIterator {
+        self.values.iter().map(|value| &value.0)
+    }
+
+    /// Mutably iterate over the values, in logical order of the associated
+    /// objects
+    pub fn iter_mut(
+        &mut self,
+    ) -> impl DoubleEndedIterator<Item = &mut T> + ExactSizeIterator + FusedIterator {
+        self.values.iter_mut().map(|value| &mut value.0)
+    }
+}
+//
+impl<T> Index<usize> for PerCpu<T> {
+    type Output = T;
+
+    fn index(&self, idx: usize) -> &T {
+        &self.values[idx].0
+    }
+}
+//
+impl<T> IndexMut<usize> for PerCpu<T> {
+    fn index_mut(&mut self, idx: usize) -> &mut T {
+        &mut self.values[idx].0
+    }
+}
+
+/// Value aligned to a cache line boundary
+///
+/// 128 bytes are used because some CPUs prefetch cache lines in pairs.
+#[derive(Clone, Debug, Default)]
+#[repr(align(128))]
+struct CachePadded<T>(T);
@@ -0,0 +1,210 @@
+//! Binding thread pool workers
+//!
+//! Thread pools like rayon's let you run some code at the start of each
+//! worker thread, with the worker index as a parameter. This module computes
+//! where each worker should run, and provides such a start handler that binds
+//! each worker to its CPUs.
+
+use super::{
+    binding::{CpuBindingError, CpuBindingFlags},
+    cpusets::CpuSet,
+};
+use crate::{errors::HybridError, objects::types::ObjectType, topology::Topology};
+use std::{borrow::Borrow, sync::Arc};
+
+/// # Thread pool placement
+impl Topology {
+    /// Decide where the workers of a thread pool should run
+    ///
+    /// Workers are assigned CPUs according to `placement`, only considering
+    /// the allowed CPUs of the topology. The resulting [`ThreadPoolLayout`]
+    /// tells how many workers should be spawned, and can bind each of them
+    /// to its CPUs.
+    ///
+    /// The layout does not keep a reference to this topology, so that it can
+    /// be moved to worker threads without lifetime issues. The topology must
+    /// instead be passed back when binding workers.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::cpu::thread_pool::WorkerPlacement;
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// // One worker per CPU core, bound to that core
+    /// let layout = topology.thread_pool_layout(WorkerPlacement::PerCore);
+    /// std::thread::scope(|scope| {
+    ///     for worker in 0..layout.num_threads() {
+    ///         let layout = &layout;
+    ///         scope.spawn(move || {
+    ///             if let Err(e) = layout.bind_worker(topology, worker) {
+    ///                 eprintln!("Failed to bind worker {worker}: {e}");
+    ///             }
+    ///             // ...do some work...
+    ///         });
+    ///     }
+    /// });
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    ///
+    /// With rayon, the layout can be used to configure a `ThreadPoolBuilder`,
+    /// sharing the topology with the start handler through an [`Arc`]:
+    ///
+    /// ```ignore
+    /// let topology = Arc::new(Topology::new()?);
+    /// let layout = topology.thread_pool_layout(WorkerPlacement::PerCore);
+    /// let pool = rayon::ThreadPoolBuilder::new()
+    ///     .num_threads(layout.num_threads())
+    ///     .start_handler(layout.start_handler(topology.clone()))
+    ///     .build()?;
+    /// ```
+    pub fn thread_pool_layout(&self, placement: WorkerPlacement) -> ThreadPoolLayout {
+        let allowed = self.cpuset() & self.allowed_cpuset();
+        let object_type = match placement {
+            WorkerPlacement::PerPU => ObjectType::PU,
+            WorkerPlacement::PerCore if self.depth_for_type(ObjectType::Core).is_ok() => {
+                ObjectType::Core
+            }
+            WorkerPlacement::PerCore => ObjectType::PU,
+            WorkerPlacement::PerNUMANode => ObjectType::NUMANode,
+            WorkerPlacement::PerL3Cache => ObjectType::L3Cache,
+        };
+        // Objects with the same CPUs, like the DRAM and HBM NUMA nodes of a
+        // package, should only get one worker
+        let mut cpusets = Vec::<CpuSet>::new();
+        for obj in self.objects_with_type(object_type) {
+            let Some(cpuset) = obj.cpuset() else {
+                continue;
+            };
+            let cpuset = cpuset & &allowed;
+            if !cpuset.is_empty() && !cpusets.contains(&cpuset) {
+                cpusets.push(cpuset);
+            }
+        }
+        if cpusets.is_empty() {
+            cpusets.push(allowed);
+        }
+        ThreadPoolLayout {
+            cpusets: cpusets.into(),
+        }
+    }
+}
+
+/// How thread pool workers are placed by [`Topology::thread_pool_layout()`]
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum WorkerPlacement {
+    /// One worker per PU, bound to that PU
+    PerPU,
+
+    /// One worker per CPU core, bound to the PUs of that core
+    ///
+    /// If the topology does not have [`Core`](ObjectType::Core) objects, this
+    /// is the same as [`PerPU`](Self::PerPU).
+    PerCore,
+
+    /// One worker per NUMA node, bound to the PUs that are local to that node
+    ///
+    /// NUMA nodes without local CPUs (e.g. some high-bandwidth or CXL memory
+    /// nodes) do not get a worker, and NUMA nodes that are local to the same
+    /// PUs (e.g. the DRAM and HBM nodes of a package) share a single worker.
+    PerNUMANode,
+
+    /// One worker per L3 cache, bound to the PUs that share that cache
+    ///
+    /// If the topology has no L3 cache, a single worker bound to all allowed
+    /// PUs is used.
+    PerL3Cache,
+}
+
+/// Where each worker of a thread pool should run
+///
+/// Built by [`Topology::thread_pool_layout()`]. Cloning it is cheap.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ThreadPoolLayout {
+    /// CPUs of each worker
+    cpusets: Arc<[CpuSet]>,
+}
+//
+impl ThreadPoolLayout {
+    /// Number of workers that the thread pool should have
+    pub fn num_threads(&self) -> usize {
+        self.cpusets.len()
+    }
+
+    /// CPUs that worker `index` should run on
+    ///
+    /// Indices larger than the number of workers wrap around, so that thread
+    /// pools with more workers than planned share CPUs evenly.
+    pub fn worker_cpuset(&self, index: usize) -> &CpuSet {
+        &self.cpusets[index % self.cpusets.len()]
+    }
+
+    /// Bind the current thread to the CPUs of worker `index`
+    ///
+    /// `topology` should be the topology that this layout was computed from.
+    ///
+    /// # Errors
+    ///
+    /// See [`Topology::bind_cpu()`].
+    pub fn bind_worker(
+        &self,
+        topology: &Topology,
+        index: usize,
+    ) -> Result<(), HybridError<CpuBindingError>> {
+        topology.bind_cpu(self.worker_cpuset(index), CpuBindingFlags::THREAD)
+    }
+
+    /// Thread start handler that binds each worker to its CPUs
+    ///
+    /// The handler receives the worker index as a parameter, as expected by
+    /// rayon's `ThreadPoolBuilder::start_handler()`. Binding is a best-effort
+    /// optimization, so binding errors are ignored. Use
+    /// [`bind_worker()`](Self::bind_worker()) if you need to handle them.
+    ///
+    /// The handler must outlive the thread pool, so it cannot borrow the
+    /// topology that this layout was computed from. Pass it either an
+    /// [`Arc<Topology>`] or, with the `global` feature, the `&'static`
+    /// reference returned by `Topology::global()`.
+    pub fn start_handler(
+        &self,
+        topology: impl Borrow<Topology> + Send + Sync + 'static,
+    ) -> impl Fn(usize) + Send + Sync + 'static {
+        let layout = self.clone();
+        move |index| {
+            let _ = layout.bind_worker(topology.borrow(), index);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn workers_should_have_distinct_cpus() {
+        let topology = Topology::test_instance();
+        let allowed = topology.cpuset() & topology.allowed_cpuset();
+        for placement in [
+            WorkerPlacement::PerPU,
+            WorkerPlacement::PerCore,
+            WorkerPlacement::PerNUMANode,
+            WorkerPlacement::PerL3Cache,
+        ] {
+            let layout = topology.thread_pool_layout(placement);
+            assert_ne!(layout.num_threads(), 0);
+            let cpusets = (0..layout.num_threads())
+                .map(|worker| layout.worker_cpuset(worker))
+                .collect::<Vec<_>>();
+            for (idx, cpuset) in cpusets.iter().enumerate() {
+                assert!(!cpuset.is_empty());
+                assert!(allowed.includes(cpuset));
+                assert!(!cpusets[..idx].contains(cpuset));
+            }
+            assert_eq!(
+                layout.worker_cpuset(layout.num_threads()),
+                layout.worker_cpuset(0)
+            );
+        }
+    }
+}
@@ -0,0 +1,392 @@
+//! Barriers shaped by the hardware topology
+//!
+//! A flat barrier, where every thread waits on the same synchronization
+//! object, scales poorly on multi-socket machines because all threads keep
+//! bouncing the same cache line across the whole machine. A [`TreeBarrier`]
+//! instead makes threads synchronize with threads that share the same L3
+//! cache first, then lets one representative per L3 cache synchronize at the
+//! package level, and finally one representative per package synchronize at
+//! the machine level.
+
+use super::cpusets::CpuSet;
+use crate::{
+    bitmaps::BitmapIndex,
+    objects::{depth::Depth, types::ObjectType, TopologyObject},
+    topology::Topology,
+};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{self, AtomicBool, AtomicU64},
+        Condvar, Mutex, MutexGuard, PoisonError,
+    },
+};
+use thiserror::Error;
+
+/// # Topology-aware synchronization
+impl Topology {
+    /// Set up a barrier for one thread per PU of `cpuset`
+    ///
+    /// The barrier is structured along the L3 caches and packages of the
+    /// topology, when they exist. PUs of `cpuset` that are not part of the
+    /// topology are ignored.
+    ///
+    /// Each thread must claim its [`BarrierParticipant`] using
+    /// [`TreeBarrier::participant()`] before waiting on the barrier.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::cpu::binding::CpuBindingFlags;
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// let cpuset = topology.cpuset().to_owned();
+    /// let barrier = topology.tree_barrier(&cpuset);
+    /// std::thread::scope(|scope| {
+    ///     for pu in cpuset.iter_set() {
+    ///         let barrier = &barrier;
+    ///         scope.spawn(move || {
+    ///             let _ = topology.bind_cpu(&pu.into(), CpuBindingFlags::THREAD);
+    ///             let mut participant = barrier
+    ///                 .participant(pu)
+    ///                 .expect("Each PU should be claimed only once");
+    ///             for round in 0..3 {
+    ///                 // ...do some work here...
+    ///                 if participant.wait().expect("No thread should exit early") {
+    ///                     println!("Round {round} done");
+    ///                 }
+    ///             }
+    ///         });
+    ///     }
+    /// });
+    /// ```
+    pub fn tree_barrier(&self, cpuset: &CpuSet) -> TreeBarrier {
+        let pus = cpuset
+            .iter_set()
+            .filter_map(|idx| Some((self.pu_with_os_index(usize::from(idx))?, idx)))
+            .collect::<Vec<_>>();
+
+        // Pick the levels from the innermost one to the root, ignoring those
+        // that do not cover all PUs or that are duplicated
+        let mut depths = [ObjectType::L3Cache, ObjectType::Package]
+            .into_iter()
+            .filter_map(|ty| self.depth_for_type(ty).ok())
+            .filter(|&depth| {
+                pus.iter()
+                    .all(|(pu, _)| pu.ancestor_at_depth(depth).is_some())
+            })
+            .collect::<Vec<_>>();
+        depths.sort_unstable_by_key(|depth| std::cmp::Reverse(depth.assume_normal()));
+        depths.dedup();
+        depths.push(Depth::Normal(0));
+
+        // Create one node per object at each level, keyed by level and
+        // global persistent index, then link it to its parent
+        let mut nodes = Vec::<Node>::new();
+        let mut node_ids = HashMap::<(usize, u64), usize>::new();
+        let mut leaves = HashMap::with_capacity(pus.len());
+        for (pu, idx) in pus {
+            let mut child: Option<(usize, bool)> = None;
+            for (level, &depth) in depths.iter().enumerate() {
+                let ancestor = ancestor_or_self(pu, depth);
+                let mut created = false;
+                let id = *node_ids
+                    .entry((level, ancestor.global_persistent_index()))
+                    .or_insert_with(|| {
+                        created = true;
+                        nodes.push(Node::default());
+                        nodes.len() - 1
+                    });
+                match child {
+                    None => nodes[id].expected += 1,
+                    Some((child_id, true)) => {
+                        nodes[id].expected += 1;
+                        nodes[child_id].parent = Some(id);
+                    }
+                    Some((_, false)) => {}
+                }
+                if child.is_none() {
+                    leaves.insert(idx, (id, AtomicBool::new(false)));
+                }
+                child = Some((id, created));
+            }
+        }
+        TreeBarrier {
+            nodes,
+            leaves,
+            poisoned_round: AtomicU64::new(u64::MAX),
+        }
+    }
+}
+
+/// Find the ancestor of `obj` at `depth`, or `obj` itself if it is there
+fn ancestor_or_self(obj: &TopologyObject, depth: Depth) -> &TopologyObject {
+    if obj.depth() == depth {
+        return obj;
+    }
+    obj.ancestor_at_depth(depth)
+        .expect("Barrier levels should cover all PUs")
+}
+
+/// Hierarchical barrier whose structure follows the hardware topology
+///
+/// See [`Topology::tree_barrier()`] for more information.
+///
+/// Unlike [`std::sync::Barrier`], this barrier does not block forever when a
+/// thread exits early: when a [`BarrierParticipant`] is dropped, any round
+/// that it has not completed is poisoned, and waiting for such a round
+/// returns [`BarrierPoisoned`] on every other participant.
+///
+/// This functionality is unique to the Rust hwloc bindings.
+#[derive(Debug)]
+pub struct TreeBarrier {
+    /// Nodes of the barrier tree, one per object at each level
+    nodes: Vec<Node>,
+
+    /// Leaf node of each PU, keyed by PU OS index, and whether the matching
+    /// participant has been claimed
+    leaves: HashMap<BitmapIndex, (usize, AtomicBool)>,
+
+    /// First round that cannot complete, or `u64::MAX` if there is none
+    poisoned_round: AtomicU64,
+}
+//
+impl TreeBarrier {
+    /// Number of threads that must wait on the barrier at each round
+    pub fn num_participants(&self) -> usize {
+        self.leaves.len()
+    }
+
+    /// Claim the participant for the thread associated with PU `pu`
+    ///
+    /// Every participant must be claimed and wait on the barrier for a round
+    /// to complete. Dropping a participant poisons the rounds that it has not
+    /// completed.
+    ///
+    /// Returns `None` if `pu` is not a participant of this barrier, or if its
+    /// participant has already been claimed.
+    pub fn participant(&self, pu: BitmapIndex) -> Option<BarrierParticipant<'_>> {
+        let (leaf, claimed) = self.leaves.get(&pu)?;
+        (!claimed.swap(true, atomic::Ordering::Relaxed)).then_some(BarrierParticipant {
+            barrier: self,
+            leaf: *leaf,
+            round: 0,
+        })
+    }
+
+    /// Truth that round `round` cannot complete
+    fn is_poisoned(&self, round: u64) -> bool {
+        self.poisoned_round.load(atomic::Ordering::Acquire) <= round
+    }
+
+    /// Poison round `round` and all subsequent rounds, waking up waiters
+    fn poison(&self, round: u64) {
+        self.poisoned_round
+            .fetch_min(round, atomic::Ordering::AcqRel);
+        for node in &self.nodes {
+            // Taking the lock ensures that waiters either observe the poison
+            // before waiting or are woken up by the notification
+            drop(node.lock());
+            node.released.notify_all();
+        }
+    }
+
+    /// Wait at node `id` for round `round`, tell if this thread is the last
+    /// one to arrive at the root of the tree
+    fn wait_at(&self, id: usize, round: u64) -> Result<bool, BarrierPoisoned> {
+        let node = &self.nodes[id];
+        let mut state = node.lock();
+        if self.is_poisoned(round) {
+            return Err(BarrierPoisoned);
+        }
+        state.arrived += 1;
+        if state.arrived < node.expected {
+            let generation = state.generation;
+            loop {
+                state = node
+                    .released
+                    .wait(state)
+                    .unwrap_or_else(PoisonError::into_inner);
+                if state.generation != generation {
+                    return Ok(false);
+                }
+                if self.is_poisoned(round) {
+                    return Err(BarrierPoisoned);
+                }
+            }
+        }
+
+        // Last thread to arrive represents this node at the next level, then
+        // releases the other threads on its way back down
+        state.arrived = 0;
+        drop(state);
+        let is_leader = match node.parent {
+            Some(parent) => self.wait_at(parent, round)?,
+            None => true,
+        };
+        let mut state = node.lock();
+        state.generation = state.generation.wrapping_add(1);
+        drop(state);
+        node.released.notify_all();
+        Ok(is_leader)
+    }
+}
+
+/// Node of a [`TreeBarrier`]
+#[derive(Debug, Default)]
+struct Node {
+    /// Parent node, if this is not the root
+    parent: Option<usize>,
+
+    /// Number of threads that must arrive at this node
+    expected: usize,
+
+    /// Synchronization state
+    state: Mutex<NodeState>,
+
+    /// Notified when threads waiting at this node should wake up
+    released: Condvar,
+}
+//
+impl Node {
+    /// Lock the synchronization state
+    fn lock(&self) -> MutexGuard<'_, NodeState> {
+        // No code panics while holding this lock
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Synchronization state of a [`Node`]
+#[derive(Debug, Default)]
+struct NodeState {
+    /// Number of threads that arrived during the current round
+    arrived: usize,
+
+    /// Number of completed rounds, used to detect wake-ups
+    generation: u64,
+}
+
+/// Participant of a [`TreeBarrier`], associated with one thread
+///
+/// Dropping a participant, including when its thread panics, poisons the
+/// rounds of the barrier that it has not completed.
+#[derive(Debug)]
+pub struct BarrierParticipant<'barrier> {
+    /// Barrier that this participant belongs to
+    barrier: &'barrier TreeBarrier,
+
+    /// Leaf node of the participant
+    leaf: usize,
+
+    /// Number of rounds that this participant has completed
+    round: u64,
+}
+//
+impl BarrierParticipant<'_> {
+    /// Wait for all participants to reach this point
+    ///
+    /// Returns `true` on exactly one participant per round, like
+    /// [`std::sync::BarrierWaitResult::is_leader()`].
+    ///
+    /// # Errors
+    ///
+    /// [`BarrierPoisoned`] if another participant was dropped before reaching
+    /// this round, which means that the round cannot complete.
+    pub fn wait(&mut self) -> Result<bool, BarrierPoisoned> {
+        let is_leader = self.barrier.wait_at(self.leaf, self.round)?;
+        self.round += 1;
+        Ok(is_leader)
+    }
+}
+//
+impl Drop for BarrierParticipant<'_> {
+    fn drop(&mut self) {
+        self.barrier.poison(self.round);
+    }
+}
+
+/// Error returned when waiting on a [`TreeBarrier`] round that cannot complete
+/// because a participant was dropped
+#[derive(Copy, Clone, Debug, Default, Eq, Error, Hash, PartialEq)]
+#[error("a barrier participant exited before reaching this round")]
+pub struct BarrierPoisoned;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_barrier() -> (TreeBarrier, Vec<BitmapIndex>) {
+        let topology = Topology::test_instance();
+        let cpuset = topology.cpuset().to_owned();
+        let pus = cpuset.iter_set().collect::<Vec<_>>();
+        (topology.tree_barrier(&cpuset), pus)
+    }
+
+    #[test]
+    fn participants_should_be_claimed_once() {
+        let (barrier, pus) = test_barrier();
+        assert_eq!(barrier.num_participants(), pus.len());
+        let participants = pus
+            .iter()
+            .map(|&pu| barrier.participant(pu).expect("First claim should succeed"))
+            .collect::<Vec<_>>();
+        for &pu in &pus {
+            assert!(barrier.participant(pu).is_none());
+        }
+        assert!(barrier.participant(BitmapIndex::MAX).is_none());
+        drop(participants);
+    }
+
+    #[test]
+    fn each_round_should_have_one_leader() {
+        const ROUNDS: usize = 10;
+        let (barrier, pus) = test_barrier();
+        let leaders = std::thread::scope(|scope| {
+            let handles = pus
+                .iter()
+                .map(|&pu| {
+                    let mut participant = barrier.participant(pu).unwrap();
+                    scope.spawn(move || {
+                        (0..ROUNDS)
+                            .map(|_| participant.wait().unwrap())
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+        for round in 0..ROUNDS {
+            let num_leaders = leaders.iter().filter(|thread| thread[round]).count();
+            assert_eq!(num_leaders, 1, "round {round}");
+        }
+    }
+
+    #[test]
+    fn early_exit_should_poison_later_rounds() {
+        let (barrier, pus) = test_barrier();
+        let (&first, others) = pus.split_first().unwrap();
+        std::thread::scope(|scope| {
+            let mut quitter = barrier.participant(first).unwrap();
+            let handles = others
+                .iter()
+                .map(|&pu| {
+                    let mut participant = barrier.participant(pu).unwrap();
+                    scope.spawn(move || {
+                        assert!(participant.wait().is_ok());
+                        assert_eq!(participant.wait(), Err(BarrierPoisoned));
+                        assert_eq!(participant.wait(), Err(BarrierPoisoned));
+                    })
+                })
+                .collect::<Vec<_>>();
+            assert!(quitter.wait().is_ok());
+            drop(quitter);
+            for handle in handles {
+                handle.join().unwrap();
+            }
+        });
+    }
+}
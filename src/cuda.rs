@@ -0,0 +1,139 @@
+//! CUDA-specific helpers
+
+use crate::{
+    cpu::cpusets::CpuSet,
+    objects::{
+        attributes::{ObjectAttributes, PCIDeviceAttributes},
+        types::{OSDeviceType, ObjectType},
+        TopologyObject,
+    },
+    topology::Topology,
+};
+
+/// PCI vendor ID of NVIDIA
+const NVIDIA_VENDOR_ID: u16 = 0x10de;
+
+/// # CUDA-specific helpers
+///
+/// These functions locate CUDA devices in the topology, so that applications
+/// can tell which CPU cores are close to a given GPU.
+///
+/// Unlike their hwloc counterparts, they do not link to the CUDA runtime.
+/// Devices are instead identified by the `cudaN` OS devices that hwloc's CUDA
+/// component adds to topologies built with I/O objects (see
+/// [`TopologyBuilder::with_io_type_filter()`]).
+///
+/// If hwloc was built without CUDA support, NVIDIA display and 3D
+/// controllers are enumerated in PCI bus order as a fallback. This matches
+/// CUDA device ordinals only when the `CUDA_DEVICE_ORDER` environment
+/// variable is set to `PCI_BUS_ID` and `CUDA_VISIBLE_DEVICES` is not set, so
+/// the fallback is not used otherwise.
+///
+/// [`TopologyBuilder::with_io_type_filter()`]: crate::topology::builder::TopologyBuilder::with_io_type_filter()
+//
+// Upstream docs: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__cuda.html
+impl Topology {
+    /// Get the hwloc OS device object corresponding to the CUDA device with
+    /// ordinal `device`
+    ///
+    /// Returns `None` if there is no such OS device, e.g. because hwloc was
+    /// built without CUDA support or I/O objects were filtered out. The PCI
+    /// locator fallback does not apply here.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// if let Some(gpu) = topology.cuda_device_os_device(0) {
+    ///     println!("CUDA device 0 is {gpu}");
+    /// }
+    /// ```
+    #[doc(alias = "hwloc_cuda_get_device_osdev_by_index")]
+    pub fn cuda_device_os_device(&self, device: usize) -> Option<&TopologyObject> {
+        self.os_device_with_name(OSDeviceType::CoProcessor, &format!("cuda{device}"))
+    }
+
+    /// Get the PCI device object corresponding to the CUDA device with
+    /// ordinal `device`
+    ///
+    /// Returns `None` if the device cannot be located in the topology.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// if let Some(pcidev) = topology.cuda_device_pci_device(0) {
+    ///     println!("CUDA device 0 is attached to {}", pcidev.non_io_ancestor());
+    /// }
+    /// ```
+    #[doc(alias = "hwloc_cuda_get_device_pcidev")]
+    pub fn cuda_device_pci_device(&self, device: usize) -> Option<&TopologyObject> {
+        if let Some(osdev) = self.cuda_device_os_device(device) {
+            return osdev
+                .ancestors()
+                .find(|ancestor| ancestor.object_type() == ObjectType::PCIDevice);
+        }
+        if !pci_bus_order_matches_cuda() {
+            return None;
+        }
+        let mut gpus = self
+            .pci_devices()
+            .filter_map(|pcidev| {
+                let Some(ObjectAttributes::PCIDevice(attr)) = pcidev.attributes() else {
+                    return None;
+                };
+                is_nvidia_gpu(attr).then_some((pci_bus_id(attr), pcidev))
+            })
+            .collect::<Vec<_>>();
+        gpus.sort_unstable_by_key(|(bus_id, _)| *bus_id);
+        gpus.get(device).map(|(_, pcidev)| *pcidev)
+    }
+
+    /// Get the CPU set of processors that are physically close to the CUDA
+    /// device with ordinal `device`
+    ///
+    /// This is the cpuset of the [`TopologyObject::non_io_ancestor()`] of the
+    /// PCI device returned by [`cuda_device_pci_device()`].
+    ///
+    /// Returns `None` if the device cannot be located in the topology.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::cpu::binding::CpuBindingFlags;
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// if let Some(cpuset) = topology.cuda_device_cpuset(0) {
+    ///     topology.bind_cpu(&cpuset, CpuBindingFlags::THREAD)?;
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    ///
+    /// [`cuda_device_pci_device()`]: Topology::cuda_device_pci_device()
+    #[doc(alias = "hwloc_cuda_get_device_cpuset")]
+    pub fn cuda_device_cpuset(&self, device: usize) -> Option<CpuSet> {
+        self.cuda_device_pci_device(device)?
+            .non_io_ancestor_cpuset()
+    }
+}
+
+/// Truth that CUDA device ordinals follow PCI bus order
+fn pci_bus_order_matches_cuda() -> bool {
+    std::env::var_os("CUDA_DEVICE_ORDER").map_or(false, |order| order == "PCI_BUS_ID")
+        && std::env::var_os("CUDA_VISIBLE_DEVICES").is_none()
+}
+
+/// Truth that a PCI device is an NVIDIA display or 3D controller
+fn is_nvidia_gpu(attr: &PCIDeviceAttributes) -> bool {
+    // PCI classes 0x0300 (VGA controller) and 0x0302 (3D controller)
+    attr.vendor_id() == NVIDIA_VENDOR_ID && matches!(attr.class_id(), 0x0300 | 0x0302)
+}
+
+/// PCI bus ID of a device, in a form that sorts like the PCI bus
+fn pci_bus_id(attr: &PCIDeviceAttributes) -> impl Ord + Copy {
+    (
+        attr.domain(),
+        attr.bus_id(),
+        attr.bus_device(),
+        attr.function(),
+    )
+}
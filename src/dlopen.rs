@@ -0,0 +1,327 @@
+//! Runtime loading of the hwloc library
+//!
+//! When the `dlopen` cargo feature is enabled on Unix systems, hwlocality does
+//! not link to hwloc at build time. Instead, the hwloc shared library is loaded
+//! with `dlopen()` the first time an hwloc entry point is used, and each entry
+//! point is resolved with `dlsym()` on first use.
+//!
+//! This makes it possible to ship a single binary that runs on systems with
+//! different hwloc releases. Binaries built with e.g. the `hwloc-2_11_0`
+//! feature can still run on systems with an older hwloc library, as long as
+//! they do not use the functionality that the library lacks:
+//!
+//! - hwloc entry points that report failure through an integer return code or
+//!   a NULL pointer fail with errno `ENOSYS` when they are missing from the
+//!   loaded library, which hwlocality reports as a [`RawHwlocError`].
+//!   [`RawHwlocError::missing_symbol()`] tells these failures apart from
+//!   other errors and returns the corresponding [`MissingSymbol`].
+//! - Other entry points cannot report failure, so their presence is checked
+//!   when the library is loaded, and a library that lacks any of them is
+//!   rejected with a [`LoadError`]. All such entry points have been there
+//!   since hwloc v2.0.0, so this should not happen.
+//! - If no usable hwloc library can be loaded at all, hwloc entry points that
+//!   can report failure fail with errno `ENOSYS` as if they were missing, so
+//!   that e.g. [`Topology::new()`] returns a [`RawHwlocError`]. Use [`load()`]
+//!   to find out why the library could not be loaded. Functions which cannot
+//!   report failure, like [`TopologyBuilder::new()`] or
+//!   [`get_api_version()`](crate::get_api_version()), panic with a
+//!   [`LoadError`] message in this case.
+//!
+//! Applications which want to handle these situations more gracefully can
+//! check upfront that the hwloc library is usable with [`load()`], and that
+//! specific entry points are available with [`check_symbol()`].
+//!
+//! The library is searched for under its usual shared library names by the
+//! dynamic linker, and the `HWLOCALITY_LIBHWLOC` environment variable can be
+//! used to point at a specific library file instead.
+
+#[cfg(doc)]
+use crate::{
+    errors::RawHwlocError,
+    topology::{builder::TopologyBuilder, Topology},
+};
+use errno::Errno;
+use libc::ENOSYS;
+use once_cell::sync::OnceCell;
+use std::{
+    ffi::{c_int, c_uint, c_ulong, c_void, CStr, CString},
+    ptr::NonNull,
+};
+use thiserror::Error;
+
+/// Environment variable that can be used to specify the path to the hwloc
+/// shared library
+pub const LIBRARY_PATH_VAR: &str = "HWLOCALITY_LIBHWLOC";
+
+/// Shared library names under which hwloc v2.x is searched by default
+#[cfg(target_os = "macos")]
+const LIBRARY_NAMES: &[&str] = &["libhwloc.15.dylib", "libhwloc.dylib"];
+//
+/// Shared library names under which hwloc v2.x is searched by default
+#[cfg(not(target_os = "macos"))]
+const LIBRARY_NAMES: &[&str] = &["libhwloc.so.15", "libhwloc.so"];
+
+/// Load the hwloc library, if not done already
+///
+/// hwlocality calls this automatically as needed, but applications can call
+/// it upfront to check that a usable hwloc library is installed.
+///
+/// # Errors
+///
+/// - [`LoadError`] if no hwloc library could be loaded, or if the loaded
+///   library lacks entry points which cannot report failure
+///
+/// # Examples
+///
+/// ```
+/// # use hwlocality::dlopen;
+/// if let Err(e) = dlopen::load() {
+///     eprintln!("Running without hardware locality optimizations: {e}");
+/// }
+/// ```
+pub fn load() -> Result<(), LoadError> {
+    library().map(|_| ())
+}
+
+/// Check that the hwloc library provides the entry point `name`
+///
+/// # Errors
+///
+/// - [`MissingSymbol`] if the hwloc library could not be loaded or does not
+///   provide entry point `name`
+///
+/// # Examples
+///
+/// ```
+/// # use hwlocality::dlopen;
+/// // The default nodeset is only available starting with hwloc v2.10
+/// if dlopen::check_symbol("hwloc_topology_get_default_nodeset").is_err() {
+///     eprintln!("Installed hwloc is too old, will use all NUMA nodes");
+/// }
+/// ```
+pub fn check_symbol(name: &str) -> Result<(), MissingSymbol> {
+    let missing = || MissingSymbol(name.to_owned());
+    let name = CString::new(name).map_err(|_| missing())?;
+    let library = library().map_err(|_| missing())?;
+    library.symbol(&name).map(|_| ()).ok_or_else(missing)
+}
+
+/// Error returned when the hwloc library cannot be loaded
+#[derive(Clone, Debug, Error, Eq, Hash, PartialEq)]
+#[error("failed to load the hwloc library: {0}")]
+pub struct LoadError(String);
+
+/// Error returned when an hwloc entry point is not available
+#[derive(Clone, Debug, Error, Eq, Hash, PartialEq)]
+#[error("hwloc entry point {0} is not available in the loaded hwloc library")]
+pub struct MissingSymbol(pub String);
+
+/// Handle to the dynamically loaded hwloc library
+struct Library(NonNull<c_void>);
+//
+// SAFETY: dlopen() handles can be used from any thread
+unsafe impl Send for Library {}
+//
+// SAFETY: dlsym() is thread-safe
+unsafe impl Sync for Library {}
+//
+impl Library {
+    /// Resolve an hwloc entry point
+    fn symbol(&self, name: &CStr) -> Option<NonNull<c_void>> {
+        // SAFETY: The library handle is valid and name is a valid C string
+        NonNull::new(unsafe { libc::dlsym(self.0.as_ptr(), name.as_ptr()) })
+    }
+}
+
+/// Get the hwloc library, loading it if needed
+fn library() -> Result<&'static Library, LoadError> {
+    static LIBRARY: OnceCell<Result<Library, LoadError>> = OnceCell::new();
+    LIBRARY
+        .get_or_init(|| {
+            let names = std::env::var(LIBRARY_PATH_VAR)
+                .map(|path| vec![path])
+                .unwrap_or_else(|_| LIBRARY_NAMES.iter().map(|&name| name.to_owned()).collect());
+            let mut errors = Vec::new();
+            for name in names {
+                let Ok(c_name) = CString::new(name.clone()) else {
+                    errors.push(format!("{name}: invalid library name"));
+                    continue;
+                };
+                // SAFETY: c_name is a valid C string. Loading hwloc runs its
+                //         library constructors, which have no preconditions.
+                let handle = unsafe { libc::dlopen(c_name.as_ptr(), libc::RTLD_NOW) };
+                let Some(handle) = NonNull::new(handle) else {
+                    errors.push(last_dl_error(&name));
+                    continue;
+                };
+                let library = Library(handle);
+                let missing = crate::ffi::infallible_entry_points()
+                    .into_iter()
+                    .filter(|&symbol| {
+                        let symbol = CString::new(symbol).expect("Symbol names have no NUL");
+                        library.symbol(&symbol).is_none()
+                    })
+                    .map(|symbol| MissingSymbol(symbol.to_owned()).to_string())
+                    .collect::<Vec<_>>();
+                if missing.is_empty() {
+                    return Ok(library);
+                }
+                errors.push(format!("{name}: {}", missing.join(", ")));
+            }
+            Err(LoadError(errors.join(", ")))
+        })
+        .as_ref()
+        .map_err(Clone::clone)
+}
+
+/// Describe the last dynamic linker error, which occured while handling `name`
+fn last_dl_error(name: &str) -> String {
+    // SAFETY: dlerror() has no preconditions
+    let error = unsafe { libc::dlerror() };
+    if error.is_null() {
+        format!("{name}: unknown error")
+    } else {
+        // SAFETY: dlerror() returns a valid C string when non-null
+        unsafe { CStr::from_ptr(error) }
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+/// Resolve an hwloc entry point, given its NUL-terminated name
+///
+/// # Errors
+///
+/// - [`LoadError`] if the hwloc library cannot be loaded
+pub(crate) fn symbol(name: &'static str) -> Result<Option<NonNull<c_void>>, LoadError> {
+    let name =
+        CStr::from_bytes_with_nul(name.as_bytes()).expect("Symbol name should be NUL-terminated");
+    Ok(library()?.symbol(name))
+}
+
+/// Behavior of hwloc entry points that are missing from the loaded library
+pub(crate) trait MissingSymbolFallback {
+    /// Truth that entry points with this return type can report failure
+    ///
+    /// If not, they must be provided by the loaded library, which is checked
+    /// when the library is loaded.
+    const REPORTS_FAILURE: bool;
+
+    /// Result of calling missing hwloc entry point `name`
+    fn missing_symbol(name: &'static str) -> Self;
+
+    /// Result of calling hwloc entry point `name` when the hwloc library
+    /// could not be loaded
+    ///
+    /// Entry points that can report failure fail as if they were missing.
+    /// Others cannot report the error, so they panic.
+    fn library_unavailable(name: &'static str, error: &LoadError) -> Self
+    where
+        Self: Sized,
+    {
+        if Self::REPORTS_FAILURE {
+            Self::missing_symbol(name)
+        } else {
+            panic!("{error}")
+        }
+    }
+}
+//
+impl MissingSymbolFallback for c_int {
+    const REPORTS_FAILURE: bool = true;
+
+    fn missing_symbol(_name: &'static str) -> Self {
+        errno::set_errno(Errno(ENOSYS));
+        -1
+    }
+}
+//
+impl<T> MissingSymbolFallback for *const T {
+    const REPORTS_FAILURE: bool = true;
+
+    fn missing_symbol(_name: &'static str) -> Self {
+        errno::set_errno(Errno(ENOSYS));
+        std::ptr::null()
+    }
+}
+//
+impl<T> MissingSymbolFallback for *mut T {
+    const REPORTS_FAILURE: bool = true;
+
+    fn missing_symbol(_name: &'static str) -> Self {
+        errno::set_errno(Errno(ENOSYS));
+        std::ptr::null_mut()
+    }
+}
+//
+/// Implement [`MissingSymbolFallback`] for types that cannot report failure
+///
+/// Since the library is rejected at load time if it lacks such entry points,
+/// the fallback value is never observed.
+macro_rules! impl_missing_symbol_unreachable {
+    ($($ty:ty => $value:expr),*) => {
+        $(
+            impl MissingSymbolFallback for $ty {
+                const REPORTS_FAILURE: bool = false;
+
+                fn missing_symbol(_name: &'static str) -> Self {
+                    $value
+                }
+            }
+        )*
+    };
+}
+impl_missing_symbol_unreachable!(() => (), c_uint => 0, c_ulong => 0);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn infallible_entry_points_should_be_checked_at_load_time() {
+        let infallible = crate::ffi::infallible_entry_points();
+        assert!(infallible.contains(&"hwloc_get_api_version"));
+        assert!(infallible.contains(&"hwloc_bitmap_free"));
+        assert!(!infallible.contains(&"hwloc_topology_init"));
+        assert!(!infallible.contains(&"hwloc_bitmap_alloc"));
+    }
+
+    #[test]
+    fn missing_fallible_entry_points_should_fail_with_enosys() {
+        errno::set_errno(Errno(0));
+        assert_eq!(c_int::missing_symbol("hwloc_topology_init"), -1);
+        assert_eq!(errno::errno(), Errno(ENOSYS));
+        errno::set_errno(Errno(0));
+        assert!(<*mut c_void>::missing_symbol("hwloc_bitmap_alloc").is_null());
+        assert_eq!(errno::errno(), Errno(ENOSYS));
+    }
+
+    #[test]
+    fn unavailable_library_should_be_reported_as_errors() {
+        let Err(load_error) = load() else {
+            // Only testable on systems without a usable hwloc library
+            return;
+        };
+        errno::set_errno(Errno(0));
+        assert_eq!(
+            c_int::library_unavailable("hwloc_topology_init", &load_error),
+            -1
+        );
+        assert_eq!(errno::errno(), Errno(ENOSYS));
+
+        let error = crate::topology::Topology::new().unwrap_err();
+        assert_eq!(error.api, "hwloc_topology_init");
+        assert_eq!(error.errno, Some(Errno(ENOSYS)));
+        assert_eq!(
+            error.missing_symbol(),
+            Some(MissingSymbol("hwloc_topology_init".to_owned()))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "failed to load the hwloc library")]
+    fn unavailable_library_should_panic_in_infallible_entry_points() {
+        let error = LoadError("libhwloc.so: not found".to_owned());
+        <c_uint as MissingSymbolFallback>::library_unavailable("hwloc_get_api_version", &error);
+    }
+}
@@ -1,11 +1,25 @@
 //! Shared error handling
 
+#[cfg(all(feature = "hwloc-2_5_0", target_os = "windows"))]
+use crate::cpu::binding::ProcessorGroupBindingError;
+#[cfg(doc)]
+use crate::topology::Topology;
+use crate::{
+    bitmaps::SpecializedBitmap,
+    cpu::{
+        binding::{CpuBindingError, CpuBindingFlags, CpuBoundObject},
+        cpusets::CpuSet,
+    },
+    memory::binding::{MemoryBindingError, MemoryBindingFlags},
+};
 use errno::Errno;
+use libc::{EINTR, EINVAL, ENOMEM, ENOSYS, EPERM, EXDEV};
 use std::{
     error::Error,
     ffi::{c_int, c_uint},
-    fmt::Debug,
+    fmt::{self, Debug, Display},
     ptr::NonNull,
+    sync::atomic::{self, AtomicUsize},
 };
 use thiserror::Error;
 
@@ -57,6 +71,36 @@ pub struct RawHwlocError {
     /// Observed errno value, if non-zero
     pub errno: Option<Errno>,
 }
+//
+#[cfg(all(unix, any(doc, feature = "dlopen")))]
+impl RawHwlocError {
+    /// Missing hwloc entry point that caused this error, if any
+    ///
+    /// With the `dlopen` feature, hwloc entry points that are missing from
+    /// the loaded hwloc library fail with errno `ENOSYS`. This tells such
+    /// failures apart from `ENOSYS` errors that are reported by hwloc itself.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::{cpu::binding::CpuBindingFlags, errors::HybridError};
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// if let Err(HybridError::Hwloc(e)) = topology.cpu_binding(CpuBindingFlags::THREAD) {
+    ///     match e.missing_symbol() {
+    ///         Some(missing) => eprintln!("Installed hwloc is too old: {missing}"),
+    ///         None => eprintln!("Failed to query CPU binding: {e}"),
+    ///     }
+    /// }
+    /// ```
+    pub fn missing_symbol(&self) -> Option<crate::dlopen::MissingSymbol> {
+        if self.errno != Some(Errno(ENOSYS)) {
+            return None;
+        }
+        crate::dlopen::check_symbol(self.api).err()
+    }
+}
 
 /// Call an hwloc entry point that returns a `*mut T` that should not be null
 pub(crate) fn call_hwloc_ptr_mut<T>(
@@ -70,6 +114,7 @@ pub(crate) fn call_hwloc_ptr_mut<T>(
     if let Some(ptr) = NonNull::new(result) {
         Ok(ptr)
     } else {
+        log_event!(Debug, "hwloc API {api} returned NULL with errno {errno:?}");
         Err(RawHwlocError { api, errno })
     }
 }
@@ -115,6 +160,67 @@ pub(crate) fn call_hwloc_bool(
     }
 }
 
+/// Maximal number of times an interrupted binding call is retried
+///
+/// See [`set_interrupted_call_retries()`].
+static INTERRUPTED_CALL_RETRIES: AtomicUsize = AtomicUsize::new(16);
+
+/// Set how many times a CPU or memory binding call that was interrupted by a signal
+/// (`EINTR`) is automatically retried before giving up
+///
+/// This cap ensures that a process which keeps receiving signals cannot get
+/// stuck forever retrying the same system call. Once it is reached, CPU
+/// binding functions report a [`HybridError::Hwloc`] error whose errno is
+/// `EINTR`, memory binding and allocation functions report
+/// [`MemoryBindingError::Interrupted`], and both convert into
+/// [`BindingError::Interrupted`]. It is then up to the caller to decide
+/// whether retrying later makes sense. The default is 16 retries, and 0
+/// disables retries.
+///
+/// This setting is process-wide.
+///
+/// This functionality is unique to the Rust hwloc bindings.
+///
+/// # Examples
+///
+/// ```
+/// # use hwlocality::errors;
+/// errors::set_interrupted_call_retries(100);
+/// assert_eq!(errors::interrupted_call_retries(), 100);
+/// ```
+pub fn set_interrupted_call_retries(retries: usize) {
+    INTERRUPTED_CALL_RETRIES.store(retries, atomic::Ordering::Relaxed);
+}
+
+/// Number of times an interrupted binding call is automatically retried
+///
+/// See [`set_interrupted_call_retries()`].
+///
+/// This functionality is unique to the Rust hwloc bindings.
+pub fn interrupted_call_retries() -> usize {
+    INTERRUPTED_CALL_RETRIES.load(atomic::Ordering::Relaxed)
+}
+
+/// Retry an hwloc call as long as it fails with `EINTR`, up to
+/// `max_retries` times if specified
+///
+/// If the retry budget is exhausted, the last `EINTR` error is returned.
+pub(crate) fn retry_on_eintr<R>(
+    max_retries: Option<usize>,
+    mut call: impl FnMut() -> Result<R, RawHwlocError>,
+) -> Result<R, RawHwlocError> {
+    let mut retries = 0;
+    loop {
+        match call() {
+            Err(RawHwlocError {
+                errno: Some(Errno(EINTR)),
+                ..
+            }) if max_retries.map_or(true, |max| retries < max) => retries += 1,
+            other => return other,
+        }
+    }
+}
+
 /// Raw error emitted by hwloc functions that returns a negative int on failure
 ///
 /// A few hwloc functions (most prominently topology diffing) return negative
@@ -143,7 +249,13 @@ pub(crate) fn call_hwloc_int_raw(
         let result = call();
         (result, result < 0)
     });
-    c_uint::try_from(result).map_err(|_| RawNegIntError { api, result, errno })
+    c_uint::try_from(result).map_err(|_| {
+        log_event!(
+            Debug,
+            "hwloc API {api} returned {result} with errno {errno:?}"
+        );
+        RawNegIntError { api, result, errno }
+    })
 }
 
 /// A function errored out either on the Rust or hwloc side
@@ -205,3 +317,429 @@ pub type FlagsError<Flags> = ParameterError<Flags>;
 #[derive(Copy, Clone, Debug, Default, Eq, Error, Hash, PartialEq)]
 #[error("platform does not support this operation")]
 pub struct UnsupportedError;
+
+/// Shared classification of CPU and memory binding errors
+///
+/// [`CpuBindingError`] and [`MemoryBindingError`] precisely describe what went
+/// wrong with a particular binding operation, but code which performs many
+/// kinds of binding operations may prefer to handle all binding errors in the
+/// same way. All binding errors, including the raw hwloc errors that binding
+/// functions report through [`HybridError`], can be converted into this type,
+/// whose variants and messages suggest a likely cause and remedy.
+///
+/// This functionality is unique to the Rust hwloc bindings.
+///
+/// # Examples
+///
+/// ```
+/// # use hwlocality::{
+/// #     cpu::binding::{CpuBindingError, CpuBoundObject},
+/// #     errors::BindingError,
+/// # };
+/// let error = BindingError::from(CpuBindingError::BadObject(CpuBoundObject::Thread));
+/// assert!(matches!(error, BindingError::Unsupported { .. }));
+/// ```
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[non_exhaustive]
+pub enum BindingError {
+    /// The operating system does not support this binding operation
+    ///
+    /// Support varies a lot across operating systems. For example, macOS does
+    /// not support CPU binding at all, Windows cannot bind the memory of
+    /// another process, and most operating systems cannot bind individual
+    /// threads of another process. Use [`Topology::feature_support()`] to
+    /// check which operations are supported before trying them.
+    #[error("{operation} is not supported on this system, check Topology::feature_support()")]
+    Unsupported {
+        /// Operation that is not supported
+        operation: BindingOperation,
+    },
+
+    /// The operating system does not allow this binding operation
+    ///
+    /// This typically happens when targeting a process or thread that belongs
+    /// to another user without sufficient privileges. On Linux, changing
+    /// another user's bindings requires `CAP_SYS_NICE`.
+    #[error(
+        "not allowed to query or set the binding of the target of {api}, check process privileges"
+    )]
+    PermissionDenied {
+        /// hwloc entry point that was denied
+        api: &'static str,
+    },
+
+    /// The requested CPU or NUMA node set cannot be bound to
+    ///
+    /// hwloc rejects sets that are empty or contain PUs or NUMA nodes which are
+    /// not part of the topology. Operating systems may also restrict which sets
+    /// can be bound to, e.g. some can only bind to a single PU or NUMA node.
+    /// On Linux, a set that does not contain any CPU allowed by the cgroup of
+    /// the target process is also rejected, consider intersecting the set with
+    /// [`Topology::allowed_cpuset()`].
+    #[error("invalid binding set: {details}")]
+    InvalidSet {
+        /// Explanation of what is wrong with the set
+        details: String,
+    },
+
+    /// The target CPU set spans multiple Windows processor groups
+    ///
+    /// Windows threads can only be bound inside of a single processor group.
+    /// Consider splitting the set by processor group and binding one thread to
+    /// each part instead.
+    #[error("{set} spans Windows processor groups {groups:?}, bind to a single group instead")]
+    SpansMultipleWindowsGroups {
+        /// CPU set that was requested
+        set: CpuSet,
+
+        /// Indices of the processor groups that this CPU set spans
+        groups: Vec<usize>,
+    },
+
+    /// The binding flags are not valid in this context
+    ///
+    /// Please cross-check the documentation of [`CpuBindingFlags`] or
+    /// [`MemoryBindingFlags`] and of the function you were trying to call.
+    #[error("binding flags {flags} are not valid for this operation")]
+    BadFlags {
+        /// Offending flag combination
+        flags: AnyBindingFlags,
+    },
+
+    /// Cannot query the memory location of a zero-sized memory area
+    #[error("cannot query the memory location of a zero-sized memory area")]
+    EmptyArea,
+
+    /// Memory allocation failed even before trying to bind
+    #[error("failed to allocate memory")]
+    AllocationFailed,
+
+    /// Memory bindings vary from one thread of the target process to another
+    ///
+    /// This is reported when querying a process' memory bindings with
+    /// [`MemoryBindingFlags::STRICT`]. Query individual threads instead.
+    #[error("binding varies from one thread of the process to another")]
+    MixedResults,
+
+    /// The binding call kept being interrupted by signals
+    ///
+    /// Interrupted calls are retried up to [`interrupted_call_retries()`]
+    /// times before this error is reported.
+    #[error("binding call was interrupted by signals too many times, retry later")]
+    Interrupted,
+
+    /// hwloc reported an error that has no known interpretation
+    #[error(transparent)]
+    Hwloc(RawHwlocError),
+}
+//
+impl From<CpuBindingError> for BindingError {
+    fn from(value: CpuBindingError) -> Self {
+        match value {
+            CpuBindingError::BadObject(object) => Self::Unsupported {
+                operation: BindingOperation::CpuBinding(object),
+            },
+            CpuBindingError::BadFlags(ParameterError(flags)) => Self::BadFlags {
+                flags: AnyBindingFlags::Cpu(flags),
+            },
+            CpuBindingError::BadCpuSet(object, set) => Self::InvalidSet {
+                details: format!(
+                    "the operating system cannot bind {object} to CPUs {set}, it may only support \
+                     binding to a single PU or to specific groups of PUs"
+                ),
+            },
+        }
+    }
+}
+//
+impl From<HybridError<CpuBindingError>> for BindingError {
+    fn from(value: HybridError<CpuBindingError>) -> Self {
+        match value {
+            HybridError::Rust(e) => e.into(),
+            HybridError::Hwloc(e) => Self::from_raw(e),
+        }
+    }
+}
+//
+impl<Set: SpecializedBitmap> From<MemoryBindingError<Set>> for BindingError {
+    fn from(value: MemoryBindingError<Set>) -> Self {
+        match value {
+            MemoryBindingError::Unsupported => Self::Unsupported {
+                operation: BindingOperation::MemoryBinding,
+            },
+            MemoryBindingError::BadFlags(ParameterError(flags)) => Self::BadFlags {
+                flags: AnyBindingFlags::Memory(flags),
+            },
+            MemoryBindingError::BadSet(object, set) => Self::InvalidSet {
+                details: format!(
+                    "the operating system cannot bind {object} to {set}, it may only support \
+                     binding to a single NUMA node"
+                ),
+            },
+            MemoryBindingError::BadTarget => Self::EmptyArea,
+            MemoryBindingError::AllocationFailed => Self::AllocationFailed,
+            MemoryBindingError::MixedResults => Self::MixedResults,
+            MemoryBindingError::Interrupted => Self::Interrupted,
+        }
+    }
+}
+//
+#[cfg(all(feature = "hwloc-2_5_0", target_os = "windows"))]
+impl From<ProcessorGroupBindingError> for BindingError {
+    fn from(value: ProcessorGroupBindingError) -> Self {
+        match value {
+            ProcessorGroupBindingError::Hwloc(e) => Self::from_raw(e),
+            ProcessorGroupBindingError::EmptySet => Self::InvalidSet {
+                details: "cannot bind a thread to an empty CPU set".to_owned(),
+            },
+            ProcessorGroupBindingError::MultipleGroups { set, groups } => {
+                Self::SpansMultipleWindowsGroups { set, groups }
+            }
+            ProcessorGroupBindingError::OutsideGroups(set) => Self::InvalidSet {
+                details: format!("{set} contains PUs that are not part of any processor group"),
+            },
+            ProcessorGroupBindingError::Os(errno) => Self::Hwloc(RawHwlocError {
+                api: "SetThreadGroupAffinity",
+                errno: Some(errno),
+            }),
+        }
+    }
+}
+//
+impl BindingError {
+    /// Interpret a raw error from an hwloc binding function
+    ///
+    /// hwloc binding functions report `ENOSYS` for unsupported operations,
+    /// `EPERM` for forbidden ones, `EXDEV` for sets that cannot be bound to,
+    /// and `EINVAL` for empty sets or sets which are not part of the topology.
+    ///
+    /// Other hwloc functions may use these errno values with a different
+    /// meaning, so this is not exposed as a `From` conversion.
+    pub(crate) fn from_raw(value: RawHwlocError) -> Self {
+        match value.errno.map(|errno| errno.0) {
+            Some(EINTR) => Self::Interrupted,
+            Some(ENOSYS) => Self::Unsupported {
+                operation: BindingOperation::HwlocApi(value.api),
+            },
+            Some(EPERM) => Self::PermissionDenied { api: value.api },
+            Some(EXDEV) => Self::InvalidSet {
+                details: format!(
+                    "{} cannot enforce this binding, the operating system may only support \
+                     binding to specific sets",
+                    value.api
+                ),
+            },
+            Some(EINVAL) => Self::InvalidSet {
+                details: format!(
+                    "{} rejected the set, it may be empty or contain PUs or NUMA nodes \
+                     that are not part of the topology or not allowed for the target",
+                    value.api
+                ),
+            },
+            Some(ENOMEM) => Self::AllocationFailed,
+            _ => Self::Hwloc(value),
+        }
+    }
+}
+
+/// Binding operation that is not supported, as reported by
+/// [`BindingError::Unsupported`]
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum BindingOperation {
+    /// Querying or setting the CPU binding of some object
+    CpuBinding(CpuBoundObject),
+
+    /// Querying or setting a memory binding, or allocating bound memory,
+    /// with the requested policy
+    MemoryBinding,
+
+    /// Calling an hwloc binding entry point
+    HwlocApi(&'static str),
+}
+//
+impl Display for BindingOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CpuBinding(object) => write!(f, "CPU binding of {object}"),
+            Self::MemoryBinding => f.write_str("the requested memory binding operation or policy"),
+            Self::HwlocApi(api) => write!(f, "{api} on this target"),
+        }
+    }
+}
+
+/// CPU or memory binding flags, as reported by [`BindingError::BadFlags`]
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum AnyBindingFlags {
+    /// CPU binding flags
+    Cpu(CpuBindingFlags),
+
+    /// Memory binding flags
+    Memory(MemoryBindingFlags),
+}
+//
+impl Display for AnyBindingFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cpu(flags) => write!(f, "{flags:?}"),
+            Self::Memory(flags) => write!(f, "{flags:?}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::{binding::MemoryBoundObject, nodesets::NodeSet};
+
+    fn raw(errno: Option<c_int>) -> RawHwlocError {
+        RawHwlocError {
+            api: "hwloc_set_cpubind",
+            errno: errno.map(Errno),
+        }
+    }
+
+    #[test]
+    fn cpu_binding_errors() {
+        assert_eq!(
+            BindingError::from(CpuBindingError::BadObject(CpuBoundObject::Thread)),
+            BindingError::Unsupported {
+                operation: BindingOperation::CpuBinding(CpuBoundObject::Thread),
+            }
+        );
+        let flags = CpuBindingFlags::THREAD | CpuBindingFlags::PROCESS;
+        assert_eq!(
+            BindingError::from(CpuBindingError::BadFlags(flags.into())),
+            BindingError::BadFlags {
+                flags: AnyBindingFlags::Cpu(flags),
+            }
+        );
+        let error = CpuBindingError::BadCpuSet(CpuBoundObject::ThisProgram, CpuSet::new());
+        assert!(matches!(
+            BindingError::from(error),
+            BindingError::InvalidSet { .. }
+        ));
+    }
+
+    #[test]
+    fn hybrid_cpu_binding_errors() {
+        let error = CpuBindingError::BadObject(CpuBoundObject::ProcessOrThread);
+        assert_eq!(
+            BindingError::from(HybridError::Rust(error.clone())),
+            BindingError::from(error)
+        );
+        let raw = raw(Some(EPERM));
+        assert_eq!(
+            BindingError::from(HybridError::<CpuBindingError>::Hwloc(raw)),
+            BindingError::from_raw(raw)
+        );
+    }
+
+    #[test]
+    fn memory_binding_errors() {
+        let convert = <BindingError as From<MemoryBindingError<NodeSet>>>::from;
+        assert_eq!(
+            convert(MemoryBindingError::Unsupported),
+            BindingError::Unsupported {
+                operation: BindingOperation::MemoryBinding,
+            }
+        );
+        let flags = MemoryBindingFlags::PROCESS | MemoryBindingFlags::THREAD;
+        assert_eq!(
+            convert(MemoryBindingError::BadFlags(flags.into())),
+            BindingError::BadFlags {
+                flags: AnyBindingFlags::Memory(flags),
+            }
+        );
+        assert!(matches!(
+            convert(MemoryBindingError::BadSet(
+                MemoryBoundObject::Area,
+                NodeSet::new()
+            )),
+            BindingError::InvalidSet { .. }
+        ));
+        assert_eq!(
+            convert(MemoryBindingError::BadTarget),
+            BindingError::EmptyArea
+        );
+        assert_eq!(
+            convert(MemoryBindingError::AllocationFailed),
+            BindingError::AllocationFailed
+        );
+        assert_eq!(
+            convert(MemoryBindingError::MixedResults),
+            BindingError::MixedResults
+        );
+        assert_eq!(
+            convert(MemoryBindingError::Interrupted),
+            BindingError::Interrupted
+        );
+    }
+
+    #[test]
+    fn raw_binding_errors() {
+        assert_eq!(
+            BindingError::from_raw(raw(Some(ENOSYS))),
+            BindingError::Unsupported {
+                operation: BindingOperation::HwlocApi("hwloc_set_cpubind"),
+            }
+        );
+        assert_eq!(
+            BindingError::from_raw(raw(Some(EPERM))),
+            BindingError::PermissionDenied {
+                api: "hwloc_set_cpubind"
+            }
+        );
+        for errno in [EXDEV, EINVAL] {
+            assert!(matches!(
+                BindingError::from_raw(raw(Some(errno))),
+                BindingError::InvalidSet { .. }
+            ));
+        }
+        assert_eq!(
+            BindingError::from_raw(raw(Some(ENOMEM))),
+            BindingError::AllocationFailed
+        );
+        assert_eq!(
+            BindingError::from_raw(raw(Some(EINTR))),
+            BindingError::Interrupted
+        );
+        for errno in [Some(libc::EBUSY), None] {
+            assert_eq!(
+                BindingError::from_raw(raw(errno)),
+                BindingError::Hwloc(raw(errno))
+            );
+        }
+    }
+
+    #[cfg(all(feature = "hwloc-2_5_0", target_os = "windows"))]
+    #[test]
+    fn processor_group_binding_errors() {
+        assert!(matches!(
+            BindingError::from(ProcessorGroupBindingError::EmptySet),
+            BindingError::InvalidSet { .. }
+        ));
+        let set = CpuSet::from_range(0..=127);
+        assert_eq!(
+            BindingError::from(ProcessorGroupBindingError::MultipleGroups {
+                set: set.clone(),
+                groups: vec![0, 1],
+            }),
+            BindingError::SpansMultipleWindowsGroups {
+                set,
+                groups: vec![0, 1],
+            }
+        );
+        assert!(matches!(
+            BindingError::from(ProcessorGroupBindingError::OutsideGroups(CpuSet::new())),
+            BindingError::InvalidSet { .. }
+        ));
+        assert_eq!(
+            BindingError::from(ProcessorGroupBindingError::Os(Errno(EINVAL))),
+            BindingError::Hwloc(RawHwlocError {
+                api: "SetThreadGroupAffinity",
+                errno: Some(Errno(EINVAL)),
+            })
+        );
+    }
+}
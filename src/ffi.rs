@@ -9,7 +9,12 @@ use crate::{
     errors::NulError,
     memory::binding::RawMemoryBindingPolicy,
     objects::{depth::RawDepth, distances::RawDistances, types::RawObjectType, TopologyObject},
-    topology::{builder::RawTypeFilter, support::FeatureSupport, RawTopology},
+    topology::{
+        builder::RawTypeFilter,
+        export::xml::{RawUserdataExportCallback, RawUserdataImportCallback},
+        support::FeatureSupport,
+        RawTopology,
+    },
     ProcessId, ThreadId,
 };
 #[cfg(target_os = "linux")]
@@ -166,21 +171,111 @@ pub(crate) struct IncompleteType {
     _marker: PhantomData<(*mut u8, PhantomPinned)>,
 }
 
-macro_rules! extern_c_block {
-    ($link_name:literal) => {
+/// Declare hwloc entry points by linking to the hwloc library
+#[cfg(not(all(feature = "dlopen", unix)))]
+macro_rules! hwloc_functions {
+    ($link_name:literal; $($functions:tt)*) => {
         #[link(name = $link_name)]
         extern "C" {
-            // === API versioning: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__api__version.html
+            $($functions)*
+        }
+    };
+}
+
+/// Declare hwloc entry points as wrappers that resolve the underlying symbols
+/// from a dynamically loaded hwloc library on first use
+///
+/// If a symbol is missing, or if the hwloc library cannot be loaded, the
+/// wrapper fails like the hwloc function would, see
+/// [`MissingSymbolFallback`](crate::dlopen::MissingSymbolFallback). Entry
+/// points that cannot report failure are listed by
+/// `infallible_entry_points()`, so that their presence can be checked when the
+/// library is loaded.
+#[cfg(all(feature = "dlopen", unix))]
+macro_rules! hwloc_functions {
+    (
+        $link_name:literal;
+        $(
+            $(#[cfg($cfg:meta)])*
+            $(#[must_use $($must_use_args:tt)*])?
+            pub(crate) fn $name:ident($($arg:ident: $arg_ty:ty),* $(,)?) $(-> $ret:ty)?;
+        )*
+    ) => {
+        $(
+            $(#[cfg($cfg)])*
+            $(#[must_use $($must_use_args)*])?
+            pub(crate) unsafe fn $name($($arg: $arg_ty),*) $(-> $ret)? {
+                /// Signature of the hwloc entry point
+                type Signature = unsafe extern "C" fn($($arg_ty),*) $(-> $ret)?;
+                /// Entry point, if the loaded hwloc library provides it
+                static ENTRY_POINT: once_cell::sync::OnceCell<Option<Signature>> =
+                    once_cell::sync::OnceCell::new();
+                let entry_point = ENTRY_POINT.get_or_try_init(|| {
+                    crate::dlopen::symbol(concat!(stringify!($name), "\0")).map(|ptr| {
+                        ptr.map(|ptr| {
+                            // SAFETY: hwloc exports this symbol with this signature
+                            unsafe { std::mem::transmute::<NonNull<c_void>, Signature>(ptr) }
+                        })
+                    })
+                });
+                let entry_point = match entry_point {
+                    Ok(entry_point) => entry_point,
+                    Err(e) => {
+                        return crate::dlopen::MissingSymbolFallback::library_unavailable(
+                            stringify!($name),
+                            &e,
+                        )
+                    }
+                };
+                match entry_point {
+                    Some(entry_point) => unsafe { entry_point($($arg),*) },
+                    None => crate::dlopen::MissingSymbolFallback::missing_symbol(stringify!($name)),
+                }
+            }
+        )*
+
+        /// Names of the hwloc entry points which cannot report failure, and
+        /// must thus be provided by the loaded hwloc library
+        pub(crate) fn infallible_entry_points() -> Vec<&'static str> {
+            let mut names = Vec::new();
+            $(
+                $(#[cfg($cfg)])*
+                if !<return_type!($($ret)?) as crate::dlopen::MissingSymbolFallback>::REPORTS_FAILURE {
+                    names.push(stringify!($name));
+                }
+            )*
+            names
+        }
+    };
+}
+
+/// Return type of an hwloc entry point, which defaults to `()`
+#[cfg(all(feature = "dlopen", unix))]
+macro_rules! return_type {
+    () => {
+        ()
+    };
+    ($ret:ty) => {
+        $ret
+    };
+}
+
+macro_rules! extern_c_block {
+    ($link_name:literal) => {
+        hwloc_functions! {
+            $link_name;
+
+            // === API versioning: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__api__version.html
 
             #[must_use]
             pub(crate) fn hwloc_get_api_version() -> c_uint;
 
-            // === Object types: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__object__types.html
+            // === Object types: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__object__types.html
 
             #[must_use]
             pub(crate) fn hwloc_compare_types(type1: RawObjectType, type2: RawObjectType) -> c_int;
 
-            // === Topology creation and destruction: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__creation.html
+            // === Topology creation and destruction: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__creation.html
 
             #[must_use]
             pub(crate) fn hwloc_topology_init(topology: *mut *mut RawTopology) -> c_int;
@@ -196,7 +291,7 @@ macro_rules! extern_c_block {
             pub(crate) fn hwloc_topology_abi_check(topology: *const RawTopology) -> c_int;
             pub(crate) fn hwloc_topology_check(topology: *const RawTopology);
 
-            // === Object levels, depths and types: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__levels.html
+            // === Object levels, depths and types: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__levels.html
 
             #[must_use]
             pub(crate) fn hwloc_topology_get_depth(topology: *const RawTopology) -> RawDepth;
@@ -224,7 +319,7 @@ macro_rules! extern_c_block {
                 idx: c_uint,
             ) -> *mut TopologyObject;
 
-            // === Converting between object types, attributes and strings: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__object__strings.html
+            // === Converting between object types, attributes and strings: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__object__strings.html
 
             #[must_use]
             pub(crate) fn hwloc_obj_type_snprintf(
@@ -241,9 +336,14 @@ macro_rules! extern_c_block {
                 separator: *const c_char,
                 verbose: c_int,
             ) -> c_int;
-            // NOTE: Not exposing type printf/scanf for now
+            // NOTE: Not exposing type printf/scanf for now, the following
+            //       entry point is only used to cross-check our object type
+            //       enum against the hwloc library that we link against.
+            #[cfg(test)]
+            #[must_use]
+            pub(crate) fn hwloc_obj_type_string(ty: RawObjectType) -> *const c_char;
 
-            // === Consulting and adding Key-Value info attributes: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__info__attr.html
+            // === Consulting and adding Key-Value info attributes: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__info__attr.html
 
             #[must_use]
             pub(crate) fn hwloc_obj_add_info(
@@ -252,7 +352,7 @@ macro_rules! extern_c_block {
                 value: *const c_char,
             ) -> c_int;
 
-            // === CPU binding: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__cpubinding.html
+            // === CPU binding: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__cpubinding.html
 
             #[must_use]
             pub(crate) fn hwloc_set_cpubind(
@@ -308,7 +408,7 @@ macro_rules! extern_c_block {
                 flags: c_int,
             ) -> c_int;
 
-            // === Memory binding: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__membinding.html
+            // === Memory binding: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__membinding.html
 
             #[must_use]
             pub(crate) fn hwloc_set_membind(
@@ -383,7 +483,7 @@ macro_rules! extern_c_block {
                 len: usize,
             ) -> c_int;
 
-            // === Changing the source of topology discovery: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__setsource.html
+            // === Changing the source of topology discovery: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__setsource.html
 
             #[must_use]
             pub(crate) fn hwloc_topology_set_pid(
@@ -414,7 +514,7 @@ macro_rules! extern_c_block {
                 name: *const c_char,
             ) -> c_int;
 
-            // === Topology detection configuration and query: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__configuration.html
+            // === Topology detection configuration and query: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__configuration.html
 
             #[must_use]
             pub(crate) fn hwloc_topology_set_flags(
@@ -465,7 +565,7 @@ macro_rules! extern_c_block {
             //       are hard to make work with copying, persistence and thread
             //       safety and are not so useful as to justify the effort.
 
-            // === Modifying a loaded Topology: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__tinker.html
+            // === Modifying a loaded Topology: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__tinker.html
 
             #[cfg(feature = "hwloc-2_3_0")]
             #[must_use]
@@ -510,7 +610,7 @@ macro_rules! extern_c_block {
             #[must_use]
             pub(crate) fn hwloc_topology_refresh(topology: *mut RawTopology) -> c_int;
 
-            // === Kinds of ObjectTypes: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__helper__types.html
+            // === Kinds of ObjectTypes: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__helper__types.html
 
             #[must_use]
             pub(crate) fn hwloc_obj_type_is_normal(ty: RawObjectType) -> c_int;
@@ -525,7 +625,7 @@ macro_rules! extern_c_block {
             #[must_use]
             pub(crate) fn hwloc_obj_type_is_icache(ty: RawObjectType) -> c_int;
 
-            // === Finding objects, miscellaneous helpers: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__helper__find__misc.html
+            // === Finding objects, miscellaneous helpers: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__helper__find__misc.html
 
             #[cfg(feature = "hwloc-2_2_0")]
             #[must_use]
@@ -545,7 +645,7 @@ macro_rules! extern_c_block {
                 flags: c_ulong,
             ) -> *const TopologyObject;
 
-            // === CPU and node sets of entire topologies: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__helper__topology__sets.html
+            // === CPU and node sets of entire topologies: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__helper__topology__sets.html
 
             #[must_use]
             pub(crate) fn hwloc_topology_get_complete_cpuset(
@@ -572,7 +672,7 @@ macro_rules! extern_c_block {
                 topology: *const RawTopology,
             ) -> *const RawBitmap;
 
-            // === Bitmap API: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__bitmap.html
+            // === Bitmap API: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__bitmap.html
 
             #[must_use]
             pub(crate) fn hwloc_bitmap_alloc() -> *mut RawBitmap;
@@ -590,6 +690,12 @@ macro_rules! extern_c_block {
                 len: usize,
                 bitmap: *const RawBitmap,
             ) -> c_int;
+            #[must_use]
+            pub(crate) fn hwloc_bitmap_snprintf(
+                buf: *mut c_char,
+                len: usize,
+                bitmap: *const RawBitmap,
+            ) -> c_int;
             // NOTE: Not exposing other printfs and scanfs for now
 
             pub(crate) fn hwloc_bitmap_zero(bitmap: *mut RawBitmap);
@@ -618,8 +724,11 @@ macro_rules! extern_c_block {
                 end: c_int,
             ) -> c_int;
             pub(crate) fn hwloc_bitmap_singlify(bitmap: *mut RawBitmap) -> c_int;
-            // NOTE: Not exposing ulong-based APIs for now, so no to_ulong, to_ith_ulong, to_ulongs and nr_ulongs
+            // NOTE: Not exposing ulong-based APIs for now, so no to_ulong, to_ulongs and nr_ulongs
             //       If I decide to add them, gate nr_ulongs and to_ulongs with #[cfg(feature = "hwloc-2_1_0")]
+            #[must_use]
+            pub(crate) fn hwloc_bitmap_to_ith_ulong(bitmap: *const RawBitmap, i: c_uint)
+                -> c_ulong;
 
             #[must_use]
             pub(crate) fn hwloc_bitmap_isset(bitmap: *const RawBitmap, id: c_uint) -> c_int;
@@ -698,7 +807,7 @@ macro_rules! extern_c_block {
                 right: *const RawBitmap,
             ) -> c_int;
 
-            // === Exporting Topologies to XML: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__xmlexport.html
+            // === Exporting Topologies to XML: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__xmlexport.html
 
             #[must_use]
             pub(crate) fn hwloc_topology_export_xml(
@@ -717,10 +826,34 @@ macro_rules! extern_c_block {
                 topology: *const RawTopology,
                 xmlbuffer: *mut c_char,
             );
-            // NOTE: Not exposing userdata at the moment, so no need to bind
-            //       associated API functions yet.
+            #[must_use]
+            pub(crate) fn hwloc_export_obj_userdata(
+                reserved: *mut c_void,
+                topology: *mut RawTopology,
+                obj: *mut TopologyObject,
+                name: *const c_char,
+                buffer: *const c_void,
+                length: usize,
+            ) -> c_int;
+            #[must_use]
+            pub(crate) fn hwloc_export_obj_userdata_base64(
+                reserved: *mut c_void,
+                topology: *mut RawTopology,
+                obj: *mut TopologyObject,
+                name: *const c_char,
+                buffer: *const c_void,
+                length: usize,
+            ) -> c_int;
+            pub(crate) fn hwloc_topology_set_userdata_export_callback(
+                topology: *mut RawTopology,
+                export_cb: Option<RawUserdataExportCallback>,
+            );
+            pub(crate) fn hwloc_topology_set_userdata_import_callback(
+                topology: *mut RawTopology,
+                import_cb: Option<RawUserdataImportCallback>,
+            );
 
-            // === Exporting Topologies to Synthetic: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__syntheticexport.html
+            // === Exporting Topologies to Synthetic: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__syntheticexport.html
 
             #[must_use]
             pub(crate) fn hwloc_topology_export_synthetic(
@@ -730,7 +863,7 @@ macro_rules! extern_c_block {
                 flags: c_ulong,
             ) -> c_int;
 
-            // === Retrieve distances between objects: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__distances__get.html
+            // === Retrieve distances between objects: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__distances__get.html
 
             #[must_use]
             pub(crate) fn hwloc_distances_get(
@@ -787,7 +920,7 @@ macro_rules! extern_c_block {
                 flags: c_ulong,
             ) -> c_int;
 
-            // === Add distances between objects: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__distances__add.html
+            // === Add distances between objects: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__distances__add.html
 
             #[cfg(feature = "hwloc-2_5_0")]
             #[must_use]
@@ -815,7 +948,7 @@ macro_rules! extern_c_block {
                 flags: c_ulong,
             ) -> c_int;
 
-            // === Remove distances between objects: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__distances__remove.html
+            // === Remove distances between objects: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__distances__remove.html
 
             #[cfg(feature = "hwloc-2_3_0")]
             #[must_use]
@@ -833,7 +966,7 @@ macro_rules! extern_c_block {
                 distances: *mut RawDistances,
             ) -> c_int;
 
-            // === Comparing memory node attributes for finding where to allocate on: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__memattrs.html
+            // === Comparing memory node attributes for finding where to allocate on: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__memattrs.html
 
             #[cfg(feature = "hwloc-2_3_0")]
             #[must_use]
@@ -842,6 +975,13 @@ macro_rules! extern_c_block {
                 name: *const c_char,
                 id: *mut MemoryAttributeID,
             ) -> c_int;
+            #[cfg(feature = "hwloc-2_10_0")]
+            #[must_use]
+            pub(crate) fn hwloc_topology_get_default_nodeset(
+                topology: *const RawTopology,
+                nodeset: *mut RawBitmap,
+                flags: c_ulong,
+            ) -> c_int;
             #[cfg(feature = "hwloc-2_3_0")]
             #[must_use]
             pub(crate) fn hwloc_get_local_numanode_objs(
@@ -882,7 +1022,7 @@ macro_rules! extern_c_block {
                 value: *mut u64,
             ) -> c_int;
 
-            // === Managing memory attributes: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__memattrs__manage.html
+            // === Managing memory attributes: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__memattrs__manage.html
 
             #[cfg(feature = "hwloc-2_3_0")]
             #[must_use]
@@ -939,7 +1079,7 @@ macro_rules! extern_c_block {
                 values: *mut u64,
             ) -> c_int;
 
-            // === Kinds of CPU cores: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__cpukinds.html
+            // === Kinds of CPU cores: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__cpukinds.html
 
             #[cfg(feature = "hwloc-2_4_0")]
             #[must_use]
@@ -976,7 +1116,7 @@ macro_rules! extern_c_block {
                 flags: c_ulong,
             ) -> c_int;
 
-            // === Linux-specific helpers: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__linux.html
+            // === Linux-specific helpers: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__linux.html
 
             #[cfg(target_os = "linux")]
             #[must_use]
@@ -1008,7 +1148,7 @@ macro_rules! extern_c_block {
 
             // NOTE: libnuma interop is waiting for higher quality libnuma bindings
 
-            // === Windows-specific helpers: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__windows.html
+            // === Windows-specific helpers: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__windows.html
 
             #[cfg(all(feature = "hwloc-2_5_0", target_os = "windows"))]
             #[must_use]
@@ -1025,10 +1165,9 @@ macro_rules! extern_c_block {
                 flags: c_ulong,
             ) -> c_int;
 
-            // NOTE: glibc interop is waiting for higher quality cpuset support
-            //       in the libc crate: right now, it is not possible to safely
-            //       crate a `cpu_set_t`, but functions that manipulate them
-            //       expect `&mut cpu_set_t`...
+            // NOTE: glibc interop is implemented in Rust on top of the libc
+            //       crate's `cpu_set_t`, as hwloc's versions are inline
+            //       functions. See the `cpu::cpusets` module.
 
             // TODO: Cover more later: interop, differences, sharing, etc...
             //       Beware that primitives that modify the topology should be
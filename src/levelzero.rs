@@ -0,0 +1,67 @@
+//! Level Zero-specific helpers
+
+use crate::{
+    cpu::cpusets::CpuSet,
+    objects::{types::OSDeviceType, TopologyObject},
+    topology::Topology,
+};
+
+/// # Level Zero-specific helpers
+///
+/// These functions locate oneAPI Level Zero devices, such as Intel GPUs, in
+/// the topology, so that applications can tell which CPU cores are close to
+/// them.
+///
+/// Unlike their hwloc counterparts, which take a `ze_device_handle_t`, they
+/// do not link to the Level Zero loader. Devices are instead identified by
+/// their index in the `zeN` OS devices that hwloc's Level Zero component adds
+/// to topologies built with I/O objects (see
+/// [`TopologyBuilder::with_io_type_filter()`]), so they return `None` if hwloc
+/// was built without Level Zero support. If you have a device handle, you can
+/// also query its PCI address with `zeDevicePciGetPropertiesExt()` and look it
+/// up with [`Topology::pci_device_by_bus_id()`].
+///
+/// [`TopologyBuilder::with_io_type_filter()`]: crate::topology::builder::TopologyBuilder::with_io_type_filter()
+//
+// Upstream docs: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__levelzero.html
+impl Topology {
+    /// Get the hwloc OS device object corresponding to the Level Zero device
+    /// with index `device`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// if let Some(gpu) = topology.levelzero_device_os_device(0) {
+    ///     println!("Level Zero device 0 is {gpu}");
+    /// }
+    /// ```
+    #[doc(alias = "hwloc_levelzero_get_device_osdev")]
+    pub fn levelzero_device_os_device(&self, device: usize) -> Option<&TopologyObject> {
+        self.os_device_with_name(OSDeviceType::CoProcessor, &format!("ze{device}"))
+    }
+
+    /// Get the CPU set of processors that are physically close to the Level
+    /// Zero device with index `device`
+    ///
+    /// This is the cpuset of the [`TopologyObject::non_io_ancestor()`] of the
+    /// OS device returned by [`levelzero_device_os_device()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::cpu::binding::CpuBindingFlags;
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// if let Some(cpuset) = topology.levelzero_device_cpuset(0) {
+    ///     topology.bind_cpu(&cpuset, CpuBindingFlags::THREAD)?;
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    ///
+    /// [`levelzero_device_os_device()`]: Topology::levelzero_device_os_device()
+    #[doc(alias = "hwloc_levelzero_get_device_cpuset")]
+    pub fn levelzero_device_cpuset(&self, device: usize) -> Option<CpuSet> {
+        self.levelzero_device_os_device(device)?
+            .non_io_ancestor_cpuset()
+    }
+}
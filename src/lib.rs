@@ -2,17 +2,50 @@
 #![deny(rustdoc::broken_intra_doc_links)]
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg, doc_cfg_hide))]
 #![cfg_attr(docsrs, doc(cfg_hide(doc)))]
+#![cfg_attr(feature = "allocator-api", feature(allocator_api))]
 
+/// Emit a log record at the specified level, if the `log` feature is enabled
+///
+/// Otherwise, the arguments are type-checked but no code is generated.
+macro_rules! log_event {
+    ($level:ident, $($arg:tt)+) => {{
+        #[cfg(feature = "log")]
+        log::log!(log::Level::$level, $($arg)+);
+        #[cfg(not(feature = "log"))]
+        if false {
+            let _ = format_args!($($arg)+);
+        }
+    }};
+}
+
+pub mod binding_target;
 pub mod bitmaps;
+pub mod bound_thread;
+pub mod coverage;
 pub mod cpu;
+#[cfg(any(doc, feature = "cuda"))]
+mod cuda;
+#[cfg(all(unix, any(doc, feature = "dlopen")))]
+pub mod dlopen;
 pub mod errors;
 pub(crate) mod ffi;
 pub mod info;
+#[cfg(any(doc, feature = "ze"))]
+mod levelzero;
 #[cfg(any(doc, target_os = "linux"))]
 mod linux;
 pub mod memory;
+#[cfg(any(doc, feature = "nvml"))]
+mod nvml;
 pub mod objects;
+#[cfg(any(doc, feature = "ofed"))]
+mod ofed;
 pub mod paths;
+pub mod placement;
+#[cfg(any(doc, feature = "rsmi"))]
+mod rsmi;
+#[cfg(target_os = "linux")]
+pub mod steering;
 pub mod topology;
 #[cfg(any(doc, all(feature = "hwloc-2_5_0", target_os = "windows")))]
 mod windows;
@@ -53,6 +86,9 @@ pub fn get_api_version() -> usize {
 #[cfg_attr(docsrs, doc(cfg(all())))]
 pub use topology::Topology;
 
+#[doc(inline)]
+pub use coverage::coverage;
+
 /// This module is an implementation detail of [`Sealed`]
 mod sealed {
     /// This trait can only be implemented by types inside this crate
@@ -61,3 +97,29 @@ mod sealed {
 
 /// Import of [`Sealed`] that only this crate can use
 pub(crate) use sealed::Sealed;
+
+// Compile-time checks that the main hwlocality types can be shared and moved
+// across threads, as async code and thread pools routinely need to do so.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync + ?Sized>() {}
+    assert_send_sync::<bitmaps::Bitmap>();
+    assert_send_sync::<bitmaps::BitmapRef<'static, cpu::cpusets::CpuSet>>();
+    assert_send_sync::<bitmaps::OwningBitmapRef<memory::nodesets::NodeSet>>();
+    assert_send_sync::<cpu::cpusets::CpuSet>();
+    assert_send_sync::<info::TextualInfo>();
+    assert_send_sync::<memory::binding::Bytes<'static>>();
+    assert_send_sync::<memory::nodesets::NodeSet>();
+    assert_send_sync::<objects::distances::Distances<'static>>();
+    assert_send_sync::<objects::TopologyObject>();
+    assert_send_sync::<&'static objects::TopologyObject>();
+    assert_send_sync::<topology::builder::BuildFuture>();
+    assert_send_sync::<topology::builder::TopologyBuilder>();
+    assert_send_sync::<topology::cache::TopologyCache<'static>>();
+    assert_send_sync::<topology::export::xml::XML<'static>>();
+    #[cfg(target_os = "linux")]
+    assert_send_sync::<topology::refresh::HotplugWatcher>();
+    assert_send_sync::<topology::support::FeatureSupport>();
+    assert_send_sync::<topology::Topology>();
+    #[cfg(feature = "hwloc-2_3_0")]
+    assert_send_sync::<topology::editor::TopologyEditor<'static>>();
+};
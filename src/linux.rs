@@ -2,14 +2,21 @@
 
 #[cfg(doc)]
 use crate::cpu::binding::CpuBindingFlags;
+#[cfg(doc)]
+use crate::topology::builder::BuildFlags;
 use crate::{
+    bitmaps::{Bitmap, BitmapIndex},
     cpu::cpusets::CpuSet,
     errors::{self, HybridError, RawHwlocError},
     ffi,
+    memory::nodesets::NodeSet,
     paths::{self, PathError},
     topology::Topology,
 };
-use std::path::Path;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
 
 // This file is rustdoc-visible so we must provide a substitute for
 // linux-specific libc entities when people run rustdoc on Windows.
@@ -25,7 +32,7 @@ struct pid_t;
 /// equivalents of the Linux `sched_setaffinity` and `sched_getaffinity` system
 /// calls.
 //
-// Upstream docs: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__linux.html
+// Upstream docs: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__linux.html
 impl Topology {
     /// Bind a thread `tid` on cpus given in `set`
     ///
@@ -90,7 +97,7 @@ impl Topology {
     /// topology and caches for processors, or local_cpus for devices.
     ///
     /// Note that this function ignores the [HWLOC_FSROOT environment
-    /// variable](https://hwloc.readthedocs.io/en/v2.9/envvar.html).
+    /// variable](https://hwloc.readthedocs.io/en/v2.11/envvar.html).
     #[doc(alias = "hwloc_linux_read_path_as_cpumask")]
     pub fn read_path_as_cpumask(
         &self,
@@ -104,4 +111,254 @@ impl Topology {
         .map_err(HybridError::Hwloc)?;
         Ok(set)
     }
+
+    /// PUs that currently service interrupt request line `irq`
+    ///
+    /// This reads the effective affinity of the IRQ from
+    /// `/proc/irq/<irq>/effective_affinity` when the kernel exposes it, and
+    /// falls back to the configured `/proc/irq/<irq>/smp_affinity` otherwise.
+    ///
+    /// Interrupt steering may be changed at any time by the administrator or
+    /// by daemons like `irqbalance`, so this should be called again whenever
+    /// an up-to-date answer is needed.
+    #[doc(alias = "pus_for_irq")]
+    pub fn irq_cpuset(&self, irq: u32) -> Result<CpuSet, RawHwlocError> {
+        let irq_dir = Path::new("/proc/irq").join(irq.to_string());
+        let mut path = irq_dir.join("effective_affinity");
+        if !path.exists() {
+            path = irq_dir.join("smp_affinity");
+        }
+        self.read_path_as_cpumask(path).map_err(|e| match e {
+            HybridError::Hwloc(e) => e,
+            HybridError::Rust(e) => unreachable!("IRQ paths should be valid: {e}"),
+        })
+    }
+
+    /// Bind thread `tid` to the PUs that currently service IRQ `irq`
+    ///
+    /// This combines [`irq_cpuset()`] and [`bind_tid_cpu()`], and returns the
+    /// [`CpuSet`] that the thread was bound to, so that user-space processing
+    /// of the interrupt's data can run close to the interrupt handler.
+    ///
+    /// As interrupt steering may change at runtime, this should be called
+    /// again when the binding needs to be refreshed.
+    ///
+    /// [`bind_tid_cpu()`]: Topology::bind_tid_cpu()
+    /// [`irq_cpuset()`]: Topology::irq_cpuset()
+    pub fn bind_tid_cpu_to_irq(&self, tid: pid_t, irq: u32) -> Result<CpuSet, RawHwlocError> {
+        let set = self.irq_cpuset(irq)?;
+        self.bind_tid_cpu(tid, &set)?;
+        Ok(set)
+    }
+
+    /// CPUs and NUMA nodes that a cgroup v2 can use
+    ///
+    /// `cgroup` is the path to a cgroup directory, e.g.
+    /// `/sys/fs/cgroup/system.slice/my.service`. This reads the
+    /// `cpuset.cpus.effective` and `cpuset.mems.effective` files of this
+    /// directory, which only exist if the cpuset controller is enabled for
+    /// this cgroup. Use [`effective_cgroup_cpuset()`] to find out which CPUs
+    /// and NUMA nodes the current process can use.
+    ///
+    /// This queries the kernel directly, regardless of how the topology was
+    /// built. In particular, the result does not change when the topology is
+    /// built with [`BuildFlags::INCLUDE_DISALLOWED`], which is handy when the
+    /// topology covers the whole machine but a service must only use its
+    /// share of it.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Errors
+    ///
+    /// - [`io::ErrorKind::NotFound`] if the cpuset controller is not enabled
+    ///   for `cgroup`
+    /// - [`io::ErrorKind::InvalidData`] if the cpuset files cannot be parsed
+    /// - Any other I/O error that occurs while reading the cpuset files
+    ///
+    /// [`effective_cgroup_cpuset()`]: Topology::effective_cgroup_cpuset()
+    #[doc(alias = "cpuset.cpus.effective")]
+    #[doc(alias = "cpuset.mems.effective")]
+    pub fn cgroup_cpuset(&self, cgroup: impl AsRef<Path>) -> io::Result<(CpuSet, NodeSet)> {
+        let cgroup = cgroup.as_ref();
+        let cpus = read_kernel_list(cgroup.join("cpuset.cpus.effective"))?;
+        let mems = read_kernel_list(cgroup.join("cpuset.mems.effective"))?;
+        Ok((cpus.into(), mems.into()))
+    }
+
+    /// CPUs and NUMA nodes that the cgroup v2 of the current process can use
+    ///
+    /// This locates the cgroup of the current process using
+    /// `/proc/self/cgroup`, then reads its cpuset as in [`cgroup_cpuset()`].
+    /// If the cpuset controller is not enabled for this cgroup, the cpuset of
+    /// the closest ancestor cgroup where it is enabled is used, since that is
+    /// what the kernel enforces.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Errors
+    ///
+    /// - [`io::ErrorKind::NotFound`] if the current process does not belong
+    ///   to a cgroup v2 hierarchy, or if no cpuset can be found for it
+    /// - Any error reported by [`cgroup_cpuset()`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// match topology.effective_cgroup_cpuset() {
+    ///     Ok((cpus, nodes)) => {
+    ///         println!("This process can use CPUs {cpus} and NUMA nodes {nodes}")
+    ///     }
+    ///     Err(e) => eprintln!("Could not read the cgroup cpuset: {e}"),
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    ///
+    /// [`cgroup_cpuset()`]: Topology::cgroup_cpuset()
+    pub fn effective_cgroup_cpuset(&self) -> io::Result<(CpuSet, NodeSet)> {
+        let mount = cgroup2_mount_point()?;
+        let cgroup = fs::read_to_string("/proc/self/cgroup")?
+            .lines()
+            .find_map(|line| line.strip_prefix("0::").map(str::to_owned))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "process does not belong to a cgroup v2 hierarchy",
+                )
+            })?;
+        let cgroup = mount.join(cgroup.trim_start_matches('/'));
+        for dir in cgroup.ancestors().take_while(|dir| dir.starts_with(&mount)) {
+            match self.cgroup_cpuset(dir) {
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                result => return result,
+            }
+        }
+        Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "no cpuset found for the cgroup of this process",
+        ))
+    }
+}
+
+/// Mount point of the cgroup v2 hierarchy, according to `/proc/self/mounts`
+fn cgroup2_mount_point() -> io::Result<PathBuf> {
+    find_cgroup2_mount_point(&fs::read_to_string("/proc/self/mounts")?)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "cgroup v2 is not mounted"))
+}
+
+/// Find the mount point of the cgroup v2 hierarchy in the contents of
+/// `/proc/self/mounts`
+fn find_cgroup2_mount_point(mounts: &str) -> Option<PathBuf> {
+    mounts.lines().find_map(|line| {
+        let mut fields = line.split_whitespace();
+        let (_source, target, fs_type) = (fields.next()?, fields.next()?, fields.next()?);
+        (fs_type == "cgroup2").then(|| PathBuf::from(unescape_mount_field(target)))
+    })
+}
+
+/// Undo the octal escapes, like `\040` for spaces, that the kernel uses for
+/// whitespace and backslashes in the fields of `/proc/self/mounts`
+fn unescape_mount_field(field: &str) -> String {
+    let mut result = String::with_capacity(field.len());
+    let mut rest = field;
+    while let Some(backslash) = rest.find('\\') {
+        result.push_str(&rest[..backslash]);
+        rest = &rest[backslash + 1..];
+        let escaped = rest
+            .get(..3)
+            .filter(|digits| digits.bytes().all(|digit| (b'0'..=b'7').contains(&digit)))
+            .and_then(|digits| u8::from_str_radix(digits, 8).ok());
+        if let Some(byte) = escaped {
+            result.push(char::from(byte));
+            rest = &rest[3..];
+        } else {
+            result.push('\\');
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Read a Linux kernel index list like "0-3,8,10-11", as found in sysfs and
+/// cgroupfs
+pub(crate) fn read_kernel_list(path: impl AsRef<Path>) -> io::Result<Bitmap> {
+    parse_kernel_list(&fs::read_to_string(path)?)
+}
+
+/// Parse a Linux kernel index list like "0-3,8,10-11"
+fn parse_kernel_list(list: &str) -> io::Result<Bitmap> {
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "invalid kernel index list");
+    let parse_index = |s: &str| {
+        let index = s.trim().parse::<usize>().map_err(|_| invalid())?;
+        BitmapIndex::try_from(index).map_err(|_| invalid())
+    };
+    let mut result = Bitmap::new();
+    for item in list.trim().split(',').filter(|item| !item.is_empty()) {
+        if let Some((start, end)) = item.split_once('-') {
+            let (start, end) = (parse_index(start)?, parse_index(end)?);
+            if start > end {
+                return Err(invalid());
+            }
+            result.set_range(start..=end);
+        } else {
+            result.set(parse_index(item)?);
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kernel_lists_should_parse() {
+        let parse = |list| parse_kernel_list(list).map(|bitmap| bitmap.to_string());
+        assert_eq!(parse("0").unwrap(), "0");
+        assert_eq!(parse("0-3,8,10-11\n").unwrap(), "0-3,8,10-11");
+        assert_eq!(parse("5,1-2").unwrap(), "1-2,5");
+        assert_eq!(parse("4-4").unwrap(), "4");
+        assert_eq!(parse(" 2 - 3 ").unwrap(), "2-3");
+    }
+
+    #[test]
+    fn empty_kernel_lists_should_parse() {
+        for list in ["", "\n", " "] {
+            assert!(parse_kernel_list(list).unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    fn invalid_kernel_lists_should_be_rejected() {
+        for list in ["a", "1,b", "-1", "3-", "-", "1-2-3", "3-1", "0x1", "1.5"] {
+            assert_eq!(
+                parse_kernel_list(list).unwrap_err().kind(),
+                io::ErrorKind::InvalidData,
+                "{list:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn mount_fields_should_be_unescaped() {
+        assert_eq!(unescape_mount_field("/sys/fs/cgroup"), "/sys/fs/cgroup");
+        assert_eq!(
+            unescape_mount_field("/mnt/my\\040cgroup\\011v2"),
+            "/mnt/my cgroup\tv2"
+        );
+        assert_eq!(unescape_mount_field("back\\134slash"), "back\\slash");
+        assert_eq!(unescape_mount_field("bad\\08\\"), "bad\\08\\");
+        assert_eq!(unescape_mount_field("\\04"), "\\04");
+    }
+
+    #[test]
+    fn cgroup2_mount_point_should_be_found() {
+        let mounts = "proc /proc proc rw,nosuid 0 0\n\
+                      cgroup2 /mnt/my\\040cgroup cgroup2 rw,nosuid 0 0\n";
+        assert_eq!(
+            find_cgroup2_mount_point(mounts),
+            Some(PathBuf::from("/mnt/my cgroup"))
+        );
+        assert_eq!(find_cgroup2_mount_point("proc /proc proc rw 0 0\n"), None);
+    }
 }
@@ -0,0 +1,196 @@
+//! NUMA-aware allocator for standard collections
+//!
+//! This module provides [`NodeLocalAlloc`], an implementation of the unstable
+//! [`Allocator`] trait which allocates memory that is bound to some NUMA nodes,
+//! so that standard collections like `Vec` and `Box` can be used for
+//! node-local buffers. It requires a nightly compiler.
+
+use crate::{
+    memory::{
+        binding::{Bytes, MemoryBindingFlags, MemoryBindingPolicy},
+        nodesets::NodeSet,
+    },
+    topology::{self, Topology},
+};
+use std::{
+    alloc::{AllocError, Allocator, Layout},
+    ffi::c_void,
+    mem,
+    ptr::{self, NonNull},
+};
+
+/// Allocator that binds allocations to some NUMA nodes
+///
+/// Allocations go through [`Topology::allocate_bound_memory()`], which
+/// allocates whole pages of memory. This allocator is therefore best suited to
+/// large buffers: every allocation, even a tiny one, uses at least one page.
+/// Alignments larger than the base page size, e.g. huge page alignment, are
+/// supported by allocating `align` extra bytes and aligning the block within
+/// the allocation.
+///
+/// This functionality is unique to the Rust hwloc bindings.
+///
+/// # Examples
+///
+/// ```
+/// #![feature(allocator_api)]
+/// # use hwlocality::memory::{allocator::NodeLocalAlloc, nodesets::NodeSet};
+/// # let topology = hwlocality::Topology::test_instance();
+/// let first_node = NodeSet::from(topology.nodeset().first_set().unwrap());
+/// let alloc = NodeLocalAlloc::new(&topology, first_node);
+/// match Vec::<u64, _>::try_with_capacity_in(1024, alloc) {
+///     Ok(mut buffer) => buffer.extend(0..1024),
+///     Err(e) => eprintln!("Node-local allocation is not supported: {e}"),
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct NodeLocalAlloc<'topology> {
+    /// Topology used for allocation
+    topology: &'topology Topology,
+
+    /// NUMA nodes that allocations are bound to
+    nodeset: NodeSet,
+
+    /// Memory binding policy
+    policy: MemoryBindingPolicy,
+
+    /// Memory binding flags
+    flags: MemoryBindingFlags,
+}
+//
+impl<'topology> NodeLocalAlloc<'topology> {
+    /// Allocate memory on the NUMA nodes of `nodeset`
+    ///
+    /// This uses the [`Bind`](MemoryBindingPolicy::Bind) policy, see
+    /// [`with_policy()`](Self::with_policy()) for more options.
+    pub fn new(topology: &'topology Topology, nodeset: NodeSet) -> Self {
+        Self::with_policy(
+            topology,
+            nodeset,
+            MemoryBindingPolicy::Bind,
+            MemoryBindingFlags::empty(),
+        )
+    }
+
+    /// Allocate memory on the NUMA nodes of `nodeset` with a custom policy
+    ///
+    /// `policy` and `flags` have the same meaning as in
+    /// [`Topology::allocate_bound_memory()`]. If they are not supported,
+    /// allocations fail.
+    pub fn with_policy(
+        topology: &'topology Topology,
+        nodeset: NodeSet,
+        policy: MemoryBindingPolicy,
+        flags: MemoryBindingFlags,
+    ) -> Self {
+        Self {
+            topology,
+            nodeset,
+            policy,
+            flags,
+        }
+    }
+
+    /// Topology used for allocation
+    pub fn topology(&self) -> &'topology Topology {
+        self.topology
+    }
+
+    /// NUMA nodes that allocations are bound to
+    pub fn nodeset(&self) -> &NodeSet {
+        &self.nodeset
+    }
+
+    /// Extra bytes to allocate so that a block with this `layout` can be
+    /// aligned within the allocation
+    ///
+    /// hwloc allocations are aligned to the base page size, which is enough
+    /// for most layouts. Larger alignments are handled by allocating `align`
+    /// more bytes, plus room for a pointer to the start of the allocation,
+    /// which is stored right before the aligned block.
+    fn padding(layout: Layout) -> usize {
+        let page_size = usize::try_from(topology::base_page_size())
+            .expect("Page size should fit in the address space");
+        if layout.align() <= page_size {
+            0
+        } else {
+            layout.align() + Self::HEADER_SIZE
+        }
+    }
+
+    /// Size of the pointer to the start of over-aligned allocations
+    const HEADER_SIZE: usize = mem::size_of::<*mut u8>();
+}
+//
+// SAFETY: Memory blocks come from hwloc allocations which stay valid until
+//         they are deallocated with hwloc_free, the allocator can be cloned
+//         because all clones use the same topology, and blocks are aligned
+//         either by hwloc's page-sized allocations or by padding.
+unsafe impl Allocator for NodeLocalAlloc<'_> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        if layout.size() == 0 {
+            // Zero-sized blocks only need to be non-null and aligned, and
+            // must not be associated with any allocation
+            #[allow(clippy::incompatible_msrv)] // Module requires nightly anyway
+            let dangling = ptr::without_provenance_mut::<u8>(layout.align());
+            let block = ptr::slice_from_raw_parts_mut(dangling, 0);
+            return Ok(NonNull::new(block).expect("Alignment is nonzero"));
+        }
+        let padding = Self::padding(layout);
+        let len = layout.size().checked_add(padding).ok_or(AllocError)?;
+        let mut bytes = self
+            .topology
+            .allocate_bound_memory(len, &self.nodeset, self.policy, self.flags)
+            .map_err(|_| AllocError)?;
+        let base = bytes.as_mut_ptr().cast::<u8>();
+        let block = if padding == 0 {
+            if base.align_offset(layout.align()) != 0 {
+                // Should not happen as hwloc allocates whole pages, but if it
+                // did, the Bytes would be freed on the way out
+                return Err(AllocError);
+            }
+            base
+        } else {
+            // SAFETY: The allocation has room for the header and for
+            //         `layout.align()` bytes of alignment padding before the
+            //         block, and the header may not be aligned
+            unsafe {
+                let header = base.add(Self::HEADER_SIZE);
+                let block = header.add(header.align_offset(layout.align()));
+                block
+                    .sub(Self::HEADER_SIZE)
+                    .cast::<*mut u8>()
+                    .write_unaligned(base);
+                block
+            }
+        };
+        // Ownership of the allocation is transferred to the caller, who will
+        // release it through deallocate()
+        mem::forget(bytes);
+        let block = ptr::slice_from_raw_parts_mut(block, layout.size());
+        Ok(NonNull::new(block).expect("hwloc allocations are not null"))
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        if layout.size() == 0 {
+            return;
+        }
+        let padding = Self::padding(layout);
+        let base = if padding == 0 {
+            ptr.as_ptr()
+        } else {
+            // SAFETY: allocate() stored the start of the allocation before
+            //         over-aligned blocks
+            unsafe {
+                ptr.as_ptr()
+                    .sub(Self::HEADER_SIZE)
+                    .cast::<*mut u8>()
+                    .read_unaligned()
+            }
+        };
+        let base = NonNull::new(base.cast::<c_void>()).expect("hwloc allocations are not null");
+        // Dropping the Bytes releases the allocation with hwloc_free
+        drop(unsafe { Bytes::wrap(self.topology, base, layout.size() + padding) });
+    }
+}
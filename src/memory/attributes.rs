@@ -1,5 +1,9 @@
 //! Memory attributes
 
+#[cfg(feature = "hwloc-2_10_0")]
+use crate::memory::nodesets::NodeSet;
+#[cfg(feature = "hwloc-2_8_0")]
+use crate::topology::builder::BuildFlags;
 #[cfg(doc)]
 use crate::topology::support::DiscoverySupport;
 use crate::{
@@ -8,7 +12,7 @@ use crate::{
     errors::{self, HybridError, NulError, RawHwlocError},
     ffi::{self, LibcString},
     objects::TopologyObject,
-    topology::{editor::TopologyEditor, RawTopology, Topology},
+    topology::{builder::FeatureDisabledAtBuild, editor::TopologyEditor, RawTopology, Topology},
 };
 use bitflags::bitflags;
 use derive_more::Display;
@@ -52,12 +56,18 @@ use thiserror::Error;
 /// for these nodes, if any, may then be obtained with
 /// [`MemoryAttribute::value()`] and manually compared with the desired criteria.
 ///
+/// If the topology was built with `BuildFlags::IGNORE_MEMORY_ATTRIBUTES`,
+/// memory attribute values are not queried from the operating system and only
+/// hwloc-computed attributes like capacity and locality, along with
+/// user-provided values, will be available. This can be checked using
+/// `Topology::disabled_features()`.
+///
 /// The API also supports specific objects as initiator, but it is currently not
 /// used internally by hwloc. Users may for instance use it to provide custom
 /// performance values for host memory accesses performed by GPUs.
 /// The interface actually also accepts targets that are not NUMA nodes.
 //
-// Upstream docs: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__memattrs.html
+// Upstream docs: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__memattrs.html
 impl Topology {
     /// Identifier of the memory attribute with the given name
     ///
@@ -102,9 +112,9 @@ impl Topology {
     /// [`LocalNUMANodeFlags::SMALLER_LOCALITY`], the returned array corresponds
     /// to the nodeset of that object.
     #[doc(alias = "hwloc_get_local_numanode_objs")]
-    pub fn local_numa_nodes<'topology>(
-        &'topology self,
-        target: impl Into<TargetNumaNodes<'topology>>,
+    pub fn local_numa_nodes<'target>(
+        &self,
+        target: impl Into<TargetNumaNodes<'target>>,
     ) -> Result<Vec<&TopologyObject>, RawHwlocError> {
         // Prepare to call hwloc
         let (location, flags) = target.into().into_raw_params();
@@ -140,11 +150,97 @@ impl Topology {
             })
             .collect())
     }
+
+    /// Pick a default NUMA node for allocating memory near some CPUs
+    ///
+    /// This selects the NUMA nodes whose locality covers `cpuset`, preferring
+    /// normal memory (nodes without a subtype or with the `DRAM` subtype) over
+    /// special kinds of memory like HBM or NVM, and returns the one with the
+    /// smallest locality, i.e. the one that is closest to `cpuset`.
+    ///
+    /// If no NUMA node covers all of `cpuset`, which can happen when it spans
+    /// multiple packages, nodes whose locality intersects `cpuset` are
+    /// considered instead.
+    ///
+    /// For more control over the choice of NUMA node, see
+    /// [`Topology::local_numa_nodes()`] and [`MemoryAttribute::best_target()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// let node = topology
+    ///     .default_numa_node_for(&topology.cpuset())?
+    ///     .expect("There should be at least one NUMA node");
+    /// assert!(topology.nodeset().includes(&node.nodeset().unwrap()));
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn default_numa_node_for(
+        &self,
+        cpuset: &CpuSet,
+    ) -> Result<Option<&TopologyObject>, RawHwlocError> {
+        let mut nodes = self.local_numa_nodes(TargetNumaNodes::Local {
+            location: cpuset.into(),
+            flags: LocalNUMANodeFlags::LARGER_LOCALITY,
+        })?;
+        if nodes.is_empty() {
+            nodes = self.local_numa_nodes(TargetNumaNodes::Local {
+                location: cpuset.into(),
+                flags: LocalNUMANodeFlags::LARGER_LOCALITY | LocalNUMANodeFlags::SMALLER_LOCALITY,
+            })?;
+        }
+        let is_special_memory = |node: &TopologyObject| {
+            node.subtype()
+                .map_or(false, |subtype| subtype.to_bytes() != b"DRAM")
+        };
+        let locality_size = |node: &TopologyObject| {
+            node.cpuset()
+                .and_then(|cpuset| cpuset.weight())
+                .unwrap_or(usize::MAX)
+        };
+        Ok(nodes
+            .into_iter()
+            .min_by_key(|node| (is_special_memory(node), locality_size(node))))
+    }
+
+    /// Default set of NUMA nodes
+    ///
+    /// This is the set of NUMA nodes that hold normal memory, i.e. the nodes
+    /// that the operating system uses by default for allocations. It excludes
+    /// specific kinds of memory like HBM, non-volatile memory or CXL memory
+    /// expanders, which must be explicitly requested through memory binding.
+    ///
+    /// On platforms without such heterogeneous memory, this is the same as
+    /// [`Topology::nodeset()`].
+    ///
+    /// # Errors
+    ///
+    /// This function is not expected to fail, but hwloc does not document its
+    /// failure modes, so any error is reported as a [`RawHwlocError`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// let default_nodes = topology.default_nodeset()?;
+    /// assert!(!default_nodes.is_empty());
+    /// assert!(topology.nodeset().includes(&default_nodes));
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    #[cfg(feature = "hwloc-2_10_0")]
+    #[doc(alias = "hwloc_topology_get_default_nodeset")]
+    pub fn default_nodeset(&self) -> Result<NodeSet, RawHwlocError> {
+        let mut nodeset = NodeSet::new();
+        errors::call_hwloc_int_normal("hwloc_topology_get_default_nodeset", || unsafe {
+            ffi::hwloc_topology_get_default_nodeset(self.as_ptr(), nodeset.as_mut_ptr(), 0)
+        })?;
+        Ok(nodeset)
+    }
 }
 
 /// # Managing memory attributes
 //
-// Upstream docs: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__memattrs__manage.html
+// Upstream docs: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__memattrs__manage.html
 impl<'topology> TopologyEditor<'topology> {
     /// Register a new memory attribute
     ///
@@ -552,9 +648,13 @@ impl<'topology> MemoryAttribute<'topology> {
     /// # Errors
     ///
     /// - [`BadInitiator`] if the `initiator` parameter was not set correctly
+    /// - [`FeatureDisabledAtBuild`] if there is no value and this attribute is
+    ///   gathered from the operating system, but the topology was built with
+    ///   `BuildFlags::IGNORE_MEMORY_ATTRIBUTES`
     /// - [`UnknownAttribute`] if no such attribute exists.
     ///
     /// [`BadInitiator`]: MemoryAttributeQueryError::BadInitiator
+    /// [`FeatureDisabledAtBuild`]: MemoryAttributeQueryError::FeatureDisabledAtBuild
     /// [`UnknownAttribute`]: MemoryAttributeQueryError::UnknownAttribute
     #[doc(alias = "hwloc_memattr_get_value")]
     pub fn value(
@@ -579,7 +679,10 @@ impl<'topology> MemoryAttribute<'topology> {
             Err(RawHwlocError {
                 api: _,
                 errno: Some(Errno(EINVAL)),
-            }) => Err(MemoryAttributeQueryError::UnknownAttribute),
+            }) => {
+                self.check_disabled_at_build()?;
+                Err(MemoryAttributeQueryError::UnknownAttribute)
+            }
             Err(raw_err) => unreachable!("Unexpected hwloc error: {raw_err}"),
         }
     }
@@ -599,9 +702,13 @@ impl<'topology> MemoryAttribute<'topology> {
     /// # Errors
     ///
     /// - [`BadInitiator`] if the `initiator` parameter was not set correctly
+    /// - [`FeatureDisabledAtBuild`] if no target was found and this attribute
+    ///   is gathered from the operating system, but the topology was built
+    ///   with `BuildFlags::IGNORE_MEMORY_ATTRIBUTES`
     /// - [`UnknownAttribute`] if no such attribute exists.
     ///
     /// [`BadInitiator`]: MemoryAttributeQueryError::BadInitiator
+    /// [`FeatureDisabledAtBuild`]: MemoryAttributeQueryError::FeatureDisabledAtBuild
     /// [`UnknownAttribute`]: MemoryAttributeQueryError::UnknownAttribute
     #[doc(alias = "hwloc_memattr_get_best_target")]
     pub fn best_target(
@@ -637,9 +744,13 @@ impl<'topology> MemoryAttribute<'topology> {
     ///
     /// # Errors
     ///
+    /// - [`FeatureDisabledAtBuild`] if no initiator was found and this
+    ///   attribute is gathered from the operating system, but the topology was
+    ///   built with `BuildFlags::IGNORE_MEMORY_ATTRIBUTES`
     /// - [`NoInitiator`] if this attribute does not relate to a specific initiator
     /// - [`UnknownAttribute`] if no such attribute exists.
     ///
+    /// [`FeatureDisabledAtBuild`]: MemoryAttributeQueryError::FeatureDisabledAtBuild
     /// [`NoInitiator`]: MemoryAttributeQueryError::NoInitiator
     /// [`UnknownAttribute`]: MemoryAttributeQueryError::UnknownAttribute
     #[doc(alias = "hwloc_memattr_get_best_initiator")]
@@ -691,8 +802,12 @@ impl<'topology> MemoryAttribute<'topology> {
     /// # Errors
     ///
     /// - [`BadInitiator`] if the `initiator` parameter was not set correctly
+    /// - [`FeatureDisabledAtBuild`] if no target was found and this attribute
+    ///   is gathered from the operating system, but the topology was built
+    ///   with `BuildFlags::IGNORE_MEMORY_ATTRIBUTES`
     ///
     /// [`BadInitiator`]: MemoryAttributeQueryError::BadInitiator
+    /// [`FeatureDisabledAtBuild`]: MemoryAttributeQueryError::FeatureDisabledAtBuild
     #[doc(alias = "hwloc_memattr_get_targets")]
     pub fn targets(
         &self,
@@ -729,8 +844,12 @@ impl<'topology> MemoryAttribute<'topology> {
     ///
     /// # Errors
     ///
+    /// - [`FeatureDisabledAtBuild`] if no initiator was found and this
+    ///   attribute is gathered from the operating system, but the topology was
+    ///   built with `BuildFlags::IGNORE_MEMORY_ATTRIBUTES`
     /// - [`NoInitiator`] if this attribute does not relate to a specific initiator
     ///
+    /// [`FeatureDisabledAtBuild`]: MemoryAttributeQueryError::FeatureDisabledAtBuild
     /// [`NoInitiator`]: MemoryAttributeQueryError::NoInitiator
     #[doc(alias = "hwloc_memattr_get_initiators")]
     pub fn initiators(
@@ -808,6 +927,9 @@ impl<'topology> MemoryAttribute<'topology> {
         call_ffi(&mut nr, endpoints.as_mut_ptr(), values.as_mut_ptr())
             .map_err(HybridError::Hwloc)?;
         assert_eq!(old_nr, nr, "Inconsistent node count from hwloc");
+        if endpoints.is_empty() {
+            self.check_disabled_at_build()?;
+        }
         Ok((endpoints, values))
     }
 
@@ -825,7 +947,10 @@ impl<'topology> MemoryAttribute<'topology> {
             Err(RawHwlocError {
                 api: _,
                 errno: Some(Errno(ENOENT)),
-            }) => Ok(None),
+            }) => {
+                self.check_disabled_at_build()?;
+                Ok(None)
+            }
             // All cases other than "no such attribute" should be handled by the caller
             Err(RawHwlocError {
                 api: _,
@@ -861,6 +986,22 @@ impl<'topology> MemoryAttribute<'topology> {
             .expect("Failed to decode location from hwloc")
     }
 
+    /// Check if the absence of values for this attribute may be explained by
+    /// memory attributes being disabled at build time
+    ///
+    /// Only the attributes that hwloc gathers from the operating system are
+    /// affected. Capacity and locality are computed by hwloc itself.
+    fn check_disabled_at_build(&self) -> Result<(), MemoryAttributeQueryError> {
+        #[cfg(feature = "hwloc-2_8_0")]
+        if self.id.static_flags().is_some()
+            && ![MemoryAttributeID::CAPACITY, MemoryAttributeID::LOCALITY].contains(&self.id)
+        {
+            self.topology
+                .ensure_features_enabled(BuildFlags::IGNORE_MEMORY_ATTRIBUTES)?;
+        }
+        Ok(())
+    }
+
     /// Check the initiator argument to some query
     ///
     /// If `is_optional` is true, it is okay not to provide an initiator even
@@ -907,6 +1048,11 @@ pub enum MemoryAttributeQueryError {
     /// have initiators (like NUMA node capacity).
     #[error("requested a memory attribute's initiator, but it has none")]
     NoInitiator,
+
+    /// No value was found, and memory attributes from the operating system
+    /// were disabled at build time
+    #[error(transparent)]
+    FeatureDisabledAtBuild(#[from] FeatureDisabledAtBuild),
 }
 
 /// Where to measure attributes from
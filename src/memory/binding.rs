@@ -13,14 +13,14 @@ use crate::{cpu::cpusets::CpuSet, topology::support::MemoryBindingSupport};
 use bitflags::bitflags;
 use derive_more::Display;
 use errno::{errno, Errno};
-use libc::{ENOMEM, ENOSYS, EXDEV};
+use libc::{EINTR, ENOMEM, ENOSYS, EXDEV};
 use num_enum::{IntoPrimitive, TryFromPrimitive, TryFromPrimitiveError};
 use std::{
     borrow::{Borrow, BorrowMut},
     ffi::{c_int, c_void},
     fmt::{self, Debug, Display},
     mem::MaybeUninit,
-    ops::{Deref, DerefMut},
+    ops::{Deref, DerefMut, Range},
     ptr::{self, NonNull},
 };
 use thiserror::Error;
@@ -72,7 +72,7 @@ use thiserror::Error;
 /// [`STRICT`]: MemoryBindingFlags::STRICT
 /// [`THREAD`]: MemoryBindingFlags::THREAD
 //
-// Upstream docs: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__membinding.html
+// Upstream docs: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__membinding.html
 impl Topology {
     /// Allocate some memory
     ///
@@ -192,6 +192,27 @@ impl Topology {
     /// [`PROCESS`]: MemoryBindingFlags::PROCESS
     /// [`THREAD`]: MemoryBindingFlags::THREAD
     /// [`Unsupported`]: MemoryBindingError::Unsupported
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::memory::binding::{MemoryBindingFlags, MemoryBindingPolicy};
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// // Allocate a 1 MiB arena, interleaved across all NUMA nodes
+    /// match topology.binding_allocate_memory(
+    ///     1024 * 1024,
+    ///     &*topology.nodeset(),
+    ///     MemoryBindingPolicy::Interleave,
+    ///     MemoryBindingFlags::ASSUME_SINGLE_THREAD,
+    /// ) {
+    ///     Ok(mut bytes) => {
+    ///         let arena: &mut [u8] = bytes.initialize(0);
+    ///         assert!(arena.iter().all(|&b| b == 0));
+    ///     }
+    ///     Err(e) => println!("Memory binding is not available here: {e}"),
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
     #[doc(alias = "hwloc_alloc_membind_policy")]
     pub fn binding_allocate_memory<Set: SpecializedBitmap>(
         &self,
@@ -213,12 +234,15 @@ impl Topology {
 
         // Depending on policy, we may or may not need to touch the memory to
         // enforce the binding
-        match policy {
-            MemoryBindingPolicy::FirstTouch | MemoryBindingPolicy::NextTouch => {}
-            MemoryBindingPolicy::Bind | MemoryBindingPolicy::Interleave => {
-                for b in &mut bytes[..] {
-                    *b = MaybeUninit::new(0);
-                }
+        let must_touch = match policy {
+            MemoryBindingPolicy::FirstTouch | MemoryBindingPolicy::NextTouch => false,
+            MemoryBindingPolicy::Bind | MemoryBindingPolicy::Interleave => true,
+            #[cfg(feature = "hwloc-2_11_0")]
+            MemoryBindingPolicy::WeightedInterleave => true,
+        };
+        if must_touch {
+            for b in &mut bytes[..] {
+                *b = MaybeUninit::new(0);
             }
         }
         Ok(bytes)
@@ -743,6 +767,121 @@ impl Topology {
         .map(|(set, _policy)| set)
     }
 
+    /// Migrate the memory pages of an existing memory area to the NUMA nodes
+    /// of `set`
+    ///
+    /// This is [`Topology::bind_memory_area()`] with the
+    /// [`MIGRATE`](MemoryBindingFlags::MIGRATE) flag, for raw memory areas
+    /// that cannot be expressed as a Rust reference, like memory that was
+    /// allocated by a foreign library. Prefer
+    /// [`migrate_slice()`](Topology::migrate_slice()) when possible.
+    ///
+    /// Requires [`MemoryBindingSupport::set_area()`] and
+    /// [`MemoryBindingSupport::migrate()`]. Use
+    /// [`Topology::area_memory_location()`] to check where the pages reside
+    /// after migration.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Errors
+    ///
+    /// - [`Unsupported`] if the system cannot migrate the specified memory
+    ///   area with the requested policy
+    /// - [`BadSet`] if the system can't bind memory to that node set
+    /// - [`BadTarget`] if `area` is empty
+    ///
+    /// # Safety
+    ///
+    /// `area` must be a range of memory that is allocated by the current
+    /// process, and that is not being concurrently accessed in ways that rely
+    /// on its physical location.
+    ///
+    /// [`BadSet`]: MemoryBindingError::BadSet
+    /// [`BadTarget`]: MemoryBindingError::BadTarget
+    /// [`Unsupported`]: MemoryBindingError::Unsupported
+    #[doc(alias = "hwloc_set_area_membind")]
+    pub unsafe fn migrate_area(
+        &self,
+        area: Range<*const u8>,
+        set: &NodeSet,
+        policy: MemoryBindingPolicy,
+    ) -> Result<(), MemoryBindingError<NodeSet>> {
+        let area_size = (area.end as usize).saturating_sub(area.start as usize);
+        if area_size == 0 {
+            return Err(MemoryBindingError::BadTarget);
+        }
+        self.bind_memory_impl(
+            "hwloc_set_area_membind",
+            set,
+            policy,
+            MemoryBindingFlags::MIGRATE,
+            MemoryBoundObject::Area,
+            |topology, set, policy, flags| unsafe {
+                ffi::hwloc_set_area_membind(
+                    topology,
+                    area.start.cast::<c_void>(),
+                    area_size,
+                    set,
+                    policy,
+                    flags,
+                )
+            },
+        )
+    }
+
+    /// Migrate the memory pages of `slice` to the NUMA nodes of `set`
+    ///
+    /// This is [`Topology::bind_memory_area()`] with the
+    /// [`MIGRATE`](MemoryBindingFlags::MIGRATE) flag. It is typically used to
+    /// move long-lived data next to the CPUs that will be using it from now
+    /// on, e.g. after repartitioning work across NUMA nodes.
+    ///
+    /// Memory is migrated by whole pages, so data that shares a page with the
+    /// beginning or end of `slice` may be migrated too.
+    ///
+    /// Requires [`MemoryBindingSupport::set_area()`] and
+    /// [`MemoryBindingSupport::migrate()`].
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Topology::migrate_area()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::memory::{
+    /// #     binding::{MemoryBindingFlags, MemoryBindingPolicy},
+    /// #     nodesets::NodeSet,
+    /// # };
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// let mut cache = vec![0u8; 1 << 20];
+    /// let last_node = NodeSet::from(topology.nodeset().last_set().unwrap());
+    /// match topology.migrate_slice(&mut cache[..], &last_node, MemoryBindingPolicy::Bind) {
+    ///     Ok(()) => {
+    ///         let location = topology.area_memory_location::<[u8], NodeSet>(
+    ///             &cache[..],
+    ///             MemoryBindingFlags::empty(),
+    ///         );
+    ///         println!("Cache now resides on NUMA nodes {location:?}");
+    ///     }
+    ///     Err(e) => eprintln!("Failed to migrate cache: {e}"),
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn migrate_slice<T>(
+        &self,
+        slice: &mut [T],
+        set: &NodeSet,
+        policy: MemoryBindingPolicy,
+    ) -> Result<(), MemoryBindingError<NodeSet>> {
+        let area = slice.as_ptr_range();
+        // SAFETY: The slice is owned by the current process and, since it is
+        //         mutably borrowed, cannot be accessed during migration
+        unsafe { self.migrate_area(area.start.cast()..area.end.cast(), set, policy) }
+    }
+
     /// Adjust binding flags for a certain kind of Set
     fn adjust_flags_for<Set: SpecializedBitmap>(flags: &mut MemoryBindingFlags) {
         match Set::BITMAP_KIND {
@@ -759,7 +898,7 @@ impl Topology {
         policy: MemoryBindingPolicy,
         mut flags: MemoryBindingFlags,
         target: MemoryBoundObject,
-        set_membind_like: impl FnOnce(
+        mut set_membind_like: impl FnMut(
             *const RawTopology,
             *const RawBitmap,
             RawMemoryBindingPolicy,
@@ -771,14 +910,31 @@ impl Topology {
         if !flags.is_valid(target, operation) {
             return Err(MemoryBindingError::BadFlags(flags.into()));
         }
-        memory::binding::call_hwloc_int(api, target, operation, Some(set), || {
+        log_event!(
+            Trace,
+            "Binding {target} to {} with policy {policy:?} and flags {flags:?} via {api}",
+            set.as_ref()
+        );
+        let result = memory::binding::call_hwloc_int(api, target, operation, Some(set), || {
             set_membind_like(
                 self.as_ptr(),
                 set.as_ref().as_ptr(),
                 policy.into(),
                 flags.bits(),
             )
-        })
+        });
+        let set = set.as_ref();
+        match &result {
+            Ok(()) => log_event!(
+                Debug,
+                "Bound {target} to {set} with policy {policy:?} and flags {flags:?} via {api}"
+            ),
+            Err(e) => log_event!(
+                Warn,
+                "Failed to bind {target} to {set} with policy {policy:?} and flags {flags:?} via {api}: {e}"
+            ),
+        }
+        result
     }
 
     /// Call an hwloc memory binding function to unbind some memory
@@ -787,7 +943,7 @@ impl Topology {
         api: &'static str,
         flags: MemoryBindingFlags,
         target: MemoryBoundObject,
-        set_membind_like: impl FnOnce(
+        mut set_membind_like: impl FnMut(
             *const RawTopology,
             *const RawBitmap,
             RawMemoryBindingPolicy,
@@ -798,9 +954,18 @@ impl Topology {
         if !flags.is_valid(target, operation) {
             return Err(MemoryBindingError::BadFlags(flags.into()));
         }
-        memory::binding::call_hwloc_int(api, target, operation, None, || {
+        log_event!(Trace, "Unbinding {target} with flags {flags:?} via {api}");
+        let result = memory::binding::call_hwloc_int(api, target, operation, None, || {
             set_membind_like(self.as_ptr(), ptr::null(), 0, flags.bits())
-        })
+        });
+        match &result {
+            Ok(()) => log_event!(Debug, "Unbound {target} with flags {flags:?} via {api}"),
+            Err(e) => log_event!(
+                Warn,
+                "Failed to unbind {target} with flags {flags:?} via {api}: {e}"
+            ),
+        }
+        result
     }
 
     /// Call an hwloc memory binding query function
@@ -810,7 +975,7 @@ impl Topology {
         mut flags: MemoryBindingFlags,
         target: MemoryBoundObject,
         operation: MemoryBindingOperation,
-        get_membind_like: impl FnOnce(
+        mut get_membind_like: impl FnMut(
             *const RawTopology,
             *mut RawBitmap,
             *mut RawMemoryBindingPolicy,
@@ -893,10 +1058,22 @@ bitflags! {
 
         /// Migrate existing allocated memory
         ///
+        /// Without this flag, changing the memory binding of a process, thread
+        /// or memory area only affects where pages that have not been touched
+        /// yet will be allocated, and memory that is already in use stays
+        /// where it is. With this flag, all pages that are already in use are
+        /// moved to the new NUMA nodes before the binding function returns,
+        /// which can take a long time if a lot of memory is involved.
+        ///
         /// If the memory cannot be migrated and the `STRICT` flag is set, an
         /// error will be returned.
         ///
-        /// This flag is only meaningful on operations that bind memory.
+        /// This flag is only meaningful on operations that bind memory, and
+        /// will be rejected with a [`BadFlags`] error by other operations. If
+        /// you want to decide whether to migrate at runtime, you can use
+        /// `flags.set(MemoryBindingFlags::MIGRATE, also_migrate_memory)`.
+        ///
+        /// [`BadFlags`]: MemoryBindingError::BadFlags
         ///
         /// Requires [`MemoryBindingSupport::migrate()`].
         #[doc(alias = "HWLOC_MEMBIND_MIGRATE")]
@@ -1069,6 +1246,21 @@ pub enum MemoryBindingPolicy {
     /// Requires [`MemoryBindingSupport::next_touch()`].
     #[doc(alias = "HWLOC_MEMBIND_NEXTTOUCH")]
     NextTouch = 4,
+
+    /// Allocate memory on the given nodes in an interleaved weighted manner
+    ///
+    /// The precise layout of the memory across multiple NUMA nodes is OS/system
+    /// specific. Weights are usually configured by the operating system or the
+    /// administrator, e.g. according to the bandwidth of each NUMA node.
+    ///
+    /// Weighted interleaving can be useful when the target NUMA nodes have
+    /// heterogeneous performance, e.g. local DRAM combined with CXL memory,
+    /// since plain interleaving would then be bottlenecked by the slowest node.
+    ///
+    /// Requires [`MemoryBindingSupport::weighted_interleave()`].
+    #[cfg(feature = "hwloc-2_11_0")]
+    #[doc(alias = "HWLOC_MEMBIND_WEIGHTED_INTERLEAVE")]
+    WeightedInterleave = 5,
 }
 
 /// Errors that can occur when binding memory to NUMA nodes, querying bindings,
@@ -1133,6 +1325,15 @@ pub enum MemoryBindingError<Set: SpecializedBitmap> {
     #[error("binding varies from one thread of the process to another")]
     #[doc(alias = "HWLOC_MEMBIND_MIXED")]
     MixedResults,
+
+    /// The call kept being interrupted by signals
+    ///
+    /// Interrupted calls are transparently retried, up to the limit set by
+    /// [`errors::set_interrupted_call_retries()`]. This error is reported
+    /// once that limit is reached, and it is up to the caller to decide
+    /// whether retrying later makes sense.
+    #[error("memory binding call was interrupted by signals too many times")]
+    Interrupted,
 }
 //
 impl<Set: SpecializedBitmap> From<MemoryBindingFlags> for MemoryBindingError<Set> {
@@ -1146,14 +1347,19 @@ impl<Set: SpecializedBitmap> From<MemoryBindingFlags> for MemoryBindingError<Set
 ///
 /// Validating flags is left up to the caller, to avoid allocating result
 /// objects when it can be proved upfront that the request is invalid.
+///
+/// Calls that are interrupted by a signal are transparently retried, up to the
+/// limit set by [`errors::set_interrupted_call_retries()`], after which
+/// [`MemoryBindingError::Interrupted`] is reported.
 pub(crate) fn call_hwloc_int<Set: SpecializedBitmap>(
     api: &'static str,
     object: MemoryBoundObject,
     operation: MemoryBindingOperation,
     set: Option<&Set>,
-    ffi: impl FnOnce() -> c_int,
+    mut ffi: impl FnMut() -> c_int,
 ) -> Result<(), MemoryBindingError<Set>> {
-    match errors::call_hwloc_int_normal(api, ffi) {
+    let max_retries = Some(errors::interrupted_call_retries());
+    match errors::retry_on_eintr(max_retries, || errors::call_hwloc_int_normal(api, &mut ffi)) {
         Ok(_) => Ok(()),
         Err(RawHwlocError { errno, .. }) => Err(decode_errno(
             object,
@@ -1173,20 +1379,25 @@ pub type MemoryAllocationError<Set> = MemoryBindingError<Set>;
 ///
 /// Validating flags is left up to the caller, to avoid allocating result
 /// objects when it can be proved upfront that the request is invalid.
+///
+/// Interrupted calls are retried like in [`call_hwloc_int()`].
 pub(crate) fn call_hwloc_allocate<Set: SpecializedBitmap>(
     api: &'static str,
     set: Option<&Set>,
-    ffi: impl FnOnce() -> *mut c_void,
+    mut ffi: impl FnMut() -> *mut c_void,
 ) -> Result<NonNull<c_void>, MemoryAllocationError<Set>> {
-    errors::call_hwloc_ptr_mut(api, ffi).map_err(|raw_err| {
-        decode_errno(
-            MemoryBoundObject::Area,
-            MemoryBindingOperation::Allocate,
-            set,
-            raw_err.errno.expect("Unexpected hwloc error without errno"),
-        )
-        .expect("Unexpected errno value")
-    })
+    let max_retries = Some(errors::interrupted_call_retries());
+    errors::retry_on_eintr(max_retries, || errors::call_hwloc_ptr_mut(api, &mut ffi)).map_err(
+        |raw_err| {
+            decode_errno(
+                MemoryBoundObject::Area,
+                MemoryBindingOperation::Allocate,
+                set,
+                raw_err.errno.expect("Unexpected hwloc error without errno"),
+            )
+            .expect("Unexpected errno value")
+        },
+    )
 }
 
 /// Translating hwloc errno into high-level errors
@@ -1214,6 +1425,7 @@ fn decode_errno<Set: SpecializedBitmap>(
             }
         },
         ENOMEM => Some(MemoryBindingError::AllocationFailed),
+        EINTR => Some(MemoryBindingError::Interrupted),
         _ => None,
     }
 }
@@ -1222,6 +1434,9 @@ fn decode_errno<Set: SpecializedBitmap>(
 ///
 /// This behaves like a `Box<[MaybeUninit<u8>]>` and will similarly
 /// automatically liberate the allocated memory when it goes out of scope.
+///
+/// Use [`Bytes::initialize()`] to get a `&mut [u8]` view of the allocation.
+#[doc(alias = "BoundMemory")]
 pub struct Bytes<'topology> {
     /// Underlying hwloc topology
     topology: &'topology Topology,
@@ -1244,6 +1459,25 @@ impl<'topology> Bytes<'topology> {
             data: NonNull::new_unchecked(data),
         }
     }
+
+    /// Set every byte of the allocation to `value` and return the resulting
+    /// initialized bytes
+    ///
+    /// Beware that writing to memory counts as touching it, so if this memory
+    /// was bound with a [`FirstTouch`] or [`NextTouch`] policy, this will
+    /// migrate its pages to the NUMA node of the calling thread.
+    ///
+    /// [`FirstTouch`]: MemoryBindingPolicy::FirstTouch
+    /// [`NextTouch`]: MemoryBindingPolicy::NextTouch
+    pub fn initialize(&mut self, value: u8) -> &mut [u8] {
+        let bytes = self.as_mut();
+        for b in bytes.iter_mut() {
+            *b = MaybeUninit::new(value);
+        }
+        // SAFETY: All bytes were initialized above, and MaybeUninit<u8> has
+        //         the same layout as u8
+        unsafe { &mut *(bytes as *mut [MaybeUninit<u8>] as *mut [u8]) }
+    }
 }
 
 impl AsRef<[MaybeUninit<u8>]> for Bytes<'_> {
@@ -1304,3 +1538,75 @@ impl Drop for Bytes<'_> {
         );
     }
 }
+
+unsafe impl Send for Bytes<'_> {}
+unsafe impl Sync for Bytes<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrate_flag_is_only_valid_when_binding() {
+        let objects = [
+            MemoryBoundObject::Area,
+            MemoryBoundObject::Process,
+            MemoryBoundObject::ThisProgram,
+        ];
+        for target in objects {
+            let flags = if target == MemoryBoundObject::Area {
+                MemoryBindingFlags::MIGRATE
+            } else {
+                MemoryBindingFlags::PROCESS | MemoryBindingFlags::MIGRATE
+            };
+            assert!(flags.is_valid(target, MemoryBindingOperation::Bind));
+            for operation in [
+                MemoryBindingOperation::GetBinding,
+                MemoryBindingOperation::Unbind,
+                MemoryBindingOperation::Allocate,
+                MemoryBindingOperation::GetLastLocation,
+            ] {
+                assert!(!flags.is_valid(target, operation));
+            }
+        }
+    }
+
+    #[test]
+    fn binding_without_migrate_is_valid() {
+        let flags = MemoryBindingFlags::THREAD | MemoryBindingFlags::NO_CPU_BINDING;
+        assert!(!flags.contains(MemoryBindingFlags::MIGRATE));
+        assert!(flags.is_valid(MemoryBoundObject::ThisProgram, MemoryBindingOperation::Bind));
+    }
+
+    #[test]
+    fn interrupted_calls_should_give_up_after_retry_budget() {
+        let max_calls = errors::interrupted_call_retries() + 1;
+        let interrupted = || {
+            errno::set_errno(Errno(EINTR));
+            -1
+        };
+
+        let mut calls = 0;
+        let result = call_hwloc_int::<NodeSet>(
+            "hwloc_set_membind",
+            MemoryBoundObject::ThisProgram,
+            MemoryBindingOperation::Bind,
+            None,
+            || {
+                calls += 1;
+                interrupted()
+            },
+        );
+        assert_eq!(result, Err(MemoryBindingError::Interrupted));
+        assert_eq!(calls, max_calls);
+
+        let mut calls = 0;
+        let result = call_hwloc_allocate::<NodeSet>("hwloc_alloc_membind", None, || {
+            calls += 1;
+            interrupted();
+            ptr::null_mut()
+        });
+        assert_eq!(result, Err(MemoryBindingError::Interrupted));
+        assert_eq!(calls, max_calls);
+    }
+}
@@ -1,5 +1,7 @@
 //! Memory management
 
+#[cfg(feature = "allocator-api")]
+pub mod allocator;
 #[cfg(feature = "hwloc-2_3_0")]
 pub mod attributes;
 pub mod binding;
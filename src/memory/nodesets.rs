@@ -20,7 +20,23 @@ impl NodeSet {
     /// Hence the entire topology CPU set, that one can query via
     /// [`Topology::cpuset()`], would be converted by this function into the
     /// set of all nodes that have some local CPUs.
+    ///
+    /// The reverse conversion is performed by [`CpuSet::from_nodeset()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::{cpu::cpusets::CpuSet, memory::nodesets::NodeSet};
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// // NUMA nodes that are local to the first PU
+    /// let first_pu = topology.cpuset().first_set().unwrap();
+    /// let nodeset = NodeSet::from_cpuset(&topology, &CpuSet::from(first_pu));
+    /// assert!(!nodeset.is_empty());
+    /// assert!(topology.nodeset().includes(&nodeset));
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
     #[doc(alias = "hwloc_cpuset_to_nodeset")]
+    #[doc(alias = "convert_cpuset_to_nodeset")]
     pub fn from_cpuset(topology: &Topology, cpuset: &CpuSet) -> NodeSet {
         let mut nodeset = NodeSet::new();
         for obj in topology.objects_covering_cpuset_at_depth(cpuset, Depth::NUMANode) {
@@ -0,0 +1,96 @@
+//! NVML-specific helpers
+
+use crate::{
+    cpu::cpusets::CpuSet,
+    objects::{types::OSDeviceType, TopologyObject},
+    topology::Topology,
+};
+
+/// # NVML-specific helpers
+///
+/// These functions locate NVIDIA GPUs in the topology by NVML device index or
+/// UUID, so that applications can tell which CPU cores are close to them.
+///
+/// Unlike their hwloc counterparts, they do not link to the NVML library.
+/// Devices are instead identified by the `nvmlN` OS devices that hwloc's NVML
+/// component adds to topologies built with I/O objects (see
+/// [`TopologyBuilder::with_io_type_filter()`]), so they return `None` if hwloc
+/// was built without NVML support.
+///
+/// [`TopologyBuilder::with_io_type_filter()`]: crate::topology::builder::TopologyBuilder::with_io_type_filter()
+//
+// Upstream docs: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__nvml.html
+impl Topology {
+    /// Get the hwloc OS device object corresponding to the NVML device with
+    /// index `device`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// if let Some(gpu) = topology.nvml_device_os_device(0) {
+    ///     println!("NVML device 0 is {gpu}");
+    /// }
+    /// ```
+    #[doc(alias = "hwloc_nvml_get_device_osdev_by_index")]
+    pub fn nvml_device_os_device(&self, device: usize) -> Option<&TopologyObject> {
+        self.os_device_with_name(OSDeviceType::GPU, &format!("nvml{device}"))
+    }
+
+    /// Get the hwloc OS device object corresponding to the NVML device with
+    /// UUID `uuid`
+    ///
+    /// The UUID may be given with or without the `GPU-` prefix that NVML and
+    /// `nvidia-smi -L` report. It is compared case-insensitively.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// let uuid = "GPU-d3bb4f2e-5d4c-a1f0-1f1a-0123456789ab";
+    /// if let Some(gpu) = topology.nvml_device_os_device_by_uuid(uuid) {
+    ///     let locality = gpu.non_io_ancestor();
+    ///     println!("GPU {uuid} is close to PUs {:?}", locality.cpuset());
+    /// }
+    /// ```
+    #[doc(alias = "hwloc_nvml_get_device_osdev")]
+    pub fn nvml_device_os_device_by_uuid(&self, uuid: &str) -> Option<&TopologyObject> {
+        let uuid = strip_uuid_prefix(uuid);
+        self.os_devices_with_type(OSDeviceType::GPU).find(|osdev| {
+            osdev
+                .info("NVIDIAUUID")
+                .and_then(|osdev_uuid| osdev_uuid.to_str().ok())
+                .map_or(false, |osdev_uuid| {
+                    strip_uuid_prefix(osdev_uuid).eq_ignore_ascii_case(uuid)
+                })
+        })
+    }
+
+    /// Get the CPU set of processors that are physically close to the NVML
+    /// device with index `device`
+    ///
+    /// This is the cpuset of the [`TopologyObject::non_io_ancestor()`] of the
+    /// OS device returned by [`nvml_device_os_device()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::cpu::binding::CpuBindingFlags;
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// if let Some(cpuset) = topology.nvml_device_cpuset(0) {
+    ///     topology.bind_cpu(&cpuset, CpuBindingFlags::THREAD)?;
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    ///
+    /// [`nvml_device_os_device()`]: Topology::nvml_device_os_device()
+    #[doc(alias = "hwloc_nvml_get_device_cpuset")]
+    pub fn nvml_device_cpuset(&self, device: usize) -> Option<CpuSet> {
+        self.nvml_device_os_device(device)?.non_io_ancestor_cpuset()
+    }
+}
+
+/// Remove the optional "GPU-" prefix from an NVIDIA UUID
+fn strip_uuid_prefix(uuid: &str) -> &str {
+    uuid.strip_prefix("GPU-").unwrap_or(uuid)
+}
@@ -1,7 +1,7 @@
 //! Object attributes
 
-// - Main docs: https://hwloc.readthedocs.io/en/v2.9/unionhwloc__obj__attr__u.html
-// - Union semantics: https://hwloc.readthedocs.io/en/v2.9/attributes.html#attributes_normal
+// - Main docs: https://hwloc.readthedocs.io/en/v2.11/unionhwloc__obj__attr__u.html
+// - Union semantics: https://hwloc.readthedocs.io/en/v2.11/attributes.html#attributes_normal
 
 #[cfg(doc)]
 use crate::topology::support::DiscoverySupport;
@@ -250,7 +250,7 @@ impl CacheAttributes {
     #[doc(alias = "hwloc_cache_attr_s::type")]
     #[doc(alias = "hwloc_obj_attr_u::hwloc_cache_attr_s::type")]
     pub fn cache_type(&self) -> CacheType {
-        self.ty.try_into().expect("Got unexpected cache type")
+        self.ty.into()
     }
 }
 
@@ -294,8 +294,15 @@ impl GroupAttributes {
     }
 
     /// Internally-used kind of group
-    #[allow(unused)]
-    pub(crate) fn kind(&self) -> usize {
+    ///
+    /// hwloc uses this to decide which group should be kept when several of
+    /// them have the same locality, generally preferring lower kinds. Groups
+    /// that were inserted with `GroupMerge::Always` have the highest possible
+    /// kind, and are thus discarded in favor of any existing group with the
+    /// same locality.
+    #[doc(alias = "hwloc_group_attr_s::kind")]
+    #[doc(alias = "hwloc_obj_attr_u::hwloc_group_attr_s::kind")]
+    pub fn kind(&self) -> usize {
         ffi::expect_usize(self.kind)
     }
 
@@ -308,16 +315,19 @@ impl GroupAttributes {
 
     /// Internally-used subkind to distinguish different levels of groups with
     /// the same kind
-    #[allow(unused)]
     #[doc(alias = "hwloc_group_attr_s::subkind")]
     #[doc(alias = "hwloc_obj_attr_u::hwloc_group_attr_s::subkind")]
-    pub(crate) fn subkind(&self) -> usize {
+    pub fn subkind(&self) -> usize {
         ffi::expect_usize(self.subkind)
     }
 
     /// Flag preventing groups from being automatically merged with identical
     /// parent or children
+    ///
+    /// This is set on groups that were inserted with `GroupMerge::Never`.
     #[cfg(feature = "hwloc-2_0_4")]
+    #[doc(alias = "hwloc_group_attr_s::dont_merge")]
+    #[doc(alias = "hwloc_obj_attr_u::hwloc_group_attr_s::dont_merge")]
     pub fn merging_prevented(&self) -> bool {
         assert!(
             self.dont_merge == 0 || self.dont_merge == 1,
@@ -458,9 +468,7 @@ impl BridgeAttributes {
     #[doc(alias = "hwloc_bridge_attr_s::upstream_type")]
     #[doc(alias = "hwloc_obj_attr_u::hwloc_bridge_attr_s::upstream_type")]
     pub fn upstream_type(&self) -> BridgeType {
-        self.upstream_type
-            .try_into()
-            .expect("Got unexpected upstream type")
+        self.upstream_type.into()
     }
 
     /// Upstream attributes
@@ -474,9 +482,7 @@ impl BridgeAttributes {
     #[doc(alias = "hwloc_bridge_attr_s::downstream_type")]
     #[doc(alias = "hwloc_obj_attr_u::hwloc_bridge_attr_s::downstream_type")]
     pub fn downstream_type(&self) -> BridgeType {
-        self.downstream_type
-            .try_into()
-            .expect("Got unexpected downstream type")
+        self.downstream_type.into()
     }
 
     /// Downstream attributes
@@ -539,14 +545,23 @@ impl<'attr> UpstreamAttributes<'attr> {
         unsafe {
             match ty {
                 BridgeType::PCI => Some(Self::PCI(&attr.pci)),
-                BridgeType::Host => None,
+                BridgeType::Host | BridgeType::Unknown(_) => None,
             }
         }
     }
 }
 
 /// Downstream PCI device attributes
+///
+/// The PCI buses behind a bridge are numbered contiguously, from
+/// [`secondary_bus()`] to [`subordinate_bus()`] inclusive, within a
+/// single PCI [`domain()`].
+///
+/// [`domain()`]: Self::domain()
+/// [`secondary_bus()`]: Self::secondary_bus()
+/// [`subordinate_bus()`]: Self::subordinate_bus()
 #[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq)]
+#[doc(alias = "hwloc_bridge_attr_s::hwloc_bridge_downstream_pci_attr_s")]
 #[repr(C)]
 pub struct DownstreamPCIAttributes {
     domain: PCIDomain,
@@ -555,17 +570,28 @@ pub struct DownstreamPCIAttributes {
 }
 //
 impl DownstreamPCIAttributes {
+    /// PCI domain of the downstream buses
+    #[doc(alias = "hwloc_bridge_attr_s::hwloc_bridge_downstream_pci_attr_s::domain")]
     pub fn domain(&self) -> PCIDomain {
         self.domain
     }
 
+    /// First PCI bus number behind the bridge
+    #[doc(alias = "hwloc_bridge_attr_s::hwloc_bridge_downstream_pci_attr_s::secondary_bus")]
     pub fn secondary_bus(&self) -> u8 {
         self.secondary_bus
     }
 
+    /// Last PCI bus number behind the bridge
+    #[doc(alias = "hwloc_bridge_attr_s::hwloc_bridge_downstream_pci_attr_s::subordinate_bus")]
     pub fn subordinate_bus(&self) -> u8 {
         self.subordinate_bus
     }
+
+    /// Truth that the specified PCI bus is behind the bridge
+    pub fn covers_bus(&self, domain: PCIDomain, bus_id: u8) -> bool {
+        self.domain == domain && (self.secondary_bus..=self.subordinate_bus).contains(&bus_id)
+    }
 }
 
 /// hwloc FFI for hwloc_bridge_attr_s::downstream
@@ -593,6 +619,7 @@ impl<'attr> DownstreamAttributes<'attr> {
             match ty {
                 BridgeType::PCI => Some(Self::PCI(&attr.pci)),
                 BridgeType::Host => unreachable!("Host bridge type should not appear downstream"),
+                BridgeType::Unknown(_) => None,
             }
         }
     }
@@ -614,6 +641,6 @@ impl OSDeviceAttributes {
     #[doc(alias = "hwloc_osdev_attr_s::type")]
     #[doc(alias = "hwloc_obj_attr_u::hwloc_osdev_attr_s::type")]
     pub fn device_type(&self) -> OSDeviceType {
-        self.ty.try_into().expect("Got unexpected OS device type")
+        self.ty.into()
     }
 }
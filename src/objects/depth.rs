@@ -1,11 +1,12 @@
 //! Object depth
 
-// Main docs: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__levels.html
+// Main docs: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__levels.html
 
 use crate::ffi;
 #[cfg(doc)]
 use crate::objects::types::ObjectType;
 use std::{
+    cmp::Ordering,
     ffi::{c_int, c_uint},
     fmt,
 };
@@ -51,6 +52,86 @@ pub enum Depth {
 }
 
 impl Depth {
+    /// Raw hwloc depth value meaning that no object of the requested type
+    /// exists in the topology, see [`DepthError::None`]
+    #[doc(alias = "HWLOC_TYPE_DEPTH_UNKNOWN")]
+    pub const RAW_UNKNOWN: c_int = -1;
+
+    /// Raw hwloc depth value meaning that objects of the requested type exist
+    /// at different depths in the topology, see [`DepthError::Multiple`]
+    #[doc(alias = "HWLOC_TYPE_DEPTH_MULTIPLE")]
+    pub const RAW_MULTIPLE: c_int = -2;
+
+    /// Raw hwloc value of [`Depth::NUMANode`]
+    #[doc(alias = "HWLOC_TYPE_DEPTH_NUMANODE")]
+    pub const RAW_NUMANODE: c_int = -3;
+
+    /// Raw hwloc value of [`Depth::Bridge`]
+    #[doc(alias = "HWLOC_TYPE_DEPTH_BRIDGE")]
+    pub const RAW_BRIDGE: c_int = -4;
+
+    /// Raw hwloc value of [`Depth::PCIDevice`]
+    #[doc(alias = "HWLOC_TYPE_DEPTH_PCI_DEVICE")]
+    pub const RAW_PCI_DEVICE: c_int = -5;
+
+    /// Raw hwloc value of [`Depth::OSDevice`]
+    #[doc(alias = "HWLOC_TYPE_DEPTH_OS_DEVICE")]
+    pub const RAW_OS_DEVICE: c_int = -6;
+
+    /// Raw hwloc value of [`Depth::Misc`]
+    #[doc(alias = "HWLOC_TYPE_DEPTH_MISC")]
+    pub const RAW_MISC: c_int = -7;
+
+    /// Raw hwloc value of [`Depth::MemCache`]
+    #[cfg(feature = "hwloc-2_1_0")]
+    #[doc(alias = "HWLOC_TYPE_DEPTH_MEMCACHE")]
+    pub const RAW_MEMCACHE: c_int = -8;
+
+    /// Translate a depth from the `int` representation used by hwloc's C API
+    ///
+    /// This is useful when receiving depths from C code that follows hwloc
+    /// conventions, such as custom hwloc plugins.
+    ///
+    /// # Errors
+    ///
+    /// - [`DepthError::None`] for [`RAW_UNKNOWN`](Self::RAW_UNKNOWN)
+    /// - [`DepthError::Multiple`] for [`RAW_MULTIPLE`](Self::RAW_MULTIPLE)
+    /// - [`DepthError::Unknown`] for other negative values that do not match
+    ///   a known virtual depth
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::objects::depth::{Depth, DepthError};
+    /// assert_eq!(Depth::from_raw(2), Ok(Depth::Normal(2)));
+    /// assert_eq!(Depth::from_raw(Depth::RAW_NUMANODE), Ok(Depth::NUMANode));
+    /// assert_eq!(Depth::from_raw(Depth::RAW_MULTIPLE), Err(DepthError::Multiple));
+    /// ```
+    pub fn from_raw(raw: c_int) -> DepthResult {
+        Self::try_from(raw)
+    }
+
+    /// Translate this depth into the `int` representation used by hwloc's C
+    /// API
+    ///
+    /// This is useful when passing depths to C code that follows hwloc
+    /// conventions, such as custom hwloc plugins.
+    ///
+    /// # Panics
+    ///
+    /// If this is a [`Depth::Normal`] depth that does not fit in a C `int`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::objects::depth::Depth;
+    /// assert_eq!(Depth::Normal(2).to_raw(), 2);
+    /// assert_eq!(Depth::NUMANode.to_raw(), Depth::RAW_NUMANODE);
+    /// ```
+    pub fn to_raw(self) -> c_int {
+        RawDepth::from(self)
+    }
+
     /// Assert that this should be a normal object depth
     pub fn assume_normal(self) -> usize {
         usize::try_from(self).expect("Not a normal object depth")
@@ -77,6 +158,30 @@ impl fmt::Display for Depth {
     }
 }
 
+/// Depths are ordered from the root of the topology tree to its leaves
+///
+/// Normal depths are ordered by their numerical value, which is the distance
+/// from the root [`Machine`](ObjectType::Machine) object. Virtual depths
+/// designate special children lists that live outside of the main tree, so
+/// they are only comparable with themselves.
+///
+/// ```
+/// # use hwlocality::objects::depth::Depth;
+/// assert!(Depth::Normal(0) < Depth::Normal(1));
+/// assert_eq!(Depth::NUMANode.partial_cmp(&Depth::NUMANode), Some(std::cmp::Ordering::Equal));
+/// assert_eq!(Depth::NUMANode.partial_cmp(&Depth::Normal(0)), None);
+/// assert_eq!(Depth::NUMANode.partial_cmp(&Depth::Misc), None);
+/// ```
+impl PartialOrd for Depth {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Self::Normal(d1), Self::Normal(d2)) => Some(d1.cmp(d2)),
+            (v1, v2) if v1 == v2 => Some(Ordering::Equal),
+            _ => None,
+        }
+    }
+}
+
 impl From<usize> for Depth {
     fn from(value: usize) -> Self {
         Self::Normal(value)
@@ -104,15 +209,15 @@ impl TryFrom<RawDepth> for Depth {
                 let d = c_uint::try_from(d).expect("int >= 0 -> uint can't fail");
                 Ok(Self::Normal(ffi::expect_usize(d)))
             }
-            -1 => Err(DepthError::None),
-            -2 => Err(DepthError::Multiple),
-            -3 => Ok(Self::NUMANode),
-            -4 => Ok(Self::Bridge),
-            -5 => Ok(Self::PCIDevice),
-            -6 => Ok(Self::OSDevice),
-            -7 => Ok(Self::Misc),
+            Depth::RAW_UNKNOWN => Err(DepthError::None),
+            Depth::RAW_MULTIPLE => Err(DepthError::Multiple),
+            Depth::RAW_NUMANODE => Ok(Self::NUMANode),
+            Depth::RAW_BRIDGE => Ok(Self::Bridge),
+            Depth::RAW_PCI_DEVICE => Ok(Self::PCIDevice),
+            Depth::RAW_OS_DEVICE => Ok(Self::OSDevice),
+            Depth::RAW_MISC => Ok(Self::Misc),
             #[cfg(feature = "hwloc-2_1_0")]
-            -8 => Ok(Self::MemCache),
+            Depth::RAW_MEMCACHE => Ok(Self::MemCache),
             other => Err(DepthError::Unknown(other)),
         }
     }
@@ -122,13 +227,13 @@ impl From<Depth> for RawDepth {
     fn from(value: Depth) -> Self {
         match value {
             Depth::Normal(value) => value.try_into().expect("Depth is too high for hwloc"),
-            Depth::NUMANode => -3,
-            Depth::Bridge => -4,
-            Depth::PCIDevice => -5,
-            Depth::OSDevice => -6,
-            Depth::Misc => -7,
+            Depth::NUMANode => Depth::RAW_NUMANODE,
+            Depth::Bridge => Depth::RAW_BRIDGE,
+            Depth::PCIDevice => Depth::RAW_PCI_DEVICE,
+            Depth::OSDevice => Depth::RAW_OS_DEVICE,
+            Depth::Misc => Depth::RAW_MISC,
             #[cfg(feature = "hwloc-2_1_0")]
-            Depth::MemCache => -8,
+            Depth::MemCache => Depth::RAW_MEMCACHE,
         }
     }
 }
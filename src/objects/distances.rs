@@ -1,17 +1,20 @@
 //! Object distances
 
+#[cfg(feature = "hwloc-2_1_0")]
+use crate::errors::NulError;
+#[cfg(feature = "hwloc-2_3_0")]
+use crate::errors::RawHwlocError;
+#[cfg(feature = "hwloc-2_1_0")]
+use crate::ffi::LibcString;
+#[cfg(feature = "hwloc-2_8_0")]
+use crate::topology::builder::BuildFlags;
 #[cfg(feature = "hwloc-2_3_0")]
 use crate::topology::editor::TopologyEditor;
 use crate::{
-    errors::{self, RawHwlocError},
+    errors::{self, HybridError},
     ffi,
-    objects::{depth::Depth, types::ObjectType, TopologyObject},
-    topology::{RawTopology, Topology},
-};
-#[cfg(feature = "hwloc-2_1_0")]
-use crate::{
-    errors::{HybridError, NulError},
-    ffi::LibcString,
+    objects::{depth::Depth, types::ObjectType, ForeignObjectError, TopologyObject},
+    topology::{builder::FeatureDisabledAtBuild, RawTopology, Topology},
 };
 use bitflags::bitflags;
 use std::{
@@ -22,12 +25,12 @@ use std::{
     ops::{Index, IndexMut},
     ptr::{self, NonNull},
 };
-#[cfg(feature = "hwloc-2_5_0")]
+#[cfg(any(feature = "hwloc-2_1_0", feature = "hwloc-2_5_0"))]
 use thiserror::Error;
 
 /// # Retrieve distances between objects
 //
-// Upstream docs: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__distances__get.html
+// Upstream docs: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__distances__get.html
 impl Topology {
     /// Retrieve distance matrices from the topology
     ///
@@ -35,8 +38,37 @@ impl Topology {
     /// may be applied using the `kind` parameter: if it contains some
     /// [`DistancesKind`]`::FROM_xyz` options, only distance matrices matching
     /// one of them is returned. The same applies for `MEANS_xyz` options.
+    ///
+    /// If the topology was built with `BuildFlags::IGNORE_DISTANCES`, distances
+    /// from the operating system are not available and this will only report
+    /// user-provided distances, if any.
+    ///
+    /// # Errors
+    ///
+    /// - [`FeatureDisabledAtBuild`] if no distance matrix was found and the
+    ///   topology was built with `BuildFlags::IGNORE_DISTANCES`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::objects::distances::DistancesKind;
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// for distances in topology.distances(DistancesKind::MEANS_LATENCY)? {
+    ///     // Values can be indexed by (sender, receiver) object index
+    ///     for sender in 0..distances.num_objects() {
+    ///         for receiver in 0..distances.num_objects() {
+    ///             print!("{} ", distances[(sender, receiver)]);
+    ///         }
+    ///         println!();
+    ///     }
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
     #[doc(alias = "hwloc_distances_get")]
-    pub fn distances(&self, kind: DistancesKind) -> Result<Vec<Distances>, RawHwlocError> {
+    pub fn distances(
+        &self,
+        kind: DistancesKind,
+    ) -> Result<Vec<Distances>, HybridError<FeatureDisabledAtBuild>> {
         unsafe {
             self.get_distances("hwloc_distances_get", |topology, nr, distances, flags| {
                 ffi::hwloc_distances_get(topology, nr, distances, kind.bits(), flags)
@@ -48,13 +80,17 @@ impl Topology {
     ///
     /// Identical to [`distances()`] with the additional `depth` filter.
     ///
+    /// # Errors
+    ///
+    /// Same as [`distances()`].
+    ///
     /// [`distances()`]: Topology::distances()
     #[doc(alias = "hwloc_distances_get_by_depth")]
     pub fn distances_at_depth(
         &self,
         kind: DistancesKind,
         depth: impl Into<Depth>,
-    ) -> Result<Vec<Distances>, RawHwlocError> {
+    ) -> Result<Vec<Distances>, HybridError<FeatureDisabledAtBuild>> {
         let depth = depth.into();
         unsafe {
             self.get_distances(
@@ -77,13 +113,17 @@ impl Topology {
     ///
     /// Identical to [`distances()`] with the additional `ty` filter.
     ///
+    /// # Errors
+    ///
+    /// Same as [`distances()`].
+    ///
     /// [`distances()`]: Topology::distances()
     #[doc(alias = "hwloc_distances_get_by_type")]
     pub fn distances_with_type(
         &self,
         kind: DistancesKind,
         ty: ObjectType,
-    ) -> Result<Vec<Distances>, RawHwlocError> {
+    ) -> Result<Vec<Distances>, HybridError<FeatureDisabledAtBuild>> {
         unsafe {
             self.get_distances(
                 "hwloc_distances_get_by_type",
@@ -106,15 +146,37 @@ impl Topology {
     /// Usually only one distances matrix may match a given name.
     ///
     /// Names of distances matrices currently created by hwloc may be found
-    /// [in the hwloc documentation](https://hwloc.readthedocs.io/en/v2.9/topoattrs.html#topoattrs_distances).
+    /// [in the hwloc documentation](https://hwloc.readthedocs.io/en/v2.11/topoattrs.html#topoattrs_distances).
     ///
     /// # Errors
     ///
-    /// - [`NulError`] if `name` contains NUL chars.
+    /// - [`NameContainsNul`] if `name` contains NUL chars.
+    /// - [`FeatureDisabledAtBuild`] if no distance matrix was found and the
+    ///   topology was built with `BuildFlags::IGNORE_DISTANCES`
+    ///
+    /// [`FeatureDisabledAtBuild`]: DistancesWithNameError::FeatureDisabledAtBuild
+    /// [`NameContainsNul`]: DistancesWithNameError::NameContainsNul
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// // NUMA node latencies, as reported by the OS (e.g. ACPI SLIT on Linux)
+    /// for distances in topology.distances_with_name("NUMALatency")? {
+    ///     for ((sender, receiver), latency) in distances.object_distances() {
+    ///         println!("{sender:?} -> {receiver:?}: {latency}");
+    ///     }
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
     #[cfg(feature = "hwloc-2_1_0")]
     #[doc(alias = "hwloc_distances_get_by_name")]
-    pub fn distances_with_name(&self, name: &str) -> Result<Vec<Distances>, HybridError<NulError>> {
-        let name = LibcString::new(name)?;
+    #[doc(alias = "distances_by_name")]
+    pub fn distances_with_name(
+        &self,
+        name: &str,
+    ) -> Result<Vec<Distances>, HybridError<DistancesWithNameError>> {
+        let name = LibcString::new(name).map_err(DistancesWithNameError::from)?;
         unsafe {
             self.get_distances(
                 "hwloc_distances_get_by_name",
@@ -122,8 +184,11 @@ impl Topology {
                     ffi::hwloc_distances_get_by_name(topology, name.borrow(), nr, distances, flags)
                 },
             )
-            .map_err(HybridError::Hwloc)
         }
+        .map_err(|e| match e {
+            HybridError::Rust(e) => HybridError::Rust(e.into()),
+            HybridError::Hwloc(e) => HybridError::Hwloc(e),
+        })
     }
 
     /// Call one of the hwloc_distances_get(_by)? APIs
@@ -131,6 +196,9 @@ impl Topology {
     /// Takes care of all parameters except for `kind`, which is not universal
     /// to these APIs. So the last c_ulong is the flags parameter.
     ///
+    /// If no distance matrix is found, this also checks whether distances were
+    /// disabled at build time.
+    ///
     /// # Safety
     ///
     /// `getter` must perform a correct call to a `hwloc_distances_get` API
@@ -143,7 +211,7 @@ impl Topology {
             *mut *mut RawDistances,
             c_ulong,
         ) -> c_int,
-    ) -> Result<Vec<Distances>, RawHwlocError> {
+    ) -> Result<Vec<Distances>, HybridError<FeatureDisabledAtBuild>> {
         // Common setup to all getter calls
         let mut nr = 0;
         let flags = 0;
@@ -151,14 +219,20 @@ impl Topology {
         // Allocate array of distances pointers
         errors::call_hwloc_int_normal(getter_name, || {
             getter(self.as_ptr(), &mut nr, ptr::null_mut(), flags)
-        })?;
+        })
+        .map_err(HybridError::Hwloc)?;
+        #[cfg(feature = "hwloc-2_8_0")]
+        if nr == 0 {
+            self.ensure_features_enabled(BuildFlags::IGNORE_DISTANCES)?;
+        }
         let mut distances_ptrs = vec![ptr::null_mut(); ffi::expect_usize(nr)];
 
         // Let hwloc fill the distance pointers
         let old_nr = nr;
         errors::call_hwloc_int_normal(getter_name, || {
             getter(self.as_ptr(), &mut nr, distances_ptrs.as_mut_ptr(), flags)
-        })?;
+        })
+        .map_err(HybridError::Hwloc)?;
         assert_eq!(
             nr, old_nr,
             "Inconsistent reported number of distance matrices"
@@ -172,9 +246,29 @@ impl Topology {
     }
 }
 
+/// Error while querying distance matrices by name
+#[cfg(feature = "hwloc-2_1_0")]
+#[derive(Copy, Clone, Debug, Eq, Error, Hash, PartialEq)]
+pub enum DistancesWithNameError {
+    /// Provided `name` contains NUL chars
+    #[error("provided name contains NUL chars")]
+    NameContainsNul,
+
+    /// No distance matrix was found, and distances were disabled at build time
+    #[error(transparent)]
+    FeatureDisabledAtBuild(#[from] FeatureDisabledAtBuild),
+}
+//
+#[cfg(feature = "hwloc-2_1_0")]
+impl From<NulError> for DistancesWithNameError {
+    fn from(_: NulError) -> Self {
+        Self::NameContainsNul
+    }
+}
+
 /// # Add distances between objects
 //
-// Upstream docs: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__distances__add.html
+// Upstream docs: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__distances__add.html
 #[cfg(feature = "hwloc-2_5_0")]
 impl TopologyEditor<'_> {
     /// Create a new object distances matrix
@@ -206,6 +300,44 @@ impl TopologyEditor<'_> {
     /// - [`BadDistancesCount`](AddDistancesError::BadDistancesCount) if
     ///   the number of distances returned by the callback is not compatible
     ///   with the number of objects (it should be the square of it).
+    /// - [`ForeignObject`](AddDistancesError::ForeignObject) if the callback
+    ///   returns objects that do not belong to the topology that is being
+    ///   edited.
+    ///
+    /// # Examples
+    ///
+    /// Attach a user-measured bandwidth matrix to the PUs of a topology, so
+    /// that it is saved along with the topology in XML exports:
+    ///
+    /// ```
+    /// # use hwlocality::{
+    /// #     objects::{
+    /// #         distances::{AddDistancesFlags, DistancesKind},
+    /// #         types::ObjectType,
+    /// #     },
+    /// #     topology::export::xml::XMLExportFlags,
+    /// # };
+    /// let mut topology = hwlocality::Topology::test_instance().clone();
+    /// let num_pus = topology.objects_with_type(ObjectType::PU).count();
+    /// if num_pus >= 2 {
+    ///     topology.edit(|editor| {
+    ///         editor.add_distances(
+    ///             Some("MeasuredBandwidth"),
+    ///             DistancesKind::FROM_USER | DistancesKind::MEANS_BANDWIDTH,
+    ///             AddDistancesFlags::empty(),
+    ///             |topology| {
+    ///                 let pus = topology.objects_with_type(ObjectType::PU).map(Some);
+    ///                 // Replace this with actual measurements, in MB/s
+    ///                 let bandwidths = vec![1000; num_pus * num_pus];
+    ///                 (pus.collect(), bandwidths)
+    ///             },
+    ///         )
+    ///     })?;
+    ///     let xml = topology.export_xml(XMLExportFlags::default())?;
+    ///     assert!(xml.contains("MeasuredBandwidth"));
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
     #[doc(alias = "hwloc_distances_add_create")]
     #[doc(alias = "hwloc_distances_add_values")]
     #[doc(alias = "hwloc_distances_add_commit")]
@@ -233,7 +365,11 @@ impl TopologyEditor<'_> {
         let create_add_flags = 0;
         let commit_flags = flags.bits();
         //
-        let (objects, distances) = collect_objects_and_distances(self.topology());
+        let topology = self.topology();
+        let (objects, distances) = collect_objects_and_distances(topology);
+        if !objects.iter().flatten().all(|obj| topology.contains(obj)) {
+            return Err(AddDistancesError::ForeignObject.into());
+        }
         if objects.len() < 2 {
             return Err(AddDistancesError::BadObjectsCount(objects.len()).into());
         }
@@ -296,7 +432,7 @@ bitflags! {
         /// the comparisons during the grouping algorithms. The actual accuracy
         /// may be modified through the HWLOC_GROUPING_ACCURACY environment
         /// variable (see
-        /// [Environment Variables](https://hwloc.readthedocs.io/en/v2.9/envvar.html)).
+        /// [Environment Variables](https://hwloc.readthedocs.io/en/v2.11/envvar.html)).
         #[doc(alias = "HWLOC_DISTANCES_ADD_FLAG_GROUP_INACCURATE")]
         const GROUP_INACCURATE = (1<<0) | (1<<1);
     }
@@ -342,6 +478,10 @@ pub enum AddDistancesError {
         expected_distances_len: usize,
         actual_distances_len: usize,
     },
+
+    /// Provided callback returned objects from another topology
+    #[error("callback emitted objects that do not belong to the edited topology")]
+    ForeignObject,
 }
 //
 #[cfg(feature = "hwloc-2_5_0")]
@@ -350,6 +490,13 @@ impl From<NulError> for AddDistancesError {
         Self::NameContainsNul
     }
 }
+//
+#[cfg(feature = "hwloc-2_5_0")]
+impl From<ForeignObjectError> for AddDistancesError {
+    fn from(_: ForeignObjectError) -> Self {
+        Self::ForeignObject
+    }
+}
 
 /// Handle to a new distances structure during its addition to the topology
 #[cfg(feature = "hwloc-2_5_0")]
@@ -357,7 +504,7 @@ pub(crate) type DistancesAddHandle = *mut std::ffi::c_void;
 
 /// # Remove distances between objects
 //
-// Upstream docs: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__distances__remove.html
+// Upstream docs: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__distances__remove.html
 #[cfg(feature = "hwloc-2_3_0")]
 impl TopologyEditor<'_> {
     /// Remove a single distances matrix from the topology
@@ -472,7 +619,7 @@ impl RawDistances {
 ///
 /// The names and semantics of other distances matrices currently created by
 /// hwloc may be found
-/// [in the hwloc documentation](https://hwloc.readthedocs.io/en/v2.9/topoattrs.html#topoattrs_distances).
+/// [in the hwloc documentation](https://hwloc.readthedocs.io/en/v2.11/topoattrs.html#topoattrs_distances).
 ///
 /// The matrix may also contain bandwidths between random sets of objects,
 /// possibly provided by the user, as specified in the `kind` attribute provided
@@ -501,7 +648,7 @@ impl RawDistances {
 )]
 #[cfg_attr(feature = "hwloc-2_5_0", doc = "transformations to the structure.")]
 //
-// Upstream inspiration: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__distances__consult.html
+// Upstream inspiration: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__distances__consult.html
 #[doc(alias = "hwloc_distances_s")]
 pub struct Distances<'topology> {
     inner: NonNull<RawDistances>,
@@ -606,12 +753,15 @@ impl<'topology> Distances<'topology> {
     }
 
     /// Convert Option<&'topology TopologyObject> to a *const TopologyObject for
-    /// storage in objects_mut().
-    fn obj_to_ptr(obj: Option<&'topology TopologyObject>) -> *const TopologyObject {
-        if let Some(obj) = obj {
-            obj
-        } else {
-            std::ptr::null()
+    /// storage in objects_mut(), checking that it belongs to `topology`
+    fn obj_to_ptr(
+        topology: &Topology,
+        obj: Option<&'topology TopologyObject>,
+    ) -> Result<*const TopologyObject, ForeignObjectError> {
+        match obj {
+            Some(obj) if topology.contains(obj) => Ok(obj),
+            Some(_) => Err(ForeignObjectError),
+            None => Ok(std::ptr::null()),
         }
     }
 
@@ -622,23 +772,51 @@ impl<'topology> Distances<'topology> {
     /// of the [`distances_mut()`](Distances::distances_mut()),
     /// [`enumerate_distances_mut()`](Distances::enumerate_distances_mut())
     /// and [`object_distances_mut()`](Distances::object_distances_mut()) methods.
-    pub fn replace_object(&mut self, idx: usize, new_object: Option<&'topology TopologyObject>) {
-        self.objects_mut()[idx] = Self::obj_to_ptr(new_object);
+    ///
+    /// # Errors
+    ///
+    /// - [`ForeignObjectError`] if `new_object` does not belong to the
+    ///   topology that this distance matrix originates from. The distance
+    ///   matrix is left unchanged in this case.
+    ///
+    /// # Panics
+    ///
+    /// If `idx` is out of bounds.
+    pub fn replace_object(
+        &mut self,
+        idx: usize,
+        new_object: Option<&'topology TopologyObject>,
+    ) -> Result<(), ForeignObjectError> {
+        self.objects_mut()[idx] = Self::obj_to_ptr(self.topology, new_object)?;
+        Ok(())
     }
 
     /// Replace all objects using the provided (index, object) -> object mapping
     ///
     /// This is more efficient than calling [`Distances::replace_object()`] in
     /// a loop and allows you to know what object you are replacing.
+    ///
+    /// # Errors
+    ///
+    /// - [`ForeignObjectError`] if `mapping` returns an object that does not
+    ///   belong to the topology that this distance matrix originates from.
+    ///   The distance matrix is left unchanged in this case.
     pub fn replace_objects(
         &mut self,
         mut mapping: impl FnMut(usize, Option<&TopologyObject>) -> Option<&'topology TopologyObject>,
-    ) {
-        for (idx, obj) in self.objects_mut().iter_mut().enumerate() {
-            let old_obj = unsafe { ffi::deref_ptr(obj) };
-            let new_obj = mapping(idx, old_obj);
-            *obj = Self::obj_to_ptr(new_obj);
-        }
+    ) -> Result<(), ForeignObjectError> {
+        let topology = self.topology;
+        let new_objs = self
+            .objects_mut()
+            .iter()
+            .enumerate()
+            .map(|(idx, obj)| {
+                let old_obj = unsafe { ffi::deref_ptr(obj) };
+                Self::obj_to_ptr(topology, mapping(idx, old_obj))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        self.objects_mut().copy_from_slice(&new_objs);
+        Ok(())
     }
 
     /// Number of distances
@@ -825,6 +1003,9 @@ impl Drop for Distances<'_> {
     }
 }
 //
+unsafe impl Send for Distances<'_> {}
+unsafe impl Sync for Distances<'_> {}
+//
 impl Index<(usize, usize)> for Distances<'_> {
     type Output = u64;
 
@@ -5,10 +5,12 @@ pub mod depth;
 pub mod distances;
 pub mod types;
 
+#[cfg(doc)]
+use self::attributes::OSDeviceAttributes;
 use self::{
     attributes::{DownstreamAttributes, ObjectAttributes, PCIDomain, RawObjectAttributes},
     depth::{Depth, DepthError, DepthResult, RawDepth},
-    types::{CacheType, ObjectType, RawObjectType},
+    types::{CacheType, OSDeviceType, ObjectType, RawObjectType},
 };
 #[cfg(doc)]
 use crate::topology::{builder::BuildFlags, support::DiscoverySupport};
@@ -26,6 +28,7 @@ use std::{
     ffi::{c_char, c_int, c_uint, c_void, CStr},
     fmt,
     iter::FusedIterator,
+    ops::ControlFlow,
     ptr,
 };
 use thiserror::Error;
@@ -33,12 +36,12 @@ use thiserror::Error;
 /// # Object levels, depths and types
 ///
 /// Be sure to see read through the
-/// [Terms and Definitions](https://hwloc.readthedocs.io/en/v2.9/termsanddefs.html)
+/// [Terms and Definitions](https://hwloc.readthedocs.io/en/v2.11/termsanddefs.html)
 /// section of the upstream hwloc documentation to avoid any confusion about
 /// depths, child/sibling/cousin relationships, and see an example of an
 /// asymmetric topology where one package has fewer caches than its peers.
 //
-// Upstream docs: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__levels.html
+// Upstream docs: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__levels.html
 impl Topology {
     /// Depth of the hierarchical tree of objects
     ///
@@ -465,6 +468,26 @@ impl Topology {
             .expect("Root object should exist")
     }
 
+    /// Truth that `obj` belongs to this topology
+    ///
+    /// Object references from different [`Topology`] instances have the same
+    /// type, so nothing prevents them from being mixed up at compile time.
+    /// This check is cheap, as it only walks up the ancestor chain of `obj`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// let other = topology.clone();
+    /// assert!(topology.contains(topology.root_object()));
+    /// assert!(!topology.contains(other.root_object()));
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn contains(&self, obj: &TopologyObject) -> bool {
+        let root = obj.ancestors().last().unwrap_or(obj);
+        ptr::eq(root, self.root_object())
+    }
+
     /// [`TopologyObject`]s with the given [`ObjectType`]
     ///
     /// # Examples
@@ -520,6 +543,30 @@ impl Topology {
             inner: depth_iter.flat_map(move |depth| self.objects_at_depth(depth)),
         }
     }
+
+    /// Number of [`TopologyObject`]s with the given `object_type`
+    ///
+    /// This is equivalent to `topology.objects_with_type(object_type).len()`,
+    /// but the result is cached in the [`Topology`] when it is built or
+    /// edited, so it can be queried in hot code paths without traversing
+    /// topology levels.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::objects::types::ObjectType;
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// #
+    /// let num_pus = topology.object_count_by_type(ObjectType::PU);
+    /// assert!(num_pus > 0);
+    /// assert_eq!(num_pus, topology.objects_with_type(ObjectType::PU).len());
+    /// ```
+    #[doc(alias = "hwloc_get_nbobjs_by_type")]
+    pub fn object_count_by_type(&self, object_type: ObjectType) -> usize {
+        self.cached_object_count(object_type)
+    }
 }
 
 /// Iterator emitted by objects_with_type
@@ -571,9 +618,157 @@ impl<'topology, Inner: Iterator<Item = &'topology TopologyObject> + FusedIterato
 /// # Finding other objects
 //
 // This is inspired by the upstream functionality described at
-// https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__helper__find__misc.html
+// https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__helper__find__misc.html
 // but the code had to be ported to Rust because it's inline
 impl Topology {
+    /// Visit all objects of the topology in depth-first order
+    ///
+    /// This is a shorthand for calling [`TopologyObject::visit_subtree()`] on
+    /// the [root object](Self::root_object()), see its documentation for more
+    /// information.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::objects::{types::ObjectType, VisitChildren};
+    /// # use std::ops::ControlFlow;
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// // Find the first OS device, without looking below the PCI devices
+    /// let first_os_device = topology.visit(|obj, _distance| {
+    ///     match obj.object_type() {
+    ///         ObjectType::OSDevice => ControlFlow::Break(obj),
+    ///         ObjectType::PCIDevice => ControlFlow::Continue(VisitChildren::No),
+    ///         _ => ControlFlow::Continue(VisitChildren::Yes),
+    ///     }
+    /// });
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn visit<'self_, Break>(
+        &'self_ self,
+        visitor: impl FnMut(&'self_ TopologyObject, usize) -> ControlFlow<Break, VisitChildren>,
+    ) -> ControlFlow<Break> {
+        self.root_object().visit_subtree(visitor)
+    }
+
+    /// Get the object with the specified global persistent index
+    ///
+    /// Global persistent indices, as returned by
+    /// [`global_persistent_index()`], are preserved when a topology is
+    /// duplicated. So this can be used to find the counterpart of an object in
+    /// a [clone](Clone::clone()) of its topology, or to refer to objects in
+    /// serialized data.
+    ///
+    /// The lookup uses an index that is built when the topology is loaded or
+    /// edited, so it does not need to traverse the topology.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::objects::types::ObjectType;
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// let pu = topology.objects_with_type(ObjectType::PU).last().unwrap();
+    /// let gp_index = pu.global_persistent_index();
+    ///
+    /// let clone = topology.clone();
+    /// let pu_clone = clone.object_by_gp_index(gp_index).unwrap();
+    /// assert_eq!(pu_clone.object_type(), ObjectType::PU);
+    /// assert_eq!(pu_clone.os_index(), pu.os_index());
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    ///
+    /// [`global_persistent_index()`]: TopologyObject::global_persistent_index()
+    pub fn object_by_gp_index(&self, gp_index: u64) -> Option<&TopologyObject> {
+        let (depth, logical_index) = self.cached_object_position(gp_index)?;
+        Some(self.object_at_position(depth, logical_index))
+    }
+
+    /// Objects with a certain name
+    ///
+    /// Object names are usually only set on some special objects, most
+    /// notably I/O devices, e.g. the OS device named "mlx5_0" or "eth0". The
+    /// first name or info lookup on a topology builds an index of object names
+    /// and infos, which later lookups reuse without traversing the topology.
+    /// Editing the topology discards this index.
+    ///
+    /// Objects whose name is not valid UTF-8 cannot be found this way.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// for device in topology.objects_with_name("eth0") {
+    ///     println!("Found {device}");
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn objects_with_name<'self_>(
+        &'self_ self,
+        name: &str,
+    ) -> impl DoubleEndedIterator<Item = &'self_ TopologyObject>
+           + Clone
+           + ExactSizeIterator
+           + FusedIterator
+           + 'self_ {
+        self.cached_objects_with_name(name)
+            .iter()
+            .map(|&(depth, logical_index)| self.object_at_position(depth, logical_index))
+    }
+
+    /// Objects with a certain [textual info](TopologyObject::infos())
+    ///
+    /// This finds the objects which have an info named `key` with value
+    /// `value`, such as hwloc's own annotations (e.g. key "PCIVendor") or keys
+    /// that were added with [`TopologyObject::add_info()`]. The first name or
+    /// info lookup on a topology builds an index of object names and infos,
+    /// which later lookups reuse without traversing the topology. Editing the
+    /// topology discards this index.
+    ///
+    /// Infos whose key or value is not valid UTF-8 cannot be found this way.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// let root = topology.root_object();
+    /// if let Some(Ok(os_name)) = root.info("OSName").map(|name| name.to_str()) {
+    ///     let mut machines = topology.objects_with_info("OSName", os_name);
+    ///     assert!(machines.any(|obj| std::ptr::eq(obj, root)));
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn objects_with_info<'self_>(
+        &'self_ self,
+        key: &str,
+        value: &str,
+    ) -> impl DoubleEndedIterator<Item = &'self_ TopologyObject>
+           + Clone
+           + ExactSizeIterator
+           + FusedIterator
+           + 'self_ {
+        self.cached_objects_with_info(key, value)
+            .iter()
+            .map(|&(depth, logical_index)| self.object_at_position(depth, logical_index))
+    }
+
+    /// Object at a known depth and logical index
+    fn object_at_position(&self, depth: Depth, logical_index: usize) -> &TopologyObject {
+        let logical_index =
+            c_uint::try_from(logical_index).expect("Can't happen, index comes from hwloc");
+        let ptr =
+            unsafe { ffi::hwloc_get_obj_by_depth(self.as_ptr(), depth.into(), logical_index) };
+        assert!(
+            !ptr.is_null(),
+            "Got null pointer from hwloc_get_obj_by_depth"
+        );
+        unsafe { &*ptr }
+    }
+
     /// Get the object of type [`ObjectType::PU`] with the specified OS index
     ///
     /// If you want to convert an entire CPU set into the PU objects it
@@ -601,13 +796,45 @@ impl Topology {
             .filter_map(|(pu, os_index)| cpuset.is_set(os_index).then_some(pu))
     }
 
+    /// Get the logical indices of the [`ObjectType::PU`]s covered by the
+    /// specified cpuset, in increasing order
+    ///
+    /// CPU sets are indexed by OS (physical) PU index, whereas many
+    /// applications and tools like `lstopo -l` number PUs by logical index.
+    /// This converts the former into the latter. The reverse conversion is
+    /// performed by [`CpuSet::from_logical_indices()`].
+    ///
+    /// Requires [`DiscoverySupport::pu_count()`].
+    ///
+    /// This functionality is specific to the Rust bindings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::cpu::cpusets::CpuSet;
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// let cpuset = topology.cpuset();
+    /// let logical = topology.logical_indices(&cpuset).collect::<Vec<_>>();
+    /// assert_eq!(logical.len(), cpuset.weight().unwrap());
+    /// assert_eq!(CpuSet::from_logical_indices(topology, logical)?, *cpuset);
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn logical_indices<'result>(
+        &'result self,
+        cpuset: &'result CpuSet,
+    ) -> impl Iterator<Item = usize> + Clone + DoubleEndedIterator + FusedIterator + 'result {
+        self.pus_from_cpuset(cpuset)
+            .map(TopologyObject::logical_index)
+    }
+
     /// Get the object of type [`ObjectType::NUMANode`] with the specified OS index
     ///
     /// If you want to convert an entire NodeSet into the NUMANode objects it
-    /// contains, using `nodes_from_cpuset` will be more efficient than repeatedly
+    /// contains, using `nodes_from_nodeset` will be more efficient than repeatedly
     /// calling this function with every OS index from the NodeSet.
     ///
     /// Requires [`DiscoverySupport::numa_count()`].
+    #[doc(alias = "numa_node_with_os_index")]
     #[doc(alias = "hwloc_get_numanode_obj_by_os_index")]
     pub fn node_with_os_index(&self, os_index: usize) -> Option<&TopologyObject> {
         self.objs_and_os_indices(ObjectType::NUMANode)
@@ -668,12 +895,45 @@ impl Topology {
     ///
     /// # Errors
     ///
-    /// - [`MissingCpuSetError`] if `obj` does not have a cpuset.
+    /// - [`ForeignObject`] if `obj` does not belong to this topology.
+    /// - [`MissingCpuSet`] if `obj` does not have a cpuset.
+    ///
+    /// # Examples
+    ///
+    /// Pick a few cores next to an I/O device, e.g. to run polling threads:
+    ///
+    /// ```
+    /// # use hwlocality::objects::types::ObjectType;
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// # let device = topology.root_object();
+    /// // Find a core within the device's locality...
+    /// let locality = device.non_io_ancestor();
+    /// let cpuset = locality.cpuset().expect("non-I/O objects have a cpuset");
+    /// let first_core = topology
+    ///     .objects_with_type(ObjectType::Core)
+    ///     .find(|core| core.is_inside_cpuset(&cpuset))
+    ///     .expect("the locality should contain at least one core");
+    ///
+    /// // ...then complement it with the closest other cores
+    /// let cores = std::iter::once(first_core)
+    ///     .chain(topology.closest_objects(first_core)?)
+    ///     .take(4)
+    ///     .collect::<Vec<_>>();
+    /// assert!(!cores.is_empty());
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    ///
+    /// [`ForeignObject`]: ClosestObjectsError::ForeignObject
+    /// [`MissingCpuSet`]: ClosestObjectsError::MissingCpuSet
     #[doc(alias = "hwloc_get_closest_objs")]
     pub fn closest_objects<'result>(
         &'result self,
         obj: &'result TopologyObject,
-    ) -> Result<impl Iterator<Item = &TopologyObject> + 'result, MissingCpuSetError> {
+    ) -> Result<impl Iterator<Item = &TopologyObject> + 'result, ClosestObjectsError> {
+        if !self.contains(obj) {
+            return Err(ForeignObjectError.into());
+        }
+
         // This search may only be applied to objects with cpusets
         let obj_cpuset = obj.cpuset().ok_or(MissingCpuSetError)?;
 
@@ -723,7 +983,7 @@ impl Topology {
         Ok(std::iter::from_fn(move || {
             loop {
                 // Look for a cousin that is covered by the current ancestor
-                let (ancestor, ancestor_cpuset) = ancestor_and_cpuset.take()?;
+                let (ancestor, ancestor_cpuset) = ancestor_and_cpuset.as_ref()?;
                 while let Some((cousin, cousin_cpuset)) = cousins_and_cpusets.pop() {
                     if ancestor_cpuset.includes(&cousin_cpuset) {
                         return Some(cousin);
@@ -734,8 +994,7 @@ impl Topology {
 
                 // We ran out of cousins, go to a higher-level ancestor or end
                 // iteration if we reached the top of the tree.
-                let (ancestor, ancestor_cpuset) = find_larger_parent(ancestor, &ancestor_cpuset)?;
-                ancestor_and_cpuset = Some((ancestor, ancestor_cpuset));
+                ancestor_and_cpuset = Some(find_larger_parent(ancestor, ancestor_cpuset)?);
                 std::mem::swap(&mut cousins_and_cpusets, &mut next_cousins_and_cpusets);
             }
         }))
@@ -847,9 +1106,54 @@ impl Topology {
 #[error("an operation that requires a cpuset was applied to an object without one")]
 pub struct MissingCpuSetError;
 
+/// Error returned when an object from another topology is passed to an
+/// operation on this topology
+///
+/// [`TopologyObject`] references from different [`Topology`] instances have
+/// the same type, so this can only be checked at runtime using
+/// [`Topology::contains()`].
+///
+/// # Examples
+///
+/// ```
+/// # use hwlocality::objects::{types::ObjectType, ClosestObjectsError, ForeignObjectError};
+/// # let topology = hwlocality::Topology::test_instance();
+/// let other = topology.clone();
+/// let pu = other.objects_with_type(ObjectType::PU).next().unwrap();
+/// assert_eq!(
+///     topology.closest_objects(pu).err(),
+///     Some(ClosestObjectsError::ForeignObject(ForeignObjectError))
+/// );
+/// ```
+#[derive(Copy, Clone, Debug, Default, Eq, Error, Hash, PartialEq)]
+#[error("an object from another topology was passed to an operation on this topology")]
+pub struct ForeignObjectError;
+
+/// Error returned by [`Topology::closest_objects()`]
+#[derive(Copy, Clone, Debug, Eq, Error, PartialEq)]
+pub enum ClosestObjectsError {
+    /// Target object does not belong to this topology
+    #[error(transparent)]
+    ForeignObject(#[from] ForeignObjectError),
+
+    /// Target object does not have a cpuset
+    #[error(transparent)]
+    MissingCpuSet(#[from] MissingCpuSetError),
+}
+
+/// Whether [`TopologyObject::visit_subtree()`] should visit an object's children
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum VisitChildren {
+    /// Visit the children of the object
+    Yes,
+
+    /// Skip the children of the object and the subtrees below them
+    No,
+}
+
 /// # Finding I/O objects
 //
-// Inspired by https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__advanced__io.html
+// Inspired by https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__advanced__io.html
 // but inline functions had to be reimplemented in Rust. Further, queries
 // pertaining to ancestors and children were moved to the corresponding sections.
 impl Topology {
@@ -893,6 +1197,17 @@ impl Topology {
     ///
     /// - [`ParameterError`] if the given string does not match the PCI bus id
     ///   format given above
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// if let Some(device) = topology.pci_device_by_bus_id_string("0000:3b:00.0")? {
+    ///     println!("Device {device} is attached below {}", device.non_io_ancestor());
+    /// }
+    /// assert!(topology.pci_device_by_bus_id_string("not a bus id").is_err());
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
     #[doc(alias = "hwloc_get_pcidev_by_busidstring")]
     pub fn pci_device_by_bus_id_string(
         &self,
@@ -934,6 +1249,98 @@ impl Topology {
         self.objects_at_depth(Depth::OSDevice)
     }
 
+    /// Enumerate OS devices of a certain type
+    ///
+    /// # Examples
+    ///
+    /// Find the NUMA nodes that are closest to each network interface:
+    ///
+    /// ```
+    /// # use hwlocality::objects::types::OSDeviceType;
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// for nic in topology.os_devices_with_type(OSDeviceType::Network) {
+    ///     let locality = nic.non_io_ancestor();
+    ///     println!("{nic} is close to NUMA nodes {:?}", locality.nodeset());
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn os_devices_with_type(
+        &self,
+        ty: OSDeviceType,
+    ) -> impl DoubleEndedIterator<Item = &TopologyObject> + Clone + FusedIterator {
+        self.os_devices()
+            .filter(move |obj| obj.os_device_type() == Some(ty))
+    }
+
+    /// Find the OS device of a certain type with a certain name
+    ///
+    /// Used by the vendor-specific helpers, which identify devices by the
+    /// OS device names that the matching hwloc component assigns.
+    #[cfg(any(
+        doc,
+        target_os = "linux",
+        feature = "cuda",
+        feature = "nvml",
+        feature = "ofed",
+        feature = "rsmi",
+        feature = "ze"
+    ))]
+    pub(crate) fn os_device_with_name(
+        &self,
+        ty: OSDeviceType,
+        name: &str,
+    ) -> Option<&TopologyObject> {
+        self.os_devices_with_type(ty).find(|osdev| {
+            osdev
+                .name()
+                .map_or(false, |osdev_name| osdev_name.to_bytes() == name.as_bytes())
+        })
+    }
+
+    /// Enumerate GPU OS devices
+    ///
+    /// See [`TopologyObject::is_gpu()`] for more information about which
+    /// OS devices are considered to be GPUs. As with other I/O objects, the
+    /// topology must have been built with I/O discovery enabled for anything
+    /// to be listed here.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::{topology::builder::TypeFilter, Topology};
+    /// let topology = Topology::builder()
+    ///     .with_io_type_filter(TypeFilter::KeepImportant)?
+    ///     .build()?;
+    /// for gpu in topology.gpus() {
+    ///     println!("{gpu} is close to CPUs {:?}", gpu.non_io_ancestor().cpuset());
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn gpus(&self) -> impl DoubleEndedIterator<Item = &TopologyObject> + Clone + FusedIterator {
+        self.os_devices().filter(|obj| obj.is_gpu())
+    }
+
+    /// Enumerate network interface OS devices
+    ///
+    /// See [`TopologyObject::is_network_device()`] for more information about
+    /// which OS devices are considered to be network interfaces.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    pub fn nics(&self) -> impl DoubleEndedIterator<Item = &TopologyObject> + Clone + FusedIterator {
+        self.os_devices().filter(|obj| obj.is_network_device())
+    }
+
+    /// Enumerate storage OS devices
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    pub fn block_devices(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = &TopologyObject> + Clone + FusedIterator {
+        self.os_devices().filter(|obj| obj.is_block_device())
+    }
+
     /// Enumerate bridges in the system
     #[doc(alias = "hwloc_get_next_bridge")]
     pub fn bridges(
@@ -945,6 +1352,44 @@ impl Topology {
            + FusedIterator {
         self.objects_at_depth(Depth::Bridge)
     }
+
+    /// Enumerate the PCI devices that are behind a certain bridge
+    ///
+    /// This uses the range of downstream PCI buses of the bridge, as reported
+    /// by [`DownstreamPCIAttributes`], so it also reports devices that sit
+    /// behind intermediary bridges. If `bridge` is not a bridge with a PCI
+    /// downstream side, no device is reported.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// for bridge in topology.bridges() {
+    ///     let num_devices = topology.pci_devices_behind(bridge).count();
+    ///     println!("{bridge} has {num_devices} PCI device(s) behind it");
+    /// }
+    /// ```
+    ///
+    /// [`DownstreamPCIAttributes`]: crate::objects::attributes::DownstreamPCIAttributes
+    pub fn pci_devices_behind<'self_>(
+        &'self_ self,
+        bridge: &TopologyObject,
+    ) -> impl DoubleEndedIterator<Item = &'self_ TopologyObject> + Clone + FusedIterator {
+        let downstream = match bridge.attributes() {
+            Some(ObjectAttributes::Bridge(bridge)) => bridge
+                .downstream_attributes()
+                .map(|DownstreamAttributes::PCI(pci)| *pci),
+            _ => None,
+        };
+        self.pci_devices().filter(move |obj| {
+            let Some(ObjectAttributes::PCIDevice(pci)) = obj.attributes() else {
+                unreachable!("All PCI devices should have PCI attributes")
+            };
+            downstream.map_or(false, |downstream| {
+                downstream.covers_bus(pci.domain(), pci.bus_id())
+            })
+        })
+    }
 }
 
 /// Hardware topology object
@@ -961,8 +1406,8 @@ impl Topology {
 /// - [Key-value information](#key-value-information)
 //
 // Upstream docs:
-// - https://hwloc.readthedocs.io/en/v2.9/structhwloc__obj.html
-// - https://hwloc.readthedocs.io/en/v2.9/attributes.html
+// - https://hwloc.readthedocs.io/en/v2.11/structhwloc__obj.html
+// - https://hwloc.readthedocs.io/en/v2.11/attributes.html
 #[doc(alias = "hwloc_obj")]
 #[doc(alias = "hwloc_obj_t")]
 #[repr(C)]
@@ -1015,7 +1460,11 @@ impl TopologyObject {
 
     /// Subtype string to better describe the type field
     ///
-    /// See <https://hwloc.readthedocs.io/en/v2.9/attributes.html#attributes_normal>
+    /// For example, NUMA nodes of heterogeneous memory systems may have a
+    /// `DRAM`, `HBM`, `NVM`, `SPM`, `GPU-Memory` or `CXL-mem` subtype, and
+    /// Group objects may have a `Cluster`, `Module`, `Tile` or `Compute Unit`
+    /// subtype. See
+    /// <https://hwloc.readthedocs.io/en/v2.11/attributes.html#attributes_normal>
     /// for a list of subtype strings that hwloc can emit.
     #[doc(alias = "hwloc_obj::subtype")]
     pub fn subtype(&self) -> Option<&CStr> {
@@ -1026,12 +1475,24 @@ impl TopologyObject {
     ///
     /// This is something you'll often want to do when creating Group or Misc
     /// objects in order to make them more descriptive.
+    #[cfg_attr(
+        feature = "hwloc-2_3_0",
+        doc = "The subtype of objects that are already present in the topology can be changed using"
+    )]
+    #[cfg_attr(
+        feature = "hwloc-2_3_0",
+        doc = "[`TopologyEditor::set_object_subtype()`](crate::topology::editor::TopologyEditor::set_object_subtype())."
+    )]
     ///
     /// # Errors
     ///
     /// - [`NulError`] if `subtype` contains NUL chars.
     pub fn set_subtype(&mut self, subtype: &str) -> Result<(), NulError> {
-        self.subtype = LibcString::new(subtype)?.into_raw();
+        let new_subtype = LibcString::new(subtype)?.into_raw();
+        let old_subtype = std::mem::replace(&mut self.subtype, new_subtype);
+        // hwloc allocates subtype strings with malloc() and frees them with
+        // free(), so the previous one must be liberated the same way.
+        unsafe { libc::free(old_subtype.cast()) };
         Ok(())
     }
 
@@ -1050,6 +1511,56 @@ impl TopologyObject {
         unsafe { ObjectAttributes::new(self.object_type(), &self.attr) }
     }
 
+    /// Type of OS device, if this is an [`OSDevice`](ObjectType::OSDevice)
+    ///
+    /// This is a shortcut for extracting the [`OSDeviceAttributes`] of this
+    /// object and querying their
+    /// [`device_type()`](OSDeviceAttributes::device_type()).
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    #[doc(alias = "hwloc_osdev_attr_s::type")]
+    #[doc(alias = "hwloc_obj_attr_u::hwloc_osdev_attr_s::type")]
+    pub fn os_device_type(&self) -> Option<OSDeviceType> {
+        if let Some(ObjectAttributes::OSDevice(attr)) = self.attributes() {
+            Some(attr.device_type())
+        } else {
+            None
+        }
+    }
+
+    /// Truth that this is a GPU OS device
+    ///
+    /// This includes display and DRM devices, as well as the devices exposed
+    /// by GPU management libraries such as NVML or RSMI. Compute handles
+    /// exposed by APIs like CUDA or OpenCL are
+    /// [co-processors](OSDeviceType::CoProcessor) instead.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    pub fn is_gpu(&self) -> bool {
+        self.os_device_type() == Some(OSDeviceType::GPU)
+    }
+
+    /// Truth that this is a network interface OS device
+    ///
+    /// This includes both [regular network interfaces](OSDeviceType::Network)
+    /// like Ethernet adapters and [high-performance network
+    /// adapters](OSDeviceType::OpenFabrics) like InfiniBand HCAs.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    pub fn is_network_device(&self) -> bool {
+        matches!(
+            self.os_device_type(),
+            Some(OSDeviceType::Network | OSDeviceType::OpenFabrics)
+        )
+    }
+
+    /// Truth that this is a storage OS device (e.g. a block device)
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    pub fn is_block_device(&self) -> bool {
+        self.os_device_type() == Some(OSDeviceType::Storage)
+    }
+
     /// Unsafe access to object type-specific attributes
     #[cfg(feature = "hwloc-2_3_0")]
     pub(crate) fn raw_attributes(&mut self) -> Option<&mut RawObjectAttributes> {
@@ -1084,7 +1595,7 @@ impl TopologyObject {
 
 /// # Depth and ancestors
 //
-// Includes functionality inspired by https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__helper__ancestors.html
+// Includes functionality inspired by https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__helper__ancestors.html
 impl TopologyObject {
     /// Vertical index in the hierarchy
     ///
@@ -1119,6 +1630,19 @@ impl TopologyObject {
     ///
     /// Will return `None` if the requested depth is deeper than the depth of
     /// the current object.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use hwlocality::Topology;
+    /// # let topology = Topology::test_instance();
+    /// let root = topology.root_object();
+    /// for pu in topology.objects_at_depth(topology.depth() - 1) {
+    ///     let ancestor = pu.ancestor_at_depth(0).expect("PUs are below the root");
+    ///     assert!(std::ptr::eq(ancestor, root));
+    /// }
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
     #[doc(alias = "hwloc_get_ancestor_obj_by_depth")]
     pub fn ancestor_at_depth(&self, depth: impl Into<Depth>) -> Option<&TopologyObject> {
         // Fast failure path when depth is comparable
@@ -1138,6 +1662,21 @@ impl TopologyObject {
     ///
     /// Will return `None` if the requested type appears deeper than the
     /// current object or doesn't appear in the topology.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use hwlocality::{objects::types::ObjectType, Topology};
+    /// # let topology = Topology::test_instance();
+    /// for pu in topology.objects_with_type(ObjectType::PU) {
+    ///     let machine = pu
+    ///         .first_ancestor_with_type(ObjectType::Machine)
+    ///         .expect("All PUs belong to a Machine");
+    ///     assert!(std::ptr::eq(machine, topology.root_object()));
+    /// }
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[doc(alias = "ancestor_with_type")]
     #[doc(alias = "hwloc_get_ancestor_obj_by_type")]
     pub fn first_ancestor_with_type(&self, ty: ObjectType) -> Option<&TopologyObject> {
         self.ancestors()
@@ -1147,7 +1686,26 @@ impl TopologyObject {
     /// Search for the first ancestor that is shared with another object
     ///
     /// The search will always succeed unless one of `self` and `other` is the
-    /// root [`Machine`](ObjectType::Machine) object, which has no ancestors.
+    /// root [`Machine`](ObjectType::Machine) object, which has no ancestors,
+    /// or `self` and `other` belong to different topologies.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use hwlocality::{objects::types::ObjectType, Topology};
+    /// # let topology = Topology::test_instance();
+    /// let mut pus = topology.objects_with_type(ObjectType::PU);
+    /// let first_pu = pus.next().expect("There is always one PU");
+    /// if let Some(last_pu) = pus.last() {
+    ///     let ancestor = first_pu
+    ///         .common_ancestor(last_pu)
+    ///         .expect("PUs are not the root object");
+    ///     assert!(first_pu.is_in_subtree(ancestor));
+    ///     assert!(last_pu.is_in_subtree(ancestor));
+    /// }
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[doc(alias = "common_ancestor_with")]
     #[doc(alias = "hwloc_get_common_ancestor_obj")]
     pub fn common_ancestor(&self, other: &TopologyObject) -> Option<&TopologyObject> {
         // Handle degenerate case
@@ -1227,6 +1785,19 @@ impl TopologyObject {
 
     /// Truth that this object is in the subtree beginning with ancestor
     /// object `subtree_root`
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use hwlocality::{objects::types::ObjectType, Topology};
+    /// # let topology = Topology::test_instance();
+    /// let root = topology.root_object();
+    /// assert!(topology
+    ///     .objects_with_type(ObjectType::PU)
+    ///     .all(|pu| pu.is_in_subtree(root)));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[doc(alias = "is_in_subtree_of")]
     #[doc(alias = "hwloc_obj_is_in_subtree")]
     pub fn is_in_subtree(&self, subtree_root: &TopologyObject) -> bool {
         // Take a cpuset-based shortcut on normal objects
@@ -1262,12 +1833,35 @@ impl TopologyObject {
     /// Find the smallest non-I/O ancestor object. This object (normal or
     /// memory) may then be used for binding because it has CPU and node sets
     /// and because its locality is the same as this object.
+    ///
+    /// Like hwloc, this returns the object itself if it is not an I/O object.
     #[doc(alias = "hwloc_get_non_io_ancestor_obj")]
+    #[doc(alias = "first_non_io_ancestor")]
     pub fn non_io_ancestor(&self) -> &TopologyObject {
-        self.ancestors()
+        std::iter::once(self)
+            .chain(self.ancestors())
             .find(|obj| obj.cpuset().is_some())
             .expect("Per hwloc documentation, there has to be one non-I/O ancestor")
     }
+
+    /// Get the cpuset of the first non-I/O ancestor object
+    ///
+    /// This is the set of CPUs that are physically close to an I/O device, as
+    /// reported by the vendor-specific helpers.
+    #[cfg(any(
+        doc,
+        target_os = "linux",
+        feature = "cuda",
+        feature = "nvml",
+        feature = "ofed",
+        feature = "rsmi",
+        feature = "ze"
+    ))]
+    pub(crate) fn non_io_ancestor_cpuset(&self) -> Option<CpuSet> {
+        self.non_io_ancestor()
+            .cpuset()
+            .map(|cpuset| cpuset.to_owned())
+    }
 }
 
 /// Iterator over ancestors of a TopologyObject
@@ -1346,6 +1940,26 @@ impl TopologyObject {
     }
 
     /// Normal children of this object
+    ///
+    /// Memory, I/O and Misc children are respectively listed by
+    /// [`memory_children()`](Self::memory_children),
+    /// [`io_children()`](Self::io_children) and
+    /// [`misc_children()`](Self::misc_children), and
+    /// [`all_children()`](Self::all_children) lists all of them.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use hwlocality::Topology;
+    /// # let topology = Topology::test_instance();
+    /// let root = topology.root_object();
+    /// assert_eq!(root.normal_children().count(), root.normal_arity());
+    /// for child in root.normal_children() {
+    ///     assert!(std::ptr::eq(child.parent().unwrap(), root));
+    /// }
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[doc(alias = "children")]
     #[doc(alias = "hwloc_obj::children")]
     #[doc(alias = "hwloc_obj::first_child")]
     #[doc(alias = "hwloc_obj::last_child")]
@@ -1458,7 +2072,7 @@ impl TopologyObject {
         let Some(DownstreamAttributes::PCI(pci)) = bridge.downstream_attributes() else {
             return false;
         };
-        pci.domain() == domain && pci.secondary_bus() <= bus_id && pci.subordinate_bus() >= bus_id
+        pci.covers_bus(domain, bus_id)
     }
 
     /// Number of Misc children
@@ -1479,6 +2093,38 @@ impl TopologyObject {
     }
 
     /// Full list of children (normal, then memory, then I/O, then Misc)
+    ///
+    /// Like other child iterators, this yields references that borrow from
+    /// the [`Topology`] rather than from `self`, so they can be collected and
+    /// outlive the parent object reference they were obtained from.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use hwlocality::{objects::{depth::Depth, TopologyObject}, Topology};
+    /// # let topology = Topology::test_instance();
+    /// // Walk the topology tree one generation at a time
+    /// let mut generation: Vec<&TopologyObject> = vec![topology.root_object()];
+    /// let mut num_objects = 0;
+    /// while !generation.is_empty() {
+    ///     num_objects += generation.len();
+    ///     generation = generation
+    ///         .iter()
+    ///         .flat_map(|obj| obj.all_children())
+    ///         .collect();
+    /// }
+    ///
+    /// // This reaches every object in the topology
+    /// let num_normal = (0..topology.depth())
+    ///     .map(|depth| topology.objects_at_depth(depth).count())
+    ///     .sum::<usize>();
+    /// let num_virtual = Depth::VIRTUAL_DEPTHS
+    ///     .iter()
+    ///     .map(|&depth| topology.objects_at_depth(depth).count())
+    ///     .sum::<usize>();
+    /// assert_eq!(num_objects, num_normal + num_virtual);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
     #[doc(alias = "hwloc_get_next_child")]
     pub fn all_children(&self) -> impl Iterator<Item = &TopologyObject> + Clone + FusedIterator {
         self.normal_children()
@@ -1487,6 +2133,59 @@ impl TopologyObject {
             .chain(self.misc_children())
     }
 
+    /// Visit this object and all of its descendants in depth-first order
+    ///
+    /// This is a faster alternative to building iterator chains when scanning
+    /// large topologies, since subtrees that are not of interest can be
+    /// skipped entirely. Children are visited in the order of
+    /// [`all_children()`](Self::all_children()).
+    ///
+    /// The `visitor` callback receives each object along with its distance
+    /// from `self` in the tree, and decides whether the traversal should
+    /// visit the children of this object ([`VisitChildren::Yes`]), skip them
+    /// ([`VisitChildren::No`]), or stop right away ([`ControlFlow::Break`]).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::objects::{types::ObjectType, VisitChildren};
+    /// # use std::ops::ControlFlow;
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// // Count the I/O objects without looking inside of any package
+    /// let mut num_io_objects = 0;
+    /// topology.root_object().visit_subtree(|obj, _distance| {
+    ///     if obj.object_type().is_io() {
+    ///         num_io_objects += 1;
+    ///     }
+    ///     let visit_children = if obj.object_type() == ObjectType::Package {
+    ///         VisitChildren::No
+    ///     } else {
+    ///         VisitChildren::Yes
+    ///     };
+    ///     ControlFlow::<(), _>::Continue(visit_children)
+    /// });
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn visit_subtree<'self_, Break>(
+        &'self_ self,
+        mut visitor: impl FnMut(&'self_ TopologyObject, usize) -> ControlFlow<Break, VisitChildren>,
+    ) -> ControlFlow<Break> {
+        /// Recursive implementation of visit_subtree
+        fn visit_impl<'obj, Break>(
+            obj: &'obj TopologyObject,
+            distance: usize,
+            visitor: &mut impl FnMut(&'obj TopologyObject, usize) -> ControlFlow<Break, VisitChildren>,
+        ) -> ControlFlow<Break> {
+            if let VisitChildren::Yes = visitor(obj, distance)? {
+                for child in obj.all_children() {
+                    visit_impl(child, distance + 1, visitor)?;
+                }
+            }
+            ControlFlow::Continue(())
+        }
+        visit_impl(self, 0, &mut visitor)
+    }
+
     /// Iterator over singly linked lists of child TopologyObjects with arity
     fn singly_linked_children(
         &self,
@@ -1669,13 +2368,13 @@ impl TopologyObject {
     /// Complete list of (key, value) textual info pairs
     ///
     /// hwloc defines [a number of standard object info attribute names with
-    /// associated semantics](https://hwloc.readthedocs.io/en/v2.9/attributes.html#attributes_info).
+    /// associated semantics](https://hwloc.readthedocs.io/en/v2.11/attributes.html#attributes_info).
     ///
     /// Beware that hwloc allows multiple informations with the same key to
     /// exist, although no sane programs should leverage this possibility.
     #[doc(alias = "hwloc_obj::infos")]
     pub fn infos(&self) -> &[TextualInfo] {
-        if self.children.is_null() {
+        if self.infos.is_null() {
             assert_eq!(
                 self.infos_count, 0,
                 "Got null infos pointer with nonzero info count"
@@ -1731,34 +2430,77 @@ impl TopologyObject {
     }
 }
 
-// # Internal utilities
+/// # Textual description
 impl TopologyObject {
-    /// Display the TopologyObject's type and attributes
-    fn display(&self, f: &mut fmt::Formatter, verbose: bool) -> fmt::Result {
-        let type_chars = ffi::call_snprintf(|buf, len| unsafe {
+    /// Name of the type of this object, as displayed by lstopo
+    ///
+    /// Cache names are shortened to e.g. "L2d" unless `verbose` is set, in
+    /// which case they become e.g. "L2Cache". Other object types are named as
+    /// with [`ObjectType`]'s `Display` implementation, e.g. "Core" or "PU".
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::objects::types::ObjectType;
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// let pu = topology.objects_with_type(ObjectType::PU).next().unwrap();
+    /// assert_eq!(pu.type_string(false), "PU");
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    #[doc(alias = "hwloc_obj_type_snprintf")]
+    pub fn type_string(&self, verbose: bool) -> String {
+        let chars = ffi::call_snprintf(|buf, len| unsafe {
             ffi::hwloc_obj_type_snprintf(buf, len, self, verbose.into())
         });
+        unsafe { CStr::from_ptr(chars.as_ptr()) }
+            .to_string_lossy()
+            .into_owned()
+    }
 
-        let separator = if f.alternate() {
-            b"\n  \0".as_ptr()
-        } else {
-            b"  \0".as_ptr()
-        }
-        .cast::<c_char>();
-        let attr_chars = ffi::call_snprintf(|buf, len| unsafe {
-            ffi::hwloc_obj_attr_snprintf(buf, len, self, separator, verbose.into())
+    /// Attributes of this object, as displayed by lstopo
+    ///
+    /// Attributes are separated by `separator`. Only the major attributes are
+    /// displayed unless `verbose` is set.
+    ///
+    /// # Errors
+    ///
+    /// - [`NulError`] if `separator` contains NUL chars.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// let root = topology.root_object();
+    /// println!("{}", root.attributes_string(", ", true)?);
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    #[doc(alias = "hwloc_obj_attr_snprintf")]
+    pub fn attributes_string(&self, separator: &str, verbose: bool) -> Result<String, NulError> {
+        let separator = LibcString::new(separator)?;
+        let chars = ffi::call_snprintf(|buf, len| unsafe {
+            ffi::hwloc_obj_attr_snprintf(buf, len, self, separator.borrow(), verbose.into())
         });
+        Ok(unsafe { CStr::from_ptr(chars.as_ptr()) }
+            .to_string_lossy()
+            .into_owned())
+    }
+}
 
-        unsafe {
-            let type_str = CStr::from_ptr(type_chars.as_ptr()).to_string_lossy();
-            let attr_str = CStr::from_ptr(attr_chars.as_ptr()).to_string_lossy();
-            if attr_str.is_empty() {
-                write!(f, "{type_str}")
-            } else if f.alternate() {
-                write!(f, "{type_str} (\n  {attr_str}\n)")
-            } else {
-                write!(f, "{type_str} ({attr_str})")
-            }
+// # Internal utilities
+impl TopologyObject {
+    /// Display the TopologyObject's type and attributes
+    fn display(&self, f: &mut fmt::Formatter, verbose: bool) -> fmt::Result {
+        let type_str = self.type_string(verbose);
+        let separator = if f.alternate() { "\n  " } else { "  " };
+        let attr_str = self
+            .attributes_string(separator, verbose)
+            .expect("Separator does not contain NUL chars");
+        if attr_str.is_empty() {
+            write!(f, "{type_str}")
+        } else if f.alternate() {
+            write!(f, "{type_str} (\n  {attr_str}\n)")
+        } else {
+            write!(f, "{type_str} ({attr_str})")
         }
     }
 }
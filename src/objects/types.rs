@@ -1,7 +1,7 @@
 //! Object types
 
-// - Enums: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__object__types.html
-// - Kinds: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__helper__types.html
+// - Enums: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__object__types.html
+// - Kinds: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__helper__types.html
 
 use crate::{errors, ffi};
 #[cfg(doc)]
@@ -36,6 +36,9 @@ pub(crate) type RawObjectType = c_uint;
 /// - A > B if objects of `ObjectType::A` are included in type `ObjectType::B`.
 /// - [`ObjectType::Machine`] is always the highest and [`ObjectType::PU`] is
 ///   always the deepest.
+/// - A and B are unordered (`partial_cmp` returns `None`) if one of them is a
+///   memory, I/O or Misc object type and the other is a normal object type
+///   other than [`ObjectType::Machine`].
 ///
 /// It can also help to think of it as comparing the relative depths of each type, so
 /// a `ObjectType::Machine` will be smaller than a `ObjectType::PU` since the machine
@@ -216,25 +219,41 @@ impl ObjectType {
     /// Truth that this type is part of the normal hierarchy (not Memory, I/O or Misc)
     #[doc(alias = "hwloc_obj_type_is_normal")]
     pub fn is_normal(&self) -> bool {
-        unsafe { self.type_predicate("hwloc_obj_type_is_normal", ffi::hwloc_obj_type_is_normal) }
+        unsafe {
+            self.type_predicate("hwloc_obj_type_is_normal", |ty| {
+                ffi::hwloc_obj_type_is_normal(ty)
+            })
+        }
     }
 
     /// Truth that this is a CPU-side cache type (not MemCache)
     #[doc(alias = "hwloc_obj_type_is_cache")]
     pub fn is_cpu_cache(&self) -> bool {
-        unsafe { self.type_predicate("hwloc_obj_type_is_cache", ffi::hwloc_obj_type_is_cache) }
+        unsafe {
+            self.type_predicate("hwloc_obj_type_is_cache", |ty| {
+                ffi::hwloc_obj_type_is_cache(ty)
+            })
+        }
     }
 
     /// Truth that this is a CPU-side data or unified cache type (not MemCache)
     #[doc(alias = "hwloc_obj_type_is_dcache")]
     pub fn is_cpu_data_cache(&self) -> bool {
-        unsafe { self.type_predicate("hwloc_obj_type_is_dcache", ffi::hwloc_obj_type_is_dcache) }
+        unsafe {
+            self.type_predicate("hwloc_obj_type_is_dcache", |ty| {
+                ffi::hwloc_obj_type_is_dcache(ty)
+            })
+        }
     }
 
     /// Truth that this is a CPU-side instruction cache type (not MemCache)
     #[doc(alias = "hwloc_obj_type_is_icache")]
     pub fn is_cpu_instruction_cache(&self) -> bool {
-        unsafe { self.type_predicate("hwloc_obj_type_is_icache", ffi::hwloc_obj_type_is_icache) }
+        unsafe {
+            self.type_predicate("hwloc_obj_type_is_icache", |ty| {
+                ffi::hwloc_obj_type_is_icache(ty)
+            })
+        }
     }
 
     /// Truth that this is a memory object type (not Normal, I/O or Misc)
@@ -244,7 +263,11 @@ impl ObjectType {
     /// instead of normal depths like other objects in the main tree.
     #[doc(alias = "hwloc_obj_type_is_memory")]
     pub fn is_memory(&self) -> bool {
-        unsafe { self.type_predicate("hwloc_obj_type_is_memory", ffi::hwloc_obj_type_is_memory) }
+        unsafe {
+            self.type_predicate("hwloc_obj_type_is_memory", |ty| {
+                ffi::hwloc_obj_type_is_memory(ty)
+            })
+        }
     }
 
     /// Truth that this is an I/O object type (not Normal, Memory or Misc)
@@ -255,7 +278,7 @@ impl ObjectType {
     /// dedicated I/O children list.
     #[doc(alias = "hwloc_obj_type_is_io")]
     pub fn is_io(&self) -> bool {
-        unsafe { self.type_predicate("hwloc_obj_type_is_io", ffi::hwloc_obj_type_is_io) }
+        unsafe { self.type_predicate("hwloc_obj_type_is_io", |ty| ffi::hwloc_obj_type_is_io(ty)) }
     }
 
     /// Truth that this object type is a leaf of the normal hierarchy and
@@ -320,14 +343,15 @@ impl ObjectType {
     unsafe fn type_predicate(
         &self,
         api: &'static str,
-        pred: unsafe extern "C" fn(RawObjectType) -> c_int,
+        pred: impl FnOnce(RawObjectType) -> c_int,
     ) -> bool {
-        errors::call_hwloc_bool(api, || unsafe { pred(self.to_raw()) })
+        errors::call_hwloc_bool(api, || pred(self.to_raw()))
             .expect("Object type queries should not fail")
     }
 }
 
 impl PartialOrd for ObjectType {
+    #[doc(alias = "hwloc_compare_types")]
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         let result = unsafe { ffi::hwloc_compare_types(self.to_raw(), other.to_raw()) };
         match result {
@@ -340,6 +364,59 @@ impl PartialOrd for ObjectType {
     }
 }
 
+/// Raw value of an hwloc enum variant that is not known to these bindings
+///
+/// This opaque payload of the `Unknown` variant of some enums can only be
+/// created by the bindings, when hwloc reports a value that does not match
+/// any known variant. This guarantees that an `Unknown` variant never holds
+/// the raw value of a known variant, so that equality and hashing behave as
+/// expected.
+#[derive(Copy, Clone, Debug, Display, Eq, Hash, PartialEq)]
+pub struct UnknownVariant<T>(T);
+//
+impl<T> UnknownVariant<T> {
+    /// Wrap a raw value that does not match any known variant
+    ///
+    /// Callers must check that the value is not one of a known variant.
+    pub(crate) fn from_raw(raw: T) -> Self {
+        Self(raw)
+    }
+}
+//
+impl<T: Copy> UnknownVariant<T> {
+    /// Raw value, as reported by hwloc
+    pub fn get(self) -> T {
+        self.0
+    }
+}
+
+/// Implement conversions between an enum with an `Unknown` fallback variant
+/// and its raw hwloc representation
+///
+/// Converting from the raw representation always yields a known variant when
+/// there is one, so `Unknown` variants never alias known ones.
+macro_rules! impl_raw_conversions {
+    ($enum:ident, $raw:ty, { $($variant:ident = $value:literal),* $(,)? }) => {
+        impl From<$raw> for $enum {
+            fn from(value: $raw) -> Self {
+                match value {
+                    $( $value => Self::$variant, )*
+                    unknown => Self::Unknown(UnknownVariant::from_raw(unknown)),
+                }
+            }
+        }
+        //
+        impl From<$enum> for $raw {
+            fn from(value: $enum) -> Self {
+                match value {
+                    $( $enum::$variant => $value, )*
+                    $enum::Unknown(unknown) => unknown.get(),
+                }
+            }
+        }
+    };
+}
+
 /// Rust mapping of the hwloc_obj_bridge_type_e enum
 ///
 /// We can't use Rust enums to model C enums in FFI because that results in
@@ -348,10 +425,10 @@ impl PartialOrd for ObjectType {
 pub(crate) type RawBridgeType = c_uint;
 
 /// Type of one side (upstream or downstream) of an I/O bridge.
-#[derive(Copy, Clone, Debug, Display, Eq, Hash, IntoPrimitive, TryFromPrimitive, PartialEq)]
+#[derive(Copy, Clone, Debug, Display, Eq, Hash, PartialEq)]
 #[doc(alias = "hwloc_obj_bridge_type_e")]
 #[doc(alias = "hwloc_obj_bridge_type_t")]
-#[repr(u32)]
+#[non_exhaustive]
 pub enum BridgeType {
     /// Host-side of a bridge, only possible upstream
     #[doc(alias = "HWLOC_OBJ_BRIDGE_HOST")]
@@ -360,8 +437,18 @@ pub enum BridgeType {
     /// PCI-side of a bridge
     #[doc(alias = "HWLOC_OBJ_BRIDGE_PCI")]
     PCI,
+
+    /// Unknown bridge type
+    ///
+    /// This bridge type is not known to this version of the Rust bindings,
+    /// which likely means that it was introduced by a newer hwloc release.
+    #[display(fmt = "Unknown({_0})")]
+    Unknown(UnknownVariant<u32>),
 }
 
+//
+impl_raw_conversions!(BridgeType, u32, { Host = 0, PCI = 1 });
+
 /// Rust mapping of the hwloc_obj_cache_type_e enum
 ///
 /// We can't use Rust enums to model C enums in FFI because that results in
@@ -370,10 +457,10 @@ pub enum BridgeType {
 pub(crate) type RawCacheType = c_uint;
 
 /// Cache type
-#[derive(Copy, Clone, Debug, Display, Eq, Hash, IntoPrimitive, TryFromPrimitive, PartialEq)]
+#[derive(Copy, Clone, Debug, Display, Eq, Hash, PartialEq)]
 #[doc(alias = "hwloc_obj_cache_type_e")]
 #[doc(alias = "hwloc_obj_cache_type_t")]
-#[repr(u32)]
+#[non_exhaustive]
 pub enum CacheType {
     /// Unified cache
     #[doc(alias = "HWLOC_OBJ_CACHE_UNIFIED")]
@@ -383,11 +470,21 @@ pub enum CacheType {
     #[doc(alias = "HWLOC_OBJ_CACHE_DATA")]
     Data,
 
-    /// Instruction cache (filtered out by default
+    /// Instruction cache (filtered out by default)
     #[doc(alias = "HWLOC_OBJ_CACHE_INSTRUCTION")]
     Instruction,
+
+    /// Unknown cache type
+    ///
+    /// This cache type is not known to this version of the Rust bindings,
+    /// which likely means that it was introduced by a newer hwloc release.
+    #[display(fmt = "Unknown({_0})")]
+    Unknown(UnknownVariant<u32>),
 }
 
+//
+impl_raw_conversions!(CacheType, u32, { Unified = 0, Data = 1, Instruction = 2 });
+
 /// Rust mapping of the hwloc_obj_osdev_type_e enum
 ///
 /// We can't use Rust enums to model C enums in FFI because that results in
@@ -396,10 +493,10 @@ pub enum CacheType {
 pub(crate) type RawOSDeviceType = c_uint;
 
 /// Type of a OS device
-#[derive(Copy, Clone, Debug, Display, Eq, Hash, IntoPrimitive, TryFromPrimitive, PartialEq)]
+#[derive(Copy, Clone, Debug, Display, Eq, Hash, PartialEq)]
 #[doc(alias = "hwloc_obj_osdev_type_e")]
 #[doc(alias = "hwloc_obj_osdev_type_t")]
-#[repr(u32)]
+#[non_exhaustive]
 pub enum OSDeviceType {
     /// Operating system storage device (e.g. block)
     ///
@@ -445,11 +542,99 @@ pub enum OSDeviceType {
     /// "dax2.0" on Linux.
     #[doc(alias = "HWLOC_OBJ_OSDEV_MEMORY")]
     Memory,
+
+    /// Unknown OS device type
+    ///
+    /// This OS device type is not known to this version of the Rust bindings,
+    /// which likely means that it was introduced by a newer hwloc release.
+    #[display(fmt = "Unknown({_0})")]
+    Unknown(UnknownVariant<u32>),
 }
+//
+impl_raw_conversions!(OSDeviceType, u32, {
+    Storage = 0,
+    GPU = 1,
+    Network = 2,
+    OpenFabrics = 3,
+    DMA = 4,
+    CoProcessor = 5,
+    Memory = 6,
+});
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::ffi::CStr;
+
+    /// Name that hwloc is expected to give to an object type
+    fn expected_hwloc_name(ty: ObjectType) -> &'static str {
+        match ty {
+            ObjectType::Machine => "Machine",
+            ObjectType::Package => "Package",
+            ObjectType::Core => "Core",
+            ObjectType::PU => "PU",
+            ObjectType::L1Cache => "L1Cache",
+            ObjectType::L2Cache => "L2Cache",
+            ObjectType::L3Cache => "L3Cache",
+            ObjectType::L4Cache => "L4Cache",
+            ObjectType::L5Cache => "L5Cache",
+            ObjectType::L1ICache => "L1iCache",
+            ObjectType::L2ICache => "L2iCache",
+            ObjectType::L3ICache => "L3iCache",
+            ObjectType::Group => "Group",
+            ObjectType::NUMANode => "NUMANode",
+            ObjectType::Bridge => "Bridge",
+            ObjectType::PCIDevice => "PCIDev",
+            ObjectType::OSDevice => "OSDev",
+            ObjectType::Misc => "Misc",
+            #[cfg(feature = "hwloc-2_1_0")]
+            ObjectType::MemCache => "MemCache",
+            #[cfg(feature = "hwloc-2_1_0")]
+            ObjectType::Die => "Die",
+        }
+    }
+
+    #[test]
+    fn object_types_should_match_hwloc() {
+        for raw in 0..64 {
+            let name = unsafe { CStr::from_ptr(ffi::hwloc_obj_type_string(raw)) }
+                .to_str()
+                .expect("hwloc object type names should be ASCII");
+            match ObjectType::try_from(raw) {
+                Ok(ty) => assert_eq!(
+                    name,
+                    expected_hwloc_name(ty),
+                    "hwloc and hwlocality disagree on object type #{raw}"
+                ),
+                // No object type was added since hwloc v2.1, so from this
+                // version onwards, every type known to hwloc should be mapped
+                Err(_) if cfg!(feature = "hwloc-2_1_0") => assert_eq!(
+                    name, "Unknown",
+                    "hwloc object type #{raw} ({name}) is not mapped by hwlocality"
+                ),
+                Err(_) => {}
+            }
+        }
+    }
+
+    #[test]
+    fn unknown_variants_should_not_alias_known_ones() {
+        for raw in 0..64 {
+            let bridge = BridgeType::from(raw);
+            assert_eq!(u32::from(bridge), raw);
+            assert_eq!(matches!(bridge, BridgeType::Unknown(_)), raw > 1);
+
+            let cache = CacheType::from(raw);
+            assert_eq!(u32::from(cache), raw);
+            assert_eq!(matches!(cache, CacheType::Unknown(_)), raw > 2);
+
+            let osdev = OSDeviceType::from(raw);
+            assert_eq!(u32::from(osdev), raw);
+            assert_eq!(matches!(osdev, OSDeviceType::Unknown(_)), raw > 6);
+        }
+        assert_eq!(CacheType::from(0), CacheType::Unified);
+        assert_eq!(OSDeviceType::from(42).to_string(), "Unknown(42)");
+    }
 
     #[test]
     fn should_compare_object_types() {
@@ -458,5 +643,7 @@ mod tests {
 
         assert!(ObjectType::Machine < ObjectType::PU);
         assert!(ObjectType::PU > ObjectType::L1Cache);
+
+        assert_eq!(ObjectType::Bridge.partial_cmp(&ObjectType::Core), None);
     }
 }
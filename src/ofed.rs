@@ -0,0 +1,85 @@
+//! OpenFabrics Verbs-specific helpers
+
+use crate::{
+    cpu::cpusets::CpuSet,
+    objects::{types::OSDeviceType, TopologyObject},
+    topology::Topology,
+};
+
+/// # OpenFabrics Verbs-specific helpers
+///
+/// These functions locate InfiniBand and RoCE HCAs in the topology by their
+/// Verbs device name (e.g. `mlx5_0`, as returned by `ibv_get_device_name()`),
+/// so that RDMA runtimes can e.g. place completion-polling threads close to
+/// the HCA.
+///
+/// Unlike their hwloc counterparts, which take a `struct ibv_device*`, they do
+/// not link to libibverbs. Devices are instead identified by the OpenFabrics
+/// OS devices that hwloc adds to topologies built with I/O objects (see
+/// [`TopologyBuilder::with_io_type_filter()`]).
+///
+/// [`TopologyBuilder::with_io_type_filter()`]: crate::topology::builder::TopologyBuilder::with_io_type_filter()
+//
+// Upstream docs: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__openfabrics.html
+impl Topology {
+    /// Get the hwloc OS device object corresponding to the OpenFabrics Verbs
+    /// device named `name`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// if let Some(hca) = topology.ibv_device_os_device("mlx5_0") {
+    ///     println!("mlx5_0 is attached to {}", hca.non_io_ancestor());
+    /// }
+    /// ```
+    #[doc(alias = "hwloc_ibv_get_device_osdev")]
+    #[doc(alias = "hwloc_ibv_get_device_osdev_by_name")]
+    pub fn ibv_device_os_device(&self, name: &str) -> Option<&TopologyObject> {
+        self.os_device_with_name(OSDeviceType::OpenFabrics, name)
+    }
+
+    /// Get the CPU set of processors that are physically close to the
+    /// OpenFabrics Verbs device named `name`
+    ///
+    /// This is the cpuset of the [`TopologyObject::non_io_ancestor()`] of the
+    /// OS device returned by [`ibv_device_os_device()`]. On Linux, if this OS
+    /// device is not part of the topology (e.g. because I/O objects were
+    /// filtered out), the device's locality is read from sysfs instead, like
+    /// hwloc does.
+    ///
+    /// Returns `None` if the device cannot be located.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::cpu::binding::CpuBindingFlags;
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// // Run the completion-polling thread close to the HCA
+    /// if let Some(cpuset) = topology.ibv_device_cpuset("mlx5_0") {
+    ///     topology.bind_cpu(&cpuset, CpuBindingFlags::THREAD)?;
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    ///
+    /// [`ibv_device_os_device()`]: Topology::ibv_device_os_device()
+    #[doc(alias = "hwloc_ibv_get_device_cpuset")]
+    pub fn ibv_device_cpuset(&self, name: &str) -> Option<CpuSet> {
+        if let Some(osdev) = self.ibv_device_os_device(name) {
+            return osdev.non_io_ancestor_cpuset();
+        }
+        #[cfg(target_os = "linux")]
+        {
+            if !self.is_this_system() || name.contains('/') {
+                return None;
+            }
+            let path = format!("/sys/class/infiniband/{name}/device/local_cpus");
+            self.read_path_as_cpumask(path)
+                .ok()
+                .map(|cpuset| cpuset & self.cpuset())
+                .filter(|cpuset| !cpuset.is_empty())
+        }
+        #[cfg(not(target_os = "linux"))]
+        None
+    }
+}
@@ -0,0 +1,734 @@
+//! Thread placement plans
+//!
+//! A [`PlacementPlan`] maps named thread roles to locations in the topology,
+//! expressed as type/index paths like `Package:0.Core:1` that are stable across
+//! machines with the same hardware. Computing a plan and applying it are
+//! separate steps, so that a plan can be computed offline on a topology
+//! snapshot (e.g. loaded from XML), reviewed and stored in version control,
+//! then replayed on production nodes.
+//!
+//! Plans can be converted to and from a line-based text format using their
+//! [`Display`](fmt::Display) and [`FromStr`] implementations. With the `serde`
+//! feature, they can also be serialized and deserialized with serde.
+
+use crate::{
+    cpu::{
+        binding::{CpuBindingError, CpuBindingFlags},
+        cpusets::CpuSet,
+    },
+    errors::HybridError,
+    objects::{
+        types::{ObjectType, RawObjectType},
+        MissingCpuSetError, TopologyObject,
+    },
+    topology::Topology,
+};
+use std::{fmt, str::FromStr};
+use thiserror::Error;
+
+/// Assignment of named thread roles to topology locations
+///
+/// See the [module-level documentation](self) for more information.
+///
+/// This functionality is unique to the Rust hwloc bindings.
+///
+/// # Examples
+///
+/// ```
+/// # use hwlocality::placement::PlacementPlan;
+/// # let topology = hwlocality::Topology::test_instance();
+/// // Compute the plan, this could be done on another machine
+/// let plan = PlacementPlan::compute(&topology, &["network", "storage", "compute"])?;
+/// let text = plan.to_string();
+///
+/// // Parse it back and apply it, as would be done on the production node
+/// let plan = text.parse::<PlacementPlan>()?;
+/// assert_eq!(plan.entries().len(), 3);
+/// match plan.apply(&topology, "compute") {
+///     Ok(cpuset) => println!("Bound role compute to cpuset {cpuset}"),
+///     Err(e) => eprintln!("Failed to apply the compute placement: {e}"),
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PlacementPlan {
+    /// Entries of the plan, at most one per role
+    entries: Vec<PlacementEntry>,
+}
+//
+impl PlacementPlan {
+    /// Assign each role to a core, spreading roles across packages first
+    ///
+    /// If there are more roles than cores, roles wrap around and share cores.
+    /// Threads are bound with [`CpuBindingFlags::THREAD`].
+    ///
+    /// # Errors
+    ///
+    /// - [`NoCores`] if the topology does not contain any core.
+    /// - [`InvalidRole`] if a role is empty, contains whitespace, or appears
+    ///   multiple times.
+    ///
+    /// [`InvalidRole`]: PlacementError::InvalidRole
+    /// [`NoCores`]: PlacementError::NoCores
+    pub fn compute(topology: &Topology, roles: &[&str]) -> Result<Self, PlacementError> {
+        // List core locations, interleaving packages
+        let mut per_package = Vec::new();
+        for (package_idx, package) in topology.objects_with_type(ObjectType::Package).enumerate() {
+            let cpuset = package.cpuset().ok_or(MissingCpuSetError)?;
+            let num_cores = topology
+                .objects_inside_cpuset_with_type(cpuset, ObjectType::Core)
+                .count();
+            per_package.push((0..num_cores).map(move |core_idx| {
+                vec![
+                    (ObjectType::Package, package_idx),
+                    (ObjectType::Core, core_idx),
+                ]
+            }));
+        }
+        let mut locations = Vec::new();
+        loop {
+            let len_before = locations.len();
+            locations.extend(per_package.iter_mut().filter_map(Iterator::next));
+            if locations.len() == len_before {
+                break;
+            }
+        }
+        if locations.is_empty() {
+            return Err(PlacementError::NoCores);
+        }
+
+        // Assign roles to locations, wrapping around if there are more roles
+        let mut plan = Self::default();
+        for (role, location) in roles.iter().zip(locations.iter().cycle()) {
+            plan.push(PlacementEntry {
+                role: (*role).to_owned(),
+                location: location.clone(),
+                flags: CpuBindingFlags::THREAD,
+            })?;
+        }
+        Ok(plan)
+    }
+
+    /// Entries of the plan
+    pub fn entries(&self) -> &[PlacementEntry] {
+        &self.entries
+    }
+
+    /// Add an entry to the plan
+    ///
+    /// # Errors
+    ///
+    /// [`InvalidRole`](PlacementError::InvalidRole) if the role of the entry
+    /// is empty, contains whitespace, or already has an entry.
+    pub fn push(&mut self, entry: PlacementEntry) -> Result<(), PlacementError> {
+        if entry.role.is_empty()
+            || entry.role.contains(char::is_whitespace)
+            || self.entries.iter().any(|other| other.role == entry.role)
+        {
+            return Err(PlacementError::InvalidRole(entry.role));
+        }
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    /// Bind the current thread according to the entry for `role`
+    ///
+    /// Returns the cpuset that the thread was bound to.
+    ///
+    /// # Errors
+    ///
+    /// - [`UnknownRole`] if the plan has no entry for `role`.
+    /// - [`MissingLocation`] if the location of `role` does not exist in
+    ///   `topology`.
+    /// - [`MissingCpuSet`] if the location goes through an object type which
+    ///   does not have a cpuset.
+    /// - [`Binding`] if binding the current thread failed.
+    ///
+    /// [`Binding`]: PlacementError::Binding
+    /// [`MissingCpuSet`]: PlacementError::MissingCpuSet
+    /// [`MissingLocation`]: PlacementError::MissingLocation
+    /// [`UnknownRole`]: PlacementError::UnknownRole
+    pub fn apply(&self, topology: &Topology, role: &str) -> Result<CpuSet, PlacementError> {
+        let entry = self
+            .entries
+            .iter()
+            .find(|entry| entry.role == role)
+            .ok_or_else(|| PlacementError::UnknownRole(role.to_owned()))?;
+        let target = entry.resolve(topology)?;
+        let cpuset = target.cpuset().ok_or(MissingCpuSetError)?.to_owned();
+        topology.bind_cpu(&cpuset, entry.flags)?;
+        Ok(cpuset)
+    }
+}
+//
+impl fmt::Display for PlacementPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            writeln!(f, "{entry}")?;
+        }
+        Ok(())
+    }
+}
+//
+impl FromStr for PlacementPlan {
+    type Err = PlacementError;
+
+    /// Parse a plan, one [entry](PlacementEntry) per line
+    ///
+    /// Empty lines and lines starting with `#` are ignored.
+    fn from_str(s: &str) -> Result<Self, PlacementError> {
+        let mut plan = Self::default();
+        for line in s
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        {
+            plan.push(line.parse()?)?;
+        }
+        Ok(plan)
+    }
+}
+
+/// Placement of one thread role
+///
+/// The text format of an entry is `role location [flags]`, for example
+/// `compute Package:0.Core:1 THREAD|STRICT`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PlacementEntry {
+    /// Name of the thread role
+    pub role: String,
+
+    /// Path from the root object to the target object, in the format
+    /// accepted by [`Topology::object_by_type_index_path()`]
+    pub location: Vec<(ObjectType, usize)>,
+
+    /// Flags to be used when binding
+    pub flags: CpuBindingFlags,
+}
+//
+impl PlacementEntry {
+    /// Find the object that this entry targets
+    fn resolve<'topology>(
+        &self,
+        topology: &'topology Topology,
+    ) -> Result<&'topology TopologyObject, PlacementError> {
+        topology
+            .object_by_type_index_path(&self.location)?
+            .ok_or_else(|| PlacementError::MissingLocation(self.role.clone()))
+    }
+
+    /// Textual form of the location
+    fn location_string(&self) -> String {
+        self.location
+            .iter()
+            .map(|(ty, idx)| format!("{ty}:{idx}"))
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+
+    /// Textual form of the flags
+    fn flags_string(&self) -> String {
+        self.flags
+            .iter_names()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>()
+            .join("|")
+    }
+
+    /// Parse the textual form of a location
+    fn parse_location(location: &str) -> Result<Vec<(ObjectType, usize)>, PlacementError> {
+        location
+            .split('.')
+            .map(|step| {
+                let invalid = || PlacementError::InvalidLocation(location.to_owned());
+                let (ty, idx) = step.split_once(':').ok_or_else(invalid)?;
+                let ty = parse_object_type(ty).ok_or_else(invalid)?;
+                Ok((ty, idx.parse().map_err(|_| invalid())?))
+            })
+            .collect()
+    }
+
+    /// Parse the textual form of flags
+    fn parse_flags(flags: &str) -> Result<CpuBindingFlags, PlacementError> {
+        flags
+            .split('|')
+            .filter(|name| !name.is_empty())
+            .map(|name| {
+                CpuBindingFlags::from_name(name)
+                    .ok_or_else(|| PlacementError::InvalidFlags(flags.to_owned()))
+            })
+            .collect()
+    }
+}
+//
+impl fmt::Display for PlacementEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.role, self.location_string())?;
+        if !self.flags.is_empty() {
+            write!(f, " {}", self.flags_string())?;
+        }
+        Ok(())
+    }
+}
+//
+impl FromStr for PlacementEntry {
+    type Err = PlacementError;
+
+    fn from_str(line: &str) -> Result<Self, PlacementError> {
+        let mut words = line.split_whitespace();
+        let (Some(role), Some(location), flags, None) =
+            (words.next(), words.next(), words.next(), words.next())
+        else {
+            return Err(PlacementError::InvalidEntry(line.to_owned()));
+        };
+        Ok(Self {
+            role: role.to_owned(),
+            location: Self::parse_location(location)?,
+            flags: Self::parse_flags(flags.unwrap_or_default())?,
+        })
+    }
+}
+
+/// Parse the name of an object type that has a cpuset
+fn parse_object_type(name: &str) -> Option<ObjectType> {
+    // hwloc has much fewer object types than this
+    (0..RawObjectType::from(64u8))
+        .filter_map(|raw| ObjectType::try_from(raw).ok())
+        .filter(|ty| ty.is_normal() || ty.is_memory())
+        .find(|ty| ty.to_string() == name)
+}
+
+/// Error while computing, parsing or applying a [`PlacementPlan`]
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+pub enum PlacementError {
+    /// The topology does not contain any core to place threads on
+    #[error("placement plans need at least one core")]
+    NoCores,
+
+    /// A role is empty, contains whitespace, or appears multiple times
+    #[error("role {0:?} is empty, contains whitespace or is duplicated")]
+    InvalidRole(String),
+
+    /// A plan entry does not follow the `role location [flags]` format
+    #[error("expected 'role location [flags]', got {0:?}")]
+    InvalidEntry(String),
+
+    /// A location does not follow the `Type:index.Type:index` format
+    #[error("expected a location like 'Package:0.Core:1', got {0:?}")]
+    InvalidLocation(String),
+
+    /// Binding flags contain an unknown flag name
+    #[error("unknown binding flags {0:?}")]
+    InvalidFlags(String),
+
+    /// The plan does not have an entry for this role
+    #[error("no placement for role {0:?}")]
+    UnknownRole(String),
+
+    /// The location of this role does not exist in the topology
+    #[error("location of role {0:?} does not exist in this topology")]
+    MissingLocation(String),
+
+    /// An object without a cpuset was encountered
+    #[error(transparent)]
+    MissingCpuSet(#[from] MissingCpuSetError),
+
+    /// Binding the current thread failed
+    #[error(transparent)]
+    Binding(#[from] HybridError<CpuBindingError>),
+}
+
+#[cfg(feature = "serde")]
+mod serde_impls {
+    use super::{PlacementEntry, PlacementPlan};
+    use serde_core::{
+        de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor},
+        ser::{Serialize, SerializeStruct, Serializer},
+    };
+    use std::fmt;
+
+    /// Field names of a serialized [`PlacementEntry`]
+    const ENTRY_FIELDS: &[&str] = &["role", "location", "flags"];
+
+    impl Serialize for PlacementPlan {
+        /// Plans are serialized as a sequence of entries
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            self.entries.serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for PlacementPlan {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let mut plan = Self::default();
+            for entry in Vec::<PlacementEntry>::deserialize(deserializer)? {
+                plan.push(entry).map_err(de::Error::custom)?;
+            }
+            Ok(plan)
+        }
+    }
+
+    impl Serialize for PlacementEntry {
+        /// Entries are serialized as a struct whose location and flags use the
+        /// same textual form as in the [`Display`](fmt::Display) output
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut entry = serializer.serialize_struct("PlacementEntry", 3)?;
+            entry.serialize_field("role", &self.role)?;
+            entry.serialize_field("location", &self.location_string())?;
+            entry.serialize_field("flags", &self.flags_string())?;
+            entry.end()
+        }
+    }
+
+    impl<'de> Deserialize<'de> for PlacementEntry {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_struct("PlacementEntry", ENTRY_FIELDS, EntryVisitor)
+        }
+    }
+
+    /// [`Visitor`] for [`PlacementEntry`]
+    struct EntryVisitor;
+    //
+    impl EntryVisitor {
+        /// Build an entry from its serialized fields
+        fn build<E: de::Error>(
+            role: String,
+            location: &str,
+            flags: &str,
+        ) -> Result<PlacementEntry, E> {
+            Ok(PlacementEntry {
+                role,
+                location: PlacementEntry::parse_location(location).map_err(E::custom)?,
+                flags: PlacementEntry::parse_flags(flags).map_err(E::custom)?,
+            })
+        }
+    }
+    //
+    impl<'de> Visitor<'de> for EntryVisitor {
+        type Value = PlacementEntry;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a placement entry with role, location and flags")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<PlacementEntry, A::Error> {
+            let mut next = |idx| {
+                seq.next_element::<String>()?
+                    .ok_or_else(|| de::Error::invalid_length(idx, &self))
+            };
+            let role = next(0)?;
+            let location = next(1)?;
+            let flags = next(2)?;
+            Self::build(role, &location, &flags)
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<PlacementEntry, A::Error> {
+            let (mut role, mut location, mut flags) = (None, None, None);
+            while let Some(key) = map.next_key::<String>()? {
+                let field = match key.as_str() {
+                    "role" => &mut role,
+                    "location" => &mut location,
+                    "flags" => &mut flags,
+                    _ => return Err(de::Error::unknown_field(&key, ENTRY_FIELDS)),
+                };
+                if field.is_some() {
+                    return Err(de::Error::custom(format!("duplicate field `{key}`")));
+                }
+                *field = Some(map.next_value::<String>()?);
+            }
+            let role = role.ok_or_else(|| de::Error::missing_field("role"))?;
+            let location = location.ok_or_else(|| de::Error::missing_field("location"))?;
+            let flags = flags.unwrap_or_default();
+            Self::build(role, &location, &flags)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_plan() -> PlacementPlan {
+        let mut plan = PlacementPlan::default();
+        plan.push(PlacementEntry {
+            role: "network".to_owned(),
+            location: vec![(ObjectType::Package, 0), (ObjectType::Core, 1)],
+            flags: CpuBindingFlags::THREAD | CpuBindingFlags::STRICT,
+        })
+        .unwrap();
+        plan.push(PlacementEntry {
+            role: "compute".to_owned(),
+            location: vec![(ObjectType::NUMANode, 0)],
+            flags: CpuBindingFlags::empty(),
+        })
+        .unwrap();
+        plan
+    }
+
+    #[test]
+    fn text_round_trip() {
+        let plan = test_plan();
+        let text = plan.to_string();
+        assert_eq!(
+            text,
+            "network Package:0.Core:1 THREAD|STRICT\ncompute NUMANode:0\n"
+        );
+        assert_eq!(text.parse::<PlacementPlan>(), Ok(plan));
+    }
+
+    #[test]
+    fn invalid_text_should_be_rejected() {
+        for (text, error) in [
+            (
+                "network",
+                PlacementError::InvalidEntry("network".to_owned()),
+            ),
+            (
+                "network Package:x",
+                PlacementError::InvalidLocation("Package:x".to_owned()),
+            ),
+            (
+                "network Bridge:0",
+                PlacementError::InvalidLocation("Bridge:0".to_owned()),
+            ),
+            (
+                "network PU:0 FAST",
+                PlacementError::InvalidFlags("FAST".to_owned()),
+            ),
+            (
+                "network PU:0\nnetwork PU:1",
+                PlacementError::InvalidRole("network".to_owned()),
+            ),
+        ] {
+            assert_eq!(text.parse::<PlacementPlan>(), Err(error));
+        }
+    }
+
+    #[test]
+    fn computed_plans_should_resolve() {
+        let topology = Topology::test_instance();
+        let roles = ["a", "b", "c", "d", "e"];
+        let plan = PlacementPlan::compute(topology, &roles).unwrap();
+        assert_eq!(plan.entries().len(), roles.len());
+        for entry in plan.entries() {
+            let target = entry.resolve(topology).unwrap();
+            assert_eq!(target.object_type(), ObjectType::Core);
+        }
+        assert_eq!(
+            plan.apply(topology, "z"),
+            Err(PlacementError::UnknownRole("z".to_owned()))
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    mod serde {
+        use super::*;
+        use serde_core::{
+            de::{
+                value::{Error, MapDeserializer, SeqDeserializer},
+                Deserialize, IntoDeserializer,
+            },
+            ser::{self, Impossible, Serialize, SerializeSeq, SerializeStruct},
+        };
+
+        /// Minimal serialized data model, sufficient for placement plans
+        #[derive(Clone, Debug, PartialEq)]
+        enum Value {
+            Str(String),
+            Seq(Vec<Value>),
+            Struct(Vec<(&'static str, Value)>),
+        }
+
+        /// Serializer into [`Value`]
+        struct ValueSerializer;
+        //
+        macro_rules! unsupported {
+            ($($method:ident($($arg:ty),*) -> $ret:ty;)*) => {
+                $(
+                    fn $method(self, $(_: $arg),*) -> Result<$ret, Error> {
+                        Err(ser::Error::custom(stringify!($method)))
+                    }
+                )*
+            };
+        }
+        //
+        impl ser::Serializer for ValueSerializer {
+            type Ok = Value;
+            type Error = Error;
+            type SerializeSeq = SeqSerializer;
+            type SerializeTuple = Impossible<Value, Error>;
+            type SerializeTupleStruct = Impossible<Value, Error>;
+            type SerializeTupleVariant = Impossible<Value, Error>;
+            type SerializeMap = Impossible<Value, Error>;
+            type SerializeStruct = StructSerializer;
+            type SerializeStructVariant = Impossible<Value, Error>;
+
+            fn serialize_str(self, v: &str) -> Result<Value, Error> {
+                Ok(Value::Str(v.to_owned()))
+            }
+
+            fn serialize_seq(self, _len: Option<usize>) -> Result<SeqSerializer, Error> {
+                Ok(SeqSerializer(Vec::new()))
+            }
+
+            fn serialize_struct(
+                self,
+                _name: &'static str,
+                _len: usize,
+            ) -> Result<StructSerializer, Error> {
+                Ok(StructSerializer(Vec::new()))
+            }
+
+            unsupported! {
+                serialize_bool(bool) -> Value;
+                serialize_i8(i8) -> Value;
+                serialize_i16(i16) -> Value;
+                serialize_i32(i32) -> Value;
+                serialize_i64(i64) -> Value;
+                serialize_u8(u8) -> Value;
+                serialize_u16(u16) -> Value;
+                serialize_u32(u32) -> Value;
+                serialize_u64(u64) -> Value;
+                serialize_f32(f32) -> Value;
+                serialize_f64(f64) -> Value;
+                serialize_char(char) -> Value;
+                serialize_bytes(&[u8]) -> Value;
+                serialize_none() -> Value;
+                serialize_unit() -> Value;
+                serialize_unit_struct(&'static str) -> Value;
+                serialize_unit_variant(&'static str, u32, &'static str) -> Value;
+                serialize_tuple(usize) -> Impossible<Value, Error>;
+                serialize_tuple_struct(&'static str, usize) -> Impossible<Value, Error>;
+                serialize_tuple_variant(&'static str, u32, &'static str, usize)
+                    -> Impossible<Value, Error>;
+                serialize_map(Option<usize>) -> Impossible<Value, Error>;
+                serialize_struct_variant(&'static str, u32, &'static str, usize)
+                    -> Impossible<Value, Error>;
+            }
+
+            fn serialize_some<T: ?Sized + Serialize>(self, _value: &T) -> Result<Value, Error> {
+                Err(ser::Error::custom("serialize_some"))
+            }
+
+            fn serialize_newtype_struct<T: ?Sized + Serialize>(
+                self,
+                _name: &'static str,
+                _value: &T,
+            ) -> Result<Value, Error> {
+                Err(ser::Error::custom("serialize_newtype_struct"))
+            }
+
+            fn serialize_newtype_variant<T: ?Sized + Serialize>(
+                self,
+                _name: &'static str,
+                _variant_index: u32,
+                _variant: &'static str,
+                _value: &T,
+            ) -> Result<Value, Error> {
+                Err(ser::Error::custom("serialize_newtype_variant"))
+            }
+        }
+
+        /// Sequence serializer into [`Value`]
+        struct SeqSerializer(Vec<Value>);
+        //
+        impl SerializeSeq for SeqSerializer {
+            type Ok = Value;
+            type Error = Error;
+
+            fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+                self.0.push(value.serialize(ValueSerializer)?);
+                Ok(())
+            }
+
+            fn end(self) -> Result<Value, Error> {
+                Ok(Value::Seq(self.0))
+            }
+        }
+
+        /// Struct serializer into [`Value`]
+        struct StructSerializer(Vec<(&'static str, Value)>);
+        //
+        impl SerializeStruct for StructSerializer {
+            type Ok = Value;
+            type Error = Error;
+
+            fn serialize_field<T: ?Sized + Serialize>(
+                &mut self,
+                key: &'static str,
+                value: &T,
+            ) -> Result<(), Error> {
+                self.0.push((key, value.serialize(ValueSerializer)?));
+                Ok(())
+            }
+
+            fn end(self) -> Result<Value, Error> {
+                Ok(Value::Struct(self.0))
+            }
+        }
+
+        /// Deserialize a plan from a [`Value`]
+        fn deserialize_plan(value: Value) -> Result<PlacementPlan, Error> {
+            let Value::Seq(entries) = value else {
+                panic!("Plans should serialize as sequences");
+            };
+            let entries = entries.into_iter().map(|entry| {
+                let Value::Struct(fields) = entry else {
+                    panic!("Entries should serialize as structs");
+                };
+                MapDeserializer::new(fields.into_iter().map(|(key, value)| {
+                    let Value::Str(value) = value else {
+                        panic!("Entry fields should serialize as strings");
+                    };
+                    (key, value)
+                }))
+            });
+            PlacementPlan::deserialize(SeqDeserializer::new(entries))
+        }
+
+        #[test]
+        fn serde_round_trip() {
+            let plan = test_plan();
+            let value = plan.serialize(ValueSerializer).unwrap();
+            assert_eq!(
+                value,
+                Value::Seq(vec![
+                    Value::Struct(vec![
+                        ("role", Value::Str("network".to_owned())),
+                        ("location", Value::Str("Package:0.Core:1".to_owned())),
+                        ("flags", Value::Str("THREAD|STRICT".to_owned())),
+                    ]),
+                    Value::Struct(vec![
+                        ("role", Value::Str("compute".to_owned())),
+                        ("location", Value::Str("NUMANode:0".to_owned())),
+                        ("flags", Value::Str(String::new())),
+                    ]),
+                ])
+            );
+            assert_eq!(deserialize_plan(value).unwrap(), plan);
+        }
+
+        #[test]
+        fn serde_should_reject_invalid_entries() {
+            let entry = |location: &str| {
+                Value::Struct(vec![
+                    ("role", Value::Str("network".to_owned())),
+                    ("location", Value::Str(location.to_owned())),
+                    ("flags", Value::Str("THREAD".to_owned())),
+                ])
+            };
+            assert!(deserialize_plan(Value::Seq(vec![entry("PU:x")])).is_err());
+            assert!(deserialize_plan(Value::Seq(vec![entry("PU:0"), entry("PU:1")])).is_err());
+            assert!(deserialize_plan(Value::Seq(vec![entry("PU:0")])).is_ok());
+        }
+
+        #[test]
+        fn entries_should_deserialize_from_sequences() {
+            let fields = ["network", "Package:0.Core:1", "THREAD|STRICT"];
+            let entry = PlacementEntry::deserialize(SeqDeserializer::<_, Error>::new(
+                fields.into_iter().map(IntoDeserializer::into_deserializer),
+            ))
+            .unwrap();
+            assert_eq!(entry, test_plan().entries()[0]);
+        }
+    }
+}
@@ -0,0 +1,63 @@
+//! ROCm SMI-specific helpers
+
+use crate::{
+    cpu::cpusets::CpuSet,
+    objects::{types::OSDeviceType, TopologyObject},
+    topology::Topology,
+};
+
+/// # ROCm SMI-specific helpers
+///
+/// These functions locate AMD GPUs in the topology by ROCm SMI device index,
+/// so that applications can tell which CPU cores are close to them.
+///
+/// Unlike their hwloc counterparts, they do not link to the ROCm SMI library.
+/// Devices are instead identified by the `rsmiN` OS devices that hwloc's RSMI
+/// component adds to topologies built with I/O objects (see
+/// [`TopologyBuilder::with_io_type_filter()`]), so they return `None` if hwloc
+/// was built without RSMI support.
+///
+/// [`TopologyBuilder::with_io_type_filter()`]: crate::topology::builder::TopologyBuilder::with_io_type_filter()
+//
+// Upstream docs: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__rsmi.html
+impl Topology {
+    /// Get the hwloc OS device object corresponding to the ROCm SMI device
+    /// with index `device`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// if let Some(gpu) = topology.rsmi_device_os_device(0) {
+    ///     println!("RSMI device 0 is {gpu}");
+    /// }
+    /// ```
+    #[doc(alias = "hwloc_rsmi_get_device_osdev")]
+    #[doc(alias = "hwloc_rsmi_get_device_osdev_by_index")]
+    pub fn rsmi_device_os_device(&self, device: usize) -> Option<&TopologyObject> {
+        self.os_device_with_name(OSDeviceType::GPU, &format!("rsmi{device}"))
+    }
+
+    /// Get the CPU set of processors that are physically close to the ROCm
+    /// SMI device with index `device`
+    ///
+    /// This is the cpuset of the [`TopologyObject::non_io_ancestor()`] of the
+    /// OS device returned by [`rsmi_device_os_device()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::cpu::binding::CpuBindingFlags;
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// if let Some(cpuset) = topology.rsmi_device_cpuset(0) {
+    ///     topology.bind_cpu(&cpuset, CpuBindingFlags::THREAD)?;
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    ///
+    /// [`rsmi_device_os_device()`]: Topology::rsmi_device_os_device()
+    #[doc(alias = "hwloc_rsmi_get_device_cpuset")]
+    pub fn rsmi_device_cpuset(&self, device: usize) -> Option<CpuSet> {
+        self.rsmi_device_os_device(device)?.non_io_ancestor_cpuset()
+    }
+}
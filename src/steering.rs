@@ -0,0 +1,447 @@
+//! Topology-aware steering of incoming network connections
+//!
+//! Network services that run one accept loop per worker thread can ask the
+//! Linux kernel to hand each incoming connection to a worker that runs where
+//! the connection's packets are received. This requires one `SO_REUSEPORT`
+//! listening socket per CPU, tagged with the `SO_INCOMING_CPU` socket option.
+//!
+//! [`AcceptSteering`] combines this with [`Topology::distribute_items()`] and
+//! thread binding. It plans workers across the NUMA nodes that are close to a
+//! network interface, sets up one listening socket per PU of each worker, and
+//! can spawn bound workers that serve connections from these sockets.
+//!
+//! This functionality is unique to the Rust hwloc bindings.
+
+use crate::{
+    bound_thread::{BoundJoinHandle, BoundThreadBuilder, SpawnBoundError},
+    cpu::cpusets::CpuSet,
+    memory::{binding::MemoryBindingPolicy, nodesets::NodeSet},
+    objects::types::{OSDeviceType, ObjectType},
+    topology::{
+        support::{CpuBindingSupport, FeatureSupport, MemoryBindingSupport},
+        DistributeFlags, Topology,
+    },
+};
+use std::{
+    ffi::c_int,
+    io,
+    net::{SocketAddr, TcpListener, TcpStream},
+    num::NonZeroUsize,
+    os::fd::{AsRawFd, FromRawFd},
+    sync::Arc,
+};
+use thiserror::Error;
+
+/// Plan for distributing accepted connections across bound workers
+///
+/// Locality domains are the NUMA nodes that have CPUs, NUMA nodes with
+/// identical cpusets being merged into a single domain. If a network
+/// interface is specified, only the domains that are close to it are used, so
+/// that packets do not need to cross the inter-socket interconnect. Inside of
+/// each domain, workers are spread using [`Topology::distribute_items()`].
+///
+/// See the [module-level documentation](self) for more information.
+///
+/// # Examples
+///
+/// ```
+/// # use hwlocality::steering::AcceptSteering;
+/// # use std::{io::{BufRead, BufReader, Write}, net::TcpStream, num::NonZeroUsize};
+/// # let topology = hwlocality::Topology::test_instance();
+/// let steering = AcceptSteering::new(&topology, None, NonZeroUsize::new(2).unwrap())?;
+/// for domain in steering.domains() {
+///     println!("{} will use workers bound to {:?}", domain.name(), domain.workers());
+/// }
+///
+/// // Start an echo service
+/// let (address, _workers) = steering.serve(&topology, "127.0.0.1:0".parse()?, |stream| {
+///     let mut writer = stream.try_clone().unwrap();
+///     for line in BufReader::new(stream).lines().map_while(Result::ok) {
+///         let _ = writeln!(writer, "{line}");
+///     }
+/// })?;
+///
+/// // Talk to it
+/// let mut stream = TcpStream::connect(address)?;
+/// stream.write_all(b"Hello\n")?;
+/// let mut reply = String::new();
+/// BufReader::new(stream).read_line(&mut reply)?;
+/// assert_eq!(reply, "Hello\n");
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AcceptSteering {
+    /// Locality domains that serve connections
+    domains: Vec<SteeringDomain>,
+}
+//
+impl AcceptSteering {
+    /// Plan `workers_per_domain` workers in each locality domain
+    ///
+    /// If `nic` is the name of a network interface, only the domains that are
+    /// close to it are used. This requires a topology with I/O objects, see
+    /// [`TopologyBuilder::with_io_type_filter()`]. If none of the domains is
+    /// close to the interface, all of them are used.
+    ///
+    /// # Errors
+    ///
+    /// - [`UnknownInterfaceError`] if `nic` is not a network interface of the
+    ///   topology.
+    ///
+    /// [`TopologyBuilder::with_io_type_filter()`]: crate::topology::builder::TopologyBuilder::with_io_type_filter()
+    pub fn new(
+        topology: &Topology,
+        nic: Option<&str>,
+        workers_per_domain: NonZeroUsize,
+    ) -> Result<Self, UnknownInterfaceError> {
+        // Find where the NIC is attached, if any
+        let nic_cpuset = nic
+            .map(|nic| {
+                topology
+                    .os_device_with_name(OSDeviceType::Network, nic)
+                    .ok_or_else(|| UnknownInterfaceError(nic.to_owned()))
+                    .map(|osdev| osdev.non_io_ancestor_cpuset())
+            })
+            .transpose()?
+            .flatten();
+
+        // Pick domains, merging NUMA nodes with identical cpusets
+        let mut all_domains = Vec::<(String, CpuSet)>::new();
+        for node in topology.objects_with_type(ObjectType::NUMANode) {
+            let Some(cpuset) = node.cpuset() else {
+                continue;
+            };
+            if cpuset.is_empty() || all_domains.iter().any(|(_, set)| cpuset == set) {
+                continue;
+            }
+            all_domains.push((node.to_string(), cpuset.to_owned()));
+        }
+        let near_domains = all_domains
+            .iter()
+            .filter(|(_, cpuset)| {
+                nic_cpuset
+                    .as_ref()
+                    .map_or(true, |nic_cpuset| cpuset.intersects(nic_cpuset))
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        let domains = if near_domains.is_empty() {
+            all_domains
+        } else {
+            near_domains
+        };
+
+        // Spread workers inside of each domain
+        let domains = domains
+            .into_iter()
+            .map(|(name, cpuset)| {
+                let roots = topology
+                    .largest_objects_inside_cpuset(cpuset)
+                    .collect::<Vec<_>>();
+                let workers = topology
+                    .distribute_items(
+                        &roots,
+                        workers_per_domain,
+                        usize::MAX,
+                        DistributeFlags::empty(),
+                    )
+                    .expect("Domains have a non-empty cpuset, so roots can't be empty");
+                SteeringDomain { name, workers }
+            })
+            .collect();
+        Ok(Self { domains })
+    }
+
+    /// Locality domains that serve connections
+    pub fn domains(&self) -> &[SteeringDomain] {
+        &self.domains
+    }
+
+    /// Set up the listening sockets of each worker, in domain order
+    ///
+    /// Each worker gets one `SO_REUSEPORT` listening socket per PU of its
+    /// cpuset, tagged with this PU using `SO_INCOMING_CPU`. If the port of
+    /// `address` is 0, the port picked by the operating system for the first
+    /// socket is reused by all other sockets.
+    ///
+    /// # Errors
+    ///
+    /// Any error reported by the operating system while setting up sockets.
+    pub fn listen(&self, address: SocketAddr) -> io::Result<Vec<SteeredListener>> {
+        let mut address = address;
+        let mut listeners = Vec::new();
+        for cpuset in self.domains.iter().flat_map(|domain| &domain.workers) {
+            let mut sockets = Vec::new();
+            for pu in cpuset.iter_set() {
+                let socket = reuseport_listener(address, usize::from(pu))?;
+                address = socket.local_addr()?;
+                sockets.push(socket);
+            }
+            listeners.push(SteeredListener {
+                cpuset: cpuset.clone(),
+                sockets,
+            });
+        }
+        Ok(listeners)
+    }
+
+    /// Start the workers, each of which serves connections with `handler`
+    ///
+    /// Each worker is bound to its cpuset, along with its memory allocations
+    /// if the topology supports it, before it starts accepting connections.
+    /// Workers run until accepting a connection fails, and then return the
+    /// error through their [`BoundJoinHandle`].
+    ///
+    /// Returns the address that the service is listening on, along with the
+    /// handles of the workers.
+    ///
+    /// # Errors
+    ///
+    /// - [`Listen`] if the listening sockets could not be set up
+    /// - [`Spawn`] if a worker could not be spawned or bound
+    ///
+    /// [`Listen`]: ServeError::Listen
+    /// [`Spawn`]: ServeError::Spawn
+    pub fn serve(
+        &self,
+        topology: &Topology,
+        address: SocketAddr,
+        handler: impl Fn(TcpStream) + Send + Sync + 'static,
+    ) -> Result<(SocketAddr, Vec<BoundJoinHandle<io::Error>>), ServeError> {
+        let listeners = self.listen(address)?;
+        let address = listeners
+            .first()
+            .expect("There is at least one domain with one worker")
+            .local_addr()?;
+        let bind_cpu = topology.supports(
+            FeatureSupport::cpu_binding,
+            CpuBindingSupport::set_current_thread,
+        );
+        let bind_memory = topology.supports(
+            FeatureSupport::memory_binding,
+            MemoryBindingSupport::set_current_thread,
+        );
+        let handler = Arc::new(handler);
+        let mut workers = Vec::with_capacity(listeners.len());
+        for listener in listeners {
+            let mut builder = BoundThreadBuilder::new(topology);
+            if bind_cpu {
+                builder = builder.bind_cpu(listener.cpuset.clone());
+            }
+            if bind_memory {
+                builder = builder.bind_memory(
+                    NodeSet::from_cpuset(topology, &listener.cpuset),
+                    MemoryBindingPolicy::Bind,
+                );
+            }
+            let handler = handler.clone();
+            workers.push(builder.spawn(move || loop {
+                match listener.accept() {
+                    Ok((stream, _)) => handler(stream),
+                    Err(e) => return e,
+                }
+            })?);
+        }
+        Ok((address, workers))
+    }
+}
+
+/// Set of workers that serve connections from one locality domain
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SteeringDomain {
+    /// Human-readable name of the domain
+    name: String,
+
+    /// CPU set of each worker of the domain
+    workers: Vec<CpuSet>,
+}
+//
+impl SteeringDomain {
+    /// Human-readable name of the domain, e.g. `NUMANode#0`
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// CPU set of each worker of the domain
+    pub fn workers(&self) -> &[CpuSet] {
+        &self.workers
+    }
+}
+
+/// Listening sockets of one worker
+///
+/// There is one socket per PU of the worker's cpuset, so that the kernel can
+/// steer connections received on any of these PUs to this worker.
+#[derive(Debug)]
+pub struct SteeredListener {
+    /// CPUs that the worker should be bound to
+    cpuset: CpuSet,
+
+    /// Non-blocking listening sockets, one per PU of `cpuset`
+    sockets: Vec<TcpListener>,
+}
+//
+impl SteeredListener {
+    /// CPUs that the worker should be bound to
+    pub fn cpuset(&self) -> &CpuSet {
+        &self.cpuset
+    }
+
+    /// Address that the sockets are listening on
+    ///
+    /// # Errors
+    ///
+    /// Any error reported by the operating system.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.sockets
+            .first()
+            .expect("Workers have a non-empty cpuset, so there is one socket")
+            .local_addr()
+    }
+
+    /// Wait for an incoming connection on any of the worker's sockets
+    ///
+    /// # Errors
+    ///
+    /// Any error reported by the operating system, except for interrupted
+    /// waits which are retried.
+    pub fn accept(&self) -> io::Result<(TcpStream, SocketAddr)> {
+        loop {
+            let mut fds = self
+                .sockets
+                .iter()
+                .map(|socket| libc::pollfd {
+                    fd: socket.as_raw_fd(),
+                    events: libc::POLLIN,
+                    revents: 0,
+                })
+                .collect::<Vec<_>>();
+            let num_fds = libc::nfds_t::try_from(fds.len())
+                .expect("There is one socket per PU, which should fit in nfds_t");
+            // SAFETY: fds is a valid array of num_fds pollfds
+            let result = unsafe { libc::poll(fds.as_mut_ptr(), num_fds, -1) };
+            if result < 0 {
+                let error = io::Error::last_os_error();
+                if error.kind() == io::ErrorKind::Interrupted {
+                    continue;
+                }
+                return Err(error);
+            }
+            for (fd, socket) in fds.iter().zip(&self.sockets) {
+                if fd.revents == 0 {
+                    continue;
+                }
+                // Another thread may have accepted the connection first
+                match socket.accept() {
+                    Ok((stream, peer)) => {
+                        stream.set_nonblocking(false)?;
+                        return Ok((stream, peer));
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+    }
+}
+
+/// Error returned by [`AcceptSteering::new()`] when the requested network
+/// interface is not part of the topology
+#[derive(Clone, Debug, Eq, Error, Hash, PartialEq)]
+#[error("network interface {0:?} is not part of the topology")]
+pub struct UnknownInterfaceError(pub String);
+
+/// Error returned by [`AcceptSteering::serve()`]
+#[derive(Debug, Error)]
+pub enum ServeError {
+    /// Failed to set up the listening sockets
+    #[error("failed to set up listening sockets")]
+    Listen(#[from] io::Error),
+
+    /// Failed to spawn or bind a worker
+    #[error("failed to start a worker")]
+    Spawn(#[from] SpawnBoundError),
+}
+
+/// Set up a non-blocking `SO_REUSEPORT` listening socket, steering the
+/// connections received by PU `incoming_cpu` to it
+fn reuseport_listener(address: SocketAddr, incoming_cpu: usize) -> io::Result<TcpListener> {
+    let domain = match address {
+        SocketAddr::V4(_) => libc::AF_INET,
+        SocketAddr::V6(_) => libc::AF_INET6,
+    };
+    // SAFETY: No pointer is involved
+    let fd = unsafe {
+        libc::socket(
+            domain,
+            libc::SOCK_STREAM | libc::SOCK_CLOEXEC | libc::SOCK_NONBLOCK,
+            0,
+        )
+    };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: fd is a freshly created socket that nothing else owns
+    let listener = unsafe { TcpListener::from_raw_fd(fd) };
+    set_socket_option(fd, libc::SO_REUSEPORT, 1)?;
+    let incoming_cpu =
+        c_int::try_from(incoming_cpu).map_err(|_| io::Error::from_raw_os_error(libc::EINVAL))?;
+    set_socket_option(fd, libc::SO_INCOMING_CPU, incoming_cpu)?;
+
+    // SAFETY: sockaddr_storage is a plain C struct, for which all-zeroes is
+    //         a valid bit pattern
+    let mut storage: libc::sockaddr_storage = unsafe { std::mem::zeroed() };
+    let len = match address {
+        SocketAddr::V4(address) => {
+            // SAFETY: sockaddr_storage is large and aligned enough for any
+            //         socket address type
+            let sockaddr = unsafe {
+                &mut *(&mut storage as *mut libc::sockaddr_storage).cast::<libc::sockaddr_in>()
+            };
+            sockaddr.sin_family = libc::AF_INET as libc::sa_family_t;
+            sockaddr.sin_port = address.port().to_be();
+            sockaddr.sin_addr.s_addr = u32::from(*address.ip()).to_be();
+            std::mem::size_of::<libc::sockaddr_in>()
+        }
+        SocketAddr::V6(address) => {
+            // SAFETY: Same as above
+            let sockaddr = unsafe {
+                &mut *(&mut storage as *mut libc::sockaddr_storage).cast::<libc::sockaddr_in6>()
+            };
+            sockaddr.sin6_family = libc::AF_INET6 as libc::sa_family_t;
+            sockaddr.sin6_port = address.port().to_be();
+            sockaddr.sin6_flowinfo = address.flowinfo();
+            sockaddr.sin6_addr.s6_addr = address.ip().octets();
+            sockaddr.sin6_scope_id = address.scope_id();
+            std::mem::size_of::<libc::sockaddr_in6>()
+        }
+    };
+    let len = libc::socklen_t::try_from(len).expect("Socket addresses are small");
+    // SAFETY: storage holds a valid socket address of length len
+    let result = unsafe { libc::bind(fd, (&storage as *const libc::sockaddr_storage).cast(), len) };
+    // SAFETY: No pointer is involved
+    if result != 0 || unsafe { libc::listen(fd, 128) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(listener)
+}
+
+/// Set an integer socket option
+fn set_socket_option(fd: c_int, option: c_int, value: c_int) -> io::Result<()> {
+    let len = libc::socklen_t::try_from(std::mem::size_of::<c_int>()).expect("c_int is small");
+    // SAFETY: value is a valid c_int that outlives the call
+    let result = unsafe {
+        libc::setsockopt(
+            fd,
+            libc::SOL_SOCKET,
+            option,
+            (&value as *const c_int).cast(),
+            len,
+        )
+    };
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
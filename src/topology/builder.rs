@@ -10,10 +10,15 @@ use crate::topology::support::DiscoverySupport;
 #[cfg(all(doc, feature = "hwloc-2_3_0"))]
 use crate::topology::support::MiscSupport;
 use crate::{
+    cpu::cpusets::CpuSet,
     errors::{self, FlagsError, HybridError, NulError, RawHwlocError, UnsupportedError},
     ffi::{self, LibcString},
-    objects::types::ObjectType,
+    objects::{attributes::PCIDomain, types::ObjectType},
     paths::{self, PathError},
+    topology::{
+        consistency::{ConsistencyHook, ConsistencyIssue},
+        export::xml::{self, XmlUserdataCodec},
+    },
     ProcessId,
 };
 use bitflags::bitflags;
@@ -21,20 +26,47 @@ use errno::Errno;
 use libc::{EINVAL, ENOSYS};
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 use std::{
-    ffi::{c_int, c_ulong},
-    fmt::Debug,
+    ffi::{c_int, c_ulong, CStr, OsString},
+    fmt::{self, Debug},
+    future::Future,
+    ops::RangeInclusive,
+    panic::{self, AssertUnwindSafe},
     path::Path,
+    pin::Pin,
     ptr::NonNull,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    task::{Context, Poll, Waker},
+    thread,
+    time::Instant,
 };
 use thiserror::Error;
 
 /// Mechanism to build a `Topology` with custom configuration
-#[derive(Debug)]
-pub struct TopologyBuilder(NonNull<RawTopology>);
+pub struct TopologyBuilder {
+    /// Topology being built
+    raw: NonNull<RawTopology>,
+
+    /// Process that the topology is viewed from, if not the current one
+    pid: Option<ProcessId>,
+
+    /// Callback that is notified of consistency issues once the topology is
+    /// loaded, if any
+    consistency_hook: Option<ConsistencyHook>,
+}
+//
+impl Debug for TopologyBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TopologyBuilder")
+            .field("raw", &self.raw)
+            .field("pid", &self.pid)
+            .field("consistency_hook", &self.consistency_hook.is_some())
+            .finish()
+    }
+}
 
 /// # Topology building
 //
-// Upstream docs: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__creation.html
+// Upstream docs: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__creation.html
 impl TopologyBuilder {
     /// Start building a [`Topology`]
     ///
@@ -48,12 +80,24 @@ impl TopologyBuilder {
     /// # Ok::<(), anyhow::Error>(())
     /// ```
     pub fn new() -> Self {
+        Self::try_new().expect("Failed to allocate topology")
+    }
+
+    /// Start building a [`Topology`], reporting hwloc initialization failures
+    ///
+    /// Unlike [`new()`](Self::new()), this does not panic if hwloc fails to
+    /// allocate the topology, or cannot be loaded when the `dlopen` feature
+    /// is enabled.
+    pub(crate) fn try_new() -> Result<Self, RawHwlocError> {
         let mut topology: *mut RawTopology = std::ptr::null_mut();
         errors::call_hwloc_int_normal("hwloc_topology_init", || unsafe {
             ffi::hwloc_topology_init(&mut topology)
+        })?;
+        Ok(Self {
+            raw: NonNull::new(topology).expect("Got null pointer from hwloc_topology_init"),
+            pid: None,
+            consistency_hook: None,
         })
-        .expect("Failed to allocate topology");
-        Self(NonNull::new(topology).expect("Got null pointer from hwloc_topology_init"))
     }
 
     /// Load the topology with the previously specified parameters
@@ -61,6 +105,24 @@ impl TopologyBuilder {
     /// The binding of the current thread or process may temporarily change
     /// during this call but it will be restored before it returns.
     ///
+    /// # Errors
+    ///
+    /// Most configuration errors, like invalid synthetic descriptions or
+    /// unsupported flags, are reported by the [`TopologyBuilder`] methods that
+    /// set them up. Errors which can only be detected while loading the
+    /// topology are reported as a [`RawHwlocError`], whose `errno` is usually
+    /// one of the following:
+    ///
+    /// - `ENOMEM` if hwloc failed to allocate memory.
+    /// - `EINVAL` if the selected topology source turned out to be invalid,
+    ///   for example an XML input with an unexpected structure.
+    /// - Another errno set by the operating system if topology discovery
+    ///   failed for another reason.
+    ///
+    /// More detail about input parsing failures can be printed by hwloc to
+    /// stderr by setting the `HWLOC_XML_VERBOSE` or `HWLOC_SYNTHETIC_VERBOSE`
+    /// environment variable to 1.
+    ///
     /// # Examples
     ///
     /// ```
@@ -71,20 +133,349 @@ impl TopologyBuilder {
     /// # Ok::<(), anyhow::Error>(())
     /// ```
     #[doc(alias = "hwloc_topology_load")]
-    pub fn build(mut self) -> Result<Topology, RawHwlocError> {
+    pub fn build(self) -> Result<Topology, RawHwlocError> {
+        // SAFETY: No environment override is requested
+        unsafe { self.load(EnvironmentOverrides::new()) }
+    }
+
+    /// Load the topology, applying some environment overrides
+    ///
+    /// # Safety
+    ///
+    /// If `environment` is not empty, the safety contract of
+    /// [`build_with_environment_overrides()`] applies.
+    ///
+    /// [`build_with_environment_overrides()`]: Self::build_with_environment_overrides()
+    unsafe fn load(mut self, environment: EnvironmentOverrides) -> Result<Topology, RawHwlocError> {
         // Finalize the topology building
-        errors::call_hwloc_int_normal("hwloc_topology_load", || unsafe {
-            ffi::hwloc_topology_load(self.as_mut_ptr())
-        })?;
+        let load = |builder: &mut Self| {
+            errors::call_hwloc_int_normal("hwloc_topology_load", || unsafe {
+                ffi::hwloc_topology_load(builder.as_mut_ptr())
+            })
+        };
+        let flags = self.flags();
+        log_event!(Trace, "Loading topology with flags {flags:?}");
+        #[cfg(all(unix, feature = "log"))]
+        let debug_output = if !environment.is_empty() {
+            // Capturing the debug output spawns a thread, which environment
+            // overrides promise not to do
+            None
+        } else {
+            HwlocDebugCapture::new()
+        };
+        let start = Instant::now();
+        let result = if !environment.is_empty() {
+            // SAFETY: Per this function's safety contract
+            let _environment = unsafe { HwlocEnvironmentGuard::new(&environment) };
+            load(&mut self)
+        } else {
+            load(&mut self)
+        };
+        #[cfg(all(unix, feature = "log"))]
+        drop(debug_output);
+        if let Err(e) = result {
+            log_event!(
+                Warn,
+                "Failed to load topology with flags {flags:?} after {:?}: {e}",
+                start.elapsed()
+            );
+            return Err(e);
+        }
+        log_event!(
+            Debug,
+            "Loaded topology with flags {flags:?} in {:?}",
+            start.elapsed()
+        );
 
         // If that was successful, transfer RawTopology ownership to a Topology
         if cfg!(debug_assertions) {
             unsafe { ffi::hwloc_topology_check(self.as_ptr()) }
         }
-        let result = Topology(self.0);
+        let consistency_hook = self.consistency_hook.take();
+        let load_config = LoadConfig {
+            pid: self.pid,
+            environment,
+        };
+        let mut result = unsafe { Topology::from_raw(self.raw) };
+        result.load_config = load_config;
         std::mem::forget(self);
+
+        // Check the consistency of the topology if requested, and log issues
+        // in debug builds
+        let log_issues = cfg!(all(debug_assertions, feature = "log"));
+        if log_issues || consistency_hook.is_some() {
+            let mut consistency_hook = consistency_hook;
+            for issue in result.consistency_report() {
+                if log_issues {
+                    log_event!(Warn, "Loaded topology is inconsistent: {issue}");
+                }
+                if let Some(hook) = &mut consistency_hook {
+                    hook(issue);
+                }
+            }
+        }
         Ok(result)
     }
+
+    /// Check the consistency of the topology once it is loaded
+    ///
+    /// When [`build()`](Self::build()) succeeds, `hook` is called with each
+    /// issue from the [`Topology::consistency_report()`] of the new topology.
+    /// This lets you detect firmware bugs that break the assumptions of your
+    /// code at load time, for example by logging them or panicking.
+    ///
+    /// If the `log` feature is enabled, debug builds also log consistency
+    /// issues as warnings, whether a hook is set or not.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::topology::Topology;
+    /// let topology = Topology::builder()
+    ///     .on_consistency_issue(|issue| eprintln!("Unexpected topology structure: {issue}"))
+    ///     .build()?;
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn on_consistency_issue(
+        mut self,
+        hook: impl FnMut(ConsistencyIssue<'_>) + Send + 'static,
+    ) -> Self {
+        self.consistency_hook = Some(Box::new(hook));
+        self
+    }
+
+    /// Load the topology, decoding application-specific XML object userdata
+    ///
+    /// This works like [`build()`](Self::build()), except that when the
+    /// topology is loaded from an XML export that was produced by
+    /// [`Topology::export_xml_with_userdata()`], `codec` gets to
+    /// [decode](XmlUserdataCodec::decode()) the userdata that was saved along
+    /// with each object. Userdata is decoded once the topology is fully
+    /// loaded.
+    ///
+    /// hwloc decodes base64-encoded userdata before handing it over, unless
+    /// the `HWLOC_XML_USERDATA_NOT_DECODED` environment variable is set.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`build()`](Self::build()).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::{
+    /// #     errors::HybridError,
+    /// #     objects::TopologyObject,
+    /// #     topology::{
+    /// #         export::xml::{
+    /// #             XMLExportFlags, XmlUserdataCodec, XmlUserdataExporter, XmlUserdataNameError,
+    /// #         },
+    /// #         Topology,
+    /// #     },
+    /// # };
+    /// /// Attach a label to the root object
+    /// #[derive(Default)]
+    /// struct RootLabel(Option<Vec<u8>>);
+    /// //
+    /// impl XmlUserdataCodec for RootLabel {
+    ///     fn encode(
+    ///         &mut self,
+    ///         object: &TopologyObject,
+    ///         exporter: &mut XmlUserdataExporter<'_>,
+    ///     ) -> Result<(), HybridError<XmlUserdataNameError>> {
+    ///         match (&self.0, object.parent()) {
+    ///             (Some(label), None) => exporter.export(Some("label"), label),
+    ///             _ => Ok(()),
+    ///         }
+    ///     }
+    ///
+    ///     fn decode(&mut self, object: &TopologyObject, name: Option<&str>, data: &[u8]) {
+    ///         if object.parent().is_none() && name == Some("label") {
+    ///             self.0 = Some(data.to_owned());
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let mut topology = Topology::test_instance().clone();
+    /// let mut codec = RootLabel(Some(b"rack 42\n\0".to_vec()));
+    /// let xml = topology
+    ///     .export_xml_with_userdata(XMLExportFlags::empty(), &mut codec)?
+    ///     .to_string();
+    ///
+    /// let mut decoded = RootLabel::default();
+    /// let _imported = Topology::builder()
+    ///     .from_xml(&xml)?
+    ///     .build_with_xml_userdata(&mut decoded)?;
+    /// assert_eq!(decoded.0, codec.0);
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    #[doc(alias = "hwloc_topology_set_userdata_import_callback")]
+    pub fn build_with_xml_userdata(
+        mut self,
+        codec: &mut impl XmlUserdataCodec,
+    ) -> Result<Topology, RawHwlocError> {
+        unsafe {
+            ffi::hwloc_topology_set_userdata_import_callback(
+                self.as_mut_ptr(),
+                Some(xml::import_userdata_callback),
+            );
+        }
+        let (result, records) = xml::collect_imported_userdata(|| self.build());
+        let topology = result?;
+        for (gp_index, name, data) in records {
+            if let Some(object) = topology.object_by_gp_index(gp_index) {
+                codec.decode(object, name.as_deref(), &data);
+            }
+        }
+        Ok(topology)
+    }
+
+    /// Load the topology on a background thread
+    ///
+    /// Loading a topology can take a long time on large systems, especially
+    /// when I/O discovery is enabled. This method performs the work of
+    /// [`build()`](Self::build()) on a dedicated thread, and returns a future
+    /// that resolves once it is done, so that asynchronous code does not block
+    /// its executor while the topology is being loaded.
+    ///
+    /// The returned [`BuildFuture`] works with any executor and is
+    /// cancellation-safe: if it is dropped before completion, the background
+    /// thread still runs to completion (hwloc topology loading cannot be
+    /// interrupted), then discards the topology. If a background thread
+    /// cannot be spawned, the topology is loaded synchronously when the
+    /// future is first polled.
+    ///
+    /// If loading the topology panics on the background thread, the panic is
+    /// propagated to the task that polls the future.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Errors
+    ///
+    /// The future resolves to the same errors as [`build()`](Self::build()).
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// # use hwlocality::topology::{Topology, builder::BuildFlags};
+    /// let topology = Topology::builder()
+    ///     .with_flags(BuildFlags::INCLUDE_DISALLOWED)?
+    ///     .build_async()
+    ///     .await?;
+    /// ```
+    pub fn build_async(self) -> BuildFuture {
+        BuildFuture::new(self, Self::build)
+    }
+}
+
+/// Future returned by [`TopologyBuilder::build_async()`]
+///
+/// Resolves to the outcome of [`TopologyBuilder::build()`]. See
+/// [`build_async()`](TopologyBuilder::build_async()) for more information.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct BuildFuture {
+    /// State shared with the background thread
+    state: Arc<Mutex<BuildState>>,
+
+    /// Truth that the background thread was successfully spawned
+    spawned: bool,
+
+    /// Truth that the future has already resolved
+    finished: bool,
+}
+//
+impl BuildFuture {
+    /// Start building a topology with `build` on a background thread
+    fn new(builder: TopologyBuilder, build: BuildFn) -> Self {
+        let state = Arc::new(Mutex::new(BuildState {
+            builder: Some(builder),
+            build,
+            result: None,
+            waker: None,
+        }));
+        let thread_state = Arc::clone(&state);
+        let spawned = thread::Builder::new()
+            .name("hwlocality-build".to_owned())
+            .spawn(move || {
+                let builder = BuildState::lock(&thread_state).builder.take();
+                let Some(builder) = builder else {
+                    return;
+                };
+                // Catch panics so that the future does not wait forever for a
+                // result that will never come
+                let result = panic::catch_unwind(AssertUnwindSafe(|| build(builder)));
+                let waker = {
+                    let mut state = BuildState::lock(&thread_state);
+                    state.result = Some(result);
+                    state.waker.take()
+                };
+                if let Some(waker) = waker {
+                    waker.wake();
+                }
+            })
+            .is_ok();
+        Self {
+            state,
+            spawned,
+            finished: false,
+        }
+    }
+}
+//
+impl Future for BuildFuture {
+    type Output = Result<Topology, RawHwlocError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        assert!(!this.finished, "BuildFuture polled after completion");
+        let mut state = BuildState::lock(&this.state);
+        let result = if let Some(result) = state.result.take() {
+            drop(state);
+            this.finished = true;
+            result.unwrap_or_else(|payload| panic::resume_unwind(payload))
+        } else if !this.spawned {
+            let builder = state.builder.take().expect("Only taken by this branch");
+            let build = state.build;
+            drop(state);
+            this.finished = true;
+            build(builder)
+        } else {
+            state.waker = Some(cx.waker().clone());
+            return Poll::Pending;
+        };
+        Poll::Ready(result)
+    }
+}
+
+/// Function that builds a topology, normally [`TopologyBuilder::build()`]
+type BuildFn = fn(TopologyBuilder) -> Result<Topology, RawHwlocError>;
+
+/// State shared between a [`BuildFuture`] and its background thread
+#[derive(Debug)]
+struct BuildState {
+    /// Builder, until the background thread takes it
+    builder: Option<TopologyBuilder>,
+
+    /// Function that builds the topology
+    build: BuildFn,
+
+    /// Outcome of the build, or panic payload if it panicked, once available
+    result: Option<thread::Result<Result<Topology, RawHwlocError>>>,
+
+    /// Waker of the task that last polled the future
+    waker: Option<Waker>,
+}
+//
+impl BuildState {
+    /// Lock the shared state, ignoring poisoning
+    fn lock(state: &Mutex<Self>) -> MutexGuard<'_, Self> {
+        state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
 }
 
 /// # Discovery source
@@ -101,10 +492,13 @@ impl TopologyBuilder {
 /// Finally, `HWLOC_THISSYSTEM` forces [`Topology::is_this_system()`] to return
 /// true.
 ///
+/// Use [`EnvironmentOverrides::ignore_hwloc_variables()`] if this is not
+/// desired.
+///
 /// [`from_xml_file()`]: TopologyBuilder::from_xml_file()
 /// [`from_synthetic()`]: TopologyBuilder::from_synthetic()
 //
-// Upstream docs: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__setsource.html
+// Upstream docs: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__setsource.html
 impl TopologyBuilder {
     /// Change which process the topology is viewed from
     ///
@@ -119,11 +513,8 @@ impl TopologyBuilder {
     ///   system, for this process).
     #[doc(alias = "hwloc_topology_set_pid")]
     pub fn from_pid(mut self, pid: ProcessId) -> Result<Self, UnsupportedError> {
-        let result = errors::call_hwloc_int_normal("hwloc_topology_set_pid", || unsafe {
-            ffi::hwloc_topology_set_pid(self.as_mut_ptr(), pid)
-        });
-        match result {
-            Ok(_) => Ok(self),
+        match self.set_pid(pid) {
+            Ok(()) => Ok(self),
             Err(RawHwlocError {
                 api: _,
                 errno: Some(Errno(ENOSYS)),
@@ -136,7 +527,7 @@ impl TopologyBuilder {
     ///
     /// Instead of being probed from the host system, topology information will
     /// be read from the given
-    /// [textual description](https://hwloc.readthedocs.io/en/v2.9/synthetic.html).
+    /// [textual description](https://hwloc.readthedocs.io/en/v2.11/synthetic.html).
     ///
     /// Setting the environment variable `HWLOC_SYNTHETIC` may also result in
     /// this behavior.
@@ -171,7 +562,7 @@ impl TopologyBuilder {
     ///
     /// Instead of being probed from the host system, topology information will
     /// be read from the given
-    /// [XML description](https://hwloc.readthedocs.io/en/v2.9/xml.html).
+    /// [XML description](https://hwloc.readthedocs.io/en/v2.11/xml.html).
     ///
     /// CPU and memory binding operations will be ineffective with this backend,
     /// unless [`BuildFlags::ASSUME_THIS_SYSTEM`] is set to assert that the
@@ -198,7 +589,10 @@ impl TopologyBuilder {
             )
         });
         match result {
-            Ok(_) => Ok(self),
+            Ok(_) => {
+                log_event!(Debug, "Importing topology from {}B of XML", xml.len());
+                Ok(self)
+            }
             Err(RawHwlocError {
                 api: _,
                 errno: Some(Errno(EINVAL)),
@@ -228,12 +622,16 @@ impl TopologyBuilder {
     /// [`Invalid`]: XMLFileInputError::Invalid
     #[doc(alias = "hwloc_topology_set_xml")]
     pub fn from_xml_file(mut self, path: impl AsRef<Path>) -> Result<Self, XMLFileInputError> {
-        let path = paths::make_hwloc_path(path)?;
+        let rust_path = path.as_ref();
+        let path = paths::make_hwloc_path(rust_path)?;
         let result = errors::call_hwloc_int_normal("hwloc_topology_set_xml", || unsafe {
             ffi::hwloc_topology_set_xml(self.as_mut_ptr(), path.borrow())
         });
         match result {
-            Ok(_) => Ok(self),
+            Ok(_) => {
+                log_event!(Debug, "Importing topology from XML file {rust_path:?}");
+                Ok(self)
+            }
             Err(RawHwlocError {
                 api: _,
                 errno: Some(Errno(EINVAL)),
@@ -250,7 +648,7 @@ impl TopologyBuilder {
     /// name of a phase, for instance "linux:io". A list of components
     /// distributed with hwloc can be found
     /// [in the hwloc
-    /// documentation](https://hwloc.readthedocs.io/en/v2.9/plugins.html#plugins_list).
+    /// documentation](https://hwloc.readthedocs.io/en/v2.11/plugins.html#plugins_list).
     ///
     /// This may be used to avoid expensive parts of the discovery process. For
     /// instance, CUDA-specific discovery may be expensive and unneeded while
@@ -317,7 +715,7 @@ bitflags! {
 
 /// # Detection configuration and query
 //
-// Upstream docs: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__configuration.html
+// Upstream docs: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__configuration.html
 impl TopologyBuilder {
     /// Set topology building flags
     ///
@@ -369,8 +767,9 @@ impl TopologyBuilder {
     ///
     /// - [`CantKeepGroup`] if one attempts to set [`TypeFilter::KeepAll`] for
     ///   [`Group`] objects, which is not allowed by hwloc.
-    /// - [`CantIgnore`] if one attempt's to ignore the top- and bottom-level
-    ///   [`Machine`], [`PU`] and [`NUMANode`] types.
+    /// - [`CantIgnore`] if one attempts to set a filter other than
+    ///   [`TypeFilter::KeepAll`] for the top- and bottom-level [`Machine`],
+    ///   [`PU`] and [`NUMANode`] types, which must always be kept.
     /// - [`StructureIrrelevant`] if one attempts to set
     ///   [`TypeFilter::KeepStructure`] for I/O and [`Misc`] objects, for which
     ///   topology structure does not matter.
@@ -393,10 +792,10 @@ impl TopologyBuilder {
             (ObjectType::Group, TypeFilter::KeepAll) => {
                 return Err(TypeFilterError::CantKeepGroup.into())
             }
-            (_, TypeFilter::KeepNone) => {
-                if let ObjectType::Machine | ObjectType::PU | ObjectType::NUMANode = ty {
-                    return Err(TypeFilterError::CantIgnore(ty).into());
-                }
+            (ObjectType::Machine | ObjectType::PU | ObjectType::NUMANode, _)
+                if filter != TypeFilter::KeepAll =>
+            {
+                return Err(TypeFilterError::CantIgnore(ty).into())
             }
             (_, TypeFilter::KeepStructure) if ty.is_io() || ty == ObjectType::Misc => {
                 return Err(TypeFilterError::StructureIrrelevant.into())
@@ -424,6 +823,17 @@ impl TopologyBuilder {
     /// Set the filtering for all CPU cache object types
     ///
     /// Memory-side caches are not involved since they are not CPU caches.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::{objects::types::ObjectType, topology::{Topology, builder::TypeFilter}};
+    /// let topology = Topology::builder()
+    ///                         .with_cpu_cache_type_filter(TypeFilter::KeepNone)?
+    ///                         .build()?;
+    /// assert_eq!(topology.objects_with_type(ObjectType::L1Cache).count(), 0);
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
     #[doc(alias = "hwloc_topology_set_cache_types_filter")]
     pub fn with_cpu_cache_type_filter(mut self, filter: TypeFilter) -> Result<Self, RawHwlocError> {
         errors::call_hwloc_int_normal("hwloc_topology_set_cache_types_filter", || unsafe {
@@ -455,6 +865,17 @@ impl TopologyBuilder {
     ///   for I/O objects.
     ///
     /// [`StructureIrrelevant`]: TypeFilterError::StructureIrrelevant
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::topology::{Topology, builder::TypeFilter};
+    /// // Only keep major I/O devices (network, storage, GPUs...)
+    /// let topology = Topology::builder()
+    ///                         .with_io_type_filter(TypeFilter::KeepImportant)?
+    ///                         .build()?;
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
     #[doc(alias = "hwloc_topology_set_io_types_filter")]
     pub fn with_io_type_filter(
         mut self,
@@ -470,6 +891,93 @@ impl TopologyBuilder {
         Ok(self)
     }
 
+    /// Set the filtering for all CPU cache object types, checking that the
+    /// filter makes sense for caches
+    ///
+    /// This works like [`with_cpu_cache_type_filter()`], but rejects filters
+    /// that are not meaningful for CPU caches instead of letting hwloc
+    /// silently reinterpret them.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Errors
+    ///
+    /// - [`ImportanceIrrelevant`] if one attempts to set
+    ///   [`TypeFilter::KeepImportant`], which is only meaningful for I/O
+    ///   objects.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::{objects::types::ObjectType, topology::{Topology, builder::TypeFilter}};
+    /// let topology = Topology::builder()
+    ///                         .with_cache_filter(TypeFilter::KeepStructure)?
+    ///                         .build()?;
+    /// assert_eq!(topology.type_filter(ObjectType::L2Cache)?, TypeFilter::KeepStructure);
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    ///
+    /// [`ImportanceIrrelevant`]: TypeFilterError::ImportanceIrrelevant
+    /// [`with_cpu_cache_type_filter()`]: Self::with_cpu_cache_type_filter()
+    #[doc(alias = "hwloc_topology_set_cache_types_filter")]
+    pub fn with_cache_filter(
+        self,
+        filter: TypeFilter,
+    ) -> Result<Self, HybridError<TypeFilterError>> {
+        check_non_io_filter(filter)?;
+        self.with_cpu_cache_type_filter(filter)
+            .map_err(HybridError::Hwloc)
+    }
+
+    /// Set the filtering for all CPU instruction cache object types, checking
+    /// that the filter makes sense for caches
+    ///
+    /// This works like [`with_cpu_icache_type_filter()`], but rejects filters
+    /// that are not meaningful for CPU caches instead of letting hwloc
+    /// silently reinterpret them.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Errors
+    ///
+    /// - [`ImportanceIrrelevant`] if one attempts to set
+    ///   [`TypeFilter::KeepImportant`], which is only meaningful for I/O
+    ///   objects.
+    ///
+    /// [`ImportanceIrrelevant`]: TypeFilterError::ImportanceIrrelevant
+    /// [`with_cpu_icache_type_filter()`]: Self::with_cpu_icache_type_filter()
+    #[doc(alias = "hwloc_topology_set_icache_types_filter")]
+    pub fn with_icache_filter(
+        self,
+        filter: TypeFilter,
+    ) -> Result<Self, HybridError<TypeFilterError>> {
+        check_non_io_filter(filter)?;
+        self.with_cpu_icache_type_filter(filter)
+            .map_err(HybridError::Hwloc)
+    }
+
+    /// Set the filtering for all I/O object types, checking that the filter
+    /// makes sense for I/O objects
+    ///
+    /// This is the I/O counterpart of [`with_cache_filter()`], and works like
+    /// [`with_io_type_filter()`].
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Errors
+    ///
+    /// - [`StructureIrrelevant`] if one attempts to set
+    ///   [`TypeFilter::KeepStructure`], as topology structure does not matter
+    ///   for I/O objects.
+    ///
+    /// [`StructureIrrelevant`]: TypeFilterError::StructureIrrelevant
+    /// [`with_cache_filter()`]: Self::with_cache_filter()
+    /// [`with_io_type_filter()`]: Self::with_io_type_filter()
+    #[doc(alias = "hwloc_topology_set_io_types_filter")]
+    pub fn with_io_filter(self, filter: TypeFilter) -> Result<Self, HybridError<TypeFilterError>> {
+        self.with_io_type_filter(filter)
+    }
+
     /// Current filtering for the given object type
     pub fn type_filter(&self, ty: ObjectType) -> Result<TypeFilter, RawHwlocError> {
         let mut filter = RawTypeFilter::MAX;
@@ -690,6 +1198,16 @@ impl Default for BuildFlags {
     }
 }
 
+/// Error returned when querying topology information that was disabled at
+/// build time
+///
+/// The flags are the `IGNORE_xyz` [`BuildFlags`] that were responsible for
+/// disabling the requested information. These flags require the
+/// `hwloc-2_8_0` feature, so this error is never reported without it.
+#[derive(Copy, Clone, Debug, Error, Eq, Hash, PartialEq)]
+#[error("topology information was disabled at build time by flags {0:?}")]
+pub struct FeatureDisabledAtBuild(pub BuildFlags);
+
 /// Rust mapping of the hwloc_type_filter_e enum
 ///
 /// We can't use Rust enums to model C enums in FFI because that results in
@@ -767,24 +1285,96 @@ pub enum TypeFilterError {
     CantKeepGroup,
 
     /// Top-level and bottom-level types cannot be ignored
+    ///
+    /// [`ObjectType::Machine`], [`ObjectType::PU`] and
+    /// [`ObjectType::NUMANode`] objects must be kept with
+    /// [`TypeFilter::KeepAll`].
     #[error("can't ignore top- or bottom-level type {0}")]
     CantIgnore(ObjectType),
 
     /// Topology structure doesn't matter for I/O and Misc objects
     #[error("topology structure doesn't matter for I/O and Misc objects")]
     StructureIrrelevant,
+
+    /// Only I/O objects can be filtered by importance
+    ///
+    /// [`TypeFilter::KeepImportant`] is equivalent to [`TypeFilter::KeepAll`]
+    /// for other object types, so asking for it is likely a mistake.
+    #[error("only I/O objects can be filtered by importance")]
+    ImportanceIrrelevant,
+}
+
+/// Check that `filter` is meaningful for non-I/O object types
+fn check_non_io_filter(filter: TypeFilter) -> Result<(), TypeFilterError> {
+    if filter == TypeFilter::KeepImportant {
+        Err(TypeFilterError::ImportanceIrrelevant)
+    } else {
+        Ok(())
+    }
 }
 
 /// # General-purpose internal utilities
 impl TopologyBuilder {
     /// Contained hwloc topology pointer (for interaction with hwloc)
     fn as_ptr(&self) -> *const RawTopology {
-        self.0.as_ptr()
+        self.raw.as_ptr()
     }
 
     /// Contained mutable hwloc topology pointer (for interaction with hwloc)
     fn as_mut_ptr(&mut self) -> *mut RawTopology {
-        self.0.as_ptr()
+        self.raw.as_ptr()
+    }
+
+    /// Load the topology of process `pid`, reporting all hwloc errors
+    fn set_pid(&mut self, pid: ProcessId) -> Result<(), RawHwlocError> {
+        errors::call_hwloc_int_normal("hwloc_topology_set_pid", || unsafe {
+            ffi::hwloc_topology_set_pid(self.as_mut_ptr(), pid)
+        })?;
+        self.pid = Some(pid);
+        Ok(())
+    }
+
+    /// Reapply the configuration that a topology was built with
+    ///
+    /// The [`EnvironmentOverrides`] of `config` are not part of the builder
+    /// configuration, they must be passed to
+    /// [`build_with_environment_overrides()`].
+    ///
+    /// # Errors
+    ///
+    /// Errors from hwloc are reported as is, since the configuration may not
+    /// be valid anymore. For example, the process that the topology was
+    /// loaded from may have exited.
+    ///
+    /// [`build_with_environment_overrides()`]: Self::build_with_environment_overrides()
+    pub(crate) fn with_load_config(mut self, config: &LoadConfig) -> Result<Self, RawHwlocError> {
+        if let Some(pid) = config.pid {
+            self.set_pid(pid)?;
+        }
+        Ok(self)
+    }
+}
+
+/// Part of the [`TopologyBuilder`] configuration that hwloc does not record in
+/// the topology
+///
+/// It is kept in the [`Topology`] so that [`Topology::refresh()`] can load it
+/// again the same way.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct LoadConfig {
+    /// Process that the topology is viewed from, if not the current one
+    pub(crate) pid: Option<ProcessId>,
+
+    /// Environment overrides that were passed to
+    /// [`TopologyBuilder::build_with_environment_overrides()`]
+    pub(crate) environment: EnvironmentOverrides,
+}
+//
+impl LoadConfig {
+    /// Truth that loading a topology with this configuration modifies the
+    /// process environment
+    pub(crate) fn overrides_environment(&self) -> bool {
+        !self.environment.is_empty()
     }
 }
 
@@ -794,6 +1384,456 @@ impl Default for TopologyBuilder {
     }
 }
 
+/// # Process environment overrides
+///
+/// hwloc reads many `HWLOC_*` environment variables while a topology is being
+/// loaded, and these variables can change the result (`HWLOC_XMLFILE`,
+/// `HWLOC_SYNTHETIC`, `HWLOC_COMPONENTS`...). hwloc provides no topology flag
+/// or `hwloc_topology_set_*` function that makes it ignore them, so the only
+/// way to hide them is to modify the process environment while the topology is
+/// being loaded. This can only be done soundly in a critical section where no
+/// other code of the process uses the environment, which is why it is exposed
+/// as a separate, `unsafe` way to [build](TopologyBuilder::build()) the
+/// topology instead of a normal builder option.
+//
+// Upstream docs: https://hwloc.readthedocs.io/en/v2.11/envvar.html
+impl TopologyBuilder {
+    /// Load the topology while overriding the process environment
+    ///
+    /// This works like [`build()`], but temporarily modifies the `HWLOC_*`
+    /// environment variables as specified by `overrides` while the topology is
+    /// being loaded. The original environment is restored before this function
+    /// returns or unwinds.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`build()`].
+    ///
+    /// # Safety
+    ///
+    /// The process environment is shared by all threads, and it is not safe to
+    /// modify it while another thread uses it. This function is a critical
+    /// section: from the moment it is called to the moment it returns, no
+    /// other thread of the process may...
+    ///
+    /// - Read or modify the environment, whether through
+    ///   [`std::env`](mod@std::env), through C functions like `getenv()` and
+    ///   `setenv()`, or through libraries that use them. This includes
+    ///   building another topology with [`build()`], since hwloc reads the
+    ///   environment while loading a topology.
+    /// - Fork the process or spawn child processes, for example with
+    ///   [`std::process::Command`], as children would inherit a modified
+    ///   environment.
+    ///
+    /// This function does not spawn any thread or process by itself, and calls
+    /// to this function from multiple threads are serialized. The simplest way
+    /// to uphold this contract is to call it before the program spawns any
+    /// thread. If that cannot be guaranteed, use
+    /// [`try_build_with_environment_overrides()`] instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::topology::{Topology, builder::EnvironmentOverrides};
+    /// let overrides = EnvironmentOverrides::new().ignore_hwloc_variables();
+    /// // SAFETY: No other thread uses the environment, forks or spawns
+    /// //         processes while the topology is being loaded
+    /// let topology = unsafe { Topology::builder().build_with_environment_overrides(&overrides) }?;
+    /// assert!(topology.is_this_system());
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    ///
+    /// [`build()`]: TopologyBuilder::build()
+    /// [`try_build_with_environment_overrides()`]: TopologyBuilder::try_build_with_environment_overrides()
+    #[doc(alias = "hwloc_topology_load")]
+    pub unsafe fn build_with_environment_overrides(
+        self,
+        overrides: &EnvironmentOverrides,
+    ) -> Result<Topology, RawHwlocError> {
+        // SAFETY: Per this function's safety contract
+        unsafe { self.load(overrides.clone()) }
+    }
+
+    /// Load the topology while overriding the process environment, if this
+    /// can be done safely
+    ///
+    /// This is a safe version of [`build_with_environment_overrides()`]. It
+    /// only modifies the environment if the current process is known to have
+    /// a single thread, in which case no other thread can use the environment,
+    /// fork or spawn processes while the topology is being loaded. This must
+    /// therefore be called before the program spawns any thread, including
+    /// threads spawned by libraries or by the Rust test harness.
+    ///
+    /// Checking the number of threads is currently only supported on Linux.
+    /// On other operating systems, any non-empty `overrides` is rejected.
+    ///
+    /// If `overrides` [is empty](EnvironmentOverrides::is_empty()), this is
+    /// equivalent to [`build()`].
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Errors
+    ///
+    /// - [`EnvironmentOverrideError`] if `overrides` is not empty and the
+    ///   process is not known to be single-threaded
+    /// - Same hwloc errors as [`build()`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::topology::{Topology, builder::EnvironmentOverrides};
+    /// let overrides = EnvironmentOverrides::new().ignore_hwloc_variables();
+    /// match Topology::builder().try_build_with_environment_overrides(&overrides) {
+    ///     Ok(topology) => assert!(topology.is_this_system()),
+    ///     Err(e) => println!("Could not build the topology: {e}"),
+    /// }
+    /// ```
+    ///
+    /// [`build()`]: TopologyBuilder::build()
+    /// [`build_with_environment_overrides()`]: TopologyBuilder::build_with_environment_overrides()
+    pub fn try_build_with_environment_overrides(
+        self,
+        overrides: &EnvironmentOverrides,
+    ) -> Result<Topology, HybridError<EnvironmentOverrideError>> {
+        if !overrides.is_empty() && !is_single_threaded() {
+            return Err(EnvironmentOverrideError.into());
+        }
+        // SAFETY: Either no override is requested, or this is the only thread
+        //         of the process, so no other thread can use the environment,
+        //         fork or spawn processes until this function returns
+        unsafe { self.build_with_environment_overrides(overrides) }.map_err(HybridError::Hwloc)
+    }
+}
+
+/// Modifications to the process environment that should be applied while a
+/// topology is being loaded
+///
+/// See [`TopologyBuilder::build_with_environment_overrides()`] for the reason
+/// why these are not regular [`TopologyBuilder`] options, and how they are
+/// applied.
+///
+/// This functionality is unique to the Rust hwloc bindings.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct EnvironmentOverrides {
+    /// Truth that `HWLOC_*` environment variables should be hidden from hwloc
+    ignore_hwloc_variables: bool,
+
+    /// Value of `HWLOC_PCI_LOCALITY` to be used, if any
+    pci_locality: Option<String>,
+}
+//
+impl EnvironmentOverrides {
+    /// Start with no override, so that the environment is left as is
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Prevent `HWLOC_*` environment variables from affecting topology loading
+    ///
+    /// By default, hwloc lets environment variables like `HWLOC_XMLFILE`,
+    /// `HWLOC_SYNTHETIC`, `HWLOC_FSROOT`, `HWLOC_THISSYSTEM` or
+    /// `HWLOC_COMPONENTS` override the configuration of the topology. With
+    /// this option, all `HWLOC_*` environment variables are removed from the
+    /// process environment while the topology is being loaded, then restored,
+    /// so that the result only depends on the programmatic configuration.
+    ///
+    /// This only affects the environment variables that hwloc reads at load
+    /// time. Those which hwloc reads when the library is initialized, like
+    /// `HWLOC_PLUGINS_PATH`, are not affected.
+    pub fn ignore_hwloc_variables(mut self) -> Self {
+        self.ignore_hwloc_variables = true;
+        self
+    }
+
+    /// Override the locality of the I/O devices behind some PCI buses
+    ///
+    /// Some firmwares report incorrect PCI-to-NUMA affinity, or none at all.
+    /// This method moves the I/O devices behind buses `buses` of PCI domain
+    /// `domain`, along with their bridges, near the CPUs of `cpuset`.
+    ///
+    /// This is the programmatic equivalent of the `HWLOC_PCI_LOCALITY`
+    /// environment variable, which is set while the topology is being loaded.
+    /// Calling this method multiple times accumulates overrides, which
+    /// replace any `HWLOC_PCI_LOCALITY` value from the process environment.
+    ///
+    /// I/O objects are only kept if [`TopologyBuilder::with_io_type_filter()`]
+    /// is used to enable them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::topology::{
+    /// #     Topology,
+    /// #     builder::{EnvironmentOverrides, TypeFilter},
+    /// # };
+    /// # let topology = Topology::test_instance();
+    /// // Move the devices behind PCI buses 0000:80-ff near the last PU
+    /// let last_pu = topology.cpuset().last_set().unwrap();
+    /// let overrides =
+    ///     EnvironmentOverrides::new().with_pci_locality(0, 0x80..=0xff, &last_pu.into());
+    /// let builder = Topology::builder().with_io_type_filter(TypeFilter::KeepImportant)?;
+    /// if let Ok(topology) = builder.try_build_with_environment_overrides(&overrides) {
+    ///     println!("Topology with PCI locality overrides: {topology:?}");
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn with_pci_locality(
+        mut self,
+        domain: PCIDomain,
+        buses: RangeInclusive<u8>,
+        cpuset: &CpuSet,
+    ) -> Self {
+        let entry = pci_locality_entry(domain, buses, cpuset);
+        match &mut self.pci_locality {
+            Some(pci_locality) => {
+                pci_locality.push(';');
+                pci_locality.push_str(&entry);
+            }
+            None => self.pci_locality = Some(entry),
+        }
+        self
+    }
+
+    /// Truth that these overrides leave the environment untouched
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Error returned when environment overrides cannot be applied safely because
+/// the process may have other threads
+///
+/// See [`TopologyBuilder::try_build_with_environment_overrides()`].
+#[derive(Copy, Clone, Debug, Default, Eq, Error, Hash, PartialEq)]
+#[error("environment overrides can only be applied safely while the process has a single thread")]
+pub struct EnvironmentOverrideError;
+
+/// Truth that the current process is known to have a single thread
+///
+/// Since only the calling thread exists, no other thread can appear until the
+/// calling thread spawns one, so the answer stays valid until then.
+pub(crate) fn is_single_threaded() -> bool {
+    #[cfg(target_os = "linux")]
+    {
+        std::fs::read_dir("/proc/self/task").map_or(false, |tasks| tasks.count() == 1)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        false
+    }
+}
+
+/// `HWLOC_PCI_LOCALITY` entry that moves PCI buses `buses` of domain `domain`
+/// near the CPUs of `cpuset`
+fn pci_locality_entry(domain: PCIDomain, buses: RangeInclusive<u8>, cpuset: &CpuSet) -> String {
+    let mask = ffi::call_snprintf(|buf, len| unsafe {
+        ffi::hwloc_bitmap_snprintf(buf, len, cpuset.as_ptr())
+    });
+    let mask = unsafe { CStr::from_ptr(mask.as_ptr()) }.to_string_lossy();
+    format!(
+        "{domain:04x}:{:02x}-{:02x} {mask}",
+        buses.start(),
+        buses.end()
+    )
+}
+
+/// Temporarily modified `HWLOC_*` environment
+///
+/// The original environment is restored when this guard is dropped, including
+/// during unwinding.
+struct HwlocEnvironmentGuard {
+    /// Original values of the environment variables that were modified
+    saved: Vec<(OsString, OsString)>,
+
+    /// Truth that `HWLOC_PCI_LOCALITY` was overriden
+    pci_locality_overriden: bool,
+
+    /// Serializes environment modifications from this module, released after
+    /// the environment has been restored
+    _lock: MutexGuard<'static, ()>,
+}
+//
+impl HwlocEnvironmentGuard {
+    /// Name of the environment variable that overrides PCI locality
+    const PCI_LOCALITY: &'static str = "HWLOC_PCI_LOCALITY";
+
+    /// Modify the `HWLOC_*` environment until the guard is dropped
+    ///
+    /// If [`EnvironmentOverrides::ignore_hwloc_variables()`] was called, all
+    /// `HWLOC_*` environment variables are removed. If
+    /// [`EnvironmentOverrides::with_pci_locality()`] was called,
+    /// `HWLOC_PCI_LOCALITY` is set accordingly.
+    ///
+    /// # Safety
+    ///
+    /// The safety contract of
+    /// [`TopologyBuilder::build_with_environment_overrides()`] applies until
+    /// the guard is dropped.
+    unsafe fn new(overrides: &EnvironmentOverrides) -> Self {
+        let ignore_hwloc_variables = overrides.ignore_hwloc_variables;
+        static ENV_LOCK: Mutex<()> = Mutex::new(());
+        let lock = ENV_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+        let saved = std::env::vars_os()
+            .filter(|(key, _)| {
+                key.to_str().map_or(false, |key| {
+                    if ignore_hwloc_variables {
+                        key.starts_with("HWLOC_")
+                    } else {
+                        key == Self::PCI_LOCALITY
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        // Build the guard first, so that the environment is restored even if
+        // a modification panics
+        let mut guard = Self {
+            saved,
+            pci_locality_overriden: false,
+            _lock: lock,
+        };
+        if ignore_hwloc_variables {
+            for (key, _) in &guard.saved {
+                std::env::remove_var(key);
+            }
+        }
+        if let Some(pci_locality) = &overrides.pci_locality {
+            guard.pci_locality_overriden = true;
+            std::env::set_var(Self::PCI_LOCALITY, pci_locality);
+        }
+        guard
+    }
+}
+//
+impl Drop for HwlocEnvironmentGuard {
+    fn drop(&mut self) {
+        if self.pci_locality_overriden {
+            std::env::remove_var(Self::PCI_LOCALITY);
+        }
+        for (key, value) in self.saved.drain(..) {
+            std::env::set_var(key, value);
+        }
+    }
+}
+
+/// Forwarding of hwloc's debug output to the log crate
+///
+/// Debug builds of hwloc print their debug output, which is controlled by the
+/// `HWLOC_DEBUG_VERBOSE` environment variable, directly to stderr. While this
+/// guard is alive, stderr is redirected to a pipe, and each line that is
+/// written there is logged at the `Debug` level with the `hwloc` target.
+///
+/// Since stderr is shared by the whole process, output from other threads is
+/// forwarded to the log too while the guard is alive, so the redirection is
+/// only set up when it was explicitly requested by setting
+/// `HWLOC_DEBUG_VERBOSE` to a nonzero value and the `hwloc` log target is
+/// enabled at the `Debug` level.
+///
+/// Redirections are serialized by a process-wide lock, so that concurrent
+/// builds do not save and restore each other's pipe instead of the original
+/// stderr.
+#[cfg(all(unix, feature = "log"))]
+struct HwlocDebugCapture {
+    /// Duplicate of the original stderr file descriptor
+    saved_stderr: c_int,
+
+    /// Thread that forwards the pipe's contents to the log
+    forwarder: Option<thread::JoinHandle<()>>,
+
+    /// Lock that prevents other captures from redirecting stderr until this
+    /// one has restored it
+    ///
+    /// Fields are dropped after [`Drop::drop()`] runs, so the lock is only
+    /// released once stderr is restored and the forwarder has stopped.
+    _lock: MutexGuard<'static, ()>,
+}
+//
+#[cfg(all(unix, feature = "log"))]
+impl HwlocDebugCapture {
+    /// Start capturing stderr if hwloc debug output was requested
+    ///
+    /// Returns `None` if the output was not requested or if stderr could not
+    /// be redirected, in which case it is left alone.
+    fn new() -> Option<Self> {
+        let requested = std::env::var_os("HWLOC_DEBUG_VERBOSE")
+            .map_or(false, |verbose| !verbose.is_empty() && verbose != "0");
+        if !requested || !log::log_enabled!(target: "hwloc", log::Level::Debug) {
+            return None;
+        }
+        Self::start()
+    }
+
+    /// Lock that only lets one capture redirect stderr at a time
+    fn lock() -> MutexGuard<'static, ()> {
+        static STDERR_LOCK: Mutex<()> = Mutex::new(());
+        STDERR_LOCK.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Start capturing stderr unconditionally
+    fn start() -> Option<Self> {
+        use std::{
+            fs::File,
+            io::{BufRead, BufReader},
+            os::fd::FromRawFd,
+        };
+
+        let lock = Self::lock();
+
+        // Set up the pipe and the thread that reads from it
+        let mut fds = [0; 2];
+        if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+            return None;
+        }
+        let [read_fd, write_fd] = fds;
+        // SAFETY: read_fd is a freshly created file descriptor that we own
+        let reader = BufReader::new(unsafe { File::from_raw_fd(read_fd) });
+        let forwarder = thread::Builder::new()
+            .name("hwloc-debug".to_owned())
+            .spawn(move || {
+                for line in reader.lines().map_while(Result::ok) {
+                    log::debug!(target: "hwloc", "{line}");
+                }
+            });
+        let Ok(forwarder) = forwarder else {
+            unsafe { libc::close(write_fd) };
+            return None;
+        };
+
+        // Redirect stderr to the pipe. From now on, stderr holds the only
+        // write end of the pipe, so the forwarder stops once it is restored.
+        let saved_stderr = unsafe { libc::dup(libc::STDERR_FILENO) };
+        let redirected =
+            saved_stderr >= 0 && unsafe { libc::dup2(write_fd, libc::STDERR_FILENO) } >= 0;
+        unsafe { libc::close(write_fd) };
+        if !redirected {
+            if saved_stderr >= 0 {
+                unsafe { libc::close(saved_stderr) };
+            }
+            let _ = forwarder.join();
+            return None;
+        }
+        Some(Self {
+            saved_stderr,
+            forwarder: Some(forwarder),
+            _lock: lock,
+        })
+    }
+}
+//
+#[cfg(all(unix, feature = "log"))]
+impl Drop for HwlocDebugCapture {
+    fn drop(&mut self) {
+        unsafe {
+            libc::dup2(self.saved_stderr, libc::STDERR_FILENO);
+            libc::close(self.saved_stderr);
+        }
+        if let Some(forwarder) = self.forwarder.take() {
+            let _ = forwarder.join();
+        }
+    }
+}
+
 impl Drop for TopologyBuilder {
     fn drop(&mut self) {
         if cfg!(debug_assertions) {
@@ -802,3 +1842,264 @@ impl Drop for TopologyBuilder {
         unsafe { ffi::hwloc_topology_destroy(self.as_mut_ptr()) }
     }
 }
+
+// NOTE: An hwloc topology is not bound to the thread that created it, so it can
+//       be configured on one thread and built on another. Shared access is
+//       limited to read-only queries of the configuration, which are safe.
+unsafe impl Send for TopologyBuilder {}
+unsafe impl Sync for TopologyBuilder {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_flags_should_be_known_to_hwloc() {
+        // hwloc rejects unknown topology flags with EINVAL, so this checks
+        // that no flag bit drifted away from the hwloc headers
+        for flag in BuildFlags::all().iter() {
+            let flags = flag | BuildFlags::ASSUME_THIS_SYSTEM;
+            assert!(flags.is_valid());
+            let builder = Topology::builder().with_flags(flags).unwrap();
+            assert_eq!(builder.flags(), flags);
+        }
+    }
+
+    #[test]
+    fn class_filters_should_reject_irrelevant_filters() {
+        assert_eq!(
+            TopologyBuilder::new()
+                .with_cache_filter(TypeFilter::KeepImportant)
+                .unwrap_err(),
+            HybridError::Rust(TypeFilterError::ImportanceIrrelevant)
+        );
+        assert_eq!(
+            TopologyBuilder::new()
+                .with_icache_filter(TypeFilter::KeepImportant)
+                .unwrap_err(),
+            HybridError::Rust(TypeFilterError::ImportanceIrrelevant)
+        );
+        assert_eq!(
+            TopologyBuilder::new()
+                .with_io_filter(TypeFilter::KeepStructure)
+                .unwrap_err(),
+            HybridError::Rust(TypeFilterError::StructureIrrelevant)
+        );
+    }
+
+    #[test]
+    fn class_filters_should_only_affect_their_class() {
+        let builder = TopologyBuilder::new()
+            .with_cache_filter(TypeFilter::KeepNone)
+            .unwrap()
+            .with_icache_filter(TypeFilter::KeepStructure)
+            .unwrap()
+            .with_io_filter(TypeFilter::KeepImportant)
+            .unwrap();
+        for ty in (0..).map_while(|raw: u32| ObjectType::try_from(raw).ok()) {
+            let expected = if ty.is_cpu_instruction_cache() {
+                TypeFilter::KeepStructure
+            } else if ty.is_cpu_data_cache() {
+                TypeFilter::KeepNone
+            } else if ty.is_io() {
+                TypeFilter::KeepImportant
+            } else {
+                TopologyBuilder::new().type_filter(ty).unwrap()
+            };
+            assert_eq!(builder.type_filter(ty).unwrap(), expected, "{ty}");
+        }
+    }
+
+    #[test]
+    fn pci_locality_entries_should_use_hwloc_format() {
+        let first_pus = CpuSet::from_range(0..=3);
+        assert_eq!(
+            pci_locality_entry(0, 0x80..=0xff, &first_pus),
+            "0000:80-ff 0x0000000f"
+        );
+        let pu_33 = CpuSet::from_range(33..=33);
+        assert_eq!(
+            pci_locality_entry(0x1a, 0..=0x0f, &pu_33),
+            "001a:00-0f 0x00000002,0x00000000"
+        );
+    }
+
+    #[test]
+    fn pci_locality_overrides_should_accumulate() {
+        let overrides = EnvironmentOverrides::new()
+            .with_pci_locality(0, 0x80..=0xff, &CpuSet::from_range(0..=3))
+            .with_pci_locality(0x1a, 0..=0x0f, &CpuSet::from_range(33..=33));
+        assert!(!overrides.is_empty());
+        assert_eq!(
+            overrides.pci_locality.as_deref(),
+            Some("0000:80-ff 0x0000000f;001a:00-0f 0x00000002,0x00000000")
+        );
+    }
+
+    #[test]
+    fn environment_overrides_should_require_a_single_thread() {
+        // The test harness runs tests outside of the main thread
+        assert!(!is_single_threaded());
+        let overrides = EnvironmentOverrides::new().ignore_hwloc_variables();
+        assert_eq!(
+            TopologyBuilder::new()
+                .try_build_with_environment_overrides(&overrides)
+                .unwrap_err(),
+            HybridError::Rust(EnvironmentOverrideError)
+        );
+        let topology = TopologyBuilder::new()
+            .try_build_with_environment_overrides(&EnvironmentOverrides::new())
+            .unwrap();
+        assert!(!topology.load_config.overrides_environment());
+    }
+
+    #[test]
+    fn built_topology_should_remember_load_config() {
+        let topology = TopologyBuilder::new().build().unwrap();
+        assert_eq!(topology.load_config, LoadConfig::default());
+        assert!(!topology.load_config.overrides_environment());
+
+        let pid = std::process::id() as ProcessId;
+        if let Ok(builder) = TopologyBuilder::new().from_pid(pid) {
+            let topology = builder.build().unwrap();
+            assert_eq!(topology.load_config.pid, Some(pid));
+        }
+    }
+
+    #[test]
+    fn should_only_keep_all_top_and_bottom_types() {
+        for ty in [ObjectType::Machine, ObjectType::PU, ObjectType::NUMANode] {
+            for filter in [
+                TypeFilter::KeepNone,
+                TypeFilter::KeepStructure,
+                TypeFilter::KeepImportant,
+            ] {
+                assert_eq!(
+                    Topology::builder()
+                        .with_type_filter(ty, filter)
+                        .unwrap_err(),
+                    HybridError::Rust(TypeFilterError::CantIgnore(ty))
+                );
+            }
+            assert!(Topology::builder()
+                .with_type_filter(ty, TypeFilter::KeepAll)
+                .is_ok());
+        }
+    }
+
+    /// Minimal executor that polls `future` on the current thread
+    fn block_on<F: Future>(future: F) -> F::Output {
+        struct ThreadWaker(thread::Thread);
+        //
+        impl std::task::Wake for ThreadWaker {
+            fn wake(self: Arc<Self>) {
+                self.0.unpark();
+            }
+        }
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut context = Context::from_waker(&waker);
+        let mut future = Box::pin(future);
+        loop {
+            match future.as_mut().poll(&mut context) {
+                Poll::Ready(output) => break output,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    #[test]
+    fn build_async() {
+        let topology = block_on(Topology::builder().build_async()).unwrap();
+        assert_eq!(topology.depth(), Topology::test_instance().depth());
+    }
+
+    #[test]
+    fn build_async_should_propagate_panics() {
+        let future = BuildFuture::new(Topology::builder(), |_| panic!("build panicked"));
+        let result = panic::catch_unwind(AssertUnwindSafe(|| block_on(future)));
+        let payload = result.expect_err("Panic should be propagated");
+        assert_eq!(payload.downcast_ref::<&str>(), Some(&"build panicked"));
+    }
+
+    #[cfg(all(unix, feature = "log"))]
+    #[test]
+    fn hwloc_debug_output_should_be_logged() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        /// Logger that records the messages of the hwloc target while this
+        /// test is running, and ignores everything else
+        struct Recorder {
+            active: AtomicBool,
+            lines: Mutex<Vec<String>>,
+        }
+        //
+        impl log::Log for Recorder {
+            fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+                self.active.load(Ordering::Acquire) && metadata.target() == "hwloc"
+            }
+
+            fn log(&self, record: &log::Record<'_>) {
+                if self.enabled(record.metadata()) {
+                    self.lines
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .push(record.args().to_string());
+                }
+            }
+
+            fn flush(&self) {}
+        }
+        static RECORDER: Recorder = Recorder {
+            active: AtomicBool::new(false),
+            lines: Mutex::new(Vec::new()),
+        };
+        if log::set_logger(&RECORDER).is_err() {
+            return;
+        }
+        let max_level = log::max_level();
+        log::set_max_level(log::LevelFilter::Debug);
+        RECORDER.active.store(true, Ordering::Release);
+
+        let capture = HwlocDebugCapture::start().expect("Failed to redirect stderr");
+        let message = b"hwloc debug message\n";
+        let written =
+            unsafe { libc::write(libc::STDERR_FILENO, message.as_ptr().cast(), message.len()) };
+        drop(capture);
+
+        RECORDER.active.store(false, Ordering::Release);
+        log::set_max_level(max_level);
+        assert_eq!(written, isize::try_from(message.len()).unwrap());
+        assert!(RECORDER
+            .lines
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|line| line == "hwloc debug message"));
+    }
+
+    #[cfg(all(unix, feature = "log"))]
+    #[test]
+    fn concurrent_hwloc_debug_captures_should_restore_stderr() {
+        let stderr_target = |fd| {
+            let mut stat = std::mem::MaybeUninit::<libc::stat>::uninit();
+            assert_eq!(unsafe { libc::fstat(fd, stat.as_mut_ptr()) }, 0);
+            let stat = unsafe { stat.assume_init() };
+            (stat.st_dev, stat.st_ino)
+        };
+        let original = {
+            let _lock = HwlocDebugCapture::lock();
+            stderr_target(libc::STDERR_FILENO)
+        };
+        thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for _ in 0..16 {
+                        drop(HwlocDebugCapture::start().expect("Failed to redirect stderr"));
+                    }
+                });
+            }
+        });
+        let _lock = HwlocDebugCapture::lock();
+        assert_eq!(stderr_target(libc::STDERR_FILENO), original);
+    }
+}
@@ -0,0 +1,229 @@
+//! Memoization of expensive topology queries
+
+use crate::{
+    cpu::cpusets::CpuSet,
+    errors::HybridError,
+    memory::nodesets::NodeSet,
+    objects::{
+        distances::{Distances, DistancesKind},
+        TopologyObject,
+    },
+    topology::{builder::FeatureDisabledAtBuild, Topology},
+};
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    sync::{Arc, PoisonError, RwLock},
+};
+
+/// # Memoizing topology queries
+impl Topology {
+    /// Set up a cache of the answers to expensive queries on this topology
+    ///
+    /// See [`TopologyCache`] for more information.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// let cache = topology.cache();
+    /// let cpuset = topology.cpuset().to_owned();
+    /// // Only the first query goes through hwloc, the second one is memoized
+    /// let nodeset = cache.nodeset_from_cpuset(&cpuset);
+    /// assert_eq!(cache.nodeset_from_cpuset(&cpuset), nodeset);
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn cache(&self) -> TopologyCache<'_> {
+        TopologyCache::new(self)
+    }
+}
+
+/// Memoized answers to expensive [`Topology`] queries
+///
+/// Schedulers and other query-heavy code often ask the same questions about a
+/// topology over and over again, and each of these questions goes through
+/// hwloc and allocates a new answer. This cache remembers the answers to some
+/// of the most expensive queries, keyed by their input.
+///
+/// The cache can be shared between threads: answers are stored behind a
+/// [`RwLock`], so that concurrent lookups of known answers do not block each
+/// other. Since the topology is borrowed for the lifetime of the cache, it
+/// cannot be modified while the cache exists, and answers never get stale.
+///
+/// Lookups are not free either. Hashing a bitmap key takes a couple of hwloc
+/// calls plus one per machine word up to its last set (or unset, for infinite
+/// bitmaps) index, a hit then compares the key with the memoized one, and
+/// bitmap answers are cloned, which allocates. Answers that are lists of
+/// objects or distance matrices are shared through an [`Arc`] instead. This is
+/// much cheaper than the queries themselves on large topologies, but on small
+/// topologies, or for queries that are only made once, calling the
+/// [`Topology`] methods directly can be faster.
+///
+/// The cache grows without bound as new queries are made. Use
+/// [`clear()`](Self::clear()) to reclaim memory if needed.
+///
+/// This functionality is unique to the Rust hwloc bindings.
+#[derive(Debug)]
+pub struct TopologyCache<'topology> {
+    /// Topology being queried
+    topology: &'topology Topology,
+
+    /// Memoized [`Topology::largest_objects_inside_cpuset()`] results
+    largest_objects_inside_cpuset: Memo<CpuSet, Arc<[&'topology TopologyObject]>>,
+
+    /// Memoized [`NodeSet::from_cpuset()`] results
+    nodeset_from_cpuset: Memo<CpuSet, NodeSet>,
+
+    /// Memoized [`CpuSet::from_nodeset()`] results
+    cpuset_from_nodeset: Memo<NodeSet, CpuSet>,
+
+    /// Memoized [`Topology::distances()`] results
+    distances: Memo<DistancesKind, Arc<[Distances<'topology>]>>,
+}
+//
+impl<'topology> TopologyCache<'topology> {
+    /// Set up an empty cache for `topology`
+    pub fn new(topology: &'topology Topology) -> Self {
+        Self {
+            topology,
+            largest_objects_inside_cpuset: Memo::default(),
+            nodeset_from_cpuset: Memo::default(),
+            cpuset_from_nodeset: Memo::default(),
+            distances: Memo::default(),
+        }
+    }
+
+    /// Topology whose queries are being memoized
+    pub fn topology(&self) -> &'topology Topology {
+        self.topology
+    }
+
+    /// Memoized [`Topology::largest_objects_inside_cpuset()`]
+    pub fn largest_objects_inside_cpuset(&self, set: &CpuSet) -> Arc<[&'topology TopologyObject]> {
+        self.largest_objects_inside_cpuset
+            .get_or_insert_with(set, || {
+                self.topology
+                    .largest_objects_inside_cpuset(set.clone())
+                    .collect()
+            })
+    }
+
+    /// Memoized [`NodeSet::from_cpuset()`]
+    pub fn nodeset_from_cpuset(&self, cpuset: &CpuSet) -> NodeSet {
+        self.nodeset_from_cpuset
+            .get_or_insert_with(cpuset, || NodeSet::from_cpuset(self.topology, cpuset))
+    }
+
+    /// Memoized [`CpuSet::from_nodeset()`]
+    pub fn cpuset_from_nodeset(&self, nodeset: &NodeSet) -> CpuSet {
+        self.cpuset_from_nodeset
+            .get_or_insert_with(nodeset, || CpuSet::from_nodeset(self.topology, nodeset))
+    }
+
+    /// Memoized [`Topology::distances()`]
+    ///
+    /// Errors are not memoized, so the query is retried on the next call.
+    ///
+    /// # Errors
+    ///
+    /// See [`Topology::distances()`].
+    pub fn distances(
+        &self,
+        kind: DistancesKind,
+    ) -> Result<Arc<[Distances<'topology>]>, HybridError<FeatureDisabledAtBuild>> {
+        self.distances.try_get_or_insert_with(&kind, || {
+            self.topology
+                .distances(kind)
+                .map(|distances| distances.into())
+        })
+    }
+
+    /// Distance between a pair of objects, according to the first distance
+    /// matrix of kind `kind` that features both objects
+    ///
+    /// Returns the distance from the first to the second input object and the
+    /// distance from the second to the first input object, or `None` if no
+    /// memoized distance matrix of kind `kind` features both objects.
+    ///
+    /// # Errors
+    ///
+    /// See [`Topology::distances()`].
+    pub fn object_pair_distance(
+        &self,
+        kind: DistancesKind,
+        objects: (&TopologyObject, &TopologyObject),
+    ) -> Result<Option<(u64, u64)>, HybridError<FeatureDisabledAtBuild>> {
+        Ok(self
+            .distances(kind)?
+            .iter()
+            .find_map(|distances| distances.object_pair_distance(objects)))
+    }
+
+    /// Forget all memoized answers
+    pub fn clear(&self) {
+        self.largest_objects_inside_cpuset.clear();
+        self.nodeset_from_cpuset.clear();
+        self.cpuset_from_nodeset.clear();
+        self.distances.clear();
+    }
+}
+
+/// Memoized answers to one kind of query
+#[derive(Debug)]
+struct Memo<Key, Value>(RwLock<HashMap<Key, Value>>);
+//
+impl<Key: Clone + Eq + Hash, Value: Clone> Memo<Key, Value> {
+    /// Look up the answer for `key`, computing it with `compute` if needed
+    fn get_or_insert_with(&self, key: &Key, compute: impl FnOnce() -> Value) -> Value {
+        match self.try_get_or_insert_with(key, || Ok::<_, std::convert::Infallible>(compute())) {
+            Ok(value) => value,
+            Err(never) => match never {},
+        }
+    }
+
+    /// Look up the answer for `key`, computing it with `compute` if needed
+    ///
+    /// If `compute` fails, the error is returned and nothing is memoized.
+    fn try_get_or_insert_with<Error>(
+        &self,
+        key: &Key,
+        compute: impl FnOnce() -> Result<Value, Error>,
+    ) -> Result<Value, Error> {
+        // Fast path: the answer is already known
+        if let Some(value) = self
+            .0
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(key)
+        {
+            return Ok(value.clone());
+        }
+
+        // Slow path: compute the answer without holding the lock, then record
+        // it unless another thread did so in the meantime
+        let value = compute()?;
+        Ok(self
+            .0
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(key.clone())
+            .or_insert(value)
+            .clone())
+    }
+
+    /// Forget all memoized answers
+    fn clear(&self) {
+        self.0
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+}
+//
+impl<Key, Value> Default for Memo<Key, Value> {
+    fn default() -> Self {
+        Self(RwLock::new(HashMap::new()))
+    }
+}
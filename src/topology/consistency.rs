@@ -0,0 +1,157 @@
+//! Checking topology invariants that downstream code commonly relies on
+//!
+//! hwloc itself guarantees a number of properties about the topologies that it
+//! builds, but some assumptions which are true on most machines are not
+//! guaranteed, and buggy firmware can break others. This module lets you check
+//! them before relying on them.
+
+use super::Topology;
+use crate::{
+    cpu::cpusets::CpuSet,
+    objects::{types::ObjectType, TopologyObject},
+};
+use derive_more::Display;
+
+/// # Consistency checks
+impl Topology {
+    /// Check invariants of the normal object tree that downstream code
+    /// commonly relies on, and report the violations
+    ///
+    /// An empty report means that all of the following holds:
+    ///
+    /// - Every PU has a [`Core`] ancestor. This is not the case on machines
+    ///   where the operating system does not report cores, or when cores are
+    ///   filtered out at build time.
+    /// - The cpusets of the normal children of any object are disjoint.
+    /// - The cpuset of any object which has normal children is the union of
+    ///   the cpusets of these children.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// for issue in topology.consistency_report() {
+    ///     eprintln!("Unexpected topology structure: {issue:?}");
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    ///
+    /// [`Core`]: ObjectType::Core
+    pub fn consistency_report(&self) -> Vec<ConsistencyIssue<'_>> {
+        let mut issues = Vec::new();
+        for pu in self.objects_with_type(ObjectType::PU) {
+            if pu.first_ancestor_with_type(ObjectType::Core).is_none() {
+                issues.push(ConsistencyIssue::PUWithoutCore(pu));
+            }
+        }
+        for depth in 0..self.depth() {
+            for parent in self.objects_at_depth(depth) {
+                check_children(parent, &mut issues);
+            }
+        }
+        issues
+    }
+}
+
+/// Check the invariants relating an object to its normal children
+fn check_children<'topology>(
+    parent: &'topology TopologyObject,
+    issues: &mut Vec<ConsistencyIssue<'topology>>,
+) {
+    let children = parent
+        .normal_children()
+        .filter_map(|child| Some((child, child.cpuset()?)))
+        .collect::<Vec<_>>();
+    if children.is_empty() {
+        return;
+    }
+    let mut union = CpuSet::new();
+    for (idx, (child, cpuset)) in children.iter().enumerate() {
+        if union.intersects(cpuset) {
+            for (sibling, sibling_cpuset) in &children[..idx] {
+                if sibling_cpuset.intersects(cpuset) {
+                    issues.push(ConsistencyIssue::OverlappingSiblings(sibling, child));
+                }
+            }
+        }
+        union |= &**cpuset;
+    }
+    if parent.cpuset().map_or(false, |cpuset| cpuset != union) {
+        issues.push(ConsistencyIssue::CpusetNotUnionOfChildren(parent));
+    }
+}
+
+/// Topology invariant violation reported by [`Topology::consistency_report()`]
+#[derive(Copy, Clone, Debug, Display)]
+pub enum ConsistencyIssue<'topology> {
+    /// This PU does not have a [`Core`] ancestor
+    ///
+    /// [`Core`]: ObjectType::Core
+    #[display(fmt = "{_0} has no Core ancestor")]
+    PUWithoutCore(&'topology TopologyObject),
+
+    /// These normal siblings have overlapping cpusets
+    #[display(fmt = "siblings {_0} and {_1} have overlapping cpusets")]
+    OverlappingSiblings(&'topology TopologyObject, &'topology TopologyObject),
+
+    /// This object's cpuset is not the union of its normal children's cpusets
+    #[display(fmt = "cpuset of {_0} is not the union of its children's cpusets")]
+    CpusetNotUnionOfChildren(&'topology TopologyObject),
+}
+
+/// Callback that is notified of consistency issues at load time
+pub(crate) type ConsistencyHook = Box<dyn FnMut(ConsistencyIssue<'_>) + Send>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    #[test]
+    fn regular_topology_should_be_consistent() {
+        let topology = Topology::builder()
+            .from_synthetic("pack:2 core:2 pu:2")
+            .unwrap()
+            .build()
+            .unwrap();
+        assert!(topology.consistency_report().is_empty());
+    }
+
+    #[test]
+    fn pus_without_cores_should_be_reported() {
+        let topology = Topology::builder()
+            .from_synthetic("pack:2 pu:3")
+            .unwrap()
+            .build()
+            .unwrap();
+        let report = topology.consistency_report();
+        assert_eq!(report.len(), 6);
+        for issue in report {
+            let ConsistencyIssue::PUWithoutCore(pu) = issue else {
+                panic!("Unexpected issue {issue}");
+            };
+            assert_eq!(pu.object_type(), ObjectType::PU);
+        }
+    }
+
+    #[test]
+    fn hook_should_be_called_at_load_time() {
+        let num_issues = Arc::new(AtomicUsize::new(0));
+        let hook_issues = Arc::clone(&num_issues);
+        let topology = Topology::builder()
+            .from_synthetic("pack:2 pu:3")
+            .unwrap()
+            .on_consistency_issue(move |_issue| {
+                hook_issues.fetch_add(1, Ordering::Relaxed);
+            })
+            .build()
+            .unwrap();
+        assert_eq!(
+            num_issues.load(Ordering::Relaxed),
+            topology.consistency_report().len()
+        );
+    }
+}
@@ -0,0 +1,190 @@
+//! What a containerized process actually has access to
+//!
+//! Container runtimes like Docker or Kubernetes usually restrict the CPUs and
+//! NUMA nodes that a process may use through Linux cgroups, without changing
+//! what the hardware looks like. hwloc exposes both views of the machine, and
+//! this module combines them into a single answer.
+
+use super::{
+    builder::{BuildFlags, TopologyBuilder},
+    Topology,
+};
+use crate::{cpu::cpusets::CpuSet, errors::RawHwlocError, memory::nodesets::NodeSet};
+
+/// # Container support
+impl Topology {
+    /// Find out what the current container actually has access to
+    ///
+    /// This loads the topology of the host twice: once with
+    /// [`BuildFlags::INCLUDE_DISALLOWED`] to see the full machine, and once
+    /// with default settings, which strips CPUs and NUMA nodes that the current
+    /// process is not allowed to use (e.g. due to Linux cgroup restrictions).
+    /// The differences between the two are reported as hidden resources.
+    /// Offline CPUs and NUMA nodes are not reported as hidden, since no
+    /// process can use them.
+    ///
+    /// # Errors
+    ///
+    /// See [`TopologyBuilder::build()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::Topology;
+    /// let view = Topology::container_view()?;
+    /// println!(
+    ///     "Running in a container: {}, usable CPUs: {}, hidden CPUs: {}",
+    ///     view.is_containerized(),
+    ///     view.topology().allowed_cpuset(),
+    ///     view.hidden_cpuset()
+    /// );
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn container_view() -> Result<ContainerView, RawHwlocError> {
+        let full = TopologyBuilder::try_new()?
+            .with_flags(BuildFlags::INCLUDE_DISALLOWED)
+            .expect("INCLUDE_DISALLOWED should be a valid flag")
+            .build()?;
+        let (hidden_cpuset, hidden_nodeset) = hidden_resources(&full);
+        Ok(ContainerView {
+            topology: Topology::new()?,
+            containerized: is_containerized(),
+            hidden_cpuset,
+            hidden_nodeset,
+        })
+    }
+}
+
+/// CPUs and NUMA nodes of a topology built with
+/// [`BuildFlags::INCLUDE_DISALLOWED`] that the current process may not use
+///
+/// Offline resources, which are only part of the complete sets, are not
+/// reported since nobody can use them.
+fn hidden_resources(full: &Topology) -> (CpuSet, NodeSet) {
+    (
+        full.cpuset() - full.allowed_cpuset(),
+        full.nodeset() - full.allowed_nodeset(),
+    )
+}
+
+/// What the current container has access to
+///
+/// Returned by [`Topology::container_view()`].
+#[derive(Clone, Debug)]
+pub struct ContainerView {
+    /// Topology restricted to the resources that are available to us
+    topology: Topology,
+
+    /// Truth that we appear to be running inside of a container
+    containerized: bool,
+
+    /// CPUs that exist on the host but cannot be used
+    hidden_cpuset: CpuSet,
+
+    /// NUMA nodes that exist on the host but cannot be used
+    hidden_nodeset: NodeSet,
+}
+//
+impl ContainerView {
+    /// Topology of the resources that the current process may use
+    ///
+    /// Disallowed CPUs and NUMA nodes, and objects which only contain
+    /// disallowed resources, are not present in this topology.
+    pub fn topology(&self) -> &Topology {
+        &self.topology
+    }
+
+    /// Extract the topology of the resources that the process may use
+    pub fn into_topology(self) -> Topology {
+        self.topology
+    }
+
+    /// Truth that the current process appears to be running in a container
+    ///
+    /// This is a best-effort heuristic, based on the presence of the
+    /// `/.dockerenv` and `/run/.containerenv` files created by the Docker and
+    /// Podman runtimes, of Kubernetes service environment variables, and on
+    /// the current process being at the root of a cgroup namespace. It always
+    /// returns `false` on operating systems other than Linux.
+    ///
+    /// Resource restrictions can apply whether or not a container is
+    /// detected, see [`hidden_cpuset()`](Self::hidden_cpuset()) and
+    /// [`hidden_nodeset()`](Self::hidden_nodeset()) for these.
+    pub fn is_containerized(&self) -> bool {
+        self.containerized
+    }
+
+    /// CPUs that exist on the host, but that the process may not use
+    pub fn hidden_cpuset(&self) -> &CpuSet {
+        &self.hidden_cpuset
+    }
+
+    /// NUMA nodes that exist on the host, but that the process may not use
+    pub fn hidden_nodeset(&self) -> &NodeSet {
+        &self.hidden_nodeset
+    }
+
+    /// Truth that some host resources are hidden from the process
+    pub fn is_restricted(&self) -> bool {
+        !(self.hidden_cpuset.is_empty() && self.hidden_nodeset.is_empty())
+    }
+}
+
+/// Check for common signs of containerization
+#[cfg(target_os = "linux")]
+fn is_containerized() -> bool {
+    use std::{fs, path::Path};
+    if Path::new("/.dockerenv").exists()
+        || Path::new("/run/.containerenv").exists()
+        || std::env::var_os("KUBERNETES_SERVICE_HOST").is_some()
+    {
+        return true;
+    }
+    // Outside of a cgroup namespace, processes other than init are usually
+    // not in the root cgroup of the cgroup v2 hierarchy
+    fs::read_to_string("/proc/self/cgroup").map_or(false, |cgroups| {
+        std::process::id() != 1 && cgroups.lines().any(|line| line == "0::/")
+    })
+}
+//
+#[cfg(not(target_os = "linux"))]
+fn is_containerized() -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Machine with 8 CPUs and 2 NUMA nodes, where CPUs 4-7 and NUMA node 1
+    /// are offline, and only CPUs 0-1 are allowed
+    const PARTIALLY_ONLINE_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE topology SYSTEM "hwloc2.dtd">
+<topology version="2.0">
+  <object type="Machine" os_index="0" cpuset="0x0000000f" complete_cpuset="0x000000ff" allowed_cpuset="0x00000003" nodeset="0x00000001" complete_nodeset="0x00000003" allowed_nodeset="0x00000001" gp_index="1">
+    <object type="NUMANode" os_index="0" cpuset="0x0000000f" complete_cpuset="0x0000000f" nodeset="0x00000001" complete_nodeset="0x00000001" gp_index="2" local_memory="1073741824"/>
+    <object type="PU" os_index="0" cpuset="0x00000001" complete_cpuset="0x00000001" nodeset="0x00000001" complete_nodeset="0x00000001" gp_index="3"/>
+    <object type="PU" os_index="1" cpuset="0x00000002" complete_cpuset="0x00000002" nodeset="0x00000001" complete_nodeset="0x00000001" gp_index="4"/>
+    <object type="PU" os_index="2" cpuset="0x00000004" complete_cpuset="0x00000004" nodeset="0x00000001" complete_nodeset="0x00000001" gp_index="5"/>
+    <object type="PU" os_index="3" cpuset="0x00000008" complete_cpuset="0x00000008" nodeset="0x00000001" complete_nodeset="0x00000001" gp_index="6"/>
+  </object>
+</topology>
+"#;
+
+    #[test]
+    fn offline_resources_should_not_be_hidden() {
+        let full = Topology::builder()
+            .from_xml(PARTIALLY_ONLINE_XML)
+            .unwrap()
+            .with_flags(BuildFlags::INCLUDE_DISALLOWED)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_ne!(full.complete_cpuset(), full.cpuset());
+        assert_ne!(full.complete_nodeset(), full.nodeset());
+
+        let (hidden_cpuset, hidden_nodeset) = hidden_resources(&full);
+        assert_eq!(hidden_cpuset, CpuSet::from_range(2..=3));
+        assert!(hidden_nodeset.is_empty());
+    }
+}
@@ -7,7 +7,7 @@ use crate::{
     errors::{self, HybridError, NulError, ParameterError, RawHwlocError},
     ffi::{self, LibcString},
     memory::nodesets::NodeSet,
-    objects::TopologyObject,
+    objects::{ForeignObjectError, TopologyObject},
     topology::Topology,
 };
 #[cfg(doc)]
@@ -19,15 +19,16 @@ use bitflags::bitflags;
 use derive_more::Display;
 use libc::{EINVAL, ENOMEM};
 use std::{
-    ffi::c_ulong,
+    ffi::{c_uint, c_ulong},
     fmt,
     panic::{AssertUnwindSafe, UnwindSafe},
     ptr,
 };
+use thiserror::Error;
 
 /// # Modifying a loaded `Topology`
 //
-// Upstream docs: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__tinker.html
+// Upstream docs: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__tinker.html
 impl Topology {
     /// Modify this topology
     ///
@@ -43,6 +44,50 @@ impl Topology {
     /// efficient topology editing, the right thing to do would be to set up an
     /// alternate hwloc Rust binding optimized for that, with some code sharing
     /// with respect to hwlocality.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::{
+    /// #     objects::types::ObjectType,
+    /// #     topology::{editor::RestrictFlags, Topology},
+    /// # };
+    /// let mut topology = Topology::test_instance().clone();
+    ///
+    /// // Restrict the topology to the first PU
+    /// let first_pu = topology
+    ///     .objects_with_type(ObjectType::PU)
+    ///     .next()
+    ///     .unwrap()
+    ///     .cpuset()
+    ///     .unwrap()
+    ///     .to_owned();
+    /// topology.edit(|editor| editor.restrict(&first_pu, RestrictFlags::empty()))?;
+    /// assert_eq!(topology.objects_with_type(ObjectType::PU).count(), 1);
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    ///
+    /// Editing may invalidate [`TopologyObject`] references, so the borrow
+    /// checker will not let you keep any of them across an edit:
+    ///
+    /// ```compile_fail
+    /// # use hwlocality::{
+    /// #     objects::types::ObjectType,
+    /// #     topology::{editor::RestrictFlags, Topology},
+    /// # };
+    /// let mut topology = Topology::test_instance().clone();
+    /// let last_pu = topology.objects_with_type(ObjectType::PU).last().unwrap();
+    /// let first_pu = topology
+    ///     .objects_with_type(ObjectType::PU)
+    ///     .next()
+    ///     .unwrap()
+    ///     .cpuset()
+    ///     .unwrap()
+    ///     .to_owned();
+    /// topology.edit(|editor| editor.restrict(&first_pu, RestrictFlags::empty()))?;
+    /// println!("{last_pu}");  // Error: last_pu may have been removed
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
     #[doc(alias = "hwloc_topology_refresh")]
     pub fn edit<R>(&mut self, edit: impl UnwindSafe + FnOnce(&mut TopologyEditor) -> R) -> R {
         // Set up topology editing
@@ -53,7 +98,7 @@ impl Topology {
         let result = std::panic::catch_unwind(move || edit(&mut editor));
 
         // Force eager evaluation of all caches
-        self.refresh();
+        self.refresh_caches();
 
         // Return user callback result or resume unwinding as appropriate
         match result {
@@ -70,7 +115,7 @@ impl Topology {
     /// A process abort will occur if this fails as we must not let an invalid
     /// `Topology` state escape, not even via unwinding, as that would result in
     /// undefined behavior (mutation which the compiler assumes will not happen).
-    pub(crate) fn refresh(&mut self) {
+    pub(crate) fn refresh_caches(&mut self) {
         let result = errors::call_hwloc_int_normal("hwloc_topology_refresh", || unsafe {
             ffi::hwloc_topology_refresh(self.as_mut_ptr())
         });
@@ -81,6 +126,7 @@ impl Topology {
         if cfg!(debug_assertions) {
             unsafe { ffi::hwloc_topology_check(self.as_ptr()) }
         }
+        self.update_derived_data();
     }
 }
 
@@ -125,7 +171,7 @@ impl<'topology> TopologyEditor<'topology> {
     /// This requires rebuilding inner caches, which can be costly. Prefer
     /// accessing the topology before or after editing it if possible.
     pub fn topology(&mut self) -> &Topology {
-        self.topology_mut().refresh();
+        self.topology_mut().refresh_caches();
         self.topology_mut()
     }
 
@@ -142,7 +188,7 @@ impl<'topology> TopologyEditor<'topology> {
 
 /// # Basic modifications
 //
-// Upstream docs: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__tinker.html
+// Upstream docs: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__tinker.html
 impl TopologyEditor<'_> {
     /// Restrict the topology to the given CPU set or nodeset
     ///
@@ -255,13 +301,19 @@ impl TopologyEditor<'_> {
     /// will be created.
     ///
     /// Use the `merge` option to control hwloc's propension to merge groups
-    /// with hierarchically-identical topology objects.
+    /// with hierarchically-identical topology objects. Whether merging was
+    /// prevented can later be checked with
+    /// [`GroupAttributes::merging_prevented()`](crate::objects::attributes::GroupAttributes::merging_prevented).
     ///
     /// After insertion, [`TopologyObject::set_subtype()`] can be used to
     /// display something other than "Group" as the type name for this object in
     /// `lstopo`, and custom name/value info pairs may be added using
     /// [`TopologyObject::add_info()`].
     ///
+    /// If `find_children` returns objects that do not belong to the topology
+    /// that is being edited, [`GroupInsertResult::Failed`] is returned with a
+    /// [`ForeignObjectError`].
+    ///
     /// [`Group`]: ObjectType::Group
     //
     // NOTE: In the future, find_children will be an
@@ -281,26 +333,33 @@ impl TopologyEditor<'_> {
         });
         let mut group = match group {
             Ok(group) => group,
-            Err(e) => return GroupInsertResult::Failed(e),
+            Err(e) => return GroupInsertResult::Failed(HybridError::Hwloc(e)),
         };
 
         // Expand cpu sets and node sets to cover designated children
         // NOTE: This function may panic, in which case an allocation will be
         //       leaked, but hwloc does not provide a way to liberate it...
-        let children = find_children(self.topology());
+        let topology = self.topology();
+        let children = find_children(topology);
         for child in children {
+            if !topology.contains(child) {
+                return GroupInsertResult::Failed(ForeignObjectError.into());
+            }
             let result = errors::call_hwloc_int_normal("hwloc_obj_add_other_obj_sets", || unsafe {
                 ffi::hwloc_obj_add_other_obj_sets(group.as_ptr(), child)
             });
             if let Err(e) = result {
-                return GroupInsertResult::Failed(e);
+                return GroupInsertResult::Failed(HybridError::Hwloc(e));
             }
         }
 
         // Adjust hwloc's propension to merge groups if instructed to do so
+        //
+        // NOTE: GroupAttributes is Copy, so care must be taken to modify the
+        //       attributes of the group object in place, not a copy of them.
         if let Some(merge) = merge {
-            let mut group_attributes = unsafe {
-                group
+            let group_attributes = unsafe {
+                &mut group
                     .as_mut()
                     .raw_attributes()
                     .expect("Expected group attributes")
@@ -319,7 +378,7 @@ impl TopologyEditor<'_> {
         match result {
             Ok(result) if result == group => GroupInsertResult::New(unsafe { group.as_mut() }),
             Ok(mut other) => GroupInsertResult::Existing(unsafe { other.as_mut() }),
-            Err(e) => GroupInsertResult::Failed(e),
+            Err(e) => GroupInsertResult::Failed(HybridError::Hwloc(e)),
         }
     }
 
@@ -339,16 +398,21 @@ impl TopologyEditor<'_> {
     ///
     /// # Errors
     ///
-    /// None will be returned if an error occurs or if Misc objects are
-    /// filtered out of the topology via [`TypeFilter::KeepNone`].
+    /// - [`NameContainsNul`] if `name` contains NUL chars.
+    /// - [`ForeignParent`] if `find_parent` returns an object that does not
+    ///   belong to the topology that is being edited.
+    /// - An hwloc error if Misc objects are filtered out of the topology via
+    ///   [`TypeFilter::KeepNone`].
     ///
+    /// [`NameContainsNul`]: InsertMiscError::NameContainsNul
+    /// [`ForeignParent`]: InsertMiscError::ForeignParent
     /// [`Misc`]: ObjectType::Misc
     #[doc(alias = "hwloc_topology_insert_misc_object")]
     pub fn insert_misc_object(
         &mut self,
         name: &str,
         find_parent: impl FnOnce(&Topology) -> &TopologyObject,
-    ) -> Result<&mut TopologyObject, HybridError<NulError>> {
+    ) -> Result<&mut TopologyObject, HybridError<InsertMiscError>> {
         // This is on the edge of violating Rust's aliasing rules, but I think
         // it should work out because...
         //
@@ -369,15 +433,96 @@ impl TopologyEditor<'_> {
         // allowed to assume that nothing changed behind that shared reference.
         // So letting the client keep hold of it would be highly problematic.
         //
-        let parent: *const TopologyObject = find_parent(self.topology());
+        let topology = self.topology();
+        let parent = find_parent(topology);
+        if !topology.contains(parent) {
+            return Err(InsertMiscError::from(ForeignObjectError).into());
+        }
+        let parent: *const TopologyObject = parent;
         let parent = parent.cast_mut();
-        let name = LibcString::new(name)?;
+        let name = LibcString::new(name).map_err(InsertMiscError::from)?;
         let mut ptr = errors::call_hwloc_ptr_mut("hwloc_topology_insert_misc_object", || unsafe {
             ffi::hwloc_topology_insert_misc_object(self.topology_mut_ptr(), parent, name.borrow())
         })
         .map_err(HybridError::Hwloc)?;
         Ok(unsafe { ptr.as_mut() })
     }
+
+    /// Set the [subtype](TopologyObject::subtype()) of an existing object
+    ///
+    /// The object whose subtype should be changed is selected by
+    /// `find_object`. This can be used to provide information that hwloc
+    /// could not detect, for example to tell which NUMA nodes of a
+    /// heterogeneous memory system are high-bandwidth memory.
+    ///
+    /// # Errors
+    ///
+    /// - [`SubtypeContainsNul`] if `subtype` contains NUL chars.
+    /// - [`ForeignObject`] if `find_object` returns an object that does not
+    ///   belong to the topology that is being edited.
+    ///
+    /// [`SubtypeContainsNul`]: SetSubtypeError::SubtypeContainsNul
+    /// [`ForeignObject`]: SetSubtypeError::ForeignObject
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::{
+    /// #     objects::{types::ObjectType, ForeignObjectError},
+    /// #     topology::editor::SetSubtypeError,
+    /// #     Topology,
+    /// # };
+    /// let mut topology = Topology::test_instance().clone();
+    /// topology.edit(|editor| {
+    ///     editor.set_object_subtype("HBM", |topology| {
+    ///         topology
+    ///             .objects_with_type(ObjectType::NUMANode)
+    ///             .next()
+    ///             .expect("There should be at least one NUMA node")
+    ///     })
+    /// })?;
+    /// let node = topology
+    ///     .objects_with_type(ObjectType::NUMANode)
+    ///     .next()
+    ///     .unwrap();
+    /// assert_eq!(node.subtype().unwrap().to_str()?, "HBM");
+    ///
+    /// // Objects from other topologies are rejected
+    /// let other = Topology::test_instance();
+    /// let result = topology.edit(|editor| {
+    ///     editor.set_object_subtype("HBM", |_| other.root_object())
+    /// });
+    /// assert_eq!(result, Err(SetSubtypeError::ForeignObject(ForeignObjectError)));
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    #[doc(alias = "hwloc_obj::subtype")]
+    pub fn set_object_subtype(
+        &mut self,
+        subtype: &str,
+        find_object: impl FnOnce(&Topology) -> &TopologyObject,
+    ) -> Result<(), SetSubtypeError> {
+        // See insert_misc_object() for a discussion of the aliasing concerns.
+        // Here hwloc is not involved but we modify the object ourselves, so we
+        // must not write through a pointer derived from the &TopologyObject.
+        // Instead, we look up the object again from the *mut RawTopology, by
+        // its depth and logical index, which uniquely identify it.
+        let topology = self.topology();
+        let object = find_object(topology);
+        if !topology.contains(object) {
+            return Err(ForeignObjectError.into());
+        }
+        let depth = object.depth();
+        let logical_index =
+            c_uint::try_from(object.logical_index()).expect("Can't happen, index comes from hwloc");
+        let object = unsafe {
+            ffi::hwloc_get_obj_by_depth(self.topology_mut_ptr(), depth.into(), logical_index)
+        };
+        assert!(
+            !object.is_null(),
+            "Got null pointer from hwloc_get_obj_by_depth"
+        );
+        unsafe { (*object).set_subtype(subtype) }.map_err(SetSubtypeError::from)
+    }
 }
 
 bitflags! {
@@ -520,13 +665,40 @@ pub enum GroupInsertResult<'topology> {
     /// it in favor of existing topology object at the same location.
     Existing(&'topology mut TopologyObject),
 
-    /// One hwloc API call failed
+    /// Group insertion failed
     ///
-    /// This can happen if there are conflicting sets in the topology tree,
-    /// if [`Group`](ObjectType::Group) objects are filtered out of the
-    /// topology through [`TypeFilter::KeepNone`], or if the effective CPU set
-    /// or NUMA node set ends up being empty.
-    Failed(RawHwlocError),
+    /// A [`ForeignObjectError`] is reported if the `find_children` callback
+    /// returned objects from another topology.
+    ///
+    /// An hwloc API call may also fail if there are conflicting sets in the
+    /// topology tree, if [`Group`](ObjectType::Group) objects are filtered out
+    /// of the topology through [`TypeFilter::KeepNone`], or if the effective
+    /// CPU set or NUMA node set ends up being empty.
+    Failed(HybridError<ForeignObjectError>),
+}
+
+/// Error returned by [`TopologyEditor::insert_misc_object()`]
+#[derive(Copy, Clone, Debug, Eq, Error, Hash, PartialEq)]
+pub enum InsertMiscError {
+    /// Requested name contains NUL chars
+    #[error(transparent)]
+    NameContainsNul(#[from] NulError),
+
+    /// Requested parent does not belong to the topology that is being edited
+    #[error(transparent)]
+    ForeignParent(#[from] ForeignObjectError),
+}
+
+/// Error returned by [`TopologyEditor::set_object_subtype()`]
+#[derive(Copy, Clone, Debug, Eq, Error, Hash, PartialEq)]
+pub enum SetSubtypeError {
+    /// Requested subtype contains NUL chars
+    #[error(transparent)]
+    SubtypeContainsNul(#[from] NulError),
+
+    /// Requested object does not belong to the topology that is being edited
+    #[error(transparent)]
+    ForeignObject(#[from] ForeignObjectError),
 }
 
 // NOTE: Do not implement traits like AsRef/Deref/Borrow, that would be unsafe
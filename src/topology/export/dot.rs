@@ -0,0 +1,211 @@
+//! Exporting topologies to Graphviz DOT
+
+use crate::{
+    objects::{types::ObjectType, TopologyObject},
+    topology::Topology,
+};
+use std::fmt::Write;
+
+/// # Exporting Topologies to Graphviz DOT
+impl Topology {
+    /// Export the topology as a Graphviz DOT graph
+    ///
+    /// The resulting graph is an lstopo-like tree where each topology object
+    /// is a node, connected to its parent by an edge. It can be rendered with
+    /// Graphviz tools, e.g. `dot -Tsvg topology.dot -o topology.svg`.
+    ///
+    /// Which objects are displayed can be tuned using `options`. When an
+    /// object is hidden, its displayed descendants are attached to its closest
+    /// displayed ancestor.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::{objects::types::ObjectType, topology::export::dot::DotOptions};
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// // Hide caches and PUs, keeping cores and other objects
+    /// let options = DotOptions {
+    ///     hidden_types: vec![
+    ///         ObjectType::L1Cache,
+    ///         ObjectType::L2Cache,
+    ///         ObjectType::L3Cache,
+    ///         ObjectType::PU,
+    ///     ],
+    ///     ..DotOptions::default()
+    /// };
+    /// let dot = topology.to_dot(&options);
+    /// assert!(dot.starts_with("digraph topology {"));
+    /// assert_eq!(dot.matches("label=\"PU").count(), 0);
+    /// assert_eq!(
+    ///     dot.matches("label=\"Core").count(),
+    ///     topology.objects_with_type(ObjectType::Core).count()
+    /// );
+    /// ```
+    #[doc(alias = "lstopo")]
+    pub fn to_dot(&self, options: &DotOptions) -> String {
+        let mut dot = String::from("digraph topology {\n");
+        dot.push_str("    rankdir=TB;\n");
+        dot.push_str("    node [shape=box, style=\"rounded,filled\", fontname=\"monospace\"];\n");
+        let root = self.root_object();
+        write_node(&mut dot, root, options);
+        write_descendants(&mut dot, root, root, 0, options);
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// Options for [`Topology::to_dot()`]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct DotOptions {
+    /// Maximal depth of displayed objects below the root object
+    ///
+    /// The root object has depth 0, its children have depth 1, and so on.
+    /// Memory, I/O and Misc children count as one level below their parent.
+    /// Objects that lie deeper are not displayed, nor are their descendants.
+    ///
+    /// By default, all objects are displayed.
+    pub max_depth: Option<usize>,
+
+    /// Types of objects that should not be displayed
+    ///
+    /// Their descendants are still displayed unless filtered out otherwise.
+    pub hidden_types: Vec<ObjectType>,
+
+    /// Truth that memory objects (NUMA nodes, memory-side caches) should be
+    /// displayed
+    ///
+    /// Defaults to `true`.
+    pub memory: bool,
+
+    /// Truth that I/O objects (bridges, PCI and OS devices) should be
+    /// displayed
+    ///
+    /// Note that I/O objects are only present in the topology if they were
+    /// requested at construction time, see
+    /// [`TopologyBuilder::with_io_type_filter()`].
+    ///
+    /// Defaults to `true`.
+    ///
+    /// [`TopologyBuilder::with_io_type_filter()`]: crate::topology::builder::TopologyBuilder::with_io_type_filter()
+    pub io: bool,
+
+    /// Truth that Misc objects should be displayed
+    ///
+    /// Defaults to `true`.
+    pub misc: bool,
+
+    /// Truth that the cpuset of each object should be displayed
+    ///
+    /// Defaults to `false`.
+    pub cpusets: bool,
+}
+//
+impl Default for DotOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: None,
+            hidden_types: Vec::new(),
+            memory: true,
+            io: true,
+            misc: true,
+            cpusets: false,
+        }
+    }
+}
+
+/// Write the descendants of `parent`, attaching them to `displayed_parent`
+fn write_descendants(
+    dot: &mut String,
+    parent: &TopologyObject,
+    displayed_parent: &TopologyObject,
+    parent_depth: usize,
+    options: &DotOptions,
+) {
+    let depth = parent_depth + 1;
+    if options
+        .max_depth
+        .map_or(false, |max_depth| depth > max_depth)
+    {
+        return;
+    }
+    for child in parent.all_children() {
+        let ty = child.object_type();
+        if (ty.is_memory() && !options.memory)
+            || (ty.is_io() && !options.io)
+            || (ty == ObjectType::Misc && !options.misc)
+        {
+            // Whole subtree is filtered out
+            continue;
+        }
+        if !options.hidden_types.contains(&ty) {
+            write_node(dot, child, options);
+            let style = if ty.is_normal() {
+                ""
+            } else {
+                " [style=dashed]"
+            };
+            writeln!(
+                dot,
+                "    {} -> {}{style};",
+                node_id(displayed_parent),
+                node_id(child)
+            )
+            .expect("Writing to a String can't fail");
+            write_descendants(dot, child, child, depth, options);
+        } else {
+            write_descendants(dot, child, displayed_parent, depth, options);
+        }
+    }
+}
+
+/// Write the DOT node that represents `obj`
+fn write_node(dot: &mut String, obj: &TopologyObject, options: &DotOptions) {
+    let ty = obj.object_type();
+    let mut label = format!("{obj}\nL#{}", obj.logical_index());
+    if let Some(os_index) = obj.os_index() {
+        write!(label, " P#{os_index}").expect("Writing to a String can't fail");
+    }
+    if let Some(name) = obj.name() {
+        write!(label, "\n{}", name.to_string_lossy()).expect("Writing to a String can't fail");
+    }
+    if options.cpusets {
+        if let Some(cpuset) = obj.cpuset() {
+            write!(label, "\ncpuset {cpuset}").expect("Writing to a String can't fail");
+        }
+    }
+    let color = if ty.is_memory() {
+        "#d6e8c8"
+    } else if ty.is_io() {
+        "#d8dbe8"
+    } else if ty == ObjectType::Misc || ty.is_cpu_cache() {
+        "#ffffff"
+    } else {
+        "#dedede"
+    };
+    writeln!(
+        dot,
+        "    {} [label=\"{}\", fillcolor=\"{color}\"];",
+        node_id(obj),
+        escape(&label)
+    )
+    .expect("Writing to a String can't fail");
+}
+
+/// DOT identifier of the node that represents `obj`
+fn node_id(obj: &TopologyObject) -> String {
+    format!("obj{}", obj.global_persistent_index())
+}
+
+/// Escape a string for use inside of a quoted DOT string
+fn escape(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            c => result.push(c),
+        }
+    }
+    result
+}
@@ -1,4 +1,5 @@
 //! Exporting topologies to textual data
 
+pub mod dot;
 pub mod synthetic;
 pub mod xml;
@@ -4,24 +4,31 @@
 use crate::{errors::NulError, topology::builder::TopologyBuilder};
 use crate::{
     errors::{self, HybridError, RawHwlocError},
-    ffi,
+    ffi::{self, LibcString},
+    objects::TopologyObject,
     paths::{self, PathError},
-    topology::Topology,
+    topology::{RawTopology, Topology},
 };
 use bitflags::bitflags;
 use std::{
+    any::Any,
     borrow::Borrow,
-    ffi::{c_char, c_uint, c_ulong, CStr, OsStr},
+    cell::{Cell, RefCell},
+    ffi::{c_char, c_uint, c_ulong, c_void, CStr, OsStr},
     fmt::{self, Debug, Display},
     hash::Hash,
+    marker::PhantomData,
     ops::{Deref, Index},
+    panic::AssertUnwindSafe,
     path::Path,
     ptr::{self, NonNull},
+    time::Instant,
 };
+use thiserror::Error;
 
 /// # Exporting Topologies to XML
 //
-// Upstream docs: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__xmlexport.html
+// Upstream docs: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__xmlexport.html
 impl Topology {
     /// Export the topology into an XML file at filesystem location `path`
     ///
@@ -53,15 +60,18 @@ impl Topology {
         path: Option<impl AsRef<Path>>,
         flags: XMLExportFlags,
     ) -> Result<(), HybridError<PathError>> {
-        let path = if let Some(path) = path {
-            paths::make_hwloc_path(path.as_ref())?
-        } else {
-            paths::make_hwloc_path(Path::new("-")).expect("Known to be valid")
-        };
+        let rust_path = path.as_ref().map_or(Path::new("-"), AsRef::as_ref);
+        let path = paths::make_hwloc_path(rust_path)?;
+        let start = Instant::now();
         errors::call_hwloc_int_normal("hwloc_topology_export_xml", || unsafe {
             ffi::hwloc_topology_export_xml(self.as_ptr(), path.borrow(), flags.bits())
         })
         .map_err(HybridError::Hwloc)?;
+        log_event!(
+            Debug,
+            "Exported topology to XML file {rust_path:?} with flags {flags:?} in {:?}",
+            start.elapsed()
+        );
         Ok(())
     }
 
@@ -88,6 +98,7 @@ impl Topology {
     pub fn export_xml(&self, flags: XMLExportFlags) -> Result<XML, RawHwlocError> {
         let mut xmlbuffer = ptr::null_mut();
         let mut buflen = 0;
+        let start = Instant::now();
         errors::call_hwloc_int_normal("hwloc_topology_export_xmlbuffer", || unsafe {
             ffi::hwloc_topology_export_xmlbuffer(
                 self.as_ptr(),
@@ -99,9 +110,101 @@ impl Topology {
         let buflen = ffi::expect_usize(
             c_uint::try_from(buflen).expect("Got negative buffer length from hwloc"),
         );
+        log_event!(
+            Debug,
+            "Exported topology to {buflen}B of XML with flags {flags:?} in {:?}",
+            start.elapsed()
+        );
         Ok(unsafe { XML::wrap(self, xmlbuffer, buflen) }
             .expect("Got null pointer from hwloc_topology_export_xmlbuffer"))
     }
+
+    /// Export the topology into an XML memory buffer, along with
+    /// application-specific object userdata
+    ///
+    /// This works like [`export_xml()`](Self::export_xml()), except that
+    /// `codec` gets to [encode](XmlUserdataCodec::encode()) some data for every
+    /// object of the topology, which is saved in the XML output. The resulting
+    /// XML can then be loaded with
+    /// [`TopologyBuilder::build_with_xml_userdata()`] so that this data is
+    /// [decoded](XmlUserdataCodec::decode()) again.
+    ///
+    /// This requires exclusive access to the topology because hwloc needs to
+    /// be told about the export callback. Call `to_string()` on the output if
+    /// you need to use the topology again while the XML is still around.
+    ///
+    /// If `codec` panics, the export is aborted and the panic is propagated
+    /// once the hwloc call has returned.
+    ///
+    /// # Errors
+    ///
+    /// - Any error returned by [`XmlUserdataCodec::encode()`], which aborts
+    ///   the export.
+    /// - [`HybridError::Hwloc`] if hwloc fails to export the topology.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::{
+    /// #     errors::HybridError,
+    /// #     objects::TopologyObject,
+    /// #     topology::export::xml::{
+    /// #         XMLExportFlags, XmlUserdataCodec, XmlUserdataExporter, XmlUserdataNameError,
+    /// #     },
+    /// # };
+    /// /// Tag every object with its OS index
+    /// struct OsIndexCodec;
+    /// //
+    /// impl XmlUserdataCodec for OsIndexCodec {
+    ///     fn encode(
+    ///         &mut self,
+    ///         object: &TopologyObject,
+    ///         exporter: &mut XmlUserdataExporter<'_>,
+    ///     ) -> Result<(), HybridError<XmlUserdataNameError>> {
+    ///         match object.os_index() {
+    ///             Some(index) => exporter.export(Some("os_index"), index.to_string().as_bytes()),
+    ///             None => Ok(()),
+    ///         }
+    ///     }
+    ///
+    ///     fn decode(&mut self, _object: &TopologyObject, _name: Option<&str>, _data: &[u8]) {}
+    /// }
+    ///
+    /// let mut topology = hwlocality::Topology::test_instance().clone();
+    /// let xml = topology.export_xml_with_userdata(XMLExportFlags::empty(), &mut OsIndexCodec)?;
+    /// assert!(xml.contains("os_index"));
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    #[doc(alias = "hwloc_topology_set_userdata_export_callback")]
+    pub fn export_xml_with_userdata(
+        &mut self,
+        flags: XMLExportFlags,
+        codec: &mut impl XmlUserdataCodec,
+    ) -> Result<XML<'_>, HybridError<XmlUserdataNameError>> {
+        // The callback stays installed afterwards, which is harmless since it
+        // does nothing when no userdata export is in progress
+        unsafe {
+            ffi::hwloc_topology_set_userdata_export_callback(
+                self.as_mut_ptr(),
+                Some(export_userdata_callback),
+            );
+        }
+        let mut state = ExportState {
+            topology: self.as_ptr(),
+            codec,
+            result: Ok(()),
+            panic: None,
+        };
+        let xml = {
+            let _active = ActiveExport::enter(&mut state);
+            self.export_xml(flags)
+        };
+        if let Some(payload) = state.panic {
+            std::panic::resume_unwind(payload);
+        }
+        state.result?;
+        xml.map_err(HybridError::Hwloc)
+    }
 }
 
 bitflags! {
@@ -123,6 +226,287 @@ impl Default for XMLExportFlags {
     }
 }
 
+/// Application-specific data that is saved along with topology objects in XML
+///
+/// hwloc lets applications attach their own data to topology objects when a
+/// topology is exported to XML, and retrieve it when the XML is loaded back.
+/// Implementations of this trait describe how some application state is
+/// converted to and from such userdata, in the spirit of serde's
+/// `Serialize`/`Deserialize` traits.
+///
+/// Use it with [`Topology::export_xml_with_userdata()`] and
+/// [`TopologyBuilder::build_with_xml_userdata()`].
+///
+/// This functionality is unique to the Rust hwloc bindings.
+#[doc(alias = "hwloc_topology_set_userdata_export_callback")]
+#[doc(alias = "hwloc_topology_set_userdata_import_callback")]
+pub trait XmlUserdataCodec {
+    /// Export some userdata for `object`
+    ///
+    /// This is called once for every object of the topology that is being
+    /// exported, and may call [`XmlUserdataExporter::export()`] any number of
+    /// times to save userdata for this object.
+    ///
+    /// # Errors
+    ///
+    /// Errors from [`XmlUserdataExporter::export()`] should be propagated, in
+    /// which case the XML export is aborted.
+    fn encode(
+        &mut self,
+        object: &TopologyObject,
+        exporter: &mut XmlUserdataExporter<'_>,
+    ) -> Result<(), HybridError<XmlUserdataNameError>>;
+
+    /// Import some userdata that was exported for `object`
+    ///
+    /// This is called once for every successful call to
+    /// [`XmlUserdataExporter::export()`] during export, with the same `name`
+    /// and `data`, after the topology has been fully loaded. Userdata of
+    /// objects that did not make it into the loaded topology (e.g. because of
+    /// [type filters](TopologyBuilder::with_type_filter())) is discarded.
+    fn decode(&mut self, object: &TopologyObject, name: Option<&str>, data: &[u8]);
+}
+
+/// Handle used by [`XmlUserdataCodec::encode()`] to export userdata
+///
+/// This functionality is unique to the Rust hwloc bindings.
+pub struct XmlUserdataExporter<'export> {
+    /// Opaque hwloc export state
+    reserved: *mut c_void,
+
+    /// Topology being exported
+    topology: *mut RawTopology,
+
+    /// Object being exported
+    object: *mut TopologyObject,
+
+    /// Borrow of the export callback arguments
+    _lifetime: PhantomData<&'export TopologyObject>,
+}
+//
+impl XmlUserdataExporter<'_> {
+    /// Save `data` as userdata of the current object, with an optional `name`
+    ///
+    /// Data made of printable ASCII characters is saved as is, other data is
+    /// automatically base64-encoded. In both cases,
+    /// [`XmlUserdataCodec::decode()`] receives the original `data` back.
+    ///
+    /// # Errors
+    ///
+    /// - [`XmlUserdataNameError`] if `name` contains characters other than
+    ///   printable ASCII characters.
+    /// - [`HybridError::Hwloc`] if hwloc fails to export the userdata.
+    #[doc(alias = "hwloc_export_obj_userdata")]
+    #[doc(alias = "hwloc_export_obj_userdata_base64")]
+    pub fn export(
+        &mut self,
+        name: Option<&str>,
+        data: &[u8],
+    ) -> Result<(), HybridError<XmlUserdataNameError>> {
+        let name = name
+            .map(|name| {
+                if !is_printable(name.as_bytes()) {
+                    return Err(XmlUserdataNameError(name.to_owned()));
+                }
+                Ok(LibcString::new(name).expect("Printable strings do not contain NUL"))
+            })
+            .transpose()?;
+        let name = name.as_ref().map_or(ptr::null(), LibcString::borrow);
+        let (data_ptr, length) = (data.as_ptr().cast::<c_void>(), data.len());
+        if is_printable(data) {
+            errors::call_hwloc_int_normal("hwloc_export_obj_userdata", || unsafe {
+                ffi::hwloc_export_obj_userdata(
+                    self.reserved,
+                    self.topology,
+                    self.object,
+                    name,
+                    data_ptr,
+                    length,
+                )
+            })
+        } else {
+            errors::call_hwloc_int_normal("hwloc_export_obj_userdata_base64", || unsafe {
+                ffi::hwloc_export_obj_userdata_base64(
+                    self.reserved,
+                    self.topology,
+                    self.object,
+                    name,
+                    data_ptr,
+                    length,
+                )
+            })
+        }
+        .map_err(HybridError::Hwloc)?;
+        Ok(())
+    }
+}
+//
+impl Debug for XmlUserdataExporter<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("XmlUserdataExporter")
+            .field("object", &self.object)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Error returned when an XML userdata name cannot be exported
+///
+/// Userdata names may only contain printable ASCII characters.
+#[derive(Clone, Debug, Default, Eq, Error, Hash, PartialEq)]
+#[error("userdata name {0:?} contains non-printable characters")]
+pub struct XmlUserdataNameError(pub String);
+
+/// Truth that hwloc can save `bytes` to an XML attribute as is
+fn is_printable(bytes: &[u8]) -> bool {
+    bytes.iter().all(|byte| (b' '..=b'~').contains(byte))
+}
+
+/// hwloc userdata export callback
+pub(crate) type RawUserdataExportCallback = unsafe extern "C" fn(
+    reserved: *mut c_void,
+    topology: *mut RawTopology,
+    obj: *mut TopologyObject,
+);
+
+/// hwloc userdata import callback
+pub(crate) type RawUserdataImportCallback = unsafe extern "C" fn(
+    topology: *mut RawTopology,
+    obj: *mut TopologyObject,
+    name: *const c_char,
+    buffer: *const c_void,
+    length: usize,
+);
+
+/// State of a userdata export in progress
+///
+/// hwloc's export callback does not take a user context parameter, so the
+/// state of the current export is published through [`ACTIVE_EXPORT`].
+struct ExportState<'codec> {
+    /// Topology being exported
+    topology: *const RawTopology,
+
+    /// User-provided codec
+    codec: &'codec mut dyn XmlUserdataCodec,
+
+    /// First error returned by the codec, if any
+    result: Result<(), HybridError<XmlUserdataNameError>>,
+
+    /// Codec panic payload, if any
+    panic: Option<Box<dyn Any + Send>>,
+}
+
+thread_local! {
+    /// Userdata export in progress on the current thread, if any
+    static ACTIVE_EXPORT: Cell<*mut ExportState<'static>> = const { Cell::new(ptr::null_mut()) };
+}
+
+/// Guard that publishes an [`ExportState`] in [`ACTIVE_EXPORT`], and restores
+/// the previously active export (if any) when dropped
+struct ActiveExport<'state> {
+    /// Previously active export
+    previous: *mut ExportState<'static>,
+
+    /// Borrow of the active export state
+    _state: PhantomData<&'state mut ExportState<'state>>,
+}
+//
+impl<'state> ActiveExport<'state> {
+    /// Publish `state` until the guard is dropped
+    fn enter<'codec>(state: &'state mut ExportState<'codec>) -> Self {
+        let state = (state as *mut ExportState<'codec>).cast::<ExportState<'static>>();
+        Self {
+            previous: ACTIVE_EXPORT.with(|active| active.replace(state)),
+            _state: PhantomData,
+        }
+    }
+}
+//
+impl Drop for ActiveExport<'_> {
+    fn drop(&mut self) {
+        ACTIVE_EXPORT.with(|active| active.set(self.previous));
+    }
+}
+
+/// Userdata export callback given to hwloc
+unsafe extern "C" fn export_userdata_callback(
+    reserved: *mut c_void,
+    topology: *mut RawTopology,
+    obj: *mut TopologyObject,
+) {
+    let state = ACTIVE_EXPORT.with(Cell::get);
+    // SAFETY: The state is published by export_xml_with_userdata for the
+    //         duration of the export, which is when hwloc calls us
+    let Some(state) = (unsafe { state.as_mut() }) else {
+        return;
+    };
+    if state.topology != topology.cast_const() || state.result.is_err() || state.panic.is_some() {
+        return;
+    }
+    let mut exporter = XmlUserdataExporter {
+        reserved,
+        topology,
+        object: obj,
+        _lifetime: PhantomData,
+    };
+    let object = unsafe { &*obj };
+    match std::panic::catch_unwind(AssertUnwindSafe(|| {
+        state.codec.encode(object, &mut exporter)
+    })) {
+        Ok(result) => state.result = result,
+        Err(payload) => state.panic = Some(payload),
+    }
+}
+
+/// Userdata records collected during an XML import
+///
+/// Records are identified by the global persistent index of the object they
+/// belong to, so that they can be decoded once the topology is fully loaded.
+pub(crate) type ImportedUserdata = Vec<(u64, Option<String>, Vec<u8>)>;
+
+thread_local! {
+    /// Userdata records of the XML import in progress on the current thread,
+    /// if any
+    static ACTIVE_IMPORT: RefCell<Option<ImportedUserdata>> = const { RefCell::new(None) };
+}
+
+/// Run `load` while collecting the userdata records that hwloc imports
+pub(crate) fn collect_imported_userdata<R>(load: impl FnOnce() -> R) -> (R, ImportedUserdata) {
+    let previous = ACTIVE_IMPORT.with(|active| active.replace(Some(Vec::new())));
+    let result = load();
+    let records = ACTIVE_IMPORT.with(|active| active.replace(previous));
+    (result, records.unwrap_or_default())
+}
+
+/// Userdata import callback given to hwloc
+pub(crate) unsafe extern "C" fn import_userdata_callback(
+    _topology: *mut RawTopology,
+    obj: *mut TopologyObject,
+    name: *const c_char,
+    buffer: *const c_void,
+    length: usize,
+) {
+    let _ = ACTIVE_IMPORT.try_with(|active| {
+        let Ok(mut active) = active.try_borrow_mut() else {
+            return;
+        };
+        let Some(records) = active.as_mut() else {
+            return;
+        };
+        let gp_index = unsafe { &*obj }.global_persistent_index();
+        let name = (!name.is_null()).then(|| {
+            unsafe { CStr::from_ptr(name) }
+                .to_string_lossy()
+                .into_owned()
+        });
+        let data = if buffer.is_null() || length == 0 {
+            Vec::new()
+        } else {
+            unsafe { std::slice::from_raw_parts(buffer.cast::<u8>(), length) }.to_vec()
+        };
+        records.push((gp_index, name, data));
+    });
+}
+
 /// XML string emitted by hwloc
 ///
 /// This behaves like a `Box<str>` and will similarly automatically
@@ -146,7 +530,7 @@ impl<'topology> XML<'topology> {
     /// # Panics
     ///
     /// If the string is not valid UTF-8 (according to
-    /// https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__xmlexport.html#ga333f79975b4eeb28a3d8fad3373583ce,
+    /// https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__xmlexport.html#ga333f79975b4eeb28a3d8fad3373583ce,
     /// hwloc should only generates ASCII at the time of writing)
     pub(crate) unsafe fn wrap(
         topology: &'topology Topology,
@@ -281,3 +665,6 @@ impl Drop for XML<'_> {
         unsafe { ffi::hwloc_free_xmlbuffer(self.topology.as_ptr(), addr) }
     }
 }
+
+unsafe impl Send for XML<'_> {}
+unsafe impl Sync for XML<'_> {}
@@ -1,34 +1,51 @@
 //! Hardware topology (main hwloc entry point)
 
 pub mod builder;
+pub mod cache;
+pub mod consistency;
+pub mod container;
 #[cfg(feature = "hwloc-2_3_0")]
 pub mod editor;
 pub mod export;
+pub mod refresh;
+pub mod summary;
 pub mod support;
 
+#[cfg(feature = "hwloc-2_8_0")]
+use self::builder::FeatureDisabledAtBuild;
 use self::{
-    builder::{BuildFlags, RawTypeFilter, TopologyBuilder, TypeFilter},
+    builder::{BuildFlags, LoadConfig, RawTypeFilter, TopologyBuilder, TypeFilter},
+    export::xml::XMLExportFlags,
     support::FeatureSupport,
 };
+#[cfg(all(doc, feature = "hwloc-2_8_0"))]
+use crate::memory::attributes::MemoryAttribute;
 #[cfg(all(feature = "hwloc-2_3_0", doc))]
 use crate::topology::support::MiscSupport;
 use crate::{
     bitmaps::{Bitmap, BitmapRef, RawBitmap, SpecializedBitmap},
-    cpu::cpusets::CpuSet,
+    cpu::{binding::CpuBindingFlags, cpusets::CpuSet},
     errors::{self, RawHwlocError},
     ffi::{self, IncompleteType},
     memory::nodesets::NodeSet,
-    objects::{types::ObjectType, TopologyObject},
+    objects::{attributes::ObjectAttributes, depth::Depth, types::ObjectType, TopologyObject},
 };
 use bitflags::bitflags;
 use errno::Errno;
 use libc::EINVAL;
+use once_cell::sync::OnceCell;
 use std::{
+    collections::{BTreeMap, HashMap},
     convert::TryInto,
     debug_assert,
-    ffi::c_ulong,
+    ffi::{c_ulong, CStr, OsStr},
+    fs,
+    iter::FusedIterator,
     num::NonZeroUsize,
+    path::{Path, PathBuf},
     ptr::{self, NonNull},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
 };
 use thiserror::Error;
 
@@ -45,6 +62,39 @@ pub(crate) struct RawTopology(IncompleteType);
 /// structure of a system. It can be used to query the system topology and to
 /// bind threads and processes to hardware CPU cores and NUMA nodes.
 ///
+/// # Thread safety
+///
+/// hwloc allows concurrent use of a topology as long as it is not modified,
+/// and the Rust type system is used to enforce this: every method that takes
+/// `&self`, including topology queries and CPU/memory binding, may be called
+/// from multiple threads at once, whereas topology modifications require
+/// `&mut self` (see `Topology::edit()`).
+///
+/// To share a topology between threads, wrap it in an
+/// [`Arc`](std::sync::Arc). You can go back to exclusive ownership for
+/// modifications using [`Arc::try_unwrap()`](std::sync::Arc::try_unwrap())
+/// once all other threads are done with it, or just [`Clone`] the topology.
+///
+/// ```
+/// # use hwlocality::Topology;
+/// use std::sync::Arc;
+///
+/// let topology = Arc::new(Topology::new()?);
+/// let handles = (0..2)
+///     .map(|_| {
+///         let topology = topology.clone();
+///         std::thread::spawn(move || topology.objects_at_depth(0).count())
+///     })
+///     .collect::<Vec<_>>();
+/// for handle in handles {
+///     assert_eq!(handle.join().unwrap(), 1);
+/// }
+/// let topology = Arc::try_unwrap(topology).expect("All threads are done");
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+///
+/// # Table of contents
+///
 /// Since there are **many** things you can do with a `Topology`, the API is
 /// broken down into sections roughly following the structure of the upstream
 /// hwloc documentation:
@@ -63,7 +113,11 @@ pub(crate) struct RawTopology(IncompleteType);
 /// - [Finding I/O objects](#finding-io-objects)
 /// - [Exporting Topologies to XML](#exporting-topologies-to-xml)
 /// - [Exporting Topologies to Synthetic](#exporting-topologies-to-synthetic)
+/// - [Exporting Topologies to Graphviz DOT](#exporting-topologies-to-graphviz-dot)
 /// - [Retrieve distances between objects](#retrieve-distances-between-objects)
+/// - [Container support](#container-support)
+/// - [Consistency checks](#consistency-checks)
+/// - [Topology summary](#topology-summary) (specific to Rust bindings)
 #[cfg_attr(
     feature = "hwloc-2_3_0",
     doc = "- [Comparing memory node attributes for finding where to allocate on](#comparing-memory-node-attributes-for-finding-where-to-allocate-on) (hwloc 2.3+)"
@@ -88,13 +142,22 @@ pub(crate) struct RawTopology(IncompleteType);
 #[derive(Debug)]
 #[doc(alias = "hwloc_topology")]
 #[doc(alias = "hwloc_topology_t")]
-pub struct Topology(NonNull<RawTopology>);
+pub struct Topology {
+    /// Underlying hwloc topology
+    raw: NonNull<RawTopology>,
+
+    /// Data derived from the hwloc topology, cached for fast access
+    derived: DerivedData,
+
+    /// Builder configuration needed to load this topology again
+    load_config: LoadConfig,
+}
 
 /// # Topology building
 //
 // Upstream docs:
-// - Creation: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__creation.html
-// - Build queries: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__configuration.html
+// - Creation: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__creation.html
+// - Build queries: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__configuration.html
 impl Topology {
     /// Creates a new Topology.
     ///
@@ -102,6 +165,10 @@ impl Topology {
     /// main entry point to this crate. A topology is returned, which contains
     /// the logical representation of the physical hardware.
     ///
+    /// # Errors
+    ///
+    /// See [`TopologyBuilder::build()`].
+    ///
     /// # Examples
     ///
     /// ```
@@ -110,7 +177,7 @@ impl Topology {
     /// # Ok::<(), anyhow::Error>(())
     /// ```
     pub fn new() -> Result<Topology, RawHwlocError> {
-        TopologyBuilder::new().build()
+        TopologyBuilder::try_new()?.build()
     }
 
     #[doc(hidden)]
@@ -133,6 +200,67 @@ impl Topology {
         &INSTANCE
     }
 
+    /// Process-wide topology
+    ///
+    /// Many libraries only need read-only access to the topology of the
+    /// machine, and building a topology with [`Topology::new()`] in each of
+    /// them wastes time and memory. This function builds a topology with the
+    /// default configuration the first time it is called, and returns a
+    /// reference to that same topology on subsequent calls.
+    ///
+    /// Like [`Topology::new()`], the default configuration does not include
+    /// I/O objects. Use [`Topology::builder()`] if you need them.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Panics
+    ///
+    /// If building the topology fails. See [`Topology::try_global()`] for a
+    /// version of this function that reports errors instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::{objects::types::ObjectType, Topology};
+    /// let num_cores = Topology::global()
+    ///     .objects_with_type(ObjectType::Core)
+    ///     .count();
+    /// println!("This machine has {num_cores} cores");
+    /// ```
+    #[cfg(any(doc, feature = "global"))]
+    pub fn global() -> &'static Self {
+        Self::try_global().expect("Failed to build the global Topology")
+    }
+
+    /// Process-wide topology, with error handling
+    ///
+    /// This is [`Topology::global()`], but errors from the initial topology
+    /// build are reported instead of causing a panic. The outcome of the
+    /// initial build is cached, so if it failed, subsequent calls will return
+    /// the same error without trying again.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Errors
+    ///
+    /// See [`TopologyBuilder::build()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::Topology;
+    /// match Topology::try_global() {
+    ///     Ok(topology) => println!("Running on {topology}"),
+    ///     Err(e) => eprintln!("Running without topology information: {e}"),
+    /// }
+    /// ```
+    #[cfg(any(doc, feature = "global"))]
+    pub fn try_global() -> Result<&'static Self, RawHwlocError> {
+        use once_cell::sync::OnceCell;
+        static GLOBAL: OnceCell<Result<Topology, RawHwlocError>> = OnceCell::new();
+        GLOBAL.get_or_init(Topology::new).as_ref().map_err(|e| *e)
+    }
+
     /// Prepare to create a Topology with custom configuration
     ///
     /// # Examples
@@ -149,6 +277,155 @@ impl Topology {
         TopologyBuilder::new()
     }
 
+    /// Reuse a topology cached in an XML file, or detect it and refresh the
+    /// cache
+    ///
+    /// Topology detection can take hundreds of milliseconds on large machines,
+    /// which is a lot for short-lived processes. This function reuses the
+    /// topology that was saved at `path` by a previous call, as long as the
+    /// file is younger than `ttl` and a quick live probe of the system agrees
+    /// with it. Otherwise, the topology is detected from scratch as in
+    /// [`Topology::new()`] and saved at `path` for the next call.
+    ///
+    /// The live probe checks that the current CPU binding of the process only
+    /// covers PUs from the cached topology's [allowed
+    /// cpuset](Topology::allowed_cpuset()), when the operating system can
+    /// tell. This catches common changes like CPUs being brought online or a
+    /// container being granted more CPUs, but not all possible hardware
+    /// changes, hence the `ttl`.
+    ///
+    /// A fingerprint of the topology is saved next to the XML file, at `path`
+    /// with an extra `.fingerprint` extension. It records the complete and
+    /// allowed cpusets and nodesets, the number of PUs and the version of the
+    /// hwloc library. The cache is only reused if the fingerprint of the
+    /// topology loaded from the XML file, computed with the current hwloc
+    /// library, is equal to the saved one. This detects mismatched or
+    /// corrupted cache files and hwloc library upgrades.
+    ///
+    /// Cached topologies are loaded with [`BuildFlags::ASSUME_THIS_SYSTEM`], so
+    /// binding functions keep working as with a freshly detected topology.
+    ///
+    /// Problems with the cache file (missing, stale, invalid, not writable...)
+    /// are not reported: this function falls back to detecting the topology,
+    /// then tries to refresh the cache and ignores any failure to do so.
+    ///
+    /// # Errors
+    ///
+    /// Only topology detection errors are reported, as in [`Topology::new()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::Topology;
+    /// # use std::time::Duration;
+    /// let path = std::env::temp_dir().join("hwlocality-doctest-cache.xml");
+    /// let detected = Topology::load_cached_or_detect(&path, Duration::from_secs(60))?;
+    /// let cached = Topology::load_cached_or_detect(&path, Duration::from_secs(60))?;
+    /// assert_eq!(cached.cpuset(), detected.cpuset());
+    /// assert!(cached.is_this_system());
+    ///
+    /// let fingerprint_path = path.with_extension("xml.fingerprint");
+    /// assert!(fingerprint_path.exists());
+    /// # std::fs::remove_file(path)?;
+    /// # std::fs::remove_file(fingerprint_path)?;
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn load_cached_or_detect(
+        path: impl AsRef<Path>,
+        ttl: Duration,
+    ) -> Result<Topology, RawHwlocError> {
+        let path = path.as_ref();
+        if let Some(topology) = Self::load_fresh_cache(path, ttl) {
+            return Ok(topology);
+        }
+        let topology = Topology::new()?;
+
+        // Go through temporary files so that concurrent processes never see a
+        // partially written cache. The XML file is written first, so that a
+        // failure in between leaves a mismatched fingerprint behind. The
+        // process ID and a counter make the names unique across processes
+        // and across threads of this process.
+        static TMP_FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let tmp_path = |path: &OsStr| {
+            let mut tmp_path = path.to_owned();
+            let counter = TMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed);
+            tmp_path.push(format!(".{}.{counter}.tmp", std::process::id()));
+            tmp_path
+        };
+        let xml_tmp_path = tmp_path(path.as_os_str());
+        if topology
+            .export_xml_file(Some(&xml_tmp_path), XMLExportFlags::default())
+            .is_err()
+            || fs::rename(&xml_tmp_path, path).is_err()
+        {
+            let _ = fs::remove_file(&xml_tmp_path);
+            return Ok(topology);
+        }
+        let fingerprint_path = Self::cache_fingerprint_path(path);
+        let fingerprint_tmp_path = tmp_path(fingerprint_path.as_os_str());
+        if fs::write(&fingerprint_tmp_path, topology.cache_fingerprint()).is_err()
+            || fs::rename(&fingerprint_tmp_path, &fingerprint_path).is_err()
+        {
+            let _ = fs::remove_file(&fingerprint_tmp_path);
+        }
+        Ok(topology)
+    }
+
+    /// Location of the fingerprint of the topology cached at `path`
+    fn cache_fingerprint_path(path: &Path) -> PathBuf {
+        let mut fingerprint_path = path.as_os_str().to_owned();
+        fingerprint_path.push(".fingerprint");
+        fingerprint_path.into()
+    }
+
+    /// Fingerprint of this topology, as saved next to cached topologies
+    fn cache_fingerprint(&self) -> String {
+        format!(
+            "hwloc_version {:#x}\n\
+             pus {}\n\
+             complete_cpuset {}\n\
+             allowed_cpuset {}\n\
+             complete_nodeset {}\n\
+             allowed_nodeset {}\n",
+            crate::get_api_version(),
+            self.objects_with_type(ObjectType::PU).count(),
+            self.complete_cpuset(),
+            self.allowed_cpuset(),
+            self.complete_nodeset(),
+            self.allowed_nodeset(),
+        )
+    }
+
+    /// Load the topology cached at `path`, if it is fresh and matches the
+    /// live system
+    fn load_fresh_cache(path: &Path, ttl: Duration) -> Option<Topology> {
+        let age = fs::metadata(path).ok()?.modified().ok()?.elapsed().ok()?;
+        if age > ttl {
+            return None;
+        }
+        let topology = TopologyBuilder::try_new()
+            .ok()?
+            .from_xml_file(path)
+            .ok()?
+            .with_flags(BuildFlags::ASSUME_THIS_SYSTEM)
+            .ok()?
+            .build()
+            .ok()?;
+        let fingerprint = fs::read_to_string(Self::cache_fingerprint_path(path)).ok()?;
+        if fingerprint != topology.cache_fingerprint() {
+            return None;
+        }
+        // If CPU binding cannot be queried on this OS, only the TTL and
+        // fingerprint apply
+        let Ok(binding) = topology.cpu_binding(CpuBindingFlags::PROCESS) else {
+            return Some(topology);
+        };
+        topology
+            .allowed_cpuset()
+            .includes(&binding)
+            .then_some(topology)
+    }
+
     /// Check that this topology is compatible with the current hwloc library
     ///
     /// This is useful when using the same topology structure (in memory) in
@@ -186,11 +463,23 @@ impl Topology {
 
     /// Flags that were used to build this topology
     ///
+    /// Library code that receives a [`Topology`] from elsewhere can use this,
+    /// together with [`Topology::type_filter()`], to check how the topology
+    /// was configured.
+    ///
     /// # Examples
     ///
     /// ```
     /// # use hwlocality::topology::{Topology, builder::BuildFlags};
     /// assert_eq!(Topology::new()?.build_flags(), BuildFlags::empty());
+    ///
+    /// // Check if objects that the process may not use are included
+    /// let topology = Topology::builder()
+    ///     .with_flags(BuildFlags::INCLUDE_DISALLOWED)?
+    ///     .build()?;
+    /// assert!(topology
+    ///     .build_flags()
+    ///     .contains(BuildFlags::INCLUDE_DISALLOWED));
     /// # Ok::<(), anyhow::Error>(())
     /// ```
     #[doc(alias = "hwloc_topology_get_flags")]
@@ -201,6 +490,76 @@ impl Topology {
         result
     }
 
+    /// Optional topology information that was disabled at build time
+    ///
+    /// This is the subset of the [`IGNORE_DISTANCES`],
+    /// [`IGNORE_MEMORY_ATTRIBUTES`] and [`IGNORE_CPU_KINDS`] flags that were
+    /// used to build this topology. On such a topology, the corresponding
+    /// queries ([`Topology::distances()`], [`Topology::cpu_kinds()`],
+    /// [`MemoryAttribute`] queries...) report a [`FeatureDisabledAtBuild`]
+    /// error when they find no information, and otherwise only return the
+    /// information that was provided by the user. Code that relies on
+    /// operating system information even when the user provided some can
+    /// check this or use [`Topology::ensure_features_enabled()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::topology::{Topology, builder::BuildFlags};
+    /// let topology = Topology::builder()
+    ///     .with_flags(BuildFlags::IGNORE_CPU_KINDS)?
+    ///     .build()?;
+    /// assert_eq!(topology.disabled_features(), BuildFlags::IGNORE_CPU_KINDS);
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    ///
+    /// [`IGNORE_CPU_KINDS`]: BuildFlags::IGNORE_CPU_KINDS
+    /// [`IGNORE_DISTANCES`]: BuildFlags::IGNORE_DISTANCES
+    /// [`IGNORE_MEMORY_ATTRIBUTES`]: BuildFlags::IGNORE_MEMORY_ATTRIBUTES
+    #[cfg(feature = "hwloc-2_8_0")]
+    pub fn disabled_features(&self) -> BuildFlags {
+        self.build_flags()
+            & (BuildFlags::IGNORE_DISTANCES
+                | BuildFlags::IGNORE_MEMORY_ATTRIBUTES
+                | BuildFlags::IGNORE_CPU_KINDS)
+    }
+
+    /// Check that some optional topology information was not disabled at
+    /// build time
+    ///
+    /// `features` should be a combination of the `IGNORE_xyz` build flags that
+    /// are reported by [`Topology::disabled_features()`], e.g.
+    /// `BuildFlags::IGNORE_DISTANCES` if you are about to query distances.
+    ///
+    /// # Errors
+    ///
+    /// - [`FeatureDisabledAtBuild`] if some of the requested `features` were
+    ///   disabled when building this topology. The error lists which ones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::topology::{Topology, builder::BuildFlags};
+    /// let topology = Topology::builder()
+    ///     .with_flags(BuildFlags::IGNORE_DISTANCES)?
+    ///     .build()?;
+    /// assert!(topology.ensure_features_enabled(BuildFlags::IGNORE_CPU_KINDS).is_ok());
+    /// assert!(topology.ensure_features_enabled(BuildFlags::IGNORE_DISTANCES).is_err());
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    #[cfg(feature = "hwloc-2_8_0")]
+    pub fn ensure_features_enabled(
+        &self,
+        features: BuildFlags,
+    ) -> Result<(), FeatureDisabledAtBuild> {
+        let disabled = self.disabled_features() & features;
+        if disabled.is_empty() {
+            Ok(())
+        } else {
+            Err(FeatureDisabledAtBuild(disabled))
+        }
+    }
+
     /// Was the topology built using the system running this program?
     ///
     /// It may not have been if, for instance, it was built using another
@@ -221,6 +580,49 @@ impl Topology {
         .expect("Should not involve faillible syscalls")
     }
 
+    /// Can this topology be used to bind threads, processes or memory?
+    ///
+    /// Binding functions do not do anything useful when the topology does
+    /// not describe [the current system](Topology::is_this_system()), for
+    /// example when it was loaded from XML without
+    /// [`BuildFlags::ASSUME_THIS_SYSTEM`], or when the operating system does
+    /// not support any kind of CPU or memory binding. This tells callers
+    /// up-front whether binding is worth attempting at all.
+    ///
+    /// Use [`Topology::feature_support()`] to check which specific binding
+    /// operations are supported.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::Topology;
+    /// let synthetic = Topology::builder().from_synthetic("pack:2 pu:2")?.build()?;
+    /// assert!(!synthetic.binding_is_effective());
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn binding_is_effective(&self) -> bool {
+        if !self.is_this_system() {
+            return false;
+        }
+        let support = self.feature_support();
+        let cpu_binding = support.cpu_binding().map_or(false, |cpu| {
+            cpu.set_current_process()
+                || cpu.set_process()
+                || cpu.set_current_thread()
+                || cpu.set_thread()
+        });
+        let memory_binding = support.memory_binding().map_or(false, |memory| {
+            memory.set_current_process()
+                || memory.set_process()
+                || memory.set_current_thread()
+                || memory.set_area()
+                || memory.alloc()
+        });
+        cpu_binding || memory_binding
+    }
+
     /// Supported hwloc features with this topology on this machine
     ///
     /// This is the information that one gets via the `hwloc-info --support` CLI.
@@ -325,7 +727,7 @@ impl Topology {
 
 /// # Distributing work items over a topology
 //
-// Inspired by https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__helper__distribute.html,
+// Inspired by https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__helper__distribute.html,
 // but the inline header implementation had to be rewritten in Rust.
 impl Topology {
     /// Distribute `num_items` work items over the topology under `roots`
@@ -369,6 +771,27 @@ impl Topology {
     ///
     /// - [`EmptyRootsError`] if there are no CPUs to distribute work to (the
     ///   union of all root cpusets is empty).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::topology::DistributeFlags;
+    /// # use std::num::NonZeroUsize;
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// // Compute one CPU set per worker thread of a thread pool
+    /// let num_workers = NonZeroUsize::new(4).unwrap();
+    /// let worker_cpusets = topology.distribute_items(
+    ///     &[topology.root_object()],
+    ///     num_workers,
+    ///     usize::MAX,
+    ///     DistributeFlags::empty(),
+    /// )?;
+    /// assert_eq!(worker_cpusets.len(), num_workers.get());
+    /// for cpuset in &worker_cpusets {
+    ///     assert!(topology.cpuset().includes(cpuset));
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
     #[doc(alias = "hwloc_distrib")]
     pub fn distribute_items(
         &self,
@@ -526,12 +949,64 @@ impl Default for DistributeFlags {
 /// topology was built with [`BuildFlags::INCLUDE_DISALLOWED`] and the specified
 /// roots only contain disallowed CPUs.
 #[derive(Copy, Clone, Debug, Default, Eq, Error, Hash, PartialEq)]
-#[error("platform does not support this operation")]
+#[error("distribution roots do not contain any accessible CPU")]
 pub struct EmptyRootsError;
 
+/// Class of topology-wide CPU or node set
+///
+/// Used by [`Topology::cpuset_of_class()`] and, despite the name,
+/// [`Topology::nodeset_of_class()`]. The "CPU and node sets of entire
+/// topologies" section of the [`Topology`] documentation explains how the
+/// classes differ.
+///
+/// This functionality is unique to the Rust hwloc bindings.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum CpuSetClass {
+    /// PUs and NUMA nodes that appear in the topology
+    ///
+    /// See [`Topology::cpuset()`] and [`Topology::nodeset()`].
+    #[doc(alias = "topology_cpuset")]
+    #[doc(alias = "topology_nodeset")]
+    Topology,
+
+    /// PUs and NUMA nodes that hwloc knows about, including offline ones
+    ///
+    /// See [`Topology::complete_cpuset()`] and [`Topology::complete_nodeset()`].
+    Complete,
+
+    /// PUs and NUMA nodes that this process is allowed to use
+    ///
+    /// See [`Topology::allowed_cpuset()`] and [`Topology::allowed_nodeset()`].
+    Allowed,
+}
+//
+impl CpuSetClass {
+    /// All set classes
+    pub const ALL: [Self; 3] = [Self::Topology, Self::Complete, Self::Allowed];
+}
+
 /// # CPU and node sets of entire topologies
+///
+/// A topology has three CPU sets and three node sets, which are easy to mix
+/// up, especially when running in a Linux cgroup or under a job scheduler:
+///
+/// - The [topology sets](Topology::cpuset()) contain the PUs and NUMA nodes
+///   that appear in the topology, i.e. that are online and were not removed by
+///   restricting the topology. These are the sets of the root object.
+/// - The [complete sets](Topology::complete_cpuset()) additionally contain
+///   PUs and NUMA nodes that hwloc knows about but that do not appear in the
+///   topology, e.g. because they are offline. Binding to them usually fails.
+/// - The [allowed sets](Topology::allowed_cpuset()) contain the PUs and NUMA
+///   nodes that this process is allowed to use, e.g. the ones of its cgroup.
+///   They only differ from the topology sets if the topology was built with
+///   [`BuildFlags::INCLUDE_DISALLOWED`], and binding to PUs or NUMA nodes
+///   outside of them fails.
+///
+/// When in doubt, binding code should restrict itself to the intersection of
+/// the topology and allowed sets. Generic code can select a set with
+/// [`CpuSetClass`].
 //
-// Upstream docs: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__helper__topology__sets.html
+// Upstream docs: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__helper__topology__sets.html
 impl Topology {
     /// Topology CPU set
     ///
@@ -549,10 +1024,9 @@ impl Topology {
     #[doc(alias = "hwloc_topology_get_topology_cpuset")]
     pub fn cpuset(&self) -> BitmapRef<CpuSet> {
         unsafe {
-            self.topology_set(
-                "hwloc_topology_get_topology_cpuset",
-                ffi::hwloc_topology_get_topology_cpuset,
-            )
+            self.topology_set("hwloc_topology_get_topology_cpuset", |topology| {
+                ffi::hwloc_topology_get_topology_cpuset(topology)
+            })
         }
     }
 
@@ -575,10 +1049,9 @@ impl Topology {
     #[doc(alias = "hwloc_topology_get_complete_cpuset")]
     pub fn complete_cpuset(&self) -> BitmapRef<CpuSet> {
         unsafe {
-            self.topology_set(
-                "hwloc_topology_get_complete_cpuset",
-                ffi::hwloc_topology_get_complete_cpuset,
-            )
+            self.topology_set("hwloc_topology_get_complete_cpuset", |topology| {
+                ffi::hwloc_topology_get_complete_cpuset(topology)
+            })
         }
     }
 
@@ -606,10 +1079,9 @@ impl Topology {
     #[doc(alias = "hwloc_topology_get_allowed_cpuset")]
     pub fn allowed_cpuset(&self) -> BitmapRef<CpuSet> {
         unsafe {
-            self.topology_set(
-                "hwloc_topology_get_allowed_cpuset",
-                ffi::hwloc_topology_get_allowed_cpuset,
-            )
+            self.topology_set("hwloc_topology_get_allowed_cpuset", |topology| {
+                ffi::hwloc_topology_get_allowed_cpuset(topology)
+            })
         }
     }
 
@@ -629,10 +1101,9 @@ impl Topology {
     #[doc(alias = "hwloc_topology_get_topology_nodeset")]
     pub fn nodeset(&self) -> BitmapRef<NodeSet> {
         unsafe {
-            self.topology_set(
-                "hwloc_topology_get_topology_nodeset",
-                ffi::hwloc_topology_get_topology_nodeset,
-            )
+            self.topology_set("hwloc_topology_get_topology_nodeset", |topology| {
+                ffi::hwloc_topology_get_topology_nodeset(topology)
+            })
         }
     }
 
@@ -655,10 +1126,9 @@ impl Topology {
     #[doc(alias = "hwloc_topology_get_complete_nodeset")]
     pub fn complete_nodeset(&self) -> BitmapRef<NodeSet> {
         unsafe {
-            self.topology_set(
-                "hwloc_topology_get_complete_nodeset",
-                ffi::hwloc_topology_get_complete_nodeset,
-            )
+            self.topology_set("hwloc_topology_get_complete_nodeset", |topology| {
+                ffi::hwloc_topology_get_complete_nodeset(topology)
+            })
         }
     }
 
@@ -686,20 +1156,181 @@ impl Topology {
     #[doc(alias = "hwloc_topology_get_allowed_nodeset")]
     pub fn allowed_nodeset(&self) -> BitmapRef<NodeSet> {
         unsafe {
-            self.topology_set(
-                "hwloc_topology_get_allowed_nodeset",
-                ffi::hwloc_topology_get_allowed_nodeset,
-            )
+            self.topology_set("hwloc_topology_get_allowed_nodeset", |topology| {
+                ffi::hwloc_topology_get_allowed_nodeset(topology)
+            })
         }
     }
 
+    /// CPU set of a certain class
+    ///
+    /// This is [`cpuset()`](Self::cpuset()),
+    /// [`complete_cpuset()`](Self::complete_cpuset()) or
+    /// [`allowed_cpuset()`](Self::allowed_cpuset()), depending on `class`.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use hwlocality::topology::{CpuSetClass, Topology};
+    /// # let topology = Topology::test_instance();
+    /// for class in CpuSetClass::ALL {
+    ///     println!("{class:?} CPUs: {}", topology.cpuset_of_class(class));
+    /// }
+    /// assert!(topology
+    ///     .cpuset_of_class(CpuSetClass::Complete)
+    ///     .includes(&topology.cpuset_of_class(CpuSetClass::Topology)));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn cpuset_of_class(&self, class: CpuSetClass) -> BitmapRef<'_, CpuSet> {
+        match class {
+            CpuSetClass::Topology => self.cpuset(),
+            CpuSetClass::Complete => self.complete_cpuset(),
+            CpuSetClass::Allowed => self.allowed_cpuset(),
+        }
+    }
+
+    /// Node set of a certain class
+    ///
+    /// This is [`nodeset()`](Self::nodeset()),
+    /// [`complete_nodeset()`](Self::complete_nodeset()) or
+    /// [`allowed_nodeset()`](Self::allowed_nodeset()), depending on `class`.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use hwlocality::topology::{CpuSetClass, Topology};
+    /// # let topology = Topology::test_instance();
+    /// for class in CpuSetClass::ALL {
+    ///     println!("{class:?} NUMA nodes: {}", topology.nodeset_of_class(class));
+    /// }
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn nodeset_of_class(&self, class: CpuSetClass) -> BitmapRef<'_, NodeSet> {
+        match class {
+            CpuSetClass::Topology => self.nodeset(),
+            CpuSetClass::Complete => self.complete_nodeset(),
+            CpuSetClass::Allowed => self.allowed_nodeset(),
+        }
+    }
+
+    /// Total memory (in bytes) in the NUMA nodes of this topology
+    ///
+    /// This is equivalent to calling [`TopologyObject::total_memory()`] on
+    /// the topology's root object, but the result is cached in the
+    /// [`Topology`] when it is built or edited.
+    ///
+    /// Requires [`DiscoverySupport::numa_memory()`].
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use hwlocality::Topology;
+    /// # let topology = Topology::test_instance();
+    /// assert_eq!(topology.total_memory(), topology.root_object().total_memory());
+    /// ```
+    ///
+    /// [`DiscoverySupport::numa_memory()`]: crate::topology::support::DiscoverySupport::numa_memory()
+    pub fn total_memory(&self) -> u64 {
+        self.derived.total_memory
+    }
+
+    /// Local memory (in bytes) of each NUMA node of this topology
+    ///
+    /// This yields each NUMA node object along with its
+    /// [`NUMANodeAttributes::local_memory()`], in logical index order. The sum
+    /// of these quantities is [`Topology::total_memory()`].
+    ///
+    /// Requires [`DiscoverySupport::numa_memory()`].
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use hwlocality::Topology;
+    /// # let topology = Topology::test_instance();
+    /// for (node, memory) in topology.memory_by_node() {
+    ///     println!("NUMA node {:?} has {memory} bytes of memory", node.os_index());
+    /// }
+    /// let total = topology.memory_by_node().map(|(_, memory)| memory).sum::<u64>();
+    /// assert_eq!(total, topology.total_memory());
+    /// ```
+    ///
+    /// [`DiscoverySupport::numa_memory()`]: crate::topology::support::DiscoverySupport::numa_memory()
+    /// [`NUMANodeAttributes::local_memory()`]: crate::objects::attributes::NUMANodeAttributes::local_memory()
+    pub fn memory_by_node(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = (&TopologyObject, u64)>
+           + Clone
+           + ExactSizeIterator
+           + FusedIterator {
+        self.objects_at_depth(Depth::NUMANode).map(|node| {
+            let Some(ObjectAttributes::NUMANode(attributes)) = node.attributes() else {
+                unreachable!("NUMA nodes should have NUMA node attributes")
+            };
+            (node, attributes.local_memory())
+        })
+    }
+
+    /// Huge page sizes available in the NUMA nodes of this topology
+    ///
+    /// This aggregates the [`NUMANodeAttributes::page_types()`] of all NUMA
+    /// nodes into a map from page size (in bytes) to the total number of pages
+    /// of this size, sorted by increasing page size. Normal pages, whose size
+    /// is the operating system's base page size, are not included.
+    ///
+    /// This can be used to decide which huge page size to use for large
+    /// allocations, based on the pages that are actually available. The result
+    /// is empty if hwloc did not discover any huge page.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use hwlocality::Topology;
+    /// # let topology = Topology::test_instance();
+    /// const GIGAPAGE: u64 = 1 << 30;
+    /// const HUGEPAGE: u64 = 2 << 20;
+    /// let sizes = topology.hugepage_sizes();
+    /// let page_size = if sizes.get(&GIGAPAGE).map_or(false, |&count| count > 0) {
+    ///     Some(GIGAPAGE)
+    /// } else if sizes.get(&HUGEPAGE).map_or(false, |&count| count > 0) {
+    ///     Some(HUGEPAGE)
+    /// } else {
+    ///     None
+    /// };
+    /// println!("Will use huge pages of size {page_size:?}");
+    /// ```
+    ///
+    /// [`NUMANodeAttributes::page_types()`]: crate::objects::attributes::NUMANodeAttributes::page_types()
+    pub fn hugepage_sizes(&self) -> BTreeMap<u64, u64> {
+        let mut sizes = BTreeMap::new();
+        for node in self.objects_at_depth(Depth::NUMANode) {
+            let Some(ObjectAttributes::NUMANode(attributes)) = node.attributes() else {
+                continue;
+            };
+            for page_type in attributes.page_types() {
+                *sizes.entry(page_type.size()).or_insert(0) += page_type.count();
+            }
+        }
+        sizes.remove(&base_page_size());
+        sizes
+    }
+
     /// Query a topology-wide `CpuSet` or `NodeSet`
     ///
     /// # Safety
     ///
     /// `getter` must be one of the functions described in the ["CPU and node
     /// sets of entire
-    /// topologies"](https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__helper__topology__sets.html)
+    /// topologies"](https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__helper__topology__sets.html)
     /// section of the hwloc documentation, which means in particular that it...
     ///
     /// - Cannot return NULL
@@ -707,7 +1338,7 @@ impl Topology {
     unsafe fn topology_set<'topology, Set: SpecializedBitmap>(
         &'topology self,
         getter_name: &'static str,
-        getter: unsafe extern "C" fn(*const RawTopology) -> *const RawBitmap,
+        getter: impl FnOnce(*const RawTopology) -> *const RawBitmap,
     ) -> BitmapRef<'topology, Set> {
         let bitmap_ref = unsafe {
             let bitmap_ptr = errors::call_hwloc_ptr(getter_name, || getter(self.as_ptr()))
@@ -718,11 +1349,92 @@ impl Topology {
     }
 }
 
+/// Size of normal memory pages, as opposed to huge pages
+#[cfg(unix)]
+pub(crate) fn base_page_size() -> u64 {
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    u64::try_from(page_size).expect("sysconf(_SC_PAGESIZE) should not fail")
+}
+//
+/// Size of normal memory pages, as opposed to huge pages
+#[cfg(windows)]
+pub(crate) fn base_page_size() -> u64 {
+    use windows_sys::Win32::System::SystemInformation::{GetSystemInfo, SYSTEM_INFO};
+    let mut info = std::mem::MaybeUninit::<SYSTEM_INFO>::uninit();
+    unsafe { GetSystemInfo(info.as_mut_ptr()) };
+    u64::from(unsafe { info.assume_init() }.dwPageSize)
+}
+
 // # General-purpose internal utilities
 impl Topology {
+    /// Wrap a loaded hwloc topology
+    ///
+    /// # Safety
+    ///
+    /// `raw` must point to a loaded hwloc topology, whose ownership is
+    /// transferred to the resulting `Topology`.
+    pub(crate) unsafe fn from_raw(raw: NonNull<RawTopology>) -> Self {
+        let mut topology = Self {
+            raw,
+            derived: DerivedData::default(),
+            load_config: LoadConfig::default(),
+        };
+        topology.derived = DerivedData::new(&topology);
+        topology
+    }
+
+    /// Recompute cached derived data after the topology was modified
+    ///
+    /// This also resets the lazily built name and info indices.
+    #[cfg(feature = "hwloc-2_3_0")]
+    pub(crate) fn update_derived_data(&mut self) {
+        self.derived = DerivedData::new(self);
+    }
+
+    /// Depth and logical index of the object with global persistent index
+    /// `gp_index`, from the derived data cache
+    pub(crate) fn cached_object_position(&self, gp_index: u64) -> Option<(Depth, usize)> {
+        self.derived.object_positions.get(&gp_index).copied()
+    }
+
+    /// Depth and logical index of the objects named `name`, from the derived
+    /// data cache
+    pub(crate) fn cached_objects_with_name(&self, name: &str) -> &[(Depth, usize)] {
+        self.object_indices()
+            .by_name
+            .get(name)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Depth and logical index of the objects which have a `key` info with
+    /// value `value`, from the derived data cache
+    pub(crate) fn cached_objects_with_info(&self, key: &str, value: &str) -> &[(Depth, usize)] {
+        self.object_indices()
+            .by_info
+            .get(key)
+            .and_then(|by_value| by_value.get(value))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Name and info indices from the derived data cache, built on first use
+    fn object_indices(&self) -> &ObjectIndices {
+        self.derived
+            .object_indices
+            .get_or_init(|| ObjectIndices::new(self))
+    }
+
+    /// Number of objects of type `object_type`, from the derived data cache
+    pub(crate) fn cached_object_count(&self, object_type: ObjectType) -> usize {
+        self.derived
+            .object_counts
+            .get(&object_type)
+            .copied()
+            .unwrap_or(0)
+    }
+
     /// Contained hwloc topology pointer (for interaction with hwloc)
     pub(crate) fn as_ptr(&self) -> *const RawTopology {
-        self.0.as_ptr()
+        self.raw.as_ptr()
     }
 
     /// Contained mutable hwloc topology pointer (for interaction with hwloc)
@@ -732,10 +1444,114 @@ impl Topology {
     /// unless followed by `hwloc_topology_refresh()`. This subtlety is handled
     /// by the [`Topology::edit()`] mechanism.
     pub(crate) fn as_mut_ptr(&mut self) -> *mut RawTopology {
-        self.0.as_ptr()
+        self.raw.as_ptr()
+    }
+}
+
+/// Data derived from a [`Topology`], computed once after it is loaded or
+/// edited so that frequent queries do not need to traverse it
+#[derive(Clone, Debug, Default)]
+struct DerivedData {
+    /// Total memory in the NUMA nodes of the topology
+    total_memory: u64,
+
+    /// Number of objects of each type present in the topology
+    object_counts: HashMap<ObjectType, usize>,
+
+    /// Depth and logical index of each object, keyed by global persistent
+    /// index
+    ///
+    /// Positions are stored instead of object pointers because they remain
+    /// valid when the topology is duplicated.
+    object_positions: HashMap<u64, (Depth, usize)>,
+
+    /// Name and info indices, built on first use
+    ///
+    /// Most applications never look up objects by name or info, so these
+    /// indices are only built when they are first needed. Like the rest of
+    /// the derived data, they are reset when the topology is modified.
+    object_indices: OnceCell<ObjectIndices>,
+}
+//
+impl DerivedData {
+    /// Compute the derived data of `topology`
+    fn new(topology: &Topology) -> Self {
+        let mut object_counts = HashMap::new();
+        let mut object_positions = HashMap::new();
+        for depth in all_depths(topology) {
+            let size = topology.size_at_depth(depth);
+            if size > 0 {
+                let object_type = topology.type_at_depth(depth).expect("Depth should exist");
+                *object_counts.entry(object_type).or_insert(0) += size;
+            }
+            for (logical_index, object) in topology.objects_at_depth(depth).enumerate() {
+                object_positions.insert(object.global_persistent_index(), (depth, logical_index));
+            }
+        }
+        Self {
+            total_memory: topology.root_object().total_memory(),
+            object_counts,
+            object_positions,
+            object_indices: OnceCell::new(),
+        }
+    }
+}
+
+/// Indices of the objects of a [`Topology`] by name and by info
+#[derive(Clone, Debug, Default)]
+struct ObjectIndices {
+    /// Depth and logical index of objects, keyed by name
+    ///
+    /// Objects whose name is not valid UTF-8 are not indexed.
+    by_name: HashMap<String, Vec<(Depth, usize)>>,
+
+    /// Depth and logical index of objects, keyed by info key, then info value
+    ///
+    /// Infos whose key or value is not valid UTF-8 are not indexed.
+    by_info: HashMap<String, HashMap<String, Vec<(Depth, usize)>>>,
+}
+//
+impl ObjectIndices {
+    /// Index the objects of `topology`
+    fn new(topology: &Topology) -> Self {
+        let mut objects_by_name = HashMap::<String, Vec<_>>::new();
+        let mut objects_by_info = HashMap::<String, HashMap<String, Vec<_>>>::new();
+        for depth in all_depths(topology) {
+            for (logical_index, object) in topology.objects_at_depth(depth).enumerate() {
+                let position = (depth, logical_index);
+                if let Some(Ok(name)) = object.name().map(CStr::to_str) {
+                    objects_by_name
+                        .entry(name.to_owned())
+                        .or_default()
+                        .push(position);
+                }
+                for info in object.infos() {
+                    let (Ok(key), Ok(value)) = (info.name().to_str(), info.value().to_str()) else {
+                        continue;
+                    };
+                    objects_by_info
+                        .entry(key.to_owned())
+                        .or_default()
+                        .entry(value.to_owned())
+                        .or_default()
+                        .push(position);
+                }
+            }
+        }
+        Self {
+            by_name: objects_by_name,
+            by_info: objects_by_info,
+        }
     }
 }
 
+/// Normal and virtual depths of `topology`
+fn all_depths(topology: &Topology) -> impl Iterator<Item = Depth> {
+    (0..topology.depth())
+        .map(Depth::from)
+        .chain(Depth::VIRTUAL_DEPTHS.iter().copied())
+}
+
 impl Clone for Topology {
     #[doc(alias = "hwloc_topology_dup")]
     fn clone(&self) -> Self {
@@ -744,7 +1560,11 @@ impl Clone for Topology {
             ffi::hwloc_topology_dup(&mut clone, self.as_ptr())
         })
         .expect("Failed to clone topology");
-        Self(NonNull::new(clone).expect("Got null pointer from hwloc_topology_dup"))
+        Self {
+            raw: NonNull::new(clone).expect("Got null pointer from hwloc_topology_dup"),
+            derived: self.derived.clone(),
+            load_config: self.load_config.clone(),
+        }
     }
 }
 
@@ -757,3 +1577,126 @@ impl Drop for Topology {
 
 unsafe impl Send for Topology {}
 unsafe impl Sync for Topology {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base_page_size() {
+        let page_size = super::base_page_size();
+        assert!(page_size >= 4096);
+        assert!(page_size.is_power_of_two());
+    }
+
+    #[test]
+    fn concurrent_cache_writers_should_not_clash() {
+        let dir =
+            std::env::temp_dir().join(format!("hwlocality-cache-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("topology.xml");
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    Topology::load_cached_or_detect(&path, Duration::ZERO).unwrap();
+                });
+            }
+        });
+
+        // Every writer renamed its own temporary files into place
+        let mut entries = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect::<Vec<_>>();
+        entries.sort();
+        assert_eq!(entries, ["topology.xml", "topology.xml.fingerprint"]);
+        assert!(Topology::load_fresh_cache(&path, Duration::from_secs(60)).is_some());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn object_indices_should_be_built_lazily() {
+        let topology = Topology::new().unwrap();
+        assert!(topology.derived.object_indices.get().is_none());
+        topology.cached_objects_with_name("");
+        assert!(topology.derived.object_indices.get().is_some());
+    }
+
+    #[cfg(feature = "hwloc-2_3_0")]
+    #[test]
+    fn object_indices_should_be_reset_on_update() {
+        let mut topology = Topology::new().unwrap();
+        topology.cached_objects_with_name("");
+        topology.update_derived_data();
+        assert!(topology.derived.object_indices.get().is_none());
+    }
+
+    #[cfg(feature = "hwloc-2_8_0")]
+    #[test]
+    fn queries_should_report_features_disabled_at_build() {
+        use crate::{
+            cpu::kinds::CpuKindsQueryError,
+            errors::HybridError,
+            memory::attributes::{MemoryAttribute, MemoryAttributeQueryError},
+            objects::distances::DistancesKind,
+        };
+
+        // Synthetic topologies have no distances, CPU kinds or memory
+        // attributes, so the queries only tell if that is expected
+        let build = |flags| {
+            Topology::builder()
+                .from_synthetic("node:2 pu:2")
+                .unwrap()
+                .with_flags(flags)
+                .unwrap()
+                .build()
+                .unwrap()
+        };
+        let enabled = build(BuildFlags::empty());
+        let disabled = build(
+            BuildFlags::IGNORE_DISTANCES
+                | BuildFlags::IGNORE_CPU_KINDS
+                | BuildFlags::IGNORE_MEMORY_ATTRIBUTES,
+        );
+
+        assert!(enabled
+            .distances(DistancesKind::empty())
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            disabled.distances(DistancesKind::empty()).unwrap_err(),
+            HybridError::Rust(FeatureDisabledAtBuild(BuildFlags::IGNORE_DISTANCES))
+        );
+
+        assert_eq!(
+            enabled.num_cpu_kinds().unwrap_err(),
+            CpuKindsQueryError::Unknown
+        );
+        assert_eq!(
+            disabled.num_cpu_kinds().unwrap_err(),
+            CpuKindsQueryError::FeatureDisabledAtBuild(FeatureDisabledAtBuild(
+                BuildFlags::IGNORE_CPU_KINDS
+            ))
+        );
+
+        let cpuset = enabled.cpuset().clone();
+        assert!(MemoryAttribute::bandwidth(&enabled)
+            .best_target(Some(&cpuset))
+            .unwrap()
+            .is_none());
+        assert!(matches!(
+            MemoryAttribute::bandwidth(&disabled).best_target(Some(&cpuset)),
+            Err(MemoryAttributeQueryError::FeatureDisabledAtBuild(_))
+        ));
+        assert!(MemoryAttribute::capacity(&disabled)
+            .best_target(None::<&CpuSet>)
+            .is_ok());
+    }
+
+    #[test]
+    fn hugepage_sizes() {
+        let topology = Topology::test_instance();
+        let sizes = topology.hugepage_sizes();
+        assert!(sizes.keys().all(|&size| size > super::base_page_size()));
+    }
+}
@@ -0,0 +1,419 @@
+//! Re-discovering the topology after hardware changes
+//!
+//! CPUs and NUMA nodes can be brought online and offline while a process is
+//! running, which is common on cloud VMs. Long-running processes can use
+//! [`Topology::refresh()`] to re-discover the topology and find out what
+//! changed, and on Linux, [`HotplugWatcher`] tells when it is a good time to
+//! do so.
+
+#[cfg(doc)]
+use crate::topology::builder::EnvironmentOverrides;
+#[cfg(target_os = "linux")]
+use crate::{bitmaps::Bitmap, linux::read_kernel_list};
+use crate::{
+    cpu::cpusets::CpuSet,
+    errors::{HybridError, RawHwlocError},
+    memory::nodesets::NodeSet,
+    objects::types::ObjectType,
+    topology::{
+        builder::{self, EnvironmentOverrideError, TopologyBuilder},
+        Topology,
+    },
+};
+#[cfg(target_os = "linux")]
+use std::{
+    io,
+    sync::mpsc::{self, RecvTimeoutError},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+/// # Re-discovering the topology
+impl Topology {
+    /// Re-discover the topology and report which PUs and NUMA nodes changed
+    ///
+    /// The topology is loaded again from the current system, using the same
+    /// [build flags](Topology::build_flags()), [type
+    /// filters](Topology::type_filter()) and [process
+    /// ID](TopologyBuilder::from_pid()) as this topology, and replaces it.
+    /// Any modification that was made to the topology after loading it, for
+    /// example using the topology editor, is lost.
+    ///
+    /// Topologies that do not describe the current system (see
+    /// [`Topology::is_this_system()`]) cannot be re-discovered, and are left
+    /// untouched. An empty delta is returned in this case.
+    ///
+    /// Topologies that were built with [`EnvironmentOverrides`] modify the
+    /// process environment while they are loaded. Like
+    /// [`TopologyBuilder::try_build_with_environment_overrides()`], this
+    /// method only applies these overrides again if the process is known to
+    /// have a single thread. Otherwise, such topologies must be re-discovered
+    /// using [`Topology::refresh_overriding_environment()`].
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Errors
+    ///
+    /// - [`EnvironmentOverrideError`] if this topology was built with
+    ///   environment overrides and the process is not known to be
+    ///   single-threaded
+    /// - Same hwloc errors as [`TopologyBuilder::build()`], and an hwloc
+    ///   error if this topology was loaded from another process which cannot
+    ///   be viewed anymore, e.g. because it has exited
+    ///
+    /// This topology is left untouched if re-discovery fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::Topology;
+    /// let mut topology = Topology::new()?;
+    /// let delta = topology.refresh()?;
+    /// if !delta.is_empty() {
+    ///     println!("PUs {} came online", delta.added_pus());
+    ///     println!("PUs {} went offline", delta.removed_pus());
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn refresh(&mut self) -> Result<TopologyDelta, HybridError<EnvironmentOverrideError>> {
+        if self.load_config.overrides_environment() && !builder::is_single_threaded() {
+            return Err(EnvironmentOverrideError.into());
+        }
+        // SAFETY: Either this topology does not override the environment, or
+        //         this is the only thread of the process, so no other thread
+        //         can use the environment, fork or spawn processes meanwhile
+        unsafe { self.refresh_overriding_environment() }.map_err(HybridError::Hwloc)
+    }
+
+    /// Re-discover a topology that was built with environment overrides
+    ///
+    /// This works like [`Topology::refresh()`], but also supports topologies
+    /// that were built with [`EnvironmentOverrides`] while other threads may be
+    /// running, by applying the same environment overrides again.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Errors
+    ///
+    /// Same hwloc errors as [`Topology::refresh()`]. This topology is left
+    /// untouched if re-discovery fails.
+    ///
+    /// # Safety
+    ///
+    /// If this topology was built with environment overrides, this method is
+    /// a critical section with the same safety contract as
+    /// [`TopologyBuilder::build_with_environment_overrides()`]: while it runs,
+    /// no other thread may use the process environment, fork the process or
+    /// spawn child processes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::{Topology, topology::builder::EnvironmentOverrides};
+    /// let overrides = EnvironmentOverrides::new().ignore_hwloc_variables();
+    /// // SAFETY: No other thread uses the environment, forks or spawns
+    /// //         processes while the topology is being loaded
+    /// let mut topology = unsafe { Topology::builder().build_with_environment_overrides(&overrides) }?;
+    /// // SAFETY: Same as above, while the topology is being refreshed
+    /// let delta = unsafe { topology.refresh_overriding_environment() }?;
+    /// println!("NUMA nodes {} came online", delta.added_numa_nodes());
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub unsafe fn refresh_overriding_environment(
+        &mut self,
+    ) -> Result<TopologyDelta, RawHwlocError> {
+        if !self.is_this_system() {
+            return Ok(TopologyDelta::default());
+        }
+        let mut builder = TopologyBuilder::try_new()?
+            .with_flags(self.build_flags())
+            .map_err(expect_hwloc_error)?;
+        for ty in (0..).map_while(|raw: u32| ObjectType::try_from(raw).ok()) {
+            builder = builder
+                .with_type_filter(ty, self.type_filter(ty)?)
+                .map_err(expect_hwloc_error)?;
+        }
+        let builder = builder.with_load_config(&self.load_config)?;
+        // SAFETY: Per this function's safety contract
+        let topology =
+            unsafe { builder.build_with_environment_overrides(&self.load_config.environment) }?;
+        let delta = TopologyDelta::new(self, &topology);
+        *self = topology;
+        Ok(delta)
+    }
+}
+
+/// Extract the hwloc error from a [`HybridError`] whose Rust-side error cannot
+/// happen because the configuration comes from an existing topology
+fn expect_hwloc_error<RustError: std::error::Error>(
+    error: HybridError<RustError>,
+) -> RawHwlocError {
+    match error {
+        HybridError::Hwloc(e) => e,
+        HybridError::Rust(e) => {
+            unreachable!("Configuration of an existing topology should be valid: {e}")
+        }
+    }
+}
+
+/// PUs and NUMA nodes that were added or removed between two topologies
+///
+/// Returned by [`Topology::refresh()`]. Only PUs and NUMA nodes that are
+/// online (i.e. part of [`Topology::cpuset()`] and [`Topology::nodeset()`])
+/// are considered.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct TopologyDelta {
+    /// PUs that appeared
+    added_pus: CpuSet,
+
+    /// PUs that disappeared
+    removed_pus: CpuSet,
+
+    /// NUMA nodes that appeared
+    added_numa_nodes: NodeSet,
+
+    /// NUMA nodes that disappeared
+    removed_numa_nodes: NodeSet,
+}
+
+impl TopologyDelta {
+    /// Compute the changes from topology `before` to topology `after`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::topology::{refresh::TopologyDelta, Topology};
+    /// let small = Topology::builder().from_synthetic("node:1 pu:2")?.build()?;
+    /// let big = Topology::builder().from_synthetic("node:2 pu:2")?.build()?;
+    /// let delta = TopologyDelta::new(&small, &big);
+    /// assert_eq!(delta.added_pus().to_string(), "2-3");
+    /// assert!(delta.removed_pus().is_empty());
+    /// assert_eq!(delta.added_numa_nodes().to_string(), "1");
+    /// assert!(delta.removed_numa_nodes().is_empty());
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn new(before: &Topology, after: &Topology) -> Self {
+        Self {
+            added_pus: after.cpuset() - before.cpuset(),
+            removed_pus: before.cpuset() - after.cpuset(),
+            added_numa_nodes: after.nodeset() - before.nodeset(),
+            removed_numa_nodes: before.nodeset() - after.nodeset(),
+        }
+    }
+
+    /// Truth that nothing changed
+    pub fn is_empty(&self) -> bool {
+        self.added_pus.is_empty()
+            && self.removed_pus.is_empty()
+            && self.added_numa_nodes.is_empty()
+            && self.removed_numa_nodes.is_empty()
+    }
+
+    /// PUs that came online
+    pub fn added_pus(&self) -> &CpuSet {
+        &self.added_pus
+    }
+
+    /// PUs that went offline
+    pub fn removed_pus(&self) -> &CpuSet {
+        &self.removed_pus
+    }
+
+    /// NUMA nodes that came online
+    pub fn added_numa_nodes(&self) -> &NodeSet {
+        &self.added_numa_nodes
+    }
+
+    /// NUMA nodes that went offline
+    pub fn removed_numa_nodes(&self) -> &NodeSet {
+        &self.removed_numa_nodes
+    }
+}
+
+/// Background thread that reports CPU and NUMA node hotplug events
+///
+/// The watcher periodically reads the lists of online CPUs and NUMA nodes
+/// from Linux's sysfs, and calls a user-provided function whenever they
+/// change. This is a good time to call [`Topology::refresh()`].
+///
+/// The watcher stops when it is dropped.
+///
+/// This functionality is unique to the Rust hwloc bindings.
+///
+/// # Examples
+///
+/// ```
+/// # use hwlocality::topology::refresh::HotplugWatcher;
+/// # use std::time::Duration;
+/// let watcher = HotplugWatcher::spawn(Duration::from_secs(1), |event| {
+///     println!("Online CPUs are now {}", event.online_cpus());
+/// });
+/// match watcher {
+///     Ok(watcher) => drop(watcher),
+///     Err(e) => eprintln!("CPU hotplug cannot be monitored: {e}"),
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[cfg(target_os = "linux")]
+#[derive(Debug)]
+pub struct HotplugWatcher {
+    /// Channel used to tell the watcher thread to stop
+    stop: Option<mpsc::Sender<()>>,
+
+    /// Watcher thread
+    thread: Option<JoinHandle<()>>,
+}
+//
+#[cfg(target_os = "linux")]
+impl HotplugWatcher {
+    /// Start watching for hotplug events every `interval`
+    ///
+    /// `on_event` is called on the watcher thread every time the set of
+    /// online CPUs or NUMA nodes changes.
+    ///
+    /// # Errors
+    ///
+    /// If the list of online CPUs cannot be read from sysfs, or if the watcher
+    /// thread cannot be spawned.
+    pub fn spawn(
+        interval: Duration,
+        mut on_event: impl FnMut(HotplugEvent) + Send + 'static,
+    ) -> io::Result<Self> {
+        let mut last = HotplugEvent::read()?;
+        let (stop, stopped) = mpsc::channel();
+        let thread = thread::Builder::new()
+            .name("hwlocality-hotplug".to_owned())
+            .spawn(move || {
+                while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                    let Ok(current) = HotplugEvent::read() else {
+                        continue;
+                    };
+                    if current != last {
+                        last = current.clone();
+                        on_event(current);
+                    }
+                }
+            })?;
+        Ok(Self {
+            stop: Some(stop),
+            thread: Some(thread),
+        })
+    }
+}
+//
+#[cfg(target_os = "linux")]
+impl Drop for HotplugWatcher {
+    fn drop(&mut self) {
+        // Dropping the sender wakes up and stops the watcher thread
+        drop(self.stop.take());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Online CPUs and NUMA nodes, as reported by a [`HotplugWatcher`]
+///
+/// Indices are OS indices, as in [`Topology::cpuset()`] and
+/// [`Topology::nodeset()`].
+#[cfg(target_os = "linux")]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct HotplugEvent {
+    /// CPUs that are online
+    online_cpus: CpuSet,
+
+    /// NUMA nodes that are online
+    online_nodes: NodeSet,
+}
+//
+#[cfg(target_os = "linux")]
+impl HotplugEvent {
+    /// Read the current online CPUs and NUMA nodes from sysfs
+    ///
+    /// Kernels without NUMA support do not report online NUMA nodes, in which
+    /// case the nodeset is empty.
+    fn read() -> io::Result<Self> {
+        let online_cpus = read_kernel_list("/sys/devices/system/cpu/online")?;
+        let online_nodes = match read_kernel_list("/sys/devices/system/node/online") {
+            Ok(nodes) => nodes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Bitmap::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(Self {
+            online_cpus: online_cpus.into(),
+            online_nodes: online_nodes.into(),
+        })
+    }
+
+    /// CPUs that are online
+    pub fn online_cpus(&self) -> &CpuSet {
+        &self.online_cpus
+    }
+
+    /// NUMA nodes that are online
+    pub fn online_nodes(&self) -> &NodeSet {
+        &self.online_nodes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::topology::builder::{EnvironmentOverrides, LoadConfig};
+
+    fn synthetic(description: &str) -> Topology {
+        Topology::builder()
+            .from_synthetic(description)
+            .unwrap()
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn delta_of_identical_topologies_is_empty() {
+        let topology = synthetic("node:2 pu:2");
+        let delta = TopologyDelta::new(&topology, &topology.clone());
+        assert!(delta.is_empty());
+        assert_eq!(delta, TopologyDelta::default());
+    }
+
+    #[test]
+    fn delta_should_report_added_and_removed_resources() {
+        let small = synthetic("node:1 pu:2");
+        let big = synthetic("node:2 pu:2");
+
+        let growth = TopologyDelta::new(&small, &big);
+        assert!(!growth.is_empty());
+        assert_eq!(growth.added_pus().to_string(), "2-3");
+        assert!(growth.removed_pus().is_empty());
+        assert_eq!(growth.added_numa_nodes().to_string(), "1");
+        assert!(growth.removed_numa_nodes().is_empty());
+
+        let shrink = TopologyDelta::new(&big, &small);
+        assert!(!shrink.is_empty());
+        assert!(shrink.added_pus().is_empty());
+        assert_eq!(shrink.removed_pus(), growth.added_pus());
+        assert!(shrink.added_numa_nodes().is_empty());
+        assert_eq!(shrink.removed_numa_nodes(), growth.added_numa_nodes());
+    }
+
+    #[test]
+    fn refresh_should_reject_environment_overrides() {
+        let mut topology = synthetic("node:2 pu:2");
+        topology.load_config = LoadConfig {
+            environment: EnvironmentOverrides::new().ignore_hwloc_variables(),
+            ..LoadConfig::default()
+        };
+        assert_eq!(
+            topology.refresh(),
+            Err(HybridError::Rust(EnvironmentOverrideError))
+        );
+    }
+
+    #[test]
+    fn refresh_of_synthetic_topology_is_noop() {
+        let mut topology = synthetic("node:2 pu:2");
+        assert!(topology.refresh().unwrap().is_empty());
+        assert_eq!(topology.cpuset().to_string(), "0-3");
+    }
+}
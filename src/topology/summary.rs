@@ -0,0 +1,274 @@
+//! Human-readable topology summary
+
+use crate::{
+    cpu::caches::DATA_CACHE_LEVELS,
+    objects::{attributes::ObjectAttributes, types::ObjectType},
+    topology::Topology,
+};
+use std::fmt;
+
+/// # Topology summary
+impl Topology {
+    /// Summarize the shape of the topology
+    ///
+    /// The summary is a compact description of the machine, such as
+    /// "2 packages × 32 cores × 2 PUs, 8 NUMA nodes, L3 32MiB×8", which is
+    /// handy for logging the machine shape at application startup. It is
+    /// also what the [`Display`](fmt::Display) implementation of [`Topology`]
+    /// prints.
+    ///
+    /// This functionality is unique to the Rust hwloc bindings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::Topology;
+    /// let topology = Topology::builder()
+    ///     .from_synthetic("pack:2 core:4 pu:2")?
+    ///     .build()?;
+    /// let summary = topology.summary();
+    /// assert_eq!(summary.num_packages(), 2);
+    /// assert_eq!(summary.cores_per_package(), Some(4));
+    /// assert_eq!(summary.pus_per_core(), Some(2));
+    /// assert!(topology.to_string().starts_with("2 packages × 4 cores × 2 PUs"));
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn summary(&self) -> TopologySummary {
+        TopologySummary::new(self)
+    }
+}
+
+impl fmt::Display for Topology {
+    /// Display the [`Topology::summary()`]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.summary())
+    }
+}
+
+/// Compact description of the shape of a topology
+///
+/// Its [`Display`](fmt::Display) implementation produces text like
+/// "2 packages × 32 cores × 2 PUs, 8 NUMA nodes, L3 32MiB×8".
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct TopologySummary {
+    /// Number of packages
+    num_packages: usize,
+
+    /// Number of cores
+    num_cores: usize,
+
+    /// Number of PUs
+    num_pus: usize,
+
+    /// Number of NUMA nodes
+    num_numa_nodes: usize,
+
+    /// Number of cores in each package, if all packages have the same number
+    cores_per_package: Option<usize>,
+
+    /// Number of PUs in each core, if all cores have the same number
+    pus_per_core: Option<usize>,
+
+    /// Type of the last-level CPU data cache, if any
+    last_level_cache_type: Option<ObjectType>,
+
+    /// Distinct sizes of last-level CPU data caches and how many caches of
+    /// each size there are, by decreasing size
+    last_level_caches: Vec<(u64, usize)>,
+}
+
+impl TopologySummary {
+    /// Summarize a topology
+    pub fn new(topology: &Topology) -> Self {
+        let num_packages = topology.object_count_by_type(ObjectType::Package);
+        let num_cores = topology.object_count_by_type(ObjectType::Core);
+        let num_pus = topology.object_count_by_type(ObjectType::PU);
+        let num_numa_nodes = topology.object_count_by_type(ObjectType::NUMANode);
+        let cores_per_package = uniform_count(topology, ObjectType::Package, ObjectType::Core);
+        let pus_per_core = uniform_count(topology, ObjectType::Core, ObjectType::PU);
+
+        let last_level_cache_type = DATA_CACHE_LEVELS
+            .iter()
+            .rev()
+            .copied()
+            .find(|&ty| topology.objects_with_type(ty).next().is_some());
+        let mut last_level_caches = Vec::<(u64, usize)>::new();
+        if let Some(ty) = last_level_cache_type {
+            for cache in topology.objects_with_type(ty) {
+                let Some(ObjectAttributes::Cache(attr)) = cache.attributes() else {
+                    unreachable!("Caches should have cache attributes")
+                };
+                match last_level_caches
+                    .iter_mut()
+                    .find(|(size, _)| *size == attr.size())
+                {
+                    Some((_, count)) => *count += 1,
+                    None => last_level_caches.push((attr.size(), 1)),
+                }
+            }
+            last_level_caches.sort_unstable_by(|(size1, _), (size2, _)| size2.cmp(size1));
+        }
+
+        Self {
+            num_packages,
+            num_cores,
+            num_pus,
+            num_numa_nodes,
+            cores_per_package,
+            pus_per_core,
+            last_level_cache_type,
+            last_level_caches,
+        }
+    }
+
+    /// Number of [`Package`](ObjectType::Package) objects
+    pub fn num_packages(&self) -> usize {
+        self.num_packages
+    }
+
+    /// Number of [`Core`](ObjectType::Core) objects
+    pub fn num_cores(&self) -> usize {
+        self.num_cores
+    }
+
+    /// Number of [`PU`](ObjectType::PU) objects
+    pub fn num_pus(&self) -> usize {
+        self.num_pus
+    }
+
+    /// Number of [`NUMANode`](ObjectType::NUMANode) objects
+    pub fn num_numa_nodes(&self) -> usize {
+        self.num_numa_nodes
+    }
+
+    /// Number of cores in each package
+    ///
+    /// Returns `None` if there are no packages or cores, or if packages do not
+    /// all have the same number of cores.
+    pub fn cores_per_package(&self) -> Option<usize> {
+        self.cores_per_package
+    }
+
+    /// Number of PUs in each core
+    ///
+    /// Returns `None` if there are no cores, or if cores do not all have the
+    /// same number of PUs (e.g. on hybrid CPUs where only some cores support
+    /// simultaneous multithreading).
+    pub fn pus_per_core(&self) -> Option<usize> {
+        self.pus_per_core
+    }
+
+    /// Type of the last-level CPU data cache (e.g. [`L3Cache`]), if any
+    ///
+    /// [`L3Cache`]: ObjectType::L3Cache
+    pub fn last_level_cache_type(&self) -> Option<ObjectType> {
+        self.last_level_cache_type
+    }
+
+    /// Distinct sizes of the last-level CPU data caches in bytes, along with
+    /// the number of caches of each size, by decreasing size
+    pub fn last_level_caches(&self) -> &[(u64, usize)] {
+        &self.last_level_caches[..]
+    }
+}
+
+impl fmt::Display for TopologySummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        match (self.cores_per_package, self.pus_per_core) {
+            (Some(cores_per_package), Some(pus_per_core)) => parts.push(format!(
+                "{} × {} × {}",
+                count(self.num_packages, "package"),
+                count(cores_per_package, "core"),
+                count(pus_per_core, "PU"),
+            )),
+            _ => {
+                if self.num_packages > 0 {
+                    parts.push(count(self.num_packages, "package"));
+                }
+                if self.num_cores > 0 {
+                    parts.push(count(self.num_cores, "core"));
+                }
+                parts.push(count(self.num_pus, "PU"));
+            }
+        }
+        if self.num_numa_nodes > 0 {
+            parts.push(count(self.num_numa_nodes, "NUMA node"));
+        }
+        if let Some(ty) = self.last_level_cache_type {
+            let level = DATA_CACHE_LEVELS
+                .iter()
+                .position(|&level| level == ty)
+                .expect("Last-level cache type comes from DATA_CACHE_LEVELS")
+                + 1;
+            let caches = self
+                .last_level_caches
+                .iter()
+                .map(|&(size, count)| format!("{}×{count}", format_bytes(size)))
+                .collect::<Vec<_>>();
+            parts.push(format!("L{level} {}", caches.join("+")));
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+/// Number of `inner` objects below each `outer` object, if it is the same for
+/// all `outer` objects and nonzero
+fn uniform_count(topology: &Topology, outer: ObjectType, inner: ObjectType) -> Option<usize> {
+    let inner_depth = topology.depth_for_type(inner).ok()?;
+    let mut counts = topology.objects_with_type(outer).map(|obj| {
+        obj.cpuset().map_or(0, |cpuset| {
+            topology
+                .objects_inside_cpuset_at_depth(&cpuset, inner_depth)
+                .count()
+        })
+    });
+    let first = counts.next().filter(|&count| count > 0)?;
+    counts.all(|count| count == first).then_some(first)
+}
+
+/// Format a quantity of objects, e.g. "1 core" or "2 cores"
+fn count(count: usize, name: &str) -> String {
+    if count == 1 {
+        format!("{count} {name}")
+    } else {
+        format!("{count} {name}s")
+    }
+}
+
+/// Format a size in bytes using binary units, e.g. "32MiB" or "1.5MiB"
+fn format_bytes(size: u64) -> String {
+    const UNITS: &[&str] = &["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+    let mut unit = 0;
+    let mut divisor = 1u64;
+    while unit + 1 < UNITS.len() && size / divisor >= 1024 {
+        unit += 1;
+        divisor *= 1024;
+    }
+    if size % divisor == 0 {
+        format!("{}{}", size / divisor, UNITS[unit])
+    } else {
+        format!("{:.1}{}", size as f64 / divisor as f64, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_bytes_units() {
+        assert_eq!(format_bytes(0), "0B");
+        assert_eq!(format_bytes(1000), "1000B");
+        assert_eq!(format_bytes(48 * 1024), "48KiB");
+        assert_eq!(format_bytes(1536 * 1024), "1.5MiB");
+        assert_eq!(format_bytes(32 * 1024 * 1024), "32MiB");
+    }
+
+    #[test]
+    fn count_plural() {
+        assert_eq!(count(0, "core"), "0 cores");
+        assert_eq!(count(1, "NUMA node"), "1 NUMA node");
+        assert_eq!(count(2, "PU"), "2 PUs");
+    }
+}
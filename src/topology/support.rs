@@ -1,7 +1,7 @@
 //! hwloc feature support
 
-// - API: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__configuration.html#gab8c76173c4a8ce1a9a9366012b1388e6
-// - Struct: https://hwloc.readthedocs.io/en/v2.9/structhwloc__topology__support.html
+// - API: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__configuration.html#gab8c76173c4a8ce1a9a9366012b1388e6
+// - Struct: https://hwloc.readthedocs.io/en/v2.11/structhwloc__topology__support.html
 
 #[cfg(doc)]
 use super::builder::BuildFlags;
@@ -96,9 +96,12 @@ impl PartialEq for FeatureSupport {
 }
 //
 impl Eq for FeatureSupport {}
+//
+unsafe impl Send for FeatureSupport {}
+unsafe impl Sync for FeatureSupport {}
 
 /// Support for discovering information about the topology
-#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq)]
+#[derive(Copy, Clone, Default, Eq, Hash, PartialEq)]
 #[doc(alias = "hwloc_topology_discovery_support")]
 #[repr(C)]
 pub struct DiscoverySupport {
@@ -150,19 +153,36 @@ impl DiscoverySupport {
 
     /// Detecting the efficiency of CPU kinds is supported
     ///
-    /// See also [Kinds of CPU cores](..s/struct.Topology.html#kinds-of-cpu-cores).
+    /// See also [Kinds of CPU cores](../struct.Topology.html#kinds-of-cpu-cores).
     #[cfg(feature = "hwloc-2_4_0")]
     #[doc(alias = "hwloc_topology_discovery_support::cpukind_efficiency")]
     pub fn cpukind_efficiency(&self) -> bool {
         support_flag(self.cpukind_efficiency)
     }
 }
+//
+impl fmt::Debug for DiscoverySupport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut debug = f.debug_struct("DiscoverySupport");
+        debug
+            .field("pu_count", &self.pu_count())
+            .field("numa_count", &self.numa_count())
+            .field("numa_memory", &self.numa_memory());
+        #[cfg(feature = "hwloc-2_1_0")]
+        debug
+            .field("disallowed_pu", &self.disallowed_pu())
+            .field("disallowed_numa", &self.disallowed_numa());
+        #[cfg(feature = "hwloc-2_4_0")]
+        debug.field("cpukind_efficiency", &self.cpukind_efficiency());
+        debug.finish()
+    }
+}
 
 /// Support for getting and setting thread/process CPU bindings
 ///
 /// A flag may be set even if the feature isn't supported in all cases
 /// (e.g. binding to random sets of non-contiguous objects).
-#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq)]
+#[derive(Copy, Clone, Default, Eq, Hash, PartialEq)]
 #[doc(alias = "hwloc_topology_cpubind_support")]
 #[repr(C)]
 pub struct CpuBindingSupport {
@@ -246,12 +266,39 @@ impl CpuBindingSupport {
         support_flag(self.get_thisthread_last_cpu_location)
     }
 }
+//
+impl fmt::Debug for CpuBindingSupport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CpuBindingSupport")
+            .field("set_current_process", &self.set_current_process())
+            .field("get_current_process", &self.get_current_process())
+            .field("set_process", &self.set_process())
+            .field("get_process", &self.get_process())
+            .field("set_current_thread", &self.set_current_thread())
+            .field("get_current_thread", &self.get_current_thread())
+            .field("set_thread", &self.set_thread())
+            .field("get_thread", &self.get_thread())
+            .field(
+                "get_current_process_last_cpu_location",
+                &self.get_current_process_last_cpu_location(),
+            )
+            .field(
+                "get_process_last_cpu_location",
+                &self.get_process_last_cpu_location(),
+            )
+            .field(
+                "get_current_thread_last_cpu_location",
+                &self.get_current_thread_last_cpu_location(),
+            )
+            .finish()
+    }
+}
 
 /// Support for getting and setting thread/process NUMA node bindings
 ///
 /// A flag may be set even if the feature isn't supported in all cases
 /// (e.g. binding to random sets of non-contiguous objects).
-#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq)]
+#[derive(Copy, Clone, Default, Eq, Hash, PartialEq)]
 #[doc(alias = "hwloc_topology_membind_support")]
 #[repr(C)]
 pub struct MemoryBindingSupport {
@@ -270,6 +317,8 @@ pub struct MemoryBindingSupport {
     nexttouch_membind: c_uchar,
     migrate_membind: c_uchar,
     get_area_memlocation: c_uchar,
+    #[cfg(feature = "hwloc-2_11_0")]
+    weighted_interleave_membind: c_uchar,
 }
 
 impl MemoryBindingSupport {
@@ -351,6 +400,13 @@ impl MemoryBindingSupport {
         support_flag(self.interleave_membind)
     }
 
+    /// Weighted interleave policy is supported
+    #[cfg(feature = "hwloc-2_11_0")]
+    #[doc(alias = "hwloc_topology_membind_support::weighted_interleave_membind")]
+    pub fn weighted_interleave(&self) -> bool {
+        support_flag(self.weighted_interleave_membind)
+    }
+
     /// Next-touch migration policy is supported
     #[doc(alias = "hwloc_topology_membind_support::nexttouch_membind")]
     pub fn next_touch(&self) -> bool {
@@ -363,10 +419,36 @@ impl MemoryBindingSupport {
         support_flag(self.migrate_membind)
     }
 }
+//
+impl fmt::Debug for MemoryBindingSupport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut debug = f.debug_struct("MemoryBindingSupport");
+        debug
+            .field("set_current_process", &self.set_current_process())
+            .field("get_current_process", &self.get_current_process())
+            .field("set_process", &self.set_process())
+            .field("get_process", &self.get_process())
+            .field("set_current_thread", &self.set_current_thread())
+            .field("get_current_thread", &self.get_current_thread())
+            .field("set_area", &self.set_area())
+            .field("get_area", &self.get_area())
+            .field("get_area_memory_location", &self.get_area_memory_location())
+            .field("alloc", &self.alloc())
+            .field("first_touch", &self.first_touch())
+            .field("bind", &self.bind())
+            .field("interleave", &self.interleave());
+        #[cfg(feature = "hwloc-2_11_0")]
+        debug.field("weighted_interleave", &self.weighted_interleave());
+        debug
+            .field("next_touch", &self.next_touch())
+            .field("migrate", &self.migrate())
+            .finish()
+    }
+}
 
 /// Miscellaneous support information
 #[cfg(feature = "hwloc-2_3_0")]
-#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq)]
+#[derive(Copy, Clone, Default, Eq, Hash, PartialEq)]
 #[doc(alias = "hwloc_topology_misc_support")]
 #[repr(C)]
 pub struct MiscSupport {
@@ -383,6 +465,15 @@ impl MiscSupport {
         support_flag(self.imported_support)
     }
 }
+//
+#[cfg(feature = "hwloc-2_3_0")]
+impl fmt::Debug for MiscSupport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MiscSupport")
+            .field("imported", &self.imported())
+            .finish()
+    }
+}
 
 /// Decode topology support flag
 fn support_flag(flag: c_uchar) -> bool {
@@ -403,6 +494,15 @@ mod tests {
         Topology::test_instance().supports(FeatureSupport::cpu_binding, kind)
     }
 
+    #[test]
+    fn debug_should_use_rust_names() {
+        let debug = format!("{:?}", DiscoverySupport::default());
+        assert!(debug.contains("pu_count: false"));
+        let debug = format!("{:?}", MemoryBindingSupport::default());
+        assert!(debug.contains("first_touch: false"));
+        assert!(!debug.contains("membind"));
+    }
+
     #[test]
     #[cfg(target_os = "linux")]
     fn should_support_cpu_binding_on_linux() {
@@ -1,10 +1,11 @@
 //! Windows-specific helpers
 
 use crate::{
-    cpu::cpusets::CpuSet,
+    cpu::{binding::ProcessorGroupBindingError, cpusets::CpuSet},
     errors::{self, RawHwlocError},
     ffi,
     topology::Topology,
+    ThreadId,
 };
 use std::{ffi::c_uint, iter::FusedIterator, num::NonZeroUsize};
 
@@ -19,7 +20,7 @@ use std::{ffi::c_uint, iter::FusedIterator, num::NonZeroUsize};
 /// groups so that applications know whether binding to a large set of PUs may
 /// fail because it spans over multiple Windows processor groups.
 //
-// Upstream docs: https://hwloc.readthedocs.io/en/v2.9/group__hwlocality__windows.html
+// Upstream docs: https://hwloc.readthedocs.io/en/v2.11/group__hwlocality__windows.html
 impl Topology {
     /// Number of Windows processor groups
     ///
@@ -76,4 +77,298 @@ impl Topology {
             }),
         )
     }
+
+    /// Split a cpuset into per-processor-group parts
+    ///
+    /// Since Windows threads may only be bound inside of a single processor
+    /// group, binding threads to a large set of PUs, such as those of a NUMA
+    /// node on a machine with more than 64 PUs, requires splitting that set
+    /// into processor groups first, then binding each thread to one part.
+    ///
+    /// This returns the index of each processor group that intersects with
+    /// `set`, along with the part of `set` that lies within it, in increasing
+    /// processor group order. This is the information returned by the Windows
+    /// `GetNumaNodeProcessorMaskEx` function when `set` is the cpuset of a NUMA
+    /// node.
+    ///
+    /// Each part can be passed to [`processor_group_of()`] or
+    /// [`processor_group_affinity()`] on its own. PUs of `set` that do not
+    /// belong to any processor group are not part of the result.
+    ///
+    /// # Errors
+    ///
+    /// One reason why this function can fail is if the topology does not match
+    /// the current system (e.g. loaded from another machine through XML).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::{cpu::binding::CpuBindingFlags, objects::types::ObjectType};
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// // Spawn one worker per processor group spanned by the first NUMA node
+    /// let node = topology
+    ///     .objects_with_type(ObjectType::NUMANode)
+    ///     .next()
+    ///     .expect("There is always one NUMA node");
+    /// let cpuset = node.cpuset().expect("NUMA nodes have a cpuset");
+    /// std::thread::scope(|scope| {
+    ///     for (group, part) in topology.split_by_processor_group(&cpuset)? {
+    ///         println!("Processor group {group} holds PUs {part} of the node");
+    ///         scope.spawn(move || -> anyhow::Result<()> {
+    ///             topology.bind_cpu(&part, CpuBindingFlags::THREAD)?;
+    ///             // ...do some work here...
+    ///             Ok(())
+    ///         });
+    ///     }
+    ///     Ok::<(), anyhow::Error>(())
+    /// })?;
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    ///
+    /// [`processor_group_affinity()`]: Self::processor_group_affinity()
+    /// [`processor_group_of()`]: Self::processor_group_of()
+    #[doc(alias = "GetNumaNodeProcessorMaskEx")]
+    pub fn split_by_processor_group(
+        &self,
+        set: &CpuSet,
+    ) -> Result<Vec<(usize, CpuSet)>, RawHwlocError> {
+        let mut parts = Vec::new();
+        for (pg_index, group) in self.processor_groups()?.enumerate() {
+            let part = group? & set;
+            if !part.is_empty() {
+                parts.push((pg_index, part));
+            }
+        }
+        Ok(parts)
+    }
+
+    /// Index of the processor group that contains all PUs of `set`
+    ///
+    /// This tells whether a thread can be bound to `set` as a whole. If not,
+    /// [`split_by_processor_group()`](Self::split_by_processor_group()) can be
+    /// used to split `set` into parts that threads can be bound to.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`processor_group_affinity()`](Self::processor_group_affinity()).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::{cpu::binding::ProcessorGroupBindingError, objects::types::ObjectType};
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// let core = topology
+    ///     .objects_with_type(ObjectType::Core)
+    ///     .next()
+    ///     .expect("There is always one core");
+    /// let cpuset = core.cpuset().expect("Cores have a cpuset");
+    /// // A core never spans multiple processor groups
+    /// let group = topology.processor_group_of(&cpuset)?;
+    /// println!("Core {core} belongs to processor group {group}");
+    ///
+    /// match topology.processor_group_of(&topology.cpuset()) {
+    ///     Ok(group) => println!("All PUs belong to processor group {group}"),
+    ///     Err(ProcessorGroupBindingError::MultipleGroups { groups, .. }) => {
+    ///         println!("PUs are spread over processor groups {groups:?}")
+    ///     }
+    ///     Err(e) => return Err(e.into()),
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn processor_group_of(&self, set: &CpuSet) -> Result<usize, ProcessorGroupBindingError> {
+        self.processor_group_affinity(set)
+            .map(|(group, _mask)| group)
+    }
+
+    /// Locate the processor group that contains all PUs of `set`
+    ///
+    /// Returns the index of the processor group along with the affinity mask
+    /// of `set` within this group, i.e. the `Group` and `Mask` fields of the
+    /// `GROUP_AFFINITY` struct that Windows thread binding functions expect.
+    ///
+    /// # Errors
+    ///
+    /// - [`EmptySet`] if `set` is empty
+    /// - [`Hwloc`] if processor groups could not be queried
+    /// - [`MultipleGroups`] if `set` spans multiple processor groups
+    /// - [`OutsideGroups`] if `set` contains PUs that do not belong to any
+    ///   processor group
+    /// - [`UnsupportedGroupLayout`] if the PUs of the processor group cannot
+    ///   be expressed as an affinity mask
+    ///
+    /// [`EmptySet`]: ProcessorGroupBindingError::EmptySet
+    /// [`Hwloc`]: ProcessorGroupBindingError::Hwloc
+    /// [`MultipleGroups`]: ProcessorGroupBindingError::MultipleGroups
+    /// [`OutsideGroups`]: ProcessorGroupBindingError::OutsideGroups
+    /// [`UnsupportedGroupLayout`]: ProcessorGroupBindingError::UnsupportedGroupLayout
+    #[doc(alias = "GROUP_AFFINITY")]
+    pub fn processor_group_affinity(
+        &self,
+        set: &CpuSet,
+    ) -> Result<(usize, usize), ProcessorGroupBindingError> {
+        if set.is_empty() {
+            return Err(ProcessorGroupBindingError::EmptySet);
+        }
+        let mut groups = Vec::new();
+        for (pg_index, group_set) in self.processor_groups()?.enumerate() {
+            let group_set = group_set?;
+            if group_set.intersects(set) {
+                groups.push((pg_index, group_set));
+            }
+        }
+        let (group, group_set) = match groups.as_slice() {
+            [] => return Err(ProcessorGroupBindingError::OutsideGroups(set.clone())),
+            [(group, group_set)] => (*group, group_set),
+            _ => {
+                return Err(ProcessorGroupBindingError::MultipleGroups {
+                    set: set.clone(),
+                    groups: groups.iter().map(|(group, _)| *group).collect(),
+                })
+            }
+        };
+        if !group_set.includes(set) {
+            return Err(ProcessorGroupBindingError::OutsideGroups(set.clone()));
+        }
+        let mask = affinity_mask(group_set, set)
+            .ok_or(ProcessorGroupBindingError::UnsupportedGroupLayout(group))?;
+        Ok((group, mask))
+    }
+
+    /// Bind Windows thread `thread` to the PUs of `set`
+    ///
+    /// Unlike [`bind_thread_cpu()`], which may fail or silently pick a subset
+    /// of `set` when it spans multiple processor groups, this function
+    /// translates `set` into a `GROUP_AFFINITY` and calls
+    /// `SetThreadGroupAffinity` directly, reporting which processor groups
+    /// are involved if `set` cannot be expressed this way.
+    ///
+    /// # Errors
+    ///
+    /// - [`Os`] if the operating system rejected the binding
+    /// - Any error reported by [`processor_group_affinity()`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hwlocality::objects::types::ObjectType;
+    /// # let topology = hwlocality::Topology::test_instance();
+    /// let core = topology
+    ///     .objects_with_type(ObjectType::Core)
+    ///     .next()
+    ///     .expect("There is always one core");
+    /// let cpuset = core.cpuset().expect("Cores have a cpuset");
+    /// let thread = unsafe { windows_sys::Win32::System::Threading::GetCurrentThread() };
+    /// topology.bind_windows_thread(thread, &cpuset)?;
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    ///
+    /// [`bind_thread_cpu()`]: Topology::bind_thread_cpu()
+    /// [`Os`]: ProcessorGroupBindingError::Os
+    /// [`processor_group_affinity()`]: Topology::processor_group_affinity()
+    #[doc(alias = "SetThreadGroupAffinity")]
+    pub fn bind_windows_thread(
+        &self,
+        thread: ThreadId,
+        set: &CpuSet,
+    ) -> Result<(), ProcessorGroupBindingError> {
+        let (group, mask) = self.processor_group_affinity(set)?;
+        let affinity = windows_sys::Win32::System::SystemInformation::GROUP_AFFINITY {
+            Mask: mask,
+            Group: u16::try_from(group).expect("Windows has at most 2^16 processor groups"),
+            Reserved: [0; 3],
+        };
+        // SAFETY: affinity is a valid GROUP_AFFINITY, and the previous
+        //         affinity is not requested
+        let result = unsafe {
+            windows_sys::Win32::System::Threading::SetThreadGroupAffinity(
+                thread,
+                &affinity,
+                std::ptr::null_mut(),
+            )
+        };
+        if result == 0 {
+            return Err(ProcessorGroupBindingError::Os(errno::errno()));
+        }
+        Ok(())
+    }
+}
+
+/// Affinity mask of `set` within the processor group whose PUs are `group_set`
+///
+/// hwloc numbers the PUs of each processor group within a window of
+/// `usize::BITS` consecutive indices that starts at a multiple of
+/// `usize::BITS`, and bit N of the affinity mask designates the N-th PU of
+/// this window. `None` is returned if `group_set` does not follow this layout.
+///
+/// `set` must be a subset of `group_set`.
+fn affinity_mask(group_set: &CpuSet, set: &CpuSet) -> Option<usize> {
+    let window_len = usize::BITS as usize;
+    let first_pu = usize::from(group_set.first_set()?);
+    let window_start = first_pu - first_pu % window_len;
+    let window_end = window_start.checked_add(window_len)?;
+    if usize::from(group_set.last_set()?) >= window_end {
+        return None;
+    }
+    set.iter_set().try_fold(0usize, |mask, pu| {
+        let bit = usize::from(pu).checked_sub(window_start)?;
+        Some(mask | 1usize.checked_shl(u32::try_from(bit).ok()?)?)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn affinity_mask_should_be_relative_to_the_group_window() {
+        let bits = usize::BITS as usize;
+
+        // First group, starting at PU 0
+        let group_set = CpuSet::from_range(0..bits);
+        let set = CpuSet::from_range(1..=2);
+        assert_eq!(affinity_mask(&group_set, &set), Some(0b110));
+
+        // Third group, whose window starts at PU 2 * usize::BITS, with the
+        // first PUs of the window missing (e.g. offline)
+        let group_set = CpuSet::from_range(2 * bits + 4..3 * bits);
+        let set = CpuSet::from_range(2 * bits + 4..=2 * bits + 5);
+        assert_eq!(affinity_mask(&group_set, &set), Some(0b11_0000));
+
+        // Last PU of a full group
+        let group_set = CpuSet::from_range(bits..2 * bits);
+        let set = CpuSet::from_range(2 * bits - 1..2 * bits);
+        assert_eq!(affinity_mask(&group_set, &set), Some(1 << (bits - 1)));
+    }
+
+    #[test]
+    fn affinity_mask_should_reject_groups_that_do_not_fit_a_window() {
+        let bits = usize::BITS as usize;
+        let group_set = CpuSet::from_range(bits / 2..bits + bits / 2);
+        let set = CpuSet::from_range(bits / 2..=bits / 2);
+        assert_eq!(affinity_mask(&group_set, &set), None);
+        assert_eq!(affinity_mask(&CpuSet::new(), &CpuSet::new()), None);
+    }
+
+    #[test]
+    fn split_by_processor_group_should_agree_with_processor_group_of() {
+        let topology = Topology::test_instance();
+        let parts = topology
+            .split_by_processor_group(&topology.cpuset())
+            .unwrap();
+        let mut covered = CpuSet::new();
+        let mut last_group = None;
+        for (group, part) in parts {
+            assert!(last_group.map_or(true, |last| group > last));
+            assert!(!part.is_empty());
+            assert!(!covered.intersects(&part));
+            assert_eq!(topology.processor_group_of(&part), Ok(group));
+            covered |= &part;
+            last_group = Some(group);
+        }
+        assert_eq!(topology.cpuset(), &covered);
+        assert!(topology
+            .split_by_processor_group(&CpuSet::new())
+            .unwrap()
+            .is_empty());
+    }
 }